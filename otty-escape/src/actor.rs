@@ -1,12 +1,16 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use cursor_icon::CursorIcon;
 
 use crate::keyboard::{
     KeyboardMode, KeyboardModeApplyBehavior, ModifyOtherKeysState,
 };
 use crate::{
-    BlockEvent, CharacterAttribute, Charset, CharsetIndex, ClearMode,
-    CursorShape, CursorStyle, Hyperlink, LineClearMode, Mode, PrivateMode, Rgb,
-    TabClearMode,
+    AttributeChangeExtent, BlockEvent, CharacterAttribute, Charset,
+    CharsetIndex, ClearMode, CursorShape, CursorStyle, Hyperlink,
+    ImagePlacement, LineClearMode, Mode, PrivateMode, ProgressState, RectArea,
+    RectAttrChange, Rgb, SemanticPromptMark, TabClearMode,
 };
 
 #[derive(Debug, PartialEq, Eq)]
@@ -15,6 +19,10 @@ use crate::{
 pub enum Action {
     /// Render the provided character at the cursor position.
     Print(char),
+    /// Render a run of printable characters, as if each was a [`Print`].
+    ///
+    /// [`Print`]: Action::Print
+    PrintString(String),
     /// Play the terminal bell / alert sound.
     Bell,
     /// Insert the requested number of blank cells at the cursor position.
@@ -31,11 +39,11 @@ pub enum Action {
     Backspace,
     /// Move the cursor to the start of the current line.
     CarriageReturn,
-    /// Advance the cursor downward, potentially scrolling.
+    /// Advance the cursor downward, potentially scrolling (IND).
     LineFeed,
     /// Move the cursor to the next line without altering horizontal position.
     NextLine,
-    /// Move down and reset to column zero (common newline behavior).
+    /// Line feed (LF/VT/FF), also returning to column zero in LNM.
     NewLine,
     /// Replace the current character with the replacement character (SUB).
     Substitute,
@@ -63,6 +71,8 @@ pub enum Action {
     MoveBackwardTabs(u16),
     /// Select which charset slot is active for subsequent character writes.
     SetActiveCharsetIndex(CharsetIndex),
+    /// Select which charset slot maps the right half of the code table.
+    SetRightCharsetIndex(CharsetIndex),
     /// Load a charset definition into the specified slot.
     ConfigureCharset(Charset, CharsetIndex),
     /// Update an indexed color in the terminal palette.
@@ -115,6 +125,8 @@ pub enum Action {
     IdentifyTerminal(Option<char>),
     /// Reply to a general device status report (DSR).
     ReportDeviceStatus(usize),
+    /// Reply to an XTGETTCAP query for the given terminfo capability names.
+    QueryTermcap(Vec<String>),
     /// Keyboard: switch to the application keypad mode.
     SetKeypadApplicationMode,
     /// Keyboard: switch back to the numeric keypad mode.
@@ -155,6 +167,30 @@ pub enum Action {
     SetWindowTitle(String),
     /// High-level block lifecycle event parsed from DCS JSON.
     BlockEvent(BlockEvent),
+    /// Image: reserve the cells of an image shown at the cursor.
+    PlaceImage(ImagePlacement),
+    /// Rectangle: fill an area with a character in the current rendition
+    /// (DECFRA).
+    RectFill { area: RectArea, c: char },
+    /// Rectangle: change the attributes of an area (DECCARA / DECRARA).
+    RectAttr {
+        area: RectArea,
+        change: RectAttrChange,
+    },
+    /// Rectangle: select the extent of area attribute changes (DECSACE).
+    SetAttributeChangeExtent(AttributeChangeExtent),
+    /// Window: update the progress indicator (ConEmu `OSC 9;4`).
+    ///
+    /// `percent` is within `0..=100`, and zero for states without one.
+    Progress { state: ProgressState, percent: u8 },
+    /// Shell integration: semantic prompt mark (`OSC 133`).
+    SemanticPrompt(SemanticPromptMark),
+    /// Shell integration: working directory of the shell (iTerm2
+    /// `OSC 1337 ; CurrentDir=`).
+    SetCurrentDir(String),
+    /// Shell integration: user variable set with iTerm2
+    /// `OSC 1337 ; SetUserVar=`, with its value already decoded.
+    SetUserVar { name: String, value: String },
 }
 
 pub trait EscapeActor {
@@ -31,6 +31,8 @@ pub enum CharacterAttribute {
     Hidden,
     /// Strikeout text.
     Strike,
+    /// Line drawn above the text.
+    Overline,
     /// Cancel bold.
     CancelBold,
     /// Cancel bold and dim.
@@ -47,6 +49,8 @@ pub enum CharacterAttribute {
     CancelHidden,
     /// Cancel strikeout.
     CancelStrike,
+    /// Cancel overline.
+    CancelOverline,
     /// Set indexed foreground color.
     Foreground(Color),
     /// Set indexed background color.
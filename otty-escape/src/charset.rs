@@ -5,6 +5,8 @@ pub enum Charset {
     Ascii,
     /// DEC Line Drawing Character set
     DecLineDrawing,
+    /// British national replacement character set, with `£` for `#`
+    British,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -62,6 +64,25 @@ impl Charset {
                 '~' => '·',
                 _ => c,
             },
+            Self::British => match c {
+                '#' => '£',
+                _ => c,
+            },
+        }
+    }
+
+    /// Map a character of the right half of the code table, given as its
+    /// ISO Latin-1 equivalent, while the charset is invoked into GR.
+    ///
+    /// The right half of ASCII is Latin-1, which is left in place. Other
+    /// sets map the byte with its high bit cleared.
+    #[inline]
+    pub fn map_right_half(self, c: char) -> char {
+        match u8::try_from(c) {
+            Ok(byte @ 0xa0..=0xff) if self != Self::Ascii => {
+                self.map(char::from(byte & 0x7f))
+            },
+            _ => c,
         }
     }
 }
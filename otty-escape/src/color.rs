@@ -1,5 +1,7 @@
-use std::fmt::{self, Display, Formatter};
-use std::str::FromStr;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::iter::Peekable;
+use core::str::FromStr;
 
 use otty_vte::CsiParam;
 
@@ -167,7 +169,7 @@ fn parse_legacy_color(color: &[u8]) -> Option<Rgb> {
 
 /// Parse colors in `rgb:r(rrr)/g(ggg)/b(bbb)` format.
 fn parse_rgb_color(input: &[u8]) -> Option<Rgb> {
-    let s = std::str::from_utf8(input).ok()?;
+    let s = core::str::from_utf8(input).ok()?;
     let colors: Vec<&str> = s.split('/').collect();
 
     if colors.len() != 3 {
@@ -191,10 +193,18 @@ fn parse_rgb_color(input: &[u8]) -> Option<Rgb> {
     Some(Rgb { r, g, b })
 }
 
-pub(crate) fn parse_sgr_color<'a, I>(iter: &mut I) -> Option<Color>
+/// Parse the color following SGR 38, 48 or 58.
+///
+/// Both the semicolon form (`38;2;r;g;b`, `38;5;n`) and the colon form
+/// (`38:2:cs:r:g:b`, `38:2::r:g:b`, `38:5:n`) are accepted.
+pub(crate) fn parse_sgr_color<'a, I>(iter: &mut Peekable<I>) -> Option<Color>
 where
     I: Iterator<Item = &'a CsiParam>,
 {
+    if iter.next_if_eq(&&CsiParam::P(b':')).is_some() {
+        return parse_sgr_color_subparams(iter);
+    }
+
     let mut iter = iter.filter(|&el| el.as_integer().is_some());
 
     match iter.next() {
@@ -221,6 +231,43 @@ where
     }
 }
 
+/// Parse the colon separated form, up to the next `;`.
+///
+/// The color space id of `38:2:cs:r:g:b` is ignored and may be left empty;
+/// the `38:2:r:g:b` form without it is accepted as well.
+fn parse_sgr_color_subparams<'a, I>(iter: &mut Peekable<I>) -> Option<Color>
+where
+    I: Iterator<Item = &'a CsiParam>,
+{
+    let mut slots = Vec::new();
+    let mut current = None;
+    while let Some(param) = iter.next_if(|&param| *param != CsiParam::P(b';')) {
+        match param {
+            CsiParam::Integer(value) => current = Some(*value),
+            CsiParam::P(b':') => slots.push(current.take()),
+            CsiParam::P(_) => return None,
+        }
+    }
+    slots.push(current);
+
+    let channel =
+        |slot: &Option<i64>| slot.and_then(|value| u8::try_from(value).ok());
+    let rgb = |[r, g, b]: &[Option<i64>; 3]| {
+        Some(Color::TrueColor(Rgb {
+            r: channel(r)?,
+            g: channel(g)?,
+            b: channel(b)?,
+        }))
+    };
+
+    match slots.as_slice() {
+        [Some(5), index] => channel(index).map(Color::Indexed),
+        [Some(2), _color_space, r, g, b, ..] => rgb(&[*r, *g, *b]),
+        [Some(2), r, g, b] => rgb(&[*r, *g, *b]),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,4 +1,4 @@
-use std::fmt;
+use core::fmt;
 
 use log::debug;
 
@@ -113,7 +113,7 @@ pub(crate) fn perform<A: EscapeActor>(byte: u8, actor: &mut A) {
         ControlCode::CarriageReturn => actor.handle(Action::CarriageReturn),
         ControlCode::LineFeed
         | ControlCode::FormFeed
-        | ControlCode::VerticalTab => actor.handle(Action::LineFeed),
+        | ControlCode::VerticalTab => actor.handle(Action::NewLine),
         ControlCode::Bell => actor.handle(Action::Bell),
         ControlCode::Substitute => actor.handle(Action::Substitute),
         ControlCode::ShiftOut => {
@@ -166,7 +166,7 @@ mod tests {
             ("\r".as_bytes(), vec![Action::CarriageReturn]),
             (
                 &[0x0A, 0x0B, 0x0C],
-                vec![Action::LineFeed, Action::LineFeed, Action::LineFeed],
+                vec![Action::NewLine, Action::NewLine, Action::NewLine],
             ),
             (
                 "\x07\x1A".as_bytes(),
@@ -194,7 +194,7 @@ mod tests {
                     Action::Print('A'),
                     Action::Backspace,
                     Action::Print('B'),
-                    Action::LineFeed,
+                    Action::NewLine,
                 ],
             ),
         ];
@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use log::debug;
 use otty_vte::CsiParam;
 
@@ -9,6 +11,7 @@ use crate::keyboard::{
 };
 use crate::mode::{ClearMode, LineClearMode, Mode, PrivateMode, TabClearMode};
 use crate::parser::ParserState;
+use crate::rect::{AttributeChangeExtent, RectArea, RectAttrChange};
 use crate::{Action, EscapeActor, NamedPrivateMode};
 
 #[inline]
@@ -120,6 +123,10 @@ pub(crate) fn perform<A: EscapeActor>(
         (b'n', params) => handle_device_status_report(actor, params, fallback),
         // DCH (CSI Ps P)
         (b'P', params) => handle_delete_character(actor, params, fallback),
+        // DECCARA (CSI Pt ; Pl ; Pb ; Pr ; Pm $ r)
+        (b'r', params @ [.., P(b'$')]) => {
+            handle_change_rect_attributes(actor, params, false, fallback)
+        },
         // DECSTBM (CSI Ps ; Ps r)
         (b'r', params) => handle_set_scrolling_region(actor, params, fallback),
         // SU (CSI Ps S)
@@ -128,8 +135,20 @@ pub(crate) fn perform<A: EscapeActor>(
         (b's', ..) => actor.handle(Action::SaveCursorPosition),
         // SD (CSI Ps T)
         (b'T', params) => handle_scroll_down(actor, params, fallback),
+        // DECRARA (CSI Pt ; Pl ; Pb ; Pr ; Pm $ t)
+        (b't', params @ [.., P(b'$')]) => {
+            handle_change_rect_attributes(actor, params, true, fallback)
+        },
         // Window manipulation (CSI Ps t) sequences.
         (b't', params) => handle_window_manipulation(actor, params, fallback),
+        // DECFRA (CSI Pch ; Pt ; Pl ; Pb ; Pr $ x)
+        (b'x', params @ [.., P(b'$')]) => {
+            handle_fill_rect(actor, params, fallback)
+        },
+        // DECSACE (CSI Ps * x)
+        (b'x', params @ [.., P(b'*')]) => {
+            handle_attribute_change_extent(actor, params, fallback)
+        },
         // ECH (CSI Ps X)
         (b'X', params) => handle_erase_characters(actor, params, fallback),
         // CBT (CSI Ps Z)
@@ -333,7 +352,7 @@ where
             carrage_return_needed: false,
         }),
         [Integer(rows)] => actor.handle(Action::MoveUp {
-            rows: *rows as usize,
+            rows: count_param(*rows),
             carrage_return_needed: false,
         }),
         _ => fallback(),
@@ -353,7 +372,7 @@ where
             carrage_return_needed: false,
         }),
         [Integer(rows)] => actor.handle(Action::MoveDown {
-            rows: *rows as usize,
+            rows: count_param(*rows),
             carrage_return_needed: false,
         }),
         _ => fallback(),
@@ -394,7 +413,7 @@ where
     match params {
         [] => actor.handle(Action::MoveForward(1)),
         [Integer(columns)] => {
-            actor.handle(Action::MoveForward(*columns as usize));
+            actor.handle(Action::MoveForward(count_param(*columns)));
         },
         _ => fallback(),
     }
@@ -430,7 +449,7 @@ where
     match params {
         [] => actor.handle(Action::MoveBackward(1)),
         [Integer(columns)] => {
-            actor.handle(Action::MoveBackward(*columns as usize));
+            actor.handle(Action::MoveBackward(count_param(*columns)));
         },
         _ => fallback(),
     }
@@ -447,9 +466,9 @@ fn handle_vertical_position_absolute<A, F>(
     use CsiParam::*;
 
     match params {
-        [] => actor.handle(Action::GotoRow(1)),
+        [] => actor.handle(Action::GotoRow(0)),
         [Integer(line_num)] => {
-            actor.handle(Action::GotoRow(*line_num as i32 - 1));
+            actor.handle(Action::GotoRow(position_param(*line_num) as i32));
         },
         _ => fallback(),
     }
@@ -472,7 +491,7 @@ fn handle_cursor_next_line<A, F>(
         }),
         [Integer(line_count)] => {
             actor.handle(Action::MoveDown {
-                rows: *line_count as usize,
+                rows: count_param(*line_count),
                 carrage_return_needed: true,
             });
         },
@@ -497,7 +516,7 @@ fn handle_cursor_preceding_line<A, F>(
         }),
         [Integer(line_count)] => {
             actor.handle(Action::MoveUp {
-                rows: *line_count as usize,
+                rows: count_param(*line_count),
                 carrage_return_needed: true,
             });
         },
@@ -516,9 +535,9 @@ fn handle_cursor_horizontal_absolute<A, F>(
     use CsiParam::*;
 
     match params {
-        [] => actor.handle(Action::GotoColumn(1)),
+        [] => actor.handle(Action::GotoColumn(0)),
         [Integer(column_num)] => {
-            actor.handle(Action::GotoColumn(*column_num as usize - 1));
+            actor.handle(Action::GotoColumn(position_param(*column_num)));
         },
         _ => fallback(),
     }
@@ -535,9 +554,9 @@ fn handle_character_position_absolute<A, F>(
     use CsiParam::*;
 
     match params {
-        [] => actor.handle(Action::GotoColumn(1)),
+        [] => actor.handle(Action::GotoColumn(0)),
         [Integer(column_num)] => {
-            actor.handle(Action::GotoColumn(*column_num as usize - 1));
+            actor.handle(Action::GotoColumn(position_param(*column_num)));
         },
         _ => fallback(),
     }
@@ -726,6 +745,81 @@ fn handle_set_scrolling_region<A, F>(
     }
 }
 
+fn handle_change_rect_attributes<A, F>(
+    actor: &mut A,
+    params: &[CsiParam],
+    reverse: bool,
+    fallback: F,
+) where
+    A: EscapeActor,
+    F: Fn(),
+{
+    let Some(values) = rect_params(params) else {
+        return fallback();
+    };
+
+    let area = rect_area(&values);
+    let raw_attributes = match values.get(4..) {
+        Some(raw) if !raw.is_empty() => raw,
+        _ => &[0],
+    };
+    let mut attributes = Vec::with_capacity(raw_attributes.len());
+    for &raw in raw_attributes {
+        match rect_attribute(raw, reverse) {
+            Some(attribute) => attributes.push(attribute),
+            None => debug!("[rect attributes] unsupported attribute {raw}"),
+        }
+    }
+
+    let change = if reverse {
+        RectAttrChange::Reverse(attributes)
+    } else {
+        RectAttrChange::Set(attributes)
+    };
+    actor.handle(Action::RectAttr { area, change });
+}
+
+fn handle_fill_rect<A, F>(actor: &mut A, params: &[CsiParam], fallback: F)
+where
+    A: EscapeActor,
+    F: Fn(),
+{
+    let Some(values) = rect_params(params) else {
+        return fallback();
+    };
+
+    let fill = values
+        .first()
+        .and_then(|&raw| char::from_u32(u32::from(raw)))
+        .filter(|c| !c.is_control());
+    match fill {
+        Some(c) => actor.handle(Action::RectFill {
+            area: rect_area(&values[1..]),
+            c,
+        }),
+        None => fallback(),
+    }
+}
+
+fn handle_attribute_change_extent<A, F>(
+    actor: &mut A,
+    params: &[CsiParam],
+    fallback: F,
+) where
+    A: EscapeActor,
+    F: Fn(),
+{
+    use CsiParam::*;
+
+    let extent = match params {
+        [P(b'*')] | [Integer(0 | 1), P(b'*')] => AttributeChangeExtent::Stream,
+        [Integer(2), P(b'*')] => AttributeChangeExtent::Rectangle,
+        _ => return fallback(),
+    };
+
+    actor.handle(Action::SetAttributeChangeExtent(extent));
+}
+
 fn handle_scroll_up<A, F>(actor: &mut A, params: &[CsiParam], fallback: F)
 where
     A: EscapeActor,
@@ -933,6 +1027,8 @@ fn handle_set_sgr_attribute<A, F>(
             Integer(49) => Some(CharacterAttribute::Background(Color::Std(
                 StdColor::Background,
             ))),
+            Integer(53) => Some(CharacterAttribute::Overline),
+            Integer(55) => Some(CharacterAttribute::CancelOverline),
             Integer(58) => parse_sgr_color(&mut iter)
                 .map(|color| CharacterAttribute::UnderlineColor(Some(color))),
             Integer(59) => Some(CharacterAttribute::UnderlineColor(None)),
@@ -1009,7 +1105,66 @@ fn parse_cursor_style(raw_shape: i64) -> Option<CursorStyle> {
     })
 }
 
-#[allow(dead_code)]
+/// Count parameter, where a missing or zero value means 1.
+fn count_param(value: i64) -> usize {
+    value.clamp(1, i64::from(u16::MAX)) as usize
+}
+
+/// Zero-based position from a one-based parameter, where zero means 1.
+fn position_param(value: i64) -> usize {
+    count_param(value) - 1
+}
+
+/// Numeric parameters of a rectangular area operation ending in `$`.
+fn rect_params(params: &[CsiParam]) -> Option<Vec<u16>> {
+    let [rest @ .., CsiParam::P(b'$')] = params else {
+        return None;
+    };
+    if rest
+        .iter()
+        .any(|param| matches!(param, CsiParam::P(byte) if *byte != b';'))
+    {
+        return None;
+    }
+
+    Some(parse_params(rest))
+}
+
+/// Area from `Pt ; Pl ; Pb ; Pr`, where missing or zero bounds default to
+/// the screen edges.
+fn rect_area(values: &[u16]) -> RectArea {
+    let value = |index: usize| values.get(index).copied().unwrap_or(0);
+
+    RectArea {
+        top: usize::from(value(0).saturating_sub(1)),
+        left: usize::from(value(1).saturating_sub(1)),
+        bottom: value(2).checked_sub(1).map(usize::from),
+        right: value(3).checked_sub(1).map(usize::from),
+    }
+}
+
+/// Attribute selected by a DECCARA or DECRARA parameter.
+///
+/// DECRARA only toggles attributes, so it does not accept cancellations.
+fn rect_attribute(raw: u16, reverse: bool) -> Option<CharacterAttribute> {
+    let attribute = match raw {
+        0 => CharacterAttribute::Reset,
+        1 => CharacterAttribute::Bold,
+        4 => CharacterAttribute::Underline,
+        5 => CharacterAttribute::BlinkSlow,
+        7 => CharacterAttribute::Reverse,
+        8 => CharacterAttribute::Hidden,
+        22 if !reverse => CharacterAttribute::CancelBold,
+        24 if !reverse => CharacterAttribute::CancelUnderline,
+        25 if !reverse => CharacterAttribute::CancelBlink,
+        27 if !reverse => CharacterAttribute::CancelReverse,
+        28 if !reverse => CharacterAttribute::CancelHidden,
+        _ => return None,
+    };
+
+    Some(attribute)
+}
+
 fn parse_params(params: &[CsiParam]) -> Vec<u16> {
     let mut values = Vec::new();
     let mut pending: Option<u16> = None;
@@ -1123,6 +1278,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn csi_private_modes_batch_applies_every_mode() {
+        let actor = RecordingEscapeActor::parse("\x1b[?1;9999;2004;2026h");
+
+        assert_eq!(actor.begin_sync_calls, 1);
+        assert_eq!(
+            actor.actions,
+            vec![
+                Action::SetPrivateMode(PrivateMode::Named(
+                    NamedPrivateMode::CursorKeys
+                )),
+                Action::SetPrivateMode(PrivateMode::Unknown(9999)),
+                Action::SetPrivateMode(PrivateMode::Named(
+                    NamedPrivateMode::BracketedPaste
+                )),
+                Action::SetPrivateMode(PrivateMode::Named(
+                    NamedPrivateMode::SyncUpdate
+                )),
+            ]
+        );
+
+        let actor = RecordingEscapeActor::parse("\x1b[?1;2004;2026l");
+        assert_eq!(actor.end_sync_calls, 1);
+        assert_eq!(actor.actions.len(), 3);
+    }
+
     #[test]
     fn csi_modify_other_keys() {
         let cases = vec![
@@ -1237,6 +1418,97 @@ mod tests {
         );
     }
 
+    #[test]
+    fn csi_rectangular_area_operations() {
+        let actor = RecordingEscapeActor::parse(
+            "\x1b[88;2;3;4;5$x\x1b[2;2;4;4;1;27$r\x1b[;;;;$t\x1b[2*x\x1b[*x\x1b[27;1;1;2;2$x",
+        );
+
+        assert_eq!(
+            actor.actions,
+            vec![
+                Action::RectFill {
+                    area: RectArea {
+                        top: 1,
+                        left: 2,
+                        bottom: Some(3),
+                        right: Some(4),
+                    },
+                    c: 'X',
+                },
+                Action::RectAttr {
+                    area: RectArea {
+                        top: 1,
+                        left: 1,
+                        bottom: Some(3),
+                        right: Some(3),
+                    },
+                    change: RectAttrChange::Set(vec![
+                        CharacterAttribute::Bold,
+                        CharacterAttribute::CancelReverse,
+                    ]),
+                },
+                Action::RectAttr {
+                    area: RectArea {
+                        top: 0,
+                        left: 0,
+                        bottom: None,
+                        right: None,
+                    },
+                    change: RectAttrChange::Reverse(vec![
+                        CharacterAttribute::Reset
+                    ]),
+                },
+                Action::SetAttributeChangeExtent(
+                    AttributeChangeExtent::Rectangle
+                ),
+                Action::SetAttributeChangeExtent(AttributeChangeExtent::Stream),
+            ]
+        );
+    }
+
+    #[test]
+    fn csi_cursor_motion_treats_zero_and_missing_params_as_one() {
+        let actor = RecordingEscapeActor::parse(
+            "\x1b[0A\x1b[0B\x1b[0e\x1b[0C\x1b[0a\x1b[0D\x1b[0E\x1b[0F\x1b[G\x1b[0G\x1b[0`\x1b[d\x1b[0d\x1b[1d",
+        );
+
+        assert_eq!(
+            actor.actions,
+            vec![
+                Action::MoveUp {
+                    rows: 1,
+                    carrage_return_needed: false,
+                },
+                Action::MoveDown {
+                    rows: 1,
+                    carrage_return_needed: false,
+                },
+                Action::MoveDown {
+                    rows: 1,
+                    carrage_return_needed: false,
+                },
+                Action::MoveForward(1),
+                Action::MoveForward(1),
+                Action::MoveBackward(1),
+                Action::MoveDown {
+                    rows: 1,
+                    carrage_return_needed: true,
+                },
+                Action::MoveUp {
+                    rows: 1,
+                    carrage_return_needed: true,
+                },
+                Action::GotoColumn(0),
+                Action::GotoColumn(0),
+                Action::GotoColumn(0),
+                Action::GotoRow(0),
+                Action::GotoRow(0),
+                Action::GotoRow(0),
+            ]
+        );
+    }
+
     #[test]
     fn csi_tab_scrolling_and_region_sequences() {
         let actor = RecordingEscapeActor::parse(
@@ -1366,6 +1638,17 @@ mod tests {
                 ],
             ),
             ("\x1b[38;2;300;0;0m", vec![]),
+            ("\x1b[38:2::300:0:0m", vec![]),
+            ("\x1b[38:2:1m", vec![]),
+            ("\x1b[9m", vec![Action::SGR(CharacterAttribute::Strike)]),
+            ("\x1b[53m", vec![Action::SGR(CharacterAttribute::Overline)]),
+            (
+                "\x1b[29;55m",
+                vec![
+                    Action::SGR(CharacterAttribute::CancelStrike),
+                    Action::SGR(CharacterAttribute::CancelOverline),
+                ],
+            ),
         ];
 
         for (input, expected) in cases {
@@ -1374,6 +1657,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn truecolor_sgr_accepts_the_colon_forms() {
+        let rgb = Color::TrueColor(Rgb {
+            r: 10,
+            g: 20,
+            b: 30,
+        });
+        let cases = [
+            "\x1b[38;2;10;20;30m",
+            "\x1b[38:2::10:20:30m",
+            "\x1b[38:2:0:10:20:30m",
+            "\x1b[38:2:10:20:30m",
+        ];
+
+        for input in cases {
+            assert_eq!(
+                RecordingEscapeActor::parse(input).actions,
+                vec![Action::SGR(CharacterAttribute::Foreground(rgb))],
+                "{input:?}"
+            );
+        }
+
+        // The colon form ends at the next `;`, where other attributes follow.
+        assert_eq!(
+            RecordingEscapeActor::parse("\x1b[48:2:0:10:20:30;1m").actions,
+            vec![
+                Action::SGR(CharacterAttribute::Background(rgb)),
+                Action::SGR(CharacterAttribute::Bold),
+            ]
+        );
+        assert_eq!(
+            RecordingEscapeActor::parse("\x1b[58:5:42m").actions,
+            vec![Action::SGR(CharacterAttribute::UnderlineColor(Some(
+                Color::Indexed(42)
+            )))]
+        );
+    }
+
     #[test]
     fn cursor_position_save_and_restore() {
         let cases = vec![
@@ -1,4 +1,8 @@
 mod block;
+pub(crate) mod termcap;
+
+use alloc::string::{String, ToString};
+use core::fmt;
 
 pub use block::{BlockEvent, BlockKind, BlockMeta, BlockPhase};
 use log::error;
@@ -44,8 +48,8 @@ impl DcsMessageKind {
     }
 }
 
-impl std::fmt::Display for DcsMessageKind {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for DcsMessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let name = match self {
             Self::Block => "block",
         };
@@ -267,6 +271,16 @@ mod tests {
         assert_eq!(block_events, 2, "expected two BlockEvent actions");
     }
 
+    #[test]
+    fn parses_xtgettcap_query() {
+        let actions = parse_with_bytes(b"\x1bP+q436f;544e\x1b\\");
+
+        assert_eq!(
+            actions,
+            [Action::QueryTermcap(vec!["Co".into(), "TN".into()])]
+        );
+    }
+
     #[test]
     fn dcs_message_parse_errors() {
         assert!(matches!(
@@ -0,0 +1,67 @@
+//! XTGETTCAP (`DCS + q Pt ST`) terminfo capability queries.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use log::debug;
+
+use crate::{Action, EscapeActor};
+
+/// Decode the `;` separated, hex encoded capability names of a query.
+pub(crate) fn parse_names(payload: &[u8]) -> Vec<String> {
+    payload
+        .split(|&byte| byte == b';')
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| {
+            let decoded = decode_hex(name);
+            if decoded.is_none() {
+                debug!("[XTGETTCAP] invalid capability name: {name:?}");
+            }
+            decoded
+        })
+        .collect()
+}
+
+pub(crate) fn perform<A: EscapeActor>(actor: &mut A, payload: &[u8]) {
+    let names = parse_names(payload);
+    if !names.is_empty() {
+        actor.handle(Action::QueryTermcap(names));
+    }
+}
+
+fn decode_hex(hex: &[u8]) -> Option<String> {
+    let pairs = hex.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+
+    let bytes = pairs
+        .map(|pair| Some(hex_value(pair[0])? << 4 | hex_value(pair[1])?))
+        .collect::<Option<Vec<u8>>>()?;
+
+    String::from_utf8(bytes).ok()
+}
+
+fn hex_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_hex_names() {
+        assert_eq!(parse_names(b"436f;544E"), ["Co", "TN"]);
+    }
+
+    #[test]
+    fn skips_malformed_names() {
+        assert_eq!(parse_names(b"436;zz;;636f6c6f7273"), ["colors"]);
+    }
+}
@@ -63,6 +63,38 @@ pub(crate) fn perform<A: EscapeActor>(
         // Designate G3 Character Set – US ASCII
         (b'B', [b'+']) => actor
             .handle(Action::ConfigureCharset(Charset::Ascii, CharsetIndex::G3)),
+        // Designate G0 Character Set – British
+        (b'A', [b'(']) => actor.handle(Action::ConfigureCharset(
+            Charset::British,
+            CharsetIndex::G0,
+        )),
+        // Designate G1 Character Set – British
+        (b'A', [b')']) => actor.handle(Action::ConfigureCharset(
+            Charset::British,
+            CharsetIndex::G1,
+        )),
+        // Designate G2 Character Set – British
+        (b'A', [b'*']) => actor.handle(Action::ConfigureCharset(
+            Charset::British,
+            CharsetIndex::G2,
+        )),
+        // Designate G3 Character Set – British
+        (b'A', [b'+']) => actor.handle(Action::ConfigureCharset(
+            Charset::British,
+            CharsetIndex::G3,
+        )),
+        // LS1R - Locking Shift G1 into GR
+        (b'~', []) => {
+            actor.handle(Action::SetRightCharsetIndex(CharsetIndex::G1))
+        },
+        // LS2R - Locking Shift G2 into GR
+        (b'}', []) => {
+            actor.handle(Action::SetRightCharsetIndex(CharsetIndex::G2))
+        },
+        // LS3R - Locking Shift G3 into GR
+        (b'|', []) => {
+            actor.handle(Action::SetRightCharsetIndex(CharsetIndex::G3))
+        },
         // DECALN https://vt100.net/docs/vt510-rm/DECALN.html
         (b'8', [b'#']) => actor.handle(Action::ScreenAlignmentDisplay),
         // ST - String Terminator
@@ -125,7 +157,7 @@ mod tests {
     #[test]
     fn esc_configure_charset_sequences() {
         let actor = RecordingActor::parse(
-            "\x1b(0\x1b)0\x1b*0\x1b+0\x1b(B\x1b)B\x1b*B\x1b+B",
+            "\x1b(0\x1b)0\x1b*0\x1b+0\x1b(B\x1b)B\x1b*B\x1b+B\x1b(A\x1b+A",
         );
 
         assert_eq!(
@@ -151,6 +183,22 @@ mod tests {
                 Action::ConfigureCharset(Charset::Ascii, CharsetIndex::G1),
                 Action::ConfigureCharset(Charset::Ascii, CharsetIndex::G2),
                 Action::ConfigureCharset(Charset::Ascii, CharsetIndex::G3),
+                Action::ConfigureCharset(Charset::British, CharsetIndex::G0),
+                Action::ConfigureCharset(Charset::British, CharsetIndex::G3),
+            ]
+        );
+    }
+
+    #[test]
+    fn esc_locking_shifts_into_gr() {
+        let actor = RecordingActor::parse("\x1b~\x1b}\x1b|");
+
+        assert_eq!(
+            actor.actions,
+            vec![
+                Action::SetRightCharsetIndex(CharsetIndex::G1),
+                Action::SetRightCharsetIndex(CharsetIndex::G2),
+                Action::SetRightCharsetIndex(CharsetIndex::G3),
             ]
         );
     }
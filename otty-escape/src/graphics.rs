@@ -0,0 +1,291 @@
+//! Geometry of inline images sent with the sixel or kitty graphics protocol.
+//!
+//! iTerm2 inline images are handled with the other `OSC 1337` sequences.
+//!
+//! Pixel data is not decoded; only the size and the placement of an image
+//! are extracted so the terminal can reserve the cells it covers.
+
+use log::debug;
+
+use crate::{Action, EscapeActor};
+
+/// Upper bound for the kitty graphics control data kept from an APC string.
+pub(crate) const MAX_KITTY_CONTROL_LEN: usize = 256;
+
+/// Protocol an image was received with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageProtocol {
+    /// DEC sixel graphics (`DCS Pa;Pb;Ph q ... ST`).
+    Sixel,
+    /// Kitty graphics protocol (`APC G ... ST`).
+    Kitty,
+    /// iTerm2 inline files (`OSC 1337 ; File= ... ST`).
+    Iterm2,
+}
+
+/// Extent of an image, in pixels or in terminal cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageSize {
+    Pixels { width: u32, height: u32 },
+    Cells { columns: u32, rows: u32 },
+}
+
+/// Image displayed at the cursor position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImagePlacement {
+    pub protocol: ImageProtocol,
+    /// Image id chosen by the application, if the protocol carries one.
+    pub id: Option<u32>,
+    pub size: ImageSize,
+    /// Whether the cursor moves past the image once it is placed.
+    pub move_cursor: bool,
+}
+
+/// Streaming size scanner for a sixel data string.
+///
+/// Tracks the raster attributes (`"Pan;Pad;Ph;Pv`) and the extent covered
+/// by sixel data, without keeping the payload around.
+#[derive(Debug, Default)]
+pub(crate) struct SixelScanner {
+    command: SixelCommand,
+    params: [u32; 4],
+    param_index: usize,
+    raster: Option<(u32, u32)>,
+    repeat: u32,
+    column: u32,
+    max_column: u32,
+    band: u32,
+    bands: u32,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SixelCommand {
+    #[default]
+    Data,
+    Raster,
+    Color,
+    Repeat,
+}
+
+impl SixelScanner {
+    pub(crate) fn advance(&mut self, byte: u8) {
+        if self.command != SixelCommand::Data {
+            match byte {
+                b'0'..=b'9' => {
+                    let digit = u32::from(byte - b'0');
+                    let param = &mut self.params[self.param_index];
+                    *param = param.saturating_mul(10).saturating_add(digit);
+                    return;
+                },
+                b';' => {
+                    self.param_index = (self.param_index + 1).min(3);
+                    return;
+                },
+                _ => self.finish_command(),
+            }
+        }
+
+        match byte {
+            b'"' => self.start_command(SixelCommand::Raster),
+            b'#' => self.start_command(SixelCommand::Color),
+            b'!' => self.start_command(SixelCommand::Repeat),
+            b'$' => self.column = 0,
+            b'-' => {
+                self.column = 0;
+                self.band += 1;
+            },
+            0x3f..=0x7e => {
+                self.column = self.column.saturating_add(self.repeat.max(1));
+                self.repeat = 0;
+                self.max_column = self.max_column.max(self.column);
+                self.bands = self.bands.max(self.band + 1);
+            },
+            _ => {},
+        }
+    }
+
+    /// Pixel size of the image, or `None` if it covers no pixels.
+    pub(crate) fn size(&mut self) -> Option<ImageSize> {
+        self.finish_command();
+
+        let (raster_width, raster_height) = self.raster.unwrap_or_default();
+        let width = raster_width.max(self.max_column);
+        let height = raster_height.max(self.bands.saturating_mul(6));
+        (width > 0 && height > 0).then_some(ImageSize::Pixels { width, height })
+    }
+
+    fn start_command(&mut self, command: SixelCommand) {
+        self.command = command;
+        self.params = [0; 4];
+        self.param_index = 0;
+    }
+
+    fn finish_command(&mut self) {
+        match self.command {
+            SixelCommand::Raster => {
+                let [_, _, width, height] = self.params;
+                self.raster = Some((width, height));
+            },
+            SixelCommand::Repeat => self.repeat = self.params[0],
+            SixelCommand::Color | SixelCommand::Data => {},
+        }
+        self.command = SixelCommand::Data;
+    }
+}
+
+/// Handle the control data of a kitty graphics command (after `G`).
+///
+/// Only transmit-and-display (`a=T`) and put (`a=p`) commands with a known
+/// size are reported; the payload itself is never inspected.
+pub(crate) fn perform_kitty<A: EscapeActor>(actor: &mut A, control: &[u8]) {
+    let mut action = b't';
+    let mut id = None;
+    let (mut width, mut height) = (None, None);
+    let (mut columns, mut rows) = (None, None);
+    let mut move_cursor = true;
+
+    for pair in control.split(|&byte| byte == b',') {
+        let [key, b'=', value @ ..] = pair else {
+            continue;
+        };
+        let number = parse_u32(value);
+        match key {
+            b'a' => action = value.first().copied().unwrap_or(action),
+            b'i' => id = number,
+            b's' => width = number,
+            b'v' => height = number,
+            b'c' => columns = number,
+            b'r' => rows = number,
+            b'C' => move_cursor = number != Some(1),
+            _ => {},
+        }
+    }
+
+    if !matches!(action, b'T' | b'p') {
+        return;
+    }
+
+    let size = match (columns, rows, width, height) {
+        (Some(columns), Some(rows), ..) => ImageSize::Cells { columns, rows },
+        (_, _, Some(width), Some(height)) => {
+            ImageSize::Pixels { width, height }
+        },
+        _ => {
+            debug!("[kitty graphics] placement without a known size");
+            return;
+        },
+    };
+
+    actor.handle(Action::PlaceImage(ImagePlacement {
+        protocol: ImageProtocol::Kitty,
+        id,
+        size,
+        move_cursor,
+    }));
+}
+
+fn parse_u32(value: &[u8]) -> Option<u32> {
+    if value.is_empty() {
+        return None;
+    }
+
+    value.iter().try_fold(0u32, |acc, &byte| {
+        let digit = (byte as char).to_digit(10)?;
+        acc.checked_mul(10)?.checked_add(digit)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::EscapeParser;
+    use crate::parser::Parser;
+
+    #[derive(Default)]
+    struct RecordingActor {
+        actions: Vec<Action>,
+    }
+
+    impl EscapeActor for RecordingActor {
+        fn handle(&mut self, action: Action) {
+            self.actions.push(action);
+        }
+    }
+
+    fn parse(bytes: &[u8]) -> Vec<Action> {
+        let mut parser: Parser<otty_vte::Parser> = Parser::new();
+        let mut actor = RecordingActor::default();
+        parser.advance(bytes, &mut actor);
+        actor.actions
+    }
+
+    #[test]
+    fn sixel_size_comes_from_raster_attributes() {
+        assert_eq!(
+            parse(b"\x1bPq\"1;1;40;30#0;2;0;0;0#0~~\x1b\\"),
+            vec![Action::PlaceImage(ImagePlacement {
+                protocol: ImageProtocol::Sixel,
+                id: None,
+                size: ImageSize::Pixels {
+                    width: 40,
+                    height: 30
+                },
+                move_cursor: true,
+            })]
+        );
+    }
+
+    #[test]
+    fn sixel_size_falls_back_to_the_data_extent() {
+        let actions = parse(b"\x1bP0;0;0q#1!12~$~~-#2~~~\x1b\\");
+
+        assert_eq!(
+            actions,
+            vec![Action::PlaceImage(ImagePlacement {
+                protocol: ImageProtocol::Sixel,
+                id: None,
+                size: ImageSize::Pixels {
+                    width: 12,
+                    height: 12
+                },
+                move_cursor: true,
+            })]
+        );
+    }
+
+    #[test]
+    fn kitty_placements_report_their_size() {
+        let actions = parse(
+            b"\x1b_Ga=T,f=100,i=7,s=90,v=36;iVBORw0KGgo=\x1b\\\
+              \x1b_Ga=p,i=7,c=10,r=2,C=1\x1b\\\
+              \x1b_Ga=t,i=8,s=9,v=9;AAAA\x1b\\\
+              \x1b_Ga=p,i=7\x1b\\",
+        );
+
+        assert_eq!(
+            actions,
+            vec![
+                Action::PlaceImage(ImagePlacement {
+                    protocol: ImageProtocol::Kitty,
+                    id: Some(7),
+                    size: ImageSize::Pixels {
+                        width: 90,
+                        height: 36
+                    },
+                    move_cursor: true,
+                }),
+                Action::PlaceImage(ImagePlacement {
+                    protocol: ImageProtocol::Kitty,
+                    id: Some(7),
+                    size: ImageSize::Cells {
+                        columns: 10,
+                        rows: 2
+                    },
+                    move_cursor: false,
+                }),
+            ]
+        );
+    }
+}
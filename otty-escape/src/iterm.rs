@@ -0,0 +1,256 @@
+//! iTerm2 proprietary sequences (`OSC 1337`).
+//!
+//! Only the metadata is extracted: the working directory, user variables
+//! and the header of inline files. File contents are never decoded.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use log::debug;
+
+use crate::graphics::{ImagePlacement, ImageProtocol, ImageSize};
+use crate::{Action, EscapeActor};
+
+/// Handle the arguments of an `OSC 1337` sequence (after `1337`).
+pub(crate) fn perform<A: EscapeActor>(actor: &mut A, args: &[&[u8]]) {
+    let Some((&first, rest)) = args.split_first() else {
+        return;
+    };
+    let Some(eq) = first.iter().position(|&byte| byte == b'=') else {
+        debug!("[iterm2] subcommand without a value");
+        return;
+    };
+    let (command, value) = (&first[..eq], &first[eq + 1..]);
+
+    match command {
+        b"CurrentDir" => {
+            // The directory may contain `;`, which split it into arguments.
+            let mut path = Vec::from(value);
+            for arg in rest {
+                path.push(b';');
+                path.extend_from_slice(arg);
+            }
+            match String::from_utf8(path) {
+                Ok(path) => actor.handle(Action::SetCurrentDir(path)),
+                Err(_) => debug!("[iterm2] current directory is not UTF-8"),
+            }
+        },
+        b"SetUserVar" => set_user_var(actor, value),
+        b"File" => place_file(actor, value, rest),
+        _ => debug!(
+            "[iterm2] unsupported subcommand {:?}",
+            String::from_utf8_lossy(command)
+        ),
+    }
+}
+
+/// Handle `SetUserVar=name=base64-value`.
+fn set_user_var<A: EscapeActor>(actor: &mut A, value: &[u8]) {
+    let Some(eq) = value.iter().position(|&byte| byte == b'=') else {
+        debug!("[iterm2] user variable without a value");
+        return;
+    };
+    let name = String::from_utf8(value[..eq].to_vec());
+    let decoded = decode_base64(&value[eq + 1..]).map(String::from_utf8);
+
+    match (name, decoded) {
+        (Ok(name), Some(Ok(value))) if !name.is_empty() => {
+            actor.handle(Action::SetUserVar { name, value })
+        },
+        _ => debug!("[iterm2] malformed user variable"),
+    }
+}
+
+/// Handle `File=key=value;...:payload`, reserving the cells of inline
+/// images whose width and height are declared in the same unit.
+fn place_file<A: EscapeActor>(actor: &mut A, first: &[u8], rest: &[&[u8]]) {
+    let mut inline = false;
+    let mut move_cursor = true;
+    let (mut width, mut height) = (None, None);
+
+    for arg in core::iter::once(first).chain(rest.iter().copied()) {
+        // The payload follows the last argument after a `:`.
+        let arg = arg.split(|&byte| byte == b':').next().unwrap_or(arg);
+        let Some(eq) = arg.iter().position(|&byte| byte == b'=') else {
+            continue;
+        };
+        let (key, value) = (&arg[..eq], &arg[eq + 1..]);
+        match key {
+            b"inline" => inline = value == b"1",
+            b"doNotMoveCursor" => move_cursor = value != b"1",
+            b"width" => width = Dimension::parse(value),
+            b"height" => height = Dimension::parse(value),
+            _ => {},
+        }
+    }
+
+    // Files that are not inline are downloads, not images.
+    if !inline {
+        return;
+    }
+
+    let size = match (width, height) {
+        (Some(Dimension::Cells(columns)), Some(Dimension::Cells(rows))) => {
+            ImageSize::Cells { columns, rows }
+        },
+        (Some(Dimension::Pixels(width)), Some(Dimension::Pixels(height))) => {
+            ImageSize::Pixels { width, height }
+        },
+        _ => {
+            debug!("[iterm2] inline image without a known size");
+            return;
+        },
+    };
+
+    actor.handle(Action::PlaceImage(ImagePlacement {
+        protocol: ImageProtocol::Iterm2,
+        id: None,
+        size,
+        move_cursor,
+    }));
+}
+
+/// Width or height declared in a `File=` header.
+///
+/// Percentages and `auto` depend on the image itself and stay unknown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Cells(u32),
+    Pixels(u32),
+}
+
+impl Dimension {
+    fn parse(value: &[u8]) -> Option<Self> {
+        match value {
+            [digits @ .., b'p', b'x'] => parse_u32(digits).map(Self::Pixels),
+            digits => parse_u32(digits).map(Self::Cells),
+        }
+    }
+}
+
+fn parse_u32(value: &[u8]) -> Option<u32> {
+    if value.is_empty() {
+        return None;
+    }
+
+    value.iter().try_fold(0u32, |acc, &byte| {
+        let digit = (byte as char).to_digit(10)?;
+        acc.checked_mul(10)?.checked_add(digit)
+    })
+}
+
+/// Decode standard base64, with or without padding.
+fn decode_base64(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for &byte in input.iter().take_while(|&&byte| byte != b'=') {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+    use crate::EscapeParser;
+    use crate::parser::Parser;
+
+    #[derive(Default)]
+    struct RecordingActor {
+        actions: Vec<Action>,
+    }
+
+    impl EscapeActor for RecordingActor {
+        fn handle(&mut self, action: Action) {
+            self.actions.push(action);
+        }
+    }
+
+    fn parse(bytes: &[u8]) -> Vec<Action> {
+        let mut parser: Parser<otty_vte::Parser> = Parser::new();
+        let mut actor = RecordingActor::default();
+        parser.advance(bytes, &mut actor);
+        actor.actions
+    }
+
+    #[test]
+    fn current_dir_and_user_vars_are_reported() {
+        assert_eq!(
+            parse(b"\x1b]1337;CurrentDir=/home/me/a;b\x07"),
+            vec![Action::SetCurrentDir("/home/me/a;b".to_string())]
+        );
+        assert_eq!(
+            parse(b"\x1b]1337;SetUserVar=branch=bWFpbg==\x1b\\"),
+            vec![Action::SetUserVar {
+                name: "branch".to_string(),
+                value: "main".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn inline_file_headers_report_their_declared_size() {
+        assert_eq!(
+            parse(
+                b"\x1b]1337;File=name=YS5wbmc=;width=20;height=4;inline=1:iVBORw0KGgo=\x07\
+                  \x1b]1337;File=inline=1;width=90px;height=36px;doNotMoveCursor=1:AA==\x07"
+            ),
+            vec![
+                Action::PlaceImage(ImagePlacement {
+                    protocol: ImageProtocol::Iterm2,
+                    id: None,
+                    size: ImageSize::Cells {
+                        columns: 20,
+                        rows: 4
+                    },
+                    move_cursor: true,
+                }),
+                Action::PlaceImage(ImagePlacement {
+                    protocol: ImageProtocol::Iterm2,
+                    id: None,
+                    size: ImageSize::Pixels {
+                        width: 90,
+                        height: 36
+                    },
+                    move_cursor: false,
+                }),
+            ]
+        );
+
+        // Downloads and images sized by percentage reserve nothing.
+        assert!(
+            parse(
+                b"\x1b]1337;File=width=2;height=2:AA==\x07\
+                    \x1b]1337;File=inline=1;width=50%;height=auto:AA==\x07"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn unknown_subcommands_are_ignored() {
+        assert!(
+            parse(b"\x1b]1337;StealFocus\x07\x1b]1337;SetBadgeFormat=eA==\x07")
+                .is_empty()
+        );
+    }
+}
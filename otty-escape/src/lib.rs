@@ -1,3 +1,7 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 mod actor;
 mod attributes;
 mod charset;
@@ -7,11 +11,16 @@ mod csi;
 mod cursor;
 mod dcs;
 mod esc;
+mod graphics;
 mod hyperlink;
+mod iterm;
 mod keyboard;
 mod mode;
 mod osc;
 mod parser;
+mod progress;
+mod rect;
+mod semantic;
 
 pub use actor::{Action, EscapeActor};
 pub use attributes::CharacterAttribute;
@@ -19,11 +28,15 @@ pub use charset::{Charset, CharsetIndex};
 pub use color::{Color, Rgb, StdColor};
 pub use cursor::{CursorShape, CursorStyle};
 pub use dcs::{BlockEvent, BlockKind, BlockMeta, BlockPhase};
+pub use graphics::{ImagePlacement, ImageProtocol, ImageSize};
 pub use hyperlink::Hyperlink;
 pub use keyboard::*;
 pub use mode::*;
 pub use otty_vte as vte;
-pub use parser::Parser;
+pub use parser::{FastParser, Parser};
+pub use progress::ProgressState;
+pub use rect::{AttributeChangeExtent, RectArea, RectAttrChange};
+pub use semantic::{PromptKind, SemanticPromptMark};
 
 pub trait EscapeParser {
     fn advance<A: EscapeActor>(&mut self, _bytes: &[u8], _actor: &mut A) {}
@@ -1,5 +1,8 @@
-use std::fmt::Write;
-use std::str::FromStr;
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::str::FromStr;
 
 use cursor_icon::CursorIcon;
 use log::debug;
@@ -8,7 +11,9 @@ use crate::color::{Rgb, StdColor, xparse_color};
 use crate::cursor::CursorShape;
 use crate::hyperlink::Hyperlink;
 use crate::parser::parse_number;
-use crate::{Action, EscapeActor};
+use crate::progress::{ProgressState, parse_percent};
+use crate::semantic::SemanticPromptMark;
+use crate::{Action, EscapeActor, iterm};
 
 /// Operating system command with raw arguments.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -27,6 +32,9 @@ enum OSC {
     ResetForegroundColor,
     ResetBackgroundColor,
     ResetCursorColor,
+    ConEmu,
+    SemanticPrompt,
+    Iterm2,
     Unhandled,
 }
 
@@ -36,6 +44,7 @@ impl From<&[u8]> for OSC {
             b"0" | b"2" => Self::SetWindowTitle,
             b"4" => Self::SetColorIndex,
             b"8" => Self::Hyperlink,
+            b"9" => Self::ConEmu,
             // xterm dynamic colors: 10=foreground, 11=background, 12=cursor
             b"10" => Self::SetTextForegroundColor,
             b"11" => Self::SetTextBackgroundColor,
@@ -47,6 +56,8 @@ impl From<&[u8]> for OSC {
             b"110" => Self::ResetForegroundColor,
             b"111" => Self::ResetBackgroundColor,
             b"112" => Self::ResetCursorColor,
+            b"133" => Self::SemanticPrompt,
+            b"1337" => Self::Iterm2,
             _ => Self::Unhandled,
         }
     }
@@ -84,6 +95,11 @@ pub(crate) fn perform<A: EscapeActor>(actor: &mut A, params: &[&[u8]]) {
         OSC::SetTextCursorColor => {
             set_dynamic_std_color(actor, params, StdColor::Cursor)
         },
+        OSC::SemanticPrompt => set_semantic_prompt(actor, params),
+        OSC::Iterm2 => iterm::perform(actor, &params[1..]),
+        OSC::ConEmu if params.get(1) == Some(&&b"4"[..]) => {
+            set_progress(actor, params)
+        },
         _ => unexpected(params),
     }
 }
@@ -185,6 +201,32 @@ fn set_cursor_style<A: EscapeActor>(actor: &mut A, params: &[&[u8]]) {
     unexpected(params);
 }
 
+/// Handle `OSC 9 ; 4 ; state ; progress ST`.
+fn set_progress<A: EscapeActor>(actor: &mut A, params: &[&[u8]]) {
+    let Some(state) = params.get(2).and_then(|s| ProgressState::from_raw(s))
+    else {
+        return unexpected(params);
+    };
+
+    let percent = match state {
+        ProgressState::Remove | ProgressState::Indeterminate => Some(0),
+        _ => parse_percent(params.get(3).copied().unwrap_or_default()),
+    };
+    let Some(percent) = percent else {
+        return unexpected(params);
+    };
+
+    actor.handle(Action::Progress { state, percent });
+}
+
+/// Handle `OSC 133 ; mark [; args] ST`.
+fn set_semantic_prompt<A: EscapeActor>(actor: &mut A, params: &[&[u8]]) {
+    match SemanticPromptMark::from_params(&params[1..]) {
+        Some(mark) => actor.handle(Action::SemanticPrompt(mark)),
+        None => unexpected(params),
+    }
+}
+
 fn reset_indexed_colors<A: EscapeActor>(actor: &mut A, params: &[&[u8]]) {
     if params.len() == 1 || params[1].is_empty() {
         // Reset all
@@ -227,7 +269,7 @@ fn set_dynamic_std_color<A: EscapeActor>(
         }),
         None => {
             // Also try hex forms understood by Rgb::from_str
-            if let Some(rgb) = std::str::from_utf8(spec)
+            if let Some(rgb) = core::str::from_utf8(spec)
                 .ok()
                 .and_then(|s| Rgb::from_str(s).ok())
             {
@@ -259,7 +301,7 @@ fn unexpected(params: &[&[u8]]) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{EscapeParser, Parser};
+    use crate::{EscapeParser, Parser, PromptKind};
 
     #[derive(Default, Debug)]
     struct RecordingActor {
@@ -455,4 +497,118 @@ mod tests {
             assert_eq!(expected, actual)
         }
     }
+
+    #[test]
+    fn conemu_progress_states() {
+        let progress = |state, percent| Action::Progress { state, percent };
+        let cases = vec![
+            ("\x1b]9;4;0\x07", vec![progress(ProgressState::Remove, 0)]),
+            (
+                "\x1b]9;4;1;42\x1b\\",
+                vec![progress(ProgressState::Normal, 42)],
+            ),
+            ("\x1b]9;4;2;7\x07", vec![progress(ProgressState::Error, 7)]),
+            (
+                "\x1b]9;4;3;50\x07",
+                vec![progress(ProgressState::Indeterminate, 0)],
+            ),
+            (
+                "\x1b]9;4;4;90\x07",
+                vec![progress(ProgressState::Paused, 90)],
+            ),
+            ("\x1b]9;4;1\x07", vec![progress(ProgressState::Normal, 0)]),
+            ("\x1b]9;4;5;10\x07", vec![]),
+            ("\x1b]9;4;1;ten\x07", vec![]),
+            ("\x1b]9;1;100\x07", vec![]),
+        ];
+
+        for (input, expected) in cases {
+            let actual = RecordingActor::parse(input).actions;
+            assert_eq!(expected, actual, "{input:?}")
+        }
+    }
+
+    #[test]
+    fn semantic_prompt_marks() {
+        let mark = Action::SemanticPrompt;
+        let cases = vec![
+            (
+                "\x1b]133;A\x07",
+                vec![mark(SemanticPromptMark::PromptStart {
+                    kind: PromptKind::Initial,
+                })],
+            ),
+            (
+                "\x1b]133;B\x07",
+                vec![mark(SemanticPromptMark::CommandStart)],
+            ),
+            (
+                "\x1b]133;C\x07",
+                vec![mark(SemanticPromptMark::OutputStart)],
+            ),
+            (
+                "\x1b]133;D;127\x07",
+                vec![mark(SemanticPromptMark::CommandFinished {
+                    exit_code: Some(127),
+                })],
+            ),
+            (
+                "\x1b]133;D\x07",
+                vec![mark(SemanticPromptMark::CommandFinished {
+                    exit_code: None,
+                })],
+            ),
+            ("\x1b]133;Z\x07", vec![]),
+            ("\x1b]133\x07", vec![]),
+        ];
+
+        for (input, expected) in cases {
+            let actual = RecordingActor::parse(input).actions;
+            assert_eq!(expected, actual, "{input:?}")
+        }
+    }
+
+    #[test]
+    fn semantic_prompt_kinds() {
+        let prompt = |kind| {
+            Action::SemanticPrompt(SemanticPromptMark::PromptStart { kind })
+        };
+        let cases = vec![
+            ("\x1b]133;A;k=i\x07", vec![prompt(PromptKind::Initial)]),
+            (
+                "\x1b]133;A;aid=7;k=c\x07",
+                vec![prompt(PromptKind::Continuation)],
+            ),
+            ("\x1b]133;P;k=s\x1b\\", vec![prompt(PromptKind::Secondary)]),
+            ("\x1b]133;P;k=r\x07", vec![prompt(PromptKind::Right)]),
+            ("\x1b]133;N\x07", vec![prompt(PromptKind::Initial)]),
+            ("\x1b]133;A;k=x\x07", vec![prompt(PromptKind::Initial)]),
+        ];
+
+        for (input, expected) in cases {
+            let actual = RecordingActor::parse(input).actions;
+            assert_eq!(expected, actual, "{input:?}")
+        }
+    }
+
+    #[test]
+    fn conemu_progress_percent_is_clamped() {
+        let cases = [
+            ("\x1b]9;4;1;100\x07", 100),
+            ("\x1b]9;4;1;250\x07", 100),
+            ("\x1b]9;4;1;99999999999\x07", 100),
+            ("\x1b]9;4;4;-20\x07", 0),
+        ];
+
+        for (input, percent) in cases {
+            let actual = RecordingActor::parse(input).actions;
+            assert!(
+                matches!(
+                    actual[..],
+                    [Action::Progress { percent: p, .. }] if p == percent
+                ),
+                "{input:?}: {actual:?}"
+            );
+        }
+    }
 }
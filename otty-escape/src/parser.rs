@@ -1,37 +1,63 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem;
+
 use log::debug;
 use otty_vte::{self, CsiParam, VTActor, VTParser};
 
 use crate::dcs::{self, max_dcs_buffer_len};
+use crate::graphics::{
+    self, ImagePlacement, ImageProtocol, MAX_KITTY_CONTROL_LEN, SixelScanner,
+};
 use crate::{Action, EscapeActor, EscapeParser, control, csi, esc, osc};
 
 struct Performer<'a, A: EscapeActor> {
     actor: &'a mut A,
     state: &'a mut ParserState,
+    /// Contiguous printable characters not yet handed to the actor.
+    text: String,
 }
 
 impl<'a, A: EscapeActor> VTActor for Performer<'a, A> {
     fn print(&mut self, c: char) {
-        self.actor.handle(Action::Print(c));
+        self.text.push(c);
         self.state.last_preceding_char = Some(c)
     }
 
     fn execute(&mut self, byte: u8) {
+        self.flush_text();
         control::perform(byte, self.actor);
     }
 
     fn hook(
         &mut self,
-        _params: &[i64],
-        _intermediates: &[u8],
+        params: &[i64],
+        intermediates: &[u8],
         _ignored_excess_intermediates: bool,
         byte: u8,
     ) {
+        self.flush_text();
         self.state.dcs.buffer.clear();
         self.state.dcs.overflow = false;
-        self.state.dcs.buffer.push(byte);
+
+        // XTGETTCAP: `DCS + q Pt ST`.
+        self.state.dcs.termcap_query =
+            params.is_empty() && intermediates == b"+" && byte == b'q';
+        // Sixel: `DCS Pa;Pb;Ph q data ST`.
+        self.state.dcs.sixel = (intermediates.is_empty() && byte == b'q')
+            .then(SixelScanner::default);
+        if !self.state.dcs.termcap_query && self.state.dcs.sixel.is_none() {
+            self.state.dcs.buffer.push(byte);
+        }
     }
 
     fn put(&mut self, byte: u8) {
+        self.flush_text();
+        if let Some(sixel) = self.state.dcs.sixel.as_mut() {
+            sixel.advance(byte);
+            return;
+        }
+
         if self.state.dcs.overflow {
             return;
         }
@@ -44,17 +70,59 @@ impl<'a, A: EscapeActor> VTActor for Performer<'a, A> {
     }
 
     fn unhook(&mut self) {
-        if self.state.dcs.overflow {
+        self.flush_text();
+        if let Some(mut sixel) = self.state.dcs.sixel.take() {
+            if let Some(size) = sixel.size() {
+                self.actor.handle(Action::PlaceImage(ImagePlacement {
+                    protocol: ImageProtocol::Sixel,
+                    id: None,
+                    size,
+                    move_cursor: true,
+                }));
+            }
+        } else if self.state.dcs.overflow {
             debug!("[dcs unhook] payload exceeded buffer limit, ignoring");
+        } else if self.state.dcs.termcap_query {
+            dcs::termcap::perform(self.actor, self.state.dcs.buffer.as_slice());
         } else {
             dcs::perform(self.actor, self.state.dcs.buffer.as_slice());
         }
 
         self.state.dcs.buffer.clear();
         self.state.dcs.overflow = false;
+        self.state.dcs.termcap_query = false;
+    }
+
+    fn apc_start(&mut self) {
+        self.flush_text();
+        self.state.apc.buffer.clear();
+        self.state.apc.control_done = false;
+    }
+
+    fn apc_put(&mut self, byte: u8) {
+        // Only the kitty graphics control data ahead of the payload is kept.
+        let apc = &mut self.state.apc;
+        if apc.control_done {
+            return;
+        }
+
+        if byte == b';' || apc.buffer.len() >= MAX_KITTY_CONTROL_LEN {
+            apc.control_done = true;
+        } else {
+            apc.buffer.push(byte);
+        }
+    }
+
+    fn apc_end(&mut self) {
+        self.flush_text();
+        if let [b'G', control @ ..] = self.state.apc.buffer.as_slice() {
+            graphics::perform_kitty(self.actor, control);
+        }
+        self.state.apc.buffer.clear();
     }
 
     fn osc_dispatch(&mut self, params: &[&[u8]], _: u8) {
+        self.flush_text();
         osc::perform(self.actor, params);
     }
 
@@ -65,6 +133,7 @@ impl<'a, A: EscapeActor> VTActor for Performer<'a, A> {
         has_ignored_intermediates: bool,
         byte: u8,
     ) {
+        self.flush_text();
         csi::perform(
             self.actor,
             self.state,
@@ -81,6 +150,7 @@ impl<'a, A: EscapeActor> VTActor for Performer<'a, A> {
         _: bool,
         byte: u8,
     ) {
+        self.flush_text();
         esc::perform(self.actor, intermediates, byte);
     }
 }
@@ -88,7 +158,34 @@ impl<'a, A: EscapeActor> VTActor for Performer<'a, A> {
 impl<'a, A: EscapeActor> Performer<'a, A> {
     #[must_use]
     fn new(state: &'a mut ParserState, actor: &'a mut A) -> Self {
-        Self { actor, state }
+        Self {
+            actor,
+            state,
+            text: String::new(),
+        }
+    }
+
+    /// Buffer a run of printable ASCII bytes that bypassed the VT parser.
+    fn print_ascii(&mut self, run: &[u8]) {
+        self.text.extend(run.iter().map(|&byte| char::from(byte)));
+        if let Some(&last) = run.last() {
+            self.state.last_preceding_char = Some(char::from(last));
+        }
+    }
+
+    /// Hand buffered printable characters to the actor, batching runs
+    /// longer than a single character.
+    fn flush_text(&mut self) {
+        let mut chars = self.text.chars();
+        let action = match (chars.next(), chars.next()) {
+            (None, _) => return,
+            (Some(c), None) => {
+                self.text.clear();
+                Action::Print(c)
+            },
+            _ => Action::PrintString(mem::take(&mut self.text)),
+        };
+        self.actor.handle(action);
     }
 }
 
@@ -96,6 +193,7 @@ impl<'a, A: EscapeActor> Performer<'a, A> {
 pub(crate) struct ParserState {
     pub last_preceding_char: Option<char>,
     dcs: DcsState,
+    apc: ApcState,
 }
 
 /// Internal state for handling DCS passthrough sequences.
@@ -103,6 +201,16 @@ pub(crate) struct ParserState {
 struct DcsState {
     buffer: Vec<u8>,
     overflow: bool,
+    termcap_query: bool,
+    sixel: Option<SixelScanner>,
+}
+
+/// Internal state for APC strings.
+#[derive(Default)]
+struct ApcState {
+    buffer: Vec<u8>,
+    /// Whether the control data was terminated or truncated.
+    control_done: bool,
 }
 /// High-level escape sequence parser that forwards semantic events to an
 /// [`EscapeActor`](crate::actor::EscapeActor).
@@ -120,6 +228,7 @@ impl<P: VTParser + Default> EscapeParser for Parser<P> {
     fn advance<A: EscapeActor>(&mut self, bytes: &[u8], actor: &mut A) {
         let mut performer = Performer::new(&mut self.state, actor);
         self.vt.advance(bytes, &mut performer);
+        performer.flush_text();
     }
 }
 
@@ -133,6 +242,62 @@ impl<P: VTParser + Default> Parser<P> {
     }
 }
 
+/// Escape parser tuned for output that is mostly printable ASCII.
+///
+/// While the VT parser sits in the ground state, runs of printable ASCII
+/// bytes are handed to the actor directly instead of stepping the state
+/// machine byte by byte. Everything else goes through the wrapped VT parser,
+/// so the emitted actions match [`Parser`] exactly.
+#[derive(Default)]
+pub struct FastParser<P: VTParser + Default> {
+    vt: P,
+    state: ParserState,
+}
+
+impl<P: VTParser + Default> EscapeParser for FastParser<P> {
+    fn advance<A: EscapeActor>(&mut self, bytes: &[u8], actor: &mut A) {
+        let mut performer = Performer::new(&mut self.state, actor);
+        let mut rest = bytes;
+
+        while !rest.is_empty() {
+            let run = if self.vt.is_ground() {
+                rest.iter()
+                    .position(|&byte| !is_printable_ascii(byte))
+                    .unwrap_or(rest.len())
+            } else {
+                0
+            };
+
+            if run > 0 {
+                performer.print_ascii(&rest[..run]);
+                rest = &rest[run..];
+                continue;
+            }
+
+            // Step the state machine one byte at a time until it settles
+            // back into ground.
+            self.vt.advance(&rest[..1], &mut performer);
+            rest = &rest[1..];
+        }
+
+        performer.flush_text();
+    }
+}
+
+impl<P: VTParser + Default> FastParser<P> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            vt: P::default(),
+            state: ParserState::default(),
+        }
+    }
+}
+
+fn is_printable_ascii(byte: u8) -> bool {
+    (0x20..=0x7e).contains(&byte)
+}
+
 pub(crate) fn parse_number(input: &[u8]) -> Option<u8> {
     if input.is_empty() {
         return None;
@@ -148,6 +313,63 @@ pub(crate) fn parse_number(input: &[u8]) -> Option<u8> {
 mod tests {
     use super::*;
 
+    #[derive(Default)]
+    struct RecordingActor {
+        actions: Vec<Action>,
+    }
+
+    impl EscapeActor for RecordingActor {
+        fn handle(&mut self, action: Action) {
+            self.actions.push(action);
+        }
+    }
+
+    #[test]
+    fn contiguous_text_is_batched_between_controls() {
+        let mut parser: Parser<otty_vte::Parser> = Parser::new();
+        let mut actor = RecordingActor::default();
+
+        parser.advance("ab\x08c界\x1b[2Pd\re".as_bytes(), &mut actor);
+        parser.advance(b"fg", &mut actor);
+
+        assert_eq!(
+            actor.actions,
+            vec![
+                Action::PrintString("ab".into()),
+                Action::Backspace,
+                Action::PrintString("c界".into()),
+                Action::DeleteChars(2),
+                Action::Print('d'),
+                Action::CarriageReturn,
+                Action::Print('e'),
+                Action::PrintString("fg".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fast_parser_matches_default_parser_across_chunk_splits() {
+        let input = "plain \x1b[1;31mred\x1b[0m\tПривет, 界\r\n\x1b]0;t\x07\
+                     x\x1b[3bz\x1bP+q544e\x1b\\\x1b7end\x08!"
+            .as_bytes();
+
+        for split in 0..=input.len() {
+            let (head, tail) = input.split_at(split);
+
+            let mut expected = RecordingActor::default();
+            let mut parser: Parser<otty_vte::Parser> = Parser::new();
+            parser.advance(head, &mut expected);
+            parser.advance(tail, &mut expected);
+
+            let mut actual = RecordingActor::default();
+            let mut parser: FastParser<otty_vte::Parser> = FastParser::new();
+            parser.advance(head, &mut actual);
+            parser.advance(tail, &mut actual);
+
+            assert_eq!(actual.actions, expected.actions, "split at {split}");
+        }
+    }
+
     #[test]
     fn parse_invalid_number() {
         assert_eq!(parse_number(b"1abc"), None);
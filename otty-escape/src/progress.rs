@@ -0,0 +1,49 @@
+//! Progress reports sent with the ConEmu `OSC 9;4` sequence.
+
+/// State of the progress indicator requested by the application.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ProgressState {
+    /// Hide the progress indicator.
+    #[default]
+    Remove,
+    /// Show the progress in the default style.
+    Normal,
+    /// Show the progress in the error style.
+    Error,
+    /// Show activity without a known completion percentage.
+    Indeterminate,
+    /// Show the progress in the paused style.
+    Paused,
+}
+
+impl ProgressState {
+    /// State selected by the first parameter of `OSC 9;4`.
+    pub(crate) fn from_raw(value: &[u8]) -> Option<Self> {
+        match value {
+            b"0" => Some(Self::Remove),
+            b"1" => Some(Self::Normal),
+            b"2" => Some(Self::Error),
+            b"3" => Some(Self::Indeterminate),
+            b"4" => Some(Self::Paused),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a progress percentage, clamping it to `0..=100`.
+///
+/// A missing value stands for zero.
+pub(crate) fn parse_percent(value: &[u8]) -> Option<u8> {
+    let (negative, digits) = match value {
+        [b'-', digits @ ..] => (true, digits),
+        digits => (false, digits),
+    };
+
+    let mut percent = 0u32;
+    for &byte in digits {
+        let digit = (byte as char).to_digit(10)?;
+        percent = percent.saturating_mul(10).saturating_add(digit);
+    }
+
+    Some(if negative { 0 } else { percent.min(100) as u8 })
+}
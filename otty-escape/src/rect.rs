@@ -0,0 +1,40 @@
+//! Rectangular area operations (DECFRA, DECCARA, DECRARA and DECSACE).
+
+use alloc::vec::Vec;
+
+use crate::CharacterAttribute;
+
+/// Screen area addressed by a rectangular area operation.
+///
+/// Bounds are 0-based and inclusive. A missing bottom or right bound
+/// extends the area to the last line or column of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RectArea {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: Option<usize>,
+    pub right: Option<usize>,
+}
+
+/// Cells affected by DECCARA and DECRARA, selected with DECSACE.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AttributeChangeExtent {
+    /// Every cell from the top-left corner to the bottom-right corner, in
+    /// reading order, like a text selection.
+    #[default]
+    Stream,
+    /// Only the cells inside the rectangle.
+    Rectangle,
+}
+
+/// Attribute change applied to a rectangular area.
+///
+/// [`CharacterAttribute::Reset`] stands for all of bold, underline,
+/// reverse and hidden.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RectAttrChange {
+    /// Set or cancel the attributes (DECCARA).
+    Set(Vec<CharacterAttribute>),
+    /// Toggle the attributes (DECRARA).
+    Reverse(Vec<CharacterAttribute>),
+}
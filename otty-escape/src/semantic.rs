@@ -0,0 +1,71 @@
+//! Semantic prompt marks sent with the `OSC 133` shell integration sequence.
+
+use core::str;
+
+/// Role of a prompt, from the `k=` option of a prompt start mark.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PromptKind {
+    /// Prompt of a new command (`k=i`, the default).
+    #[default]
+    Initial,
+    /// Prompt continuing a multi-line command, such as `PS2` (`k=c`).
+    Continuation,
+    /// Secondary prompt of the same command line (`k=s`).
+    Secondary,
+    /// Prompt drawn on the right side of the line (`k=r`).
+    Right,
+}
+
+impl PromptKind {
+    fn from_raw(value: &[u8]) -> Option<Self> {
+        match value {
+            b"i" => Some(Self::Initial),
+            b"c" => Some(Self::Continuation),
+            b"s" => Some(Self::Secondary),
+            b"r" => Some(Self::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Shell lifecycle point announced by an `OSC 133` mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticPromptMark {
+    /// The shell starts drawing a prompt (`A`, `N` or `P`).
+    PromptStart { kind: PromptKind },
+    /// The prompt ends and the user types the command (`B`).
+    CommandStart,
+    /// The command was submitted and its output follows (`C`).
+    OutputStart,
+    /// The command finished, with its exit status if reported (`D`).
+    CommandFinished { exit_code: Option<i32> },
+}
+
+impl SemanticPromptMark {
+    /// Mark selected by the parameters following `133`.
+    ///
+    /// Options other than the prompt kind, such as `aid=...`, are ignored.
+    pub(crate) fn from_params(params: &[&[u8]]) -> Option<Self> {
+        let (&mark, args) = params.split_first()?;
+        match mark {
+            b"A" | b"N" | b"P" => {
+                let kind = args
+                    .iter()
+                    .find_map(|arg| arg.strip_prefix(b"k="))
+                    .and_then(PromptKind::from_raw)
+                    .unwrap_or_default();
+                Some(Self::PromptStart { kind })
+            },
+            b"B" => Some(Self::CommandStart),
+            b"C" => Some(Self::OutputStart),
+            b"D" => {
+                let exit_code = args
+                    .first()
+                    .and_then(|value| str::from_utf8(value).ok())
+                    .and_then(|value| value.parse().ok());
+                Some(Self::CommandFinished { exit_code })
+            },
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Drives the parser using only `core` and `alloc`.
+//!
+//! Run with `cargo test -p otty-escape --no-default-features` to exercise
+//! the `no_std` build of `otty-escape` and `otty-vte`.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use otty_escape::{Action, ClearMode, EscapeActor, EscapeParser, Parser};
+
+#[derive(Default)]
+struct CollectingActor {
+    actions: Vec<Action>,
+}
+
+impl EscapeActor for CollectingActor {
+    fn handle(&mut self, action: Action) {
+        self.actions.push(action);
+    }
+}
+
+#[test]
+fn parses_bytes_into_actions_without_std() {
+    let mut parser: Parser<otty_escape::vte::Parser> = Parser::new();
+    let mut actor = CollectingActor::default();
+
+    parser.advance(b"a\x1b[3;5H\x1b[2J\x1b]2;title\x07", &mut actor);
+
+    assert_eq!(
+        actor.actions,
+        [
+            Action::Print('a'),
+            Action::Goto(2, 4),
+            Action::ClearScreen(ClearMode::All),
+            Action::SetWindowTitle(String::from("title")),
+        ]
+    );
+}
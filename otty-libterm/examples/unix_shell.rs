@@ -196,6 +196,7 @@ mod unix_shell {
         underline: RenderUnderline,
         reverse: bool,
         strike: bool,
+        overline: bool,
         foreground: AnsiColor,
         background: AnsiColor,
     }
@@ -218,14 +219,13 @@ mod unix_shell {
             };
 
             Self {
-                bold: flags.intersects(
-                    Flags::BOLD | Flags::BOLD_ITALIC | Flags::DIM_BOLD,
-                ),
-                dim: flags.intersects(Flags::DIM | Flags::DIM_BOLD),
-                italic: flags.intersects(Flags::ITALIC | Flags::BOLD_ITALIC),
+                bold: flags.contains(Flags::BOLD),
+                dim: flags.contains(Flags::DIM),
+                italic: flags.contains(Flags::ITALIC),
                 underline,
                 reverse: flags.contains(Flags::INVERSE),
                 strike: flags.contains(Flags::STRIKEOUT),
+                overline: flags.contains(Flags::OVERLINE),
                 foreground: cell.cell.fg,
                 background: cell.cell.bg,
             }
@@ -262,6 +262,9 @@ mod unix_shell {
         if attrs.strike {
             write!(out, ";9")?;
         }
+        if attrs.overline {
+            write!(out, ";53")?;
+        }
 
         write_color(out, attrs.foreground, palette, true)?;
         write_color(out, attrs.background, palette, false)?;
@@ -21,11 +21,11 @@ pub enum Error {
     #[error("runtime command channel closed")]
     RuntimeChannelClosed,
 
-    #[error("event channel is full")]
-    EventChannelFull,
-
     #[error("event channel closed")]
     EventChannelClosed,
+
+    #[error("invalid terminal options: {0}")]
+    InvalidOptions(String),
 }
 
 /// Convenient result alias for fallible operations in this crate.
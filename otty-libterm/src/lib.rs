@@ -12,6 +12,7 @@
 //!   and exposes a high-level API (`TerminalRequest` / `TerminalEvent`).
 //! - [`Runtime`], a small `mio`-based event loop that remains available as a
 //!   low-level driver stub for future tasks.
+//! - [`MultiRuntime`], the same loop driving many terminals on one thread.
 //!
 //! Front-ends usually:
 //! 1. Construct a PTY [`pty::Session`], an [`escape::EscapeParser`] instance
@@ -21,25 +22,36 @@
 //!    readiness model, and drain [`TerminalEvent`]s from [`TerminalEvents`].
 
 mod error;
+mod multi_runtime;
 mod runtime;
 mod terminal;
 
 pub use error::{Error, Result};
+pub use multi_runtime::{DriverEnd, DriverId, MultiRuntime};
 pub use otty_escape as escape;
 pub use otty_pty as pty;
 pub use otty_surface as surface;
-pub use runtime::{Driver, Runtime, RuntimeHooks, RuntimeRequestProxy};
+pub use runtime::{
+    Driver, Runtime, RuntimeHooks, RuntimeRequestProxy, RuntimeWaker,
+};
 pub use terminal::builder::{
-    DefaultParser, DefaultSurface, RuntimeTerminal, Terminal, TerminalBuilder,
+    DefaultParser, DefaultSurface, FastParser, RuntimeTerminal, Terminal,
+    TerminalBuilder,
 };
 pub use terminal::channel::{
-    ChannelConfig, ChannelRecvError, ChannelSendError, ChannelTryRecvError,
-    TerminalEvents, TerminalHandle,
+    ChannelConfig, ChannelRecvError, ChannelRecvTimeoutError, ChannelSendError,
+    ChannelTryRecvError, DirtyToken, EventSink, RequestSource, TerminalEvents,
+    TerminalHandle,
 };
+pub use terminal::clock::{Clock, SystemClock};
+pub use terminal::metrics::TerminalMetrics;
 pub use terminal::options::TerminalOptions;
+pub use terminal::profile::TermProfile;
 pub use terminal::size::TerminalSize;
+pub use terminal::termcap::Termcaps;
 pub use terminal::{
-    SnapshotArc, TerminalEngine, TerminalEvent, TerminalRequest,
+    OutputMode, SnapshotArc, TerminalEngine, TerminalErrorKind, TerminalEvent,
+    TerminalRequest,
 };
 
 #[cfg(test)]
@@ -47,6 +59,8 @@ pub(crate) mod tests {
     use std::collections::VecDeque;
     use std::io;
     use std::process::ExitStatus;
+    use std::sync::{Mutex, PoisonError};
+    use std::time::{Duration, Instant};
 
     use super::*;
 
@@ -77,15 +91,18 @@ pub(crate) mod tests {
 
     #[derive(Default)]
     pub struct FakeSession {
-        reads: VecDeque<Vec<u8>>,
+        reads: VecDeque<std::result::Result<Vec<u8>, io::ErrorKind>>,
         exit_status: Option<ExitStatus>,
+        exit_error: Option<io::ErrorKind>,
+        write_error: Option<io::ErrorKind>,
+        pub writes: Vec<Vec<u8>>,
     }
 
     impl FakeSession {
         pub fn with_reads(reads: Vec<Vec<u8>>) -> Self {
             Self {
-                reads: reads.into(),
-                exit_status: None,
+                reads: reads.into_iter().map(Ok).collect(),
+                ..Self::default()
             }
         }
 
@@ -93,6 +110,27 @@ pub(crate) mod tests {
             self.exit_status = Some(status);
             self
         }
+
+        pub fn with_exit_error(mut self, kind: io::ErrorKind) -> Self {
+            self.exit_error = Some(kind);
+            self
+        }
+
+        pub fn push_read(&mut self, chunk: &[u8]) {
+            self.reads.push_back(Ok(chunk.to_vec()));
+        }
+
+        pub fn push_read_error(&mut self, kind: io::ErrorKind) {
+            self.reads.push_back(Err(kind));
+        }
+
+        pub fn fail_writes(&mut self, kind: io::ErrorKind) {
+            self.write_error = Some(kind);
+        }
+
+        pub fn set_exit(&mut self, status: ExitStatus) {
+            self.exit_status = Some(status);
+        }
     }
 
     impl pty::Session for FakeSession {
@@ -100,14 +138,18 @@ pub(crate) mod tests {
             &mut self,
             buf: &mut [u8],
         ) -> std::result::Result<usize, pty::SessionError> {
-            if let Some(mut chunk) = self.reads.pop_front() {
-                let len = chunk.len().min(buf.len());
-                buf[..len].copy_from_slice(&chunk[..len]);
-                if len < chunk.len() {
-                    chunk.drain(0..len);
-                    self.reads.push_front(chunk);
-                }
-                return Ok(len);
+            match self.reads.pop_front() {
+                Some(Ok(mut chunk)) => {
+                    let len = chunk.len().min(buf.len());
+                    buf[..len].copy_from_slice(&chunk[..len]);
+                    if len < chunk.len() {
+                        chunk.drain(0..len);
+                        self.reads.push_front(Ok(chunk));
+                    }
+                    return Ok(len);
+                },
+                Some(Err(kind)) => return Err(io::Error::from(kind).into()),
+                None => {},
             }
             Err(io::Error::from(io::ErrorKind::WouldBlock).into())
         }
@@ -116,6 +158,10 @@ pub(crate) mod tests {
             &mut self,
             input: &[u8],
         ) -> std::result::Result<usize, pty::SessionError> {
+            if let Some(kind) = self.write_error {
+                return Err(io::Error::from(kind).into());
+            }
+            self.writes.push(input.to_vec());
             Ok(input.len())
         }
 
@@ -134,10 +180,68 @@ pub(crate) mod tests {
             &mut self,
         ) -> std::result::Result<Option<ExitStatus>, pty::SessionError>
         {
+            if let Some(kind) = self.exit_error {
+                return Err(io::Error::from(kind).into());
+            }
             Ok(self.exit_status)
         }
     }
 
+    impl pty::Pollable for FakeSession {
+        fn register(
+            &mut self,
+            _registry: &mio::Registry,
+            _interest: mio::Interest,
+            _io_token: mio::Token,
+            _child_token: mio::Token,
+        ) -> std::result::Result<(), pty::SessionError> {
+            Ok(())
+        }
+
+        fn reregister(
+            &mut self,
+            _registry: &mio::Registry,
+            _interest: mio::Interest,
+            _io_token: mio::Token,
+            _child_token: mio::Token,
+        ) -> std::result::Result<(), pty::SessionError> {
+            Ok(())
+        }
+
+        fn deregister(
+            &mut self,
+            _registry: &mio::Registry,
+        ) -> std::result::Result<(), pty::SessionError> {
+            Ok(())
+        }
+    }
+
+    /// Clock that only moves when a test advances it.
+    #[derive(Debug)]
+    pub struct ManualClock {
+        now: Mutex<Instant>,
+    }
+
+    impl ManualClock {
+        pub fn new() -> Self {
+            Self {
+                now: Mutex::new(Instant::now()),
+            }
+        }
+
+        pub fn advance(&self, by: Duration) {
+            let mut now =
+                self.now.lock().unwrap_or_else(PoisonError::into_inner);
+            *now += by;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+
     pub struct EioSession {
         exit_status: Option<ExitStatus>,
     }
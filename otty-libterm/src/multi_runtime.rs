@@ -0,0 +1,456 @@
+//! Event loop driving several terminals on one `mio` poll.
+
+use std::io::ErrorKind;
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use mio::{Events, Interest, Poll, Registry, Token, Waker};
+
+use crate::TerminalRequest;
+use crate::error::{Error, Result};
+use crate::runtime::{Driver, RuntimeWaker};
+
+const WAKE_TOKEN: Token = Token(0);
+const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+/// Identifier of a driver registered with a [`MultiRuntime`].
+///
+/// Ids of removed drivers are reused by later insertions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DriverId(usize);
+
+impl DriverId {
+    /// Token of the session I/O handle.
+    fn io_token(self) -> Token {
+        Token(1 + self.0 * 2)
+    }
+
+    /// Token of the child process handle.
+    fn child_token(self) -> Token {
+        Token(2 + self.0 * 2)
+    }
+
+    /// Driver owning `token`, and whether it is the child process token.
+    fn from_token(token: Token) -> Option<(Self, bool)> {
+        let index = token.0.checked_sub(1)?;
+        Some((Self(index / 2), index % 2 == 1))
+    }
+}
+
+/// Why a driver left a [`MultiRuntime`].
+#[derive(Debug)]
+pub enum DriverEnd {
+    /// The child process exited.
+    Exited(ExitStatus),
+    /// A shutdown request was queued through [`MultiRuntime::queue`].
+    ShutDown,
+    /// The driver failed; the other drivers keep running.
+    Failed(Error),
+}
+
+struct Slot<D> {
+    driver: D,
+    interest: Interest,
+    shutdown_requested: bool,
+}
+
+impl<D: Driver> Slot<D> {
+    /// Dispatch one readiness event to the driver.
+    fn handle_event(
+        &mut self,
+        event: &mio::event::Event,
+        is_child: bool,
+    ) -> Result<Option<DriverEnd>> {
+        if is_child {
+            let status = self.driver.check_child_exit()?;
+            return Ok(status.map(DriverEnd::Exited));
+        }
+
+        if event.is_readable() {
+            self.driver.on_readable()?;
+        }
+        if event.is_writable() {
+            self.driver.on_writable()?;
+        }
+        Ok(None)
+    }
+
+    /// Run the driver's maintenance after the poll and refresh its
+    /// interest mask.
+    fn maintain(
+        &mut self,
+        registry: &Registry,
+        id: DriverId,
+    ) -> Result<Option<DriverEnd>> {
+        self.driver.tick()?;
+        if let Some(status) = self.driver.check_child_exit()? {
+            return Ok(Some(DriverEnd::Exited(status)));
+        }
+        if self.shutdown_requested && !self.driver.is_shutting_down() {
+            return Ok(Some(DriverEnd::ShutDown));
+        }
+
+        let mut interest = Interest::READABLE;
+        if self.driver.has_pending_output() {
+            interest |= Interest::WRITABLE;
+        }
+        if interest != self.interest {
+            self.driver.reregister(
+                registry,
+                interest,
+                id.io_token(),
+                id.child_token(),
+            )?;
+            self.interest = interest;
+        }
+        Ok(None)
+    }
+}
+
+/// Mio-backed loop multiplexing many [`Driver`]s on one thread.
+///
+/// Each driver gets its own pair of tokens, so readiness is dispatched to
+/// the driver it belongs to. Drivers keep their own request and event
+/// channels; the runtime only pumps them. A driver whose child exits, that
+/// is shut down or that fails is deregistered and dropped without
+/// disturbing the others.
+pub struct MultiRuntime<D> {
+    poll: Poll,
+    events: Events,
+    waker: Arc<Waker>,
+    slots: Vec<Option<Slot<D>>>,
+}
+
+impl<D: Driver> MultiRuntime<D> {
+    /// Construct a new event loop with the default capacity.
+    pub fn new() -> Result<Self> {
+        Self::with_capacity(DEFAULT_EVENT_CAPACITY)
+    }
+
+    /// Construct a new event loop with a custom event capacity.
+    pub fn with_capacity(capacity: usize) -> Result<Self> {
+        let poll = Poll::new()?;
+        let waker = Arc::new(Waker::new(poll.registry(), WAKE_TOKEN)?);
+        Ok(Self {
+            poll,
+            events: Events::with_capacity(capacity),
+            waker,
+            slots: Vec::new(),
+        })
+    }
+
+    /// Acquire a waker that interrupts a blocked poll from any thread.
+    pub fn waker(&self) -> RuntimeWaker {
+        RuntimeWaker::new(Arc::clone(&self.waker))
+    }
+
+    /// Number of registered drivers.
+    pub fn len(&self) -> usize {
+        self.slots.iter().flatten().count()
+    }
+
+    /// Whether no driver is registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Register `driver` with the poll and start pumping it.
+    pub fn insert(&mut self, mut driver: D) -> Result<DriverId> {
+        let index = self
+            .slots
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or(self.slots.len());
+        let id = DriverId(index);
+
+        let interest = Interest::READABLE;
+        driver.register(
+            self.poll.registry(),
+            interest,
+            id.io_token(),
+            id.child_token(),
+        )?;
+        driver.set_waker(self.waker());
+
+        let slot = Some(Slot {
+            driver,
+            interest,
+            shutdown_requested: false,
+        });
+        if index == self.slots.len() {
+            self.slots.push(slot);
+        } else {
+            self.slots[index] = slot;
+        }
+        Ok(id)
+    }
+
+    /// Deregister the driver `id` and hand it back.
+    pub fn remove(&mut self, id: DriverId) -> Result<Option<D>> {
+        let Some(mut slot) = self.slots.get_mut(id.0).and_then(Option::take)
+        else {
+            return Ok(None);
+        };
+
+        slot.driver.deregister(self.poll.registry())?;
+        Ok(Some(slot.driver))
+    }
+
+    /// Queue `request` on the driver `id`.
+    ///
+    /// A shutdown request removes the driver at the end of the next
+    /// [`Self::poll_once`], once it had a chance to process the request
+    /// and a graceful shutdown has written the pending input.
+    pub fn queue(
+        &mut self,
+        id: DriverId,
+        request: TerminalRequest,
+    ) -> Result<()> {
+        let Some(slot) = self.slots.get_mut(id.0).and_then(Option::as_mut)
+        else {
+            return Ok(());
+        };
+
+        if request.is_shutdown() {
+            slot.shutdown_requested = true;
+        }
+        slot.driver.queue(request)
+    }
+
+    /// Borrow the driver `id`.
+    pub fn get_mut(&mut self, id: DriverId) -> Option<&mut D> {
+        self.slots
+            .get_mut(id.0)
+            .and_then(Option::as_mut)
+            .map(|slot| &mut slot.driver)
+    }
+
+    /// Run one poll iteration over every driver.
+    ///
+    /// Waits at most `timeout`, or until the earliest driver deadline.
+    /// Returns the drivers whose child exited, that were shut down or that
+    /// failed during the iteration; they are already deregistered and
+    /// dropped. Only a failure of the poll itself is returned as an error.
+    pub fn poll_once(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(DriverId, DriverEnd)>> {
+        let mut ended = Vec::new();
+        for (index, entry) in self.slots.iter_mut().enumerate() {
+            if let Some(slot) = entry
+                && let Err(err) = slot.driver.tick()
+            {
+                ended.push((DriverId(index), DriverEnd::Failed(err)));
+            }
+        }
+
+        let now = Instant::now();
+        let deadline = self
+            .slots
+            .iter()
+            .flatten()
+            .filter_map(|slot| slot.driver.next_deadline())
+            .min()
+            .map(|deadline| deadline.saturating_duration_since(now));
+        let timeout = match (timeout, deadline) {
+            (Some(timeout), Some(deadline)) => Some(timeout.min(deadline)),
+            (timeout, deadline) => timeout.or(deadline),
+        };
+        self.wait(timeout)?;
+
+        for event in self.events.iter() {
+            let Some((id, is_child)) = DriverId::from_token(event.token())
+            else {
+                continue;
+            };
+            let Some(slot) = self.slots.get_mut(id.0).and_then(Option::as_mut)
+            else {
+                continue;
+            };
+            if has_ended(&ended, id) {
+                continue;
+            }
+
+            match slot.handle_event(event, is_child) {
+                Ok(Some(end)) => ended.push((id, end)),
+                Ok(None) => {},
+                Err(err) => ended.push((id, DriverEnd::Failed(err))),
+            }
+        }
+
+        let registry = self.poll.registry();
+        for (index, entry) in self.slots.iter_mut().enumerate() {
+            let id = DriverId(index);
+            let Some(slot) = entry else {
+                continue;
+            };
+            if has_ended(&ended, id) {
+                continue;
+            }
+
+            match slot.maintain(registry, id) {
+                Ok(Some(end)) => ended.push((id, end)),
+                Ok(None) => {},
+                Err(err) => ended.push((id, DriverEnd::Failed(err))),
+            }
+        }
+
+        for (id, _) in &ended {
+            if let Err(err) = self.remove(*id) {
+                debug!("failed to deregister ended driver {id:?}: {err}");
+            }
+        }
+        Ok(ended)
+    }
+
+    /// Drive the registered drivers until every one of them ended.
+    pub fn run(&mut self) -> Result<()> {
+        while !self.is_empty() {
+            self.poll_once(None)?;
+        }
+        Ok(())
+    }
+
+    fn wait(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.events.clear();
+        loop {
+            match self.poll.poll(&mut self.events, timeout) {
+                Ok(()) => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(Error::Poll(err)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn has_ended(ended: &[(DriverId, DriverEnd)], id: DriverId) -> bool {
+    ended.iter().any(|(ended, _)| *ended == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surface::{Surface, SurfaceConfig};
+    use crate::tests::{FakeSession, StubParser, collect_events, exit_ok};
+    use crate::{
+        TerminalEngine, TerminalEvent, TerminalEvents, TerminalHandle,
+        TerminalOptions, TerminalSize,
+    };
+
+    type Engine = TerminalEngine<FakeSession, StubParser, Surface>;
+
+    fn engine(
+        session: FakeSession,
+    ) -> (Engine, TerminalHandle, TerminalEvents) {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        TerminalEngine::new(
+            session,
+            StubParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )
+        .expect("engine")
+    }
+
+    fn frame_count(events: &[TerminalEvent]) -> usize {
+        events
+            .iter()
+            .filter(|event| matches!(event, TerminalEvent::Frame { .. }))
+            .count()
+    }
+
+    #[test]
+    fn drivers_receive_their_own_events_and_exit_independently() -> Result<()> {
+        let (first, first_handle, first_events) =
+            engine(FakeSession::default());
+        let (second, second_handle, second_events) =
+            engine(FakeSession::default().with_exit(exit_ok()));
+
+        let mut runtime = MultiRuntime::new()?;
+        let first_id = runtime.insert(first)?;
+        let second_id = runtime.insert(second)?;
+        assert_ne!(first_id, second_id);
+
+        first_handle.request_frame().expect("request channel open");
+        second_handle.request_frame().expect("request channel open");
+        let ended = runtime.poll_once(Some(Duration::ZERO))?;
+        assert!(matches!(
+            ended.as_slice(),
+            [(id, DriverEnd::Exited(status))]
+                if *id == second_id && *status == exit_ok()
+        ));
+        assert_eq!(runtime.len(), 1);
+        assert!(runtime.get_mut(second_id).is_none());
+
+        let second = collect_events(&second_events);
+        assert_eq!(frame_count(&second), 1);
+        assert!(matches!(
+            second.last(),
+            Some(TerminalEvent::ChildExit { .. })
+        ));
+        assert_eq!(frame_count(&collect_events(&first_events)), 1);
+
+        first_handle.request_frame().expect("request channel open");
+        assert!(runtime.poll_once(Some(Duration::ZERO))?.is_empty());
+        assert_eq!(frame_count(&collect_events(&first_events)), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn failing_driver_is_removed_without_stopping_the_others() -> Result<()> {
+        let failing = FakeSession::default().with_exit_error(ErrorKind::Other);
+        let (healthy, healthy_handle, healthy_events) =
+            engine(FakeSession::default());
+        let (failing, _failing_handle, _failing_events) = engine(failing);
+
+        let mut runtime = MultiRuntime::new()?;
+        let healthy_id = runtime.insert(healthy)?;
+        let failing_id = runtime.insert(failing)?;
+
+        let ended = runtime.poll_once(Some(Duration::ZERO))?;
+        assert!(matches!(
+            ended.as_slice(),
+            [(id, DriverEnd::Failed(_))] if *id == failing_id
+        ));
+        assert!(runtime.get_mut(failing_id).is_none());
+        assert_eq!(runtime.len(), 1);
+
+        healthy_handle
+            .request_frame()
+            .expect("request channel open");
+        assert!(runtime.poll_once(Some(Duration::ZERO))?.is_empty());
+        assert!(runtime.get_mut(healthy_id).is_some());
+        assert_eq!(frame_count(&collect_events(&healthy_events)), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn queued_shutdown_removes_the_driver() -> Result<()> {
+        let (first, _first_handle, _first_events) =
+            engine(FakeSession::default());
+        let (second, _second_handle, _second_events) =
+            engine(FakeSession::default());
+
+        let mut runtime = MultiRuntime::new()?;
+        let first_id = runtime.insert(first)?;
+        let second_id = runtime.insert(second)?;
+
+        runtime.queue(second_id, TerminalRequest::Shutdown)?;
+        let ended = runtime.poll_once(Some(Duration::ZERO))?;
+        assert!(matches!(
+            ended.as_slice(),
+            [(id, DriverEnd::ShutDown)] if *id == second_id
+        ));
+        assert!(runtime.get_mut(first_id).is_some());
+        assert_eq!(runtime.len(), 1);
+
+        Ok(())
+    }
+}
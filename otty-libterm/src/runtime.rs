@@ -83,10 +83,27 @@ pub trait Driver {
 
     /// Deadline for the next tick; used to compute poll timeout.
     fn next_deadline(&self) -> Option<Instant>;
+
+    /// Whether a requested shutdown is still in progress, keeping the
+    /// driver in the loop until it finishes.
+    fn is_shutting_down(&self) -> bool {
+        false
+    }
+
+    /// Receive the waker that interrupts the runtime poll.
+    ///
+    /// Drivers with their own request channels use it to make sends
+    /// processed promptly instead of on the next poll timeout.
+    fn set_waker(&mut self, _waker: RuntimeWaker) {}
 }
 
 /// Hooks that run immediately before and after each poll iteration.
 pub trait RuntimeHooks<T: Driver + ?Sized> {
+    /// Called once before the loop starts with the runtime waker.
+    fn register_waker(&mut self, _waker: &RuntimeWaker) -> Result<()> {
+        Ok(())
+    }
+
     /// Called right before polling for OS events.
     fn before_poll(&mut self, _driver: &mut T) -> Result<()> {
         Ok(())
@@ -100,6 +117,23 @@ pub trait RuntimeHooks<T: Driver + ?Sized> {
 
 impl<T: Driver + ?Sized> RuntimeHooks<T> for () {}
 
+/// Cloneable token that interrupts a blocked [`Runtime`] poll.
+#[derive(Clone, Debug)]
+pub struct RuntimeWaker {
+    waker: Arc<Waker>,
+}
+
+impl RuntimeWaker {
+    pub(crate) fn new(waker: Arc<Waker>) -> Self {
+        Self { waker }
+    }
+
+    /// Wake the runtime loop so it drains pending requests.
+    pub fn wake(&self) -> Result<()> {
+        self.waker.wake().map_err(Error::Wake)
+    }
+}
+
 /// Proxy that used by front-ends to submit [`TerminalRequest`]s to the terminal.
 pub struct RuntimeRequestProxy {
     sender: Sender<TerminalRequest>,
@@ -121,8 +155,12 @@ impl RuntimeRequestProxy {
         self.sender
             .send(request)
             .map_err(|_| Error::RuntimeChannelClosed)?;
-        self.waker.wake().map_err(Error::Wake)?;
-        Ok(())
+        self.wake()
+    }
+
+    /// Wake the runtime loop without submitting a request.
+    pub fn wake(&self) -> Result<()> {
+        self.waker.wake().map_err(Error::Wake)
     }
 }
 
@@ -168,12 +206,23 @@ impl Runtime {
         }
     }
 
+    /// Acquire a waker that interrupts a blocked poll from any thread.
+    pub fn waker(&self) -> RuntimeWaker {
+        RuntimeWaker {
+            waker: Arc::clone(&self.waker),
+        }
+    }
+
     /// Drive a [`Driver`] until shutdown or child exit.
     pub fn run<D, H>(&mut self, driver: &mut D, mut hooks: H) -> Result<()>
     where
         D: Driver,
         H: RuntimeHooks<D>,
     {
+        let waker = self.waker();
+        hooks.register_waker(&waker)?;
+        driver.set_waker(waker);
+
         let mut interest = Interest::READABLE;
         driver.register(
             self.poll.registry(),
@@ -207,6 +256,7 @@ impl Runtime {
                             driver.on_writable()?;
                         }
                     },
+                    #[allow(clippy::collapsible_match)]
                     PTY_CHILD_TOKEN => {
                         if driver.check_child_exit()?.is_some() {
                             exit_detected = true;
@@ -241,7 +291,9 @@ impl Runtime {
                 interest = desired_interest;
             }
 
-            if exit_detected || shutdown_requested {
+            if exit_detected
+                || (shutdown_requested && !driver.is_shutting_down())
+            {
                 break;
             }
         }
@@ -272,7 +324,7 @@ impl Runtime {
         loop {
             match self.command_rx.try_recv() {
                 Ok(request) => {
-                    if matches!(request, TerminalRequest::Shutdown) {
+                    if request.is_shutdown() {
                         shutdown_requested = true;
                     }
                     driver.queue(request)?;
@@ -289,11 +341,13 @@ impl Runtime {
     }
 }
 
-impl<P, E, S> Driver for TerminalEngine<P, E, S>
+impl<P, E, S, K, R> Driver for TerminalEngine<P, E, S, K, R>
 where
     P: crate::pty::Session + Pollable,
     E: crate::escape::EscapeParser,
     S: crate::surface::SurfaceActor + crate::surface::SurfaceModel,
+    K: crate::EventSink,
+    R: crate::RequestSource,
 {
     fn register(
         &mut self,
@@ -348,6 +402,14 @@ where
     fn next_deadline(&self) -> Option<Instant> {
         TerminalEngine::next_deadline(self)
     }
+
+    fn is_shutting_down(&self) -> bool {
+        TerminalEngine::is_shutting_down(self)
+    }
+
+    fn set_waker(&mut self, waker: RuntimeWaker) {
+        TerminalEngine::set_runtime_waker(self, Some(waker));
+    }
 }
 
 #[cfg(test)]
@@ -355,13 +417,15 @@ mod tests {
     use std::process::ExitStatus;
     use std::sync::Arc;
     use std::sync::atomic::{AtomicBool, Ordering};
-    use std::time::Instant;
+    use std::thread;
+    use std::time::{Duration, Instant};
 
     use mio::{Interest, Registry, Token};
 
-    use super::{Driver, Runtime};
+    use super::{Driver, Runtime, RuntimeHooks, RuntimeWaker};
+    use crate::terminal::channel::WakerSlot;
     use crate::tests::exit_ok;
-    use crate::{Result, TerminalRequest};
+    use crate::{Result, TerminalHandle, TerminalRequest};
 
     #[derive(Default)]
     struct StubDriver {
@@ -375,6 +439,8 @@ mod tests {
         writable_count: usize,
         tick_count: usize,
         deregistered: bool,
+        handle_requests: Option<flume::Receiver<TerminalRequest>>,
+        waker: WakerSlot,
     }
 
     impl StubDriver {
@@ -432,6 +498,18 @@ mod tests {
 
         fn tick(&mut self) -> Result<()> {
             self.tick_count += 1;
+
+            let received = self
+                .handle_requests
+                .as_ref()
+                .map(|rx| rx.try_iter().collect::<Vec<_>>())
+                .unwrap_or_default();
+            for request in received {
+                if request.is_shutdown() {
+                    self.mark_exit(exit_ok());
+                }
+                self.requests.push(request);
+            }
             Ok(())
         }
 
@@ -454,6 +532,21 @@ mod tests {
         fn next_deadline(&self) -> Option<Instant> {
             self.deadline
         }
+
+        fn set_waker(&mut self, waker: RuntimeWaker) {
+            self.waker.set(Some(waker));
+        }
+    }
+
+    struct RecordingHooks {
+        registered: Arc<AtomicBool>,
+    }
+
+    impl RuntimeHooks<StubDriver> for RecordingHooks {
+        fn register_waker(&mut self, _waker: &RuntimeWaker) -> Result<()> {
+            self.registered.store(true, Ordering::SeqCst);
+            Ok(())
+        }
     }
 
     #[test]
@@ -519,6 +612,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn handle_send_wakes_blocked_runtime() -> Result<()> {
+        let mut runtime = Runtime::new()?;
+        let (tx, rx) = flume::unbounded();
+        let mut driver = StubDriver {
+            // Far enough out that only a wakeup can end the poll in time.
+            deadline: Some(Instant::now() + Duration::from_secs(30)),
+            handle_requests: Some(rx),
+            ..Default::default()
+        };
+        let handle = TerminalHandle::new(tx, driver.waker.clone());
+
+        let sender = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            handle.send(TerminalRequest::Shutdown)
+        });
+
+        let registered = Arc::new(AtomicBool::new(false));
+        let hooks = RecordingHooks {
+            registered: Arc::clone(&registered),
+        };
+        let started = Instant::now();
+        runtime.run(&mut driver, hooks)?;
+
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(sender.join().is_ok_and(|sent| sent.is_ok()));
+        assert!(
+            driver
+                .requests
+                .iter()
+                .any(|req| matches!(req, TerminalRequest::Shutdown))
+        );
+        assert!(registered.load(Ordering::SeqCst));
+
+        Ok(())
+    }
+
     #[test]
     fn runtime_ticks_with_deadline() -> Result<()> {
         let mut runtime = Runtime::new()?;
@@ -4,12 +4,19 @@ use crate::surface::{BlockSurface, SurfaceActor, SurfaceConfig, SurfaceModel};
 use crate::terminal::TerminalEngine;
 use crate::terminal::channel::{ChannelConfig, TerminalEvents, TerminalHandle};
 use crate::terminal::options::TerminalOptions;
+use crate::terminal::profile::TermProfile;
 use crate::terminal::size::TerminalSize;
-use crate::{Result, Runtime};
+use crate::{Error, Result, Runtime};
 
 /// Default escape parser used by preset builders.
 pub type DefaultParser = escape::Parser<escape::vte::Parser>;
 
+/// Parser that skips the escape state machine for plain ASCII output.
+///
+/// Produces the same surface state as [`DefaultParser`]; plug it in with
+/// [`TerminalBuilder::with_parser`].
+pub type FastParser = escape::FastParser<escape::vte::Parser>;
+
 /// Default surface used by preset builders.
 pub type DefaultSurface = BlockSurface;
 
@@ -37,7 +44,7 @@ pub struct TerminalBuilder<P, E, S> {
 
 enum SessionSource<P> {
     Provided(Option<P>),
-    Factory(Box<dyn FnMut(TerminalSize) -> Result<P> + Send>),
+    Factory(Box<dyn FnMut(TerminalSize, TermProfile) -> Result<P> + Send>),
 }
 
 impl<P, E, S> TerminalBuilder<P, E, S>
@@ -105,11 +112,59 @@ where
         self
     }
 
+    /// Bound the event channel to `capacity` events, `None` for unbounded.
+    pub fn with_event_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.options.channel_config.event_capacity = capacity;
+        self
+    }
+
+    /// Bound the request channel to `capacity` requests, `None` for
+    /// unbounded.
+    pub fn with_request_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.options.channel_config.request_capacity = capacity;
+        self
+    }
+
+    /// Let events queue without limit, the default.
+    pub fn with_unbounded_events(self) -> Self {
+        self.with_event_capacity(None)
+    }
+
+    /// Hold at most `capacity` undelivered events.
+    ///
+    /// Frames give way to other events once the channel fills; see
+    /// [`crate::TerminalEvent`].
+    pub fn with_bounded_events(self, capacity: usize) -> Self {
+        self.with_event_capacity(Some(capacity))
+    }
+
     /// Override the temporary read buffer capacity used for PTY reads.
     pub fn with_read_buffer_capacity(mut self, capacity: usize) -> Self {
         self.options.read_buffer_capacity = capacity;
         self
     }
+
+    /// Present the terminal to child programs with `profile`.
+    ///
+    /// See [`TerminalOptions::with_term_profile`].
+    pub fn with_term_profile(mut self, profile: TermProfile) -> Self {
+        self.options = self.options.with_term_profile(profile);
+        self
+    }
+
+    /// Emit at most `fps` output frames per second.
+    ///
+    /// See [`TerminalOptions::max_fps`].
+    pub fn with_max_fps(mut self, fps: u32) -> Self {
+        self.options.max_fps = Some(fps);
+        self
+    }
+
+    /// Emit memory pressure events once the surface grows past `bytes`.
+    pub fn with_memory_pressure_threshold(mut self, bytes: usize) -> Self {
+        self.options.memory_pressure_threshold = Some(bytes);
+        self
+    }
 }
 
 impl<P, E, S> TerminalBuilder<P, E, S>
@@ -119,6 +174,10 @@ where
     S: SurfaceActor + SurfaceModel,
 {
     /// Build a terminal engine, events receiver, and request handle.
+    ///
+    /// Fails with [`Error::InvalidOptions`] before spawning the session when
+    /// the options or size cannot work; a zero read buffer capacity falls
+    /// back to the default instead.
     pub fn build(self) -> Result<Terminal<P, E, S>> {
         let TerminalBuilder {
             session,
@@ -128,11 +187,13 @@ where
             size,
         } = self;
 
+        validate(&options, size)?;
+
         if options.read_buffer_capacity == 0 {
             options.read_buffer_capacity = 1024;
         }
 
-        let session = spawn_session(session, size)?;
+        let session = spawn_session(session, size, options.term_profile)?;
 
         let (mut engine, handle, events) =
             TerminalEngine::new(session, parser, surface, options)?;
@@ -192,7 +253,44 @@ where
     }
 }
 
-fn spawn_session<P>(source: SessionSource<P>, size: TerminalSize) -> Result<P>
+/// Reject option combinations the engine cannot run with.
+fn validate(options: &TerminalOptions, size: TerminalSize) -> Result<()> {
+    let invalid = |reason: String| Err(Error::InvalidOptions(reason));
+
+    let channels = &options.channel_config;
+    if channels.event_capacity == Some(0) {
+        return invalid("event channel capacity must be at least 1".into());
+    }
+    if channels.request_capacity == Some(0) {
+        return invalid("request channel capacity must be at least 1".into());
+    }
+    if options
+        .frame_interval
+        .is_some_and(|interval| interval.is_zero())
+    {
+        return invalid(
+            "frame interval must be non-zero; use None to disable batching"
+                .into(),
+        );
+    }
+    if options.max_fps == Some(0) {
+        return invalid("max fps must be at least 1".into());
+    }
+    if size.rows == 0 || size.cols == 0 {
+        return invalid(format!(
+            "terminal size must be non-empty, got {}x{}",
+            size.cols, size.rows
+        ));
+    }
+
+    Ok(())
+}
+
+fn spawn_session<P>(
+    source: SessionSource<P>,
+    size: TerminalSize,
+    profile: TermProfile,
+) -> Result<P>
 where
     P: Session,
 {
@@ -204,15 +302,16 @@ where
                 ))
             })
         },
-        SessionSource::Factory(mut factory) => factory(size),
+        SessionSource::Factory(mut factory) => factory(size, profile),
     }
 }
 
 fn local_factory(
     builder: pty::LocalSessionBuilder,
-) -> Box<dyn FnMut(TerminalSize) -> Result<pty::LocalSession> + Send> {
+) -> Box<dyn FnMut(TerminalSize, TermProfile) -> Result<pty::LocalSession> + Send>
+{
     let mut builder = Some(builder);
-    Box::new(move |size: TerminalSize| {
+    Box::new(move |size: TerminalSize, profile: TermProfile| {
         let builder = builder
             .take()
             .ok_or_else(|| {
@@ -221,15 +320,28 @@ fn local_factory(
                 ))
             })?
             .with_size(size.into());
+
+        // Variables chosen by the caller win over the profile.
+        let builder = if builder.has_env("TERM") {
+            builder
+        } else {
+            builder.with_env("TERM", profile.term())
+        };
+        let builder = match profile.colorterm() {
+            _ if builder.has_env("COLORTERM") => builder,
+            Some(colorterm) => builder.with_env("COLORTERM", colorterm),
+            None => builder.with_env_remove("COLORTERM"),
+        };
         builder.spawn().map_err(crate::Error::from)
     })
 }
 
 fn ssh_factory(
     builder: pty::SSHSessionBuilder,
-) -> Box<dyn FnMut(TerminalSize) -> Result<pty::SSHSession> + Send> {
+) -> Box<dyn FnMut(TerminalSize, TermProfile) -> Result<pty::SSHSession> + Send>
+{
     let mut builder = Some(builder);
-    Box::new(move |size: TerminalSize| {
+    Box::new(move |size: TerminalSize, profile: TermProfile| {
         let builder = builder
             .take()
             .ok_or_else(|| {
@@ -237,7 +349,149 @@ fn ssh_factory(
                     "ssh session builder already consumed",
                 ))
             })?
-            .with_size(size.into());
+            .with_size(size.into())
+            .with_term(profile.term());
         builder.spawn().map_err(crate::Error::from)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn local_builder()
+    -> TerminalBuilder<pty::LocalSession, DefaultParser, DefaultSurface> {
+        TerminalBuilder::from(pty::local("/bin/sh"))
+    }
+
+    #[test]
+    fn zero_channel_capacity_is_rejected() {
+        let result = local_builder()
+            .with_channel_config(ChannelConfig::bounded(0))
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidOptions(reason)) if reason.contains("event")
+        ));
+    }
+
+    #[test]
+    fn channel_capacity_setters_fill_the_channel_config() {
+        let builder = local_builder()
+            .with_bounded_events(8)
+            .with_request_capacity(Some(32));
+        assert_eq!(
+            builder.options.channel_config,
+            ChannelConfig {
+                event_capacity: Some(8),
+                request_capacity: Some(32),
+            }
+        );
+
+        let builder =
+            builder.with_unbounded_events().with_request_capacity(None);
+        assert_eq!(builder.options.channel_config, ChannelConfig::default());
+
+        let builder = builder.with_event_capacity(Some(4));
+        assert_eq!(builder.options.channel_config.event_capacity, Some(4));
+    }
+
+    #[test]
+    fn zero_frame_interval_is_rejected() {
+        let options = TerminalOptions {
+            frame_interval: Some(Duration::ZERO),
+            ..TerminalOptions::default()
+        };
+
+        let result = local_builder().with_options(options).build();
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidOptions(reason)) if reason.contains("frame")
+        ));
+    }
+
+    #[test]
+    fn zero_max_fps_is_rejected() {
+        let options = TerminalOptions {
+            max_fps: Some(0),
+            ..TerminalOptions::default()
+        };
+
+        let result = local_builder().with_options(options).build();
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidOptions(reason)) if reason.contains("fps")
+        ));
+    }
+
+    #[test]
+    fn empty_size_is_rejected() {
+        let size = TerminalSize {
+            rows: 0,
+            ..TerminalSize::default()
+        };
+
+        let result = local_builder().with_size(size).build();
+
+        assert!(matches!(result, Err(Error::InvalidOptions(_))));
+    }
+
+    #[test]
+    fn term_profile_sets_the_session_environment() {
+        let builder = pty::local("/bin/sh")
+            .with_arg("-c")
+            .with_arg("printf '%s:%s' \"$TERM\" \"${COLORTERM-unset}\"");
+        let builder = TerminalBuilder::from(builder)
+            .with_term_profile(TermProfile::Xterm);
+
+        assert_session_prints(builder, "xterm:unset");
+    }
+
+    #[test]
+    fn caller_environment_wins_over_the_term_profile() {
+        let builder = pty::local("/bin/sh")
+            .with_arg("-c")
+            .with_arg("printf '%s:%s' \"$TERM\" \"${COLORTERM-unset}\"")
+            .with_env("TERM", "vt220");
+        let builder = TerminalBuilder::from(builder)
+            .with_term_profile(TermProfile::Xterm256Color);
+
+        assert_session_prints(builder, "vt220:truecolor");
+    }
+
+    fn assert_session_prints(
+        builder: TerminalBuilder<
+            pty::LocalSession,
+            DefaultParser,
+            DefaultSurface,
+        >,
+        expected: &str,
+    ) {
+        let (mut engine, _handle, _events) = match builder.build() {
+            Ok(terminal) => terminal,
+            Err(Error::Session(err)) => {
+                eprintln!("skipping test; PTY allocation failed: {err}");
+                return;
+            },
+            Err(err) => panic!("failed to build terminal: {err}"),
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut output = String::new();
+        while !output.contains(expected) && Instant::now() < deadline {
+            if engine.on_readable().is_err() {
+                break;
+            }
+            let snapshot = engine.surface.snapshot_owned();
+            output = snapshot.view().cells.iter().map(|c| c.cell.c).collect();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(output.contains(expected), "unexpected output: {output}");
+    }
+}
@@ -1,16 +1,24 @@
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 
 use flume::{
-    Receiver, Sender, TryRecvError as FlumeTryRecvError,
-    TrySendError as FlumeTrySendError,
+    Receiver, RecvTimeoutError as FlumeRecvTimeoutError, Sender,
+    TryRecvError as FlumeTryRecvError, TrySendError as FlumeTrySendError,
 };
+use log::debug;
 
+use crate::runtime::RuntimeWaker;
 use crate::terminal::{TerminalEvent, TerminalRequest};
 
 const DEFAULT_WRITE_CHUNK: usize = 4096;
 
 /// Channel sizing options for terminal request/event plumbing.
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct ChannelConfig {
     /// Capacity for the event channel (`None` means unbounded).
     pub event_capacity: Option<usize>,
@@ -48,9 +56,70 @@ pub enum ChannelTryRecvError {
     Disconnected,
 }
 
+/// Error returned when a receive with a timeout fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChannelRecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
 pub type ChannelSendResult = std::result::Result<(), ChannelSendError>;
 pub type ChannelRecvResult<T> = std::result::Result<T, ChannelRecvError>;
 pub type ChannelTryRecvResult<T> = std::result::Result<T, ChannelTryRecvError>;
+pub type ChannelRecvTimeoutResult<T> =
+    std::result::Result<T, ChannelRecvTimeoutError>;
+
+/// Destination for the [`TerminalEvent`]s emitted by a
+/// [`crate::TerminalEngine`].
+pub trait EventSink {
+    /// Deliver an event without blocking.
+    fn send(&mut self, event: TerminalEvent) -> ChannelSendResult;
+
+    /// Number of events the sink accepts right now, `None` if unbounded.
+    ///
+    /// The engine drops frames rather than leave no room for the other
+    /// events of a flush.
+    fn remaining_capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Source of the [`TerminalRequest`]s drained by a
+/// [`crate::TerminalEngine`].
+pub trait RequestSource {
+    /// Take the next queued request without blocking.
+    fn try_recv(&mut self) -> Option<TerminalRequest>;
+}
+
+impl EventSink for Sender<TerminalEvent> {
+    fn send(&mut self, event: TerminalEvent) -> ChannelSendResult {
+        self.try_send(event).map_err(map_send_error)
+    }
+
+    fn remaining_capacity(&self) -> Option<usize> {
+        self.capacity()
+            .map(|capacity| capacity.saturating_sub(self.len()))
+    }
+}
+
+impl RequestSource for Receiver<TerminalRequest> {
+    fn try_recv(&mut self) -> Option<TerminalRequest> {
+        Receiver::try_recv(self).ok()
+    }
+}
+
+impl EventSink for Vec<TerminalEvent> {
+    fn send(&mut self, event: TerminalEvent) -> ChannelSendResult {
+        self.push(event);
+        Ok(())
+    }
+}
+
+impl RequestSource for VecDeque<TerminalRequest> {
+    fn try_recv(&mut self) -> Option<TerminalRequest> {
+        self.pop_front()
+    }
+}
 
 /// Helper for batching/coalescing write requests and chunking large pastes.
 pub struct WriteBatcher<'a> {
@@ -99,20 +168,132 @@ impl<'a> WriteBatcher<'a> {
     }
 }
 
+/// Waker shared between an engine and all of its request handles.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct WakerSlot {
+    waker: Arc<Mutex<Option<RuntimeWaker>>>,
+}
+
+impl WakerSlot {
+    /// Replace the waker notified after each request.
+    pub(crate) fn set(&self, waker: Option<RuntimeWaker>) {
+        if let Ok(mut slot) = self.waker.lock() {
+            *slot = waker;
+        }
+    }
+
+    /// Interrupt the runtime poll, if a runtime is attached.
+    fn wake(&self) {
+        let Ok(slot) = self.waker.lock() else {
+            return;
+        };
+
+        // The request is already queued; a failed wake only delays it
+        // until the next tick.
+        if let Some(Err(err)) = slot.as_ref().map(RuntimeWaker::wake) {
+            debug!("failed to wake terminal runtime: {err}");
+        }
+    }
+}
+
+/// Coalesced "events are queued" flag shared between an engine and its
+/// [`TerminalEvents`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DirtySignal {
+    inner: Arc<DirtyInner>,
+}
+
+#[derive(Debug, Default)]
+struct DirtyInner {
+    dirty: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl DirtySignal {
+    /// Flag that events were queued, waking a pending [`DirtyToken`].
+    pub(crate) fn notify(&self) {
+        // Already dirty: any pending token was woken by the first notify.
+        if self.inner.dirty.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let waker = self.inner.waker.lock().ok().and_then(|mut w| w.take());
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Clear the flag, returning whether it was set.
+    fn take(&self) -> bool {
+        self.inner.dirty.swap(false, Ordering::AcqRel)
+    }
+}
+
+/// Future resolving once events were queued since the flag was last taken.
+///
+/// Returned by [`TerminalEvents::dirty_token`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct DirtyToken {
+    signal: DirtySignal,
+}
+
+impl Future for DirtyToken {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.signal.take() {
+            return Poll::Ready(());
+        }
+
+        if let Ok(mut waker) = self.signal.inner.waker.lock() {
+            *waker = Some(cx.waker().clone());
+        }
+
+        // Check again: a notify between the first check and the waker
+        // registration would otherwise be missed.
+        if self.signal.take() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
 /// Cloneable handle for queuing [`TerminalRequest`]s.
 #[derive(Clone, Debug)]
 pub struct TerminalHandle {
     sender: Sender<TerminalRequest>,
+    waker: WakerSlot,
 }
 
 impl TerminalHandle {
-    pub(crate) fn new(sender: Sender<TerminalRequest>) -> Self {
-        Self { sender }
+    pub(crate) fn new(
+        sender: Sender<TerminalRequest>,
+        waker: WakerSlot,
+    ) -> Self {
+        Self { sender, waker }
     }
 
     /// Try to send a request without blocking.
+    ///
+    /// Wakes the attached [`crate::Runtime`] so the request is processed
+    /// without waiting for the next poll timeout.
     pub fn send(&self, request: TerminalRequest) -> ChannelSendResult {
-        self.sender.try_send(request).map_err(map_send_error)
+        self.sender.try_send(request).map_err(map_send_error)?;
+        self.waker.wake();
+        Ok(())
+    }
+
+    /// Attach the waker notified after every request sent through this
+    /// handle or any of its clones.
+    pub fn set_waker(&self, waker: RuntimeWaker) {
+        self.waker.set(Some(waker));
+    }
+
+    /// Ask the engine to emit a fresh, fully damaged frame.
+    pub fn request_frame(&self) -> ChannelSendResult {
+        self.send(TerminalRequest::RequestFrame)
     }
 
     /// Send a large payload by chunking it into multiple `WriteBytes` requests.
@@ -148,7 +329,9 @@ impl TerminalHandle {
         self.sender
             .send_async(request)
             .await
-            .map_err(|_| ChannelSendError::Disconnected)
+            .map_err(|_| ChannelSendError::Disconnected)?;
+        self.waker.wake();
+        Ok(())
     }
 }
 
@@ -156,15 +339,40 @@ impl TerminalHandle {
 #[derive(Debug, Clone)]
 pub struct TerminalEvents {
     receiver: Arc<Receiver<TerminalEvent>>,
+    dirty: DirtySignal,
 }
 
 impl TerminalEvents {
-    pub(crate) fn new(receiver: Receiver<TerminalEvent>) -> Self {
+    pub(crate) fn new(
+        receiver: Receiver<TerminalEvent>,
+        dirty: DirtySignal,
+    ) -> Self {
         Self {
             receiver: Arc::new(receiver),
+            dirty,
+        }
+    }
+
+    /// Wait until events were queued, without receiving them.
+    ///
+    /// Lets a renderer await a single wakeup and then drain with
+    /// [`Self::try_recv`]. Events queued before the token is awaited
+    /// resolve it right away, so no wakeup is lost between draining and
+    /// awaiting again. Only one task should await the token at a time.
+    pub fn dirty_token(&self) -> DirtyToken {
+        DirtyToken {
+            signal: self.dirty.clone(),
         }
     }
 
+    /// Clear the dirty flag, returning whether events were queued since it
+    /// was last cleared.
+    ///
+    /// Suited for renderers polling on a timer.
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.take()
+    }
+
     /// Blocking receive.
     pub fn recv(&self) -> ChannelRecvResult<TerminalEvent> {
         self.receiver
@@ -172,6 +380,16 @@ impl TerminalEvents {
             .map_err(|_| ChannelRecvError::Disconnected)
     }
 
+    /// Blocking receive that gives up once `timeout` has elapsed.
+    pub fn recv_timeout(
+        &self,
+        timeout: Duration,
+    ) -> ChannelRecvTimeoutResult<TerminalEvent> {
+        self.receiver
+            .recv_timeout(timeout)
+            .map_err(map_recv_timeout_error)
+    }
+
     /// Async receive.
     pub async fn recv_async(&self) -> ChannelRecvResult<TerminalEvent> {
         self.receiver
@@ -223,14 +441,90 @@ pub(crate) fn map_try_recv_error(
     }
 }
 
+pub(crate) fn map_recv_timeout_error(
+    err: FlumeRecvTimeoutError,
+) -> ChannelRecvTimeoutError {
+    match err {
+        FlumeRecvTimeoutError::Timeout => ChannelRecvTimeoutError::Timeout,
+        FlumeRecvTimeoutError::Disconnected => {
+            ChannelRecvTimeoutError::Disconnected
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::task::Wake;
+
     use super::*;
 
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn dirty_token_wakes_once_events_are_queued() {
+        let (_, rx) = flume::unbounded();
+        let signal = DirtySignal::default();
+        let events = TerminalEvents::new(rx, signal.clone());
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut token = events.dirty_token();
+        assert!(Pin::new(&mut token).poll(&mut cx).is_pending());
+
+        signal.notify();
+        signal.notify();
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert!(Pin::new(&mut token).poll(&mut cx).is_ready());
+        assert!(Pin::new(&mut token).poll(&mut cx).is_pending());
+
+        // An edge between draining and the next await is kept.
+        signal.notify();
+        let mut token = events.dirty_token();
+        assert!(Pin::new(&mut token).poll(&mut cx).is_ready());
+        assert!(!events.take_dirty());
+    }
+
+    #[test]
+    fn recv_timeout_tells_timeouts_from_disconnects() {
+        let (tx, rx) = flume::unbounded();
+        let events = TerminalEvents::new(rx, DirtySignal::default());
+        let timeout = Duration::from_millis(10);
+
+        let sender = std::thread::spawn(move || {
+            tx.send(TerminalEvent::Bell).expect("event sent");
+            tx
+        });
+        assert!(matches!(
+            events.recv_timeout(Duration::from_secs(5)),
+            Ok(TerminalEvent::Bell)
+        ));
+
+        let tx = sender.join().expect("sender thread");
+        assert!(matches!(
+            events.recv_timeout(timeout),
+            Err(ChannelRecvTimeoutError::Timeout)
+        ));
+
+        drop(tx);
+        assert!(matches!(
+            events.recv_timeout(timeout),
+            Err(ChannelRecvTimeoutError::Disconnected)
+        ));
+    }
+
     #[test]
     fn batcher_chunks_large_payloads() {
         let (tx, rx) = flume::bounded(10);
-        let handle = TerminalHandle::new(tx);
+        let handle = TerminalHandle::new(tx, WakerSlot::default());
         let mut batcher = handle.batcher_with_chunk_size(3);
         batcher.push(b"abcdef");
         batcher.push(b"ghi");
@@ -0,0 +1,26 @@
+//! Time source for the engine's timers.
+
+use std::fmt;
+use std::time::Instant;
+
+/// Source of the current time for sync-mode timeouts, frame batching and
+/// other timers of the engine.
+///
+/// Hosts normally keep the [`SystemClock`]; tests can plug in a clock
+/// they advance by hand to drive timeouts deterministically. Runtimes
+/// still sleep on the real clock, so a custom clock only shifts when the
+/// engine considers a deadline reached.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Current instant.
+    fn now(&self) -> Instant;
+}
+
+/// Clock backed by [`Instant::now`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
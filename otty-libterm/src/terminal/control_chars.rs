@@ -0,0 +1,26 @@
+//! Visible rendering of the control characters received from the child.
+
+/// Glyph shown for `ESC`, which would otherwise read as `^[`.
+const ESCAPE_GLYPH: &str = "\u{241B}";
+
+/// Rewrite `bytes` into `out` so that control characters print as text.
+///
+/// C0 controls and `DEL` become caret notation (`^C`, `^?`) and `ESC`
+/// becomes `␛`, so escape sequences show up verbatim instead of being
+/// interpreted. Tabs are kept, and line feeds start a new line at the
+/// first column so the output stays readable. Bytes of multi-byte UTF-8
+/// characters are never in the control range and pass through untouched.
+pub(crate) fn visualize(bytes: &[u8], out: &mut Vec<u8>) {
+    out.clear();
+    out.reserve(bytes.len());
+    for &byte in bytes {
+        match byte {
+            b'\t' => out.push(byte),
+            b'\n' => out.extend_from_slice(b"\r\n"),
+            0x1b => out.extend_from_slice(ESCAPE_GLYPH.as_bytes()),
+            0x00..=0x1f => out.extend_from_slice(&[b'^', byte + 0x40]),
+            0x7f => out.extend_from_slice(b"^?"),
+            _ => out.push(byte),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Decoding of child output for terminals that do not speak UTF-8.
+
+/// Rewrite 8-bit `bytes` into `out` as the UTF-8 the parser expects.
+///
+/// Bytes of the right half of the code table (`0xA0`..=`0xFF`) become
+/// their ISO Latin-1 characters, which the surface maps through the set
+/// invoked into GR. Everything else passes through untouched: the left
+/// half is mapped by the set invoked into GL like in UTF-8 mode, and
+/// `0x80`..=`0x9F` stay 8-bit C1 controls.
+pub(crate) fn decode(bytes: &[u8], out: &mut Vec<u8>) {
+    out.clear();
+    out.reserve(bytes.len());
+    for &byte in bytes {
+        match byte {
+            0xa0..=0xff => {
+                let mut utf8 = [0; 2];
+                out.extend_from_slice(
+                    char::from(byte).encode_utf8(&mut utf8).as_bytes(),
+                );
+            },
+            _ => out.push(byte),
+        }
+    }
+}
+
+/// Whether `ch` was decoded from a byte of the right half of the code table.
+pub(crate) fn is_right_half(ch: char) -> bool {
+    matches!(ch, '\u{a0}'..='\u{ff}')
+}
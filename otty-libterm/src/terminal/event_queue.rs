@@ -0,0 +1,166 @@
+//! Events queued for the front-end and their delivery to a possibly
+//! bounded [`EventSink`].
+
+use std::collections::VecDeque;
+
+use crate::Result;
+use crate::terminal::channel::{ChannelSendError, DirtySignal, EventSink};
+use crate::terminal::metrics::TerminalMetrics;
+use crate::terminal::{TerminalErrorKind, TerminalEvent};
+
+/// Events waiting for the next flush to the event sink.
+///
+/// On a bounded sink frames give way to the other events: a frame is only
+/// sent while the sink keeps room for the events queued after it, and a
+/// dropped frame is owed as a full repaint instead.
+#[derive(Default)]
+pub(crate) struct EventQueue {
+    events: VecDeque<TerminalEvent>,
+    /// Whether a frame was dropped on a full event channel and a repaint
+    /// is owed.
+    frame_dropped: bool,
+    /// Events dropped on a full event channel and not reported yet.
+    dropped_events: usize,
+}
+
+impl EventQueue {
+    /// Whether a dropped frame still has to be made up for.
+    pub(crate) fn is_repaint_owed(&self) -> bool {
+        self.frame_dropped
+    }
+
+    /// Queue `event`; a queued frame settles an owed repaint.
+    pub(crate) fn push(&mut self, event: TerminalEvent) {
+        self.frame_dropped &= !is_frame(&event);
+        self.events.push_back(event);
+    }
+
+    /// Take the queued `ChildExit` and every event after it, so that a
+    /// frame can be queued ahead of them.
+    pub(crate) fn split_off_exit(&mut self) -> Option<VecDeque<TerminalEvent>> {
+        let position = self.events.iter().position(|event| {
+            matches!(event, TerminalEvent::ChildExit { .. })
+        })?;
+        Some(self.events.split_off(position))
+    }
+
+    /// Queue events taken with [`Self::split_off_exit`] again.
+    pub(crate) fn extend(&mut self, events: VecDeque<TerminalEvent>) {
+        self.events.extend(events);
+    }
+
+    /// Whether `sink` has room for a frame next to the queued events, so a
+    /// snapshot taken now is not thrown away.
+    pub(crate) fn has_room_for_frame<K: EventSink>(&self, sink: &K) -> bool {
+        let other_events = self.other_events();
+        sink.remaining_capacity()
+            .is_none_or(|room| room > other_events)
+    }
+
+    /// Send the queued events to `sink`, passing each through `prepare`.
+    ///
+    /// Pending reports of dropped events go first. The `ChildExit` is never
+    /// lost: on a full sink it waits for the next flush. Returns whether a
+    /// frame was dropped, owing the client a full repaint.
+    pub(crate) fn flush<K: EventSink>(
+        &mut self,
+        sink: &mut K,
+        dirty: &DirtySignal,
+        metrics: &mut TerminalMetrics,
+        mut prepare: impl FnMut(TerminalEvent) -> TerminalEvent,
+    ) -> Result<bool> {
+        self.report_dropped_events(sink, dirty)?;
+
+        let mut other_events = self.other_events();
+        let mut dropping_frames = false;
+        let mut dropped_frame = false;
+        while let Some(event) = self.events.pop_front() {
+            if is_frame(&event) {
+                dropping_frames |= sink
+                    .remaining_capacity()
+                    .is_some_and(|room| room <= other_events);
+                if dropping_frames {
+                    dropped_frame = true;
+                    metrics.record_dropped(1);
+                    continue;
+                }
+            } else {
+                other_events -= 1;
+            }
+
+            let event = prepare(event);
+            let frame = is_frame(&event);
+            let exit = match &event {
+                TerminalEvent::ChildExit { status } => Some(*status),
+                _ => None,
+            };
+            match sink.send(event) {
+                Ok(()) => {
+                    if frame {
+                        metrics.record_sent();
+                    }
+                    dirty.notify();
+                },
+                Err(ChannelSendError::Full) if frame => {
+                    dropping_frames = true;
+                    dropped_frame = true;
+                    metrics.record_dropped(1);
+                },
+                Err(ChannelSendError::Full) => {
+                    if let Some(status) = exit {
+                        self.events
+                            .push_front(TerminalEvent::ChildExit { status });
+                        break;
+                    }
+                    self.dropped_events += 1;
+                },
+                Err(ChannelSendError::Disconnected) => {
+                    return Err(crate::Error::EventChannelClosed);
+                },
+            }
+        }
+
+        self.frame_dropped |= dropped_frame;
+        Ok(dropped_frame)
+    }
+
+    /// Tell the client about events dropped on a full channel, once the
+    /// channel has room again.
+    fn report_dropped_events<K: EventSink>(
+        &mut self,
+        sink: &mut K,
+        dirty: &DirtySignal,
+    ) -> Result<()> {
+        if self.dropped_events == 0 {
+            return Ok(());
+        }
+
+        let event = TerminalEvent::Error {
+            kind: TerminalErrorKind::EventsDropped,
+            detail: format!(
+                "dropped {} events on a full event channel",
+                self.dropped_events
+            ),
+        };
+        match sink.send(event) {
+            Ok(()) => {
+                self.dropped_events = 0;
+                dirty.notify();
+                Ok(())
+            },
+            Err(ChannelSendError::Full) => Ok(()),
+            Err(ChannelSendError::Disconnected) => {
+                Err(crate::Error::EventChannelClosed)
+            },
+        }
+    }
+
+    /// Number of queued events that are not frames.
+    fn other_events(&self) -> usize {
+        self.events.iter().filter(|event| !is_frame(event)).count()
+    }
+}
+
+fn is_frame(event: &TerminalEvent) -> bool {
+    matches!(event, TerminalEvent::Frame { .. })
+}
@@ -0,0 +1,75 @@
+//! Software flow control (XON/XOFF) between the terminal and the child.
+
+/// Resume transmission (`DC1`, Ctrl-Q).
+const XON: u8 = 0x11;
+/// Pause transmission (`DC3`, Ctrl-S).
+const XOFF: u8 = 0x13;
+
+/// XON/XOFF state for both directions of the session.
+///
+/// The child pauses terminal input by writing `XOFF` and resumes it with
+/// `XON`. The user pauses and resumes output rendering by typing Ctrl-S
+/// and Ctrl-Q, which are consumed instead of being sent to the child.
+#[derive(Debug, Default)]
+pub(crate) struct FlowControl {
+    enabled: bool,
+    input_paused: bool,
+    output_paused: bool,
+}
+
+impl FlowControl {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Whether input for the child has to be held back.
+    pub(crate) fn input_paused(&self) -> bool {
+        self.input_paused
+    }
+
+    /// Whether frames for child output have to be held back.
+    pub(crate) fn output_paused(&self) -> bool {
+        self.output_paused
+    }
+
+    /// Track the `XON`/`XOFF` bytes written by the child; the last one wins.
+    pub(crate) fn observe_output(&mut self, bytes: &[u8]) {
+        if !self.enabled {
+            return;
+        }
+
+        match bytes.iter().rev().find(|&&byte| matches!(byte, XON | XOFF)) {
+            Some(&XOFF) => self.input_paused = true,
+            Some(_) => self.input_paused = false,
+            None => {},
+        }
+    }
+
+    /// Strip Ctrl-S and Ctrl-Q from user `input`, pausing or resuming
+    /// output accordingly.
+    ///
+    /// Returns whether output was resumed, so the withheld output can be
+    /// shown.
+    pub(crate) fn filter_input(&mut self, input: &mut Vec<u8>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        let was_paused = self.output_paused;
+        input.retain(|&byte| match byte {
+            XOFF => {
+                self.output_paused = true;
+                false
+            },
+            XON => {
+                self.output_paused = false;
+                false
+            },
+            _ => true,
+        });
+        was_paused && !self.output_paused
+    }
+}
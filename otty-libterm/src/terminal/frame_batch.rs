@@ -0,0 +1,93 @@
+//! Coalescing of output frames during sustained PTY output.
+
+use std::time::{Duration, Instant};
+
+/// Cadence for frames produced by sustained PTY output.
+pub(crate) struct FrameBatchState {
+    interval: Option<Duration>,
+    last_emitted: Option<Instant>,
+    pending: bool,
+    /// Updates folded into a later frame.
+    coalesced: u64,
+}
+
+impl FrameBatchState {
+    /// Whether a frame was deferred and is still owed.
+    pub(crate) fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Number of updates folded into a later frame.
+    pub(crate) fn coalesced(&self) -> u64 {
+        self.coalesced
+    }
+
+    pub(crate) fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            last_emitted: None,
+            pending: false,
+            coalesced: 0,
+        }
+    }
+
+    /// Return whether an output frame may go out now, deferring it otherwise.
+    pub(crate) fn should_emit(&mut self, now: Instant) -> bool {
+        let Some(interval) = self.interval else {
+            return true;
+        };
+
+        let throttled = self
+            .last_emitted
+            .is_some_and(|last| now.saturating_duration_since(last) < interval);
+        self.pending |= throttled;
+        self.coalesced += u64::from(throttled);
+        !throttled
+    }
+
+    /// Record that a frame carrying all pending changes was emitted.
+    pub(crate) fn record(&mut self, now: Instant) {
+        self.last_emitted = Some(now);
+        self.pending = false;
+    }
+
+    /// Instant at which the deferred frame becomes due, if one is pending.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        if !self.pending {
+            return None;
+        }
+        Some(self.last_emitted? + self.interval?)
+    }
+
+    /// Check whether the deferred frame should be emitted at `now`.
+    pub(crate) fn is_due(&self, now: Instant) -> bool {
+        self.deadline().is_some_and(|deadline| now >= deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_batch_defers_frames_inside_the_interval() {
+        let interval = Duration::from_millis(16);
+        let mut batch = FrameBatchState::new(Some(interval));
+        let start = Instant::now();
+
+        assert!(batch.should_emit(start));
+        batch.record(start);
+        assert_eq!(batch.deadline(), None);
+
+        for ms in 1..16 {
+            assert!(!batch.should_emit(start + Duration::from_millis(ms)));
+        }
+        assert_eq!(batch.deadline(), Some(start + interval));
+        assert!(!batch.is_due(start + Duration::from_millis(15)));
+        assert!(batch.is_due(start + interval));
+
+        batch.record(start + interval);
+        assert_eq!(batch.deadline(), None);
+        assert!(FrameBatchState::new(None).should_emit(start));
+    }
+}
@@ -0,0 +1,46 @@
+//! Rate limiting of the surface memory pressure event.
+
+use std::time::{Duration, Instant};
+
+/// Rate-limited tracking of the surface memory threshold.
+pub(crate) struct MemoryPressureState {
+    threshold: Option<usize>,
+    interval: Duration,
+    last_emitted: Option<Instant>,
+}
+
+impl MemoryPressureState {
+    pub(crate) fn new(threshold: Option<usize>, interval: Duration) -> Self {
+        Self {
+            threshold,
+            interval,
+            last_emitted: None,
+        }
+    }
+
+    /// Check whether the threshold is configured at all.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.threshold.is_some()
+    }
+
+    /// Return whether an event should be emitted at `now` for the given
+    /// usage.
+    pub(crate) fn should_emit(&mut self, bytes: usize, now: Instant) -> bool {
+        let Some(threshold) = self.threshold else {
+            return false;
+        };
+        if bytes < threshold {
+            return false;
+        }
+
+        let throttled = self.last_emitted.is_some_and(|last| {
+            now.saturating_duration_since(last) < self.interval
+        });
+        if throttled {
+            return false;
+        }
+
+        self.last_emitted = Some(now);
+        true
+    }
+}
@@ -0,0 +1,30 @@
+//! Counters describing how output frames reached the front-end.
+
+/// Frame delivery counters of a [`crate::TerminalEngine`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TerminalMetrics {
+    frames_sent: u64,
+    frames_dropped: u64,
+}
+
+impl TerminalMetrics {
+    /// Frames handed to the event sink.
+    pub fn frames_sent(&self) -> u64 {
+        self.frames_sent
+    }
+
+    /// Output updates that got no frame of their own: folded into a later
+    /// frame by the frame interval or the frame rate cap, or dropped on a
+    /// full event channel.
+    pub fn frames_dropped(&self) -> u64 {
+        self.frames_dropped
+    }
+
+    pub(crate) fn record_sent(&mut self) {
+        self.frames_sent += 1;
+    }
+
+    pub(crate) fn record_dropped(&mut self, count: u64) {
+        self.frames_dropped += count;
+    }
+}
@@ -1,8 +1,20 @@
 pub mod builder;
 pub mod channel;
+pub mod clock;
+mod control_chars;
+mod eight_bit;
+mod event_queue;
+mod flow_control;
+mod frame_batch;
+mod memory_pressure;
+pub mod metrics;
 pub mod options;
+pub mod profile;
+mod shutdown;
 pub mod size;
 pub mod surface_actor;
+mod sync;
+pub mod termcap;
 
 use std::collections::VecDeque;
 use std::io::ErrorKind;
@@ -10,6 +22,7 @@ use std::process::ExitStatus;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use clock::Clock;
 use cursor_icon::CursorIcon;
 use flume::{Receiver, Sender};
 use log::debug;
@@ -17,25 +30,53 @@ use options::TerminalOptions;
 
 use crate::Result;
 use crate::escape::{
-    Action, CursorShape, CursorStyle, EscapeParser, Hyperlink,
+    CursorShape, CursorStyle, EscapeParser, Hyperlink, ProgressState,
 };
 use crate::pty::{Pollable, Session, SessionError};
+use crate::runtime::RuntimeWaker;
 use crate::surface::{
-    Point, Scroll, SelectionType, Side, SnapshotOwned, SurfaceActor,
-    SurfaceModel,
+    BlockOutput, HyperlinkSpan, Motion, Point, RegexSearch, Scroll,
+    SelectionGranularity, SelectionType, Side, SnapshotOwned, SurfaceActor,
+    SurfaceLayout, SurfaceModel,
 };
 use crate::terminal::channel::{
-    ChannelSendError, TerminalEvents, TerminalHandle, map_send_error,
+    DirtySignal, EventSink, RequestSource, TerminalEvents, TerminalHandle,
+    WakerSlot,
 };
+use crate::terminal::event_queue::EventQueue;
+use crate::terminal::flow_control::FlowControl;
+use crate::terminal::frame_batch::FrameBatchState;
+use crate::terminal::memory_pressure::MemoryPressureState;
+use crate::terminal::metrics::TerminalMetrics;
+use crate::terminal::profile::TermProfile;
+use crate::terminal::shutdown::ShutdownState;
 use crate::terminal::size::TerminalSize;
 use crate::terminal::surface_actor::TerminalSurfaceActor;
+use crate::terminal::sync::SyncState;
+use crate::terminal::termcap::Termcaps;
 
 /// Owned frame wrapper shared with terminal consumers.
 pub type SnapshotArc = Arc<SnapshotOwned>;
 
 const DEFAULT_READ_BUFFER_CAPACITY: usize = 1024;
+/// Malformed reads tolerated in a row before reading is given up on.
+const MAX_CONSECUTIVE_READ_ERRORS: usize = 8;
 
 /// Events emitted by terminal implementations to interested clients.
+///
+/// For every batch of PTY output, events driven by escape actions (title,
+/// bell, cursor, hyperlink, ...) are delivered in parse order before the
+/// [`TerminalEvent::Frame`] showing that output, followed by the block output
+/// events for the same frame. On an unbounded event channel,
+/// [`TerminalEvent::ChildExit`] always follows the final frame.
+///
+/// A bounded event channel gives the other events priority over frames: a
+/// frame that would take the room left for them is dropped, together with the
+/// frames after it, and a full repaint follows on the next tick. A burst of
+/// frames therefore never holds back a `ChildExit`, which may then arrive
+/// ahead of the repaint. Other events that still do not fit are dropped and
+/// reported by a [`TerminalErrorKind::EventsDropped`] error once the
+/// channel has room, while a `ChildExit` waits for it.
 pub enum TerminalEvent {
     /// The in-memory surface contents have changed.
     ///
@@ -57,6 +98,53 @@ pub enum TerminalEvent {
     CursorIconChanged { icon: CursorIcon },
     /// The currently active hyperlink under the cursor has changed.
     Hyperlink { link: Option<Hyperlink> },
+    /// The hovered hyperlink changed, carrying every cell the link covers.
+    HyperlinkSpan { span: Option<HyperlinkSpan> },
+    /// The surface memory estimate crossed the configured threshold.
+    ///
+    /// Hosts may respond by trimming scrollback.
+    MemoryPressure { bytes: usize },
+    /// Copy mode was left, carrying the text selected while in it.
+    CopyModeExited { selection_text: Option<String> },
+    /// The application updated its progress indicator (`OSC 9;4`).
+    ///
+    /// Reports repeating the current state and percentage are dropped, so
+    /// chatty progress bars do not flood front-ends.
+    Progress { state: ProgressState, percent: u8 },
+    /// A running command block gained output lines, see
+    /// [`BlockOutput::Appended`].
+    BlockOutput {
+        block_id: String,
+        appended_text: String,
+    },
+    /// A command block finished; no more output follows for it.
+    BlockFinished {
+        block_id: String,
+        exit_code: Option<i32>,
+    },
+    /// A recoverable problem occurred; the engine keeps running.
+    ///
+    /// Fatal problems are still returned as [`crate::Error`].
+    Error {
+        kind: TerminalErrorKind,
+        detail: String,
+    },
+}
+
+/// Operation that failed for a [`TerminalEvent::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalErrorKind {
+    /// The session returned malformed data; reading goes on with the next
+    /// chunk.
+    Read,
+    /// Writing to the session failed; the pending input was dropped.
+    Write,
+    /// The session could not be resized; the surface uses the new size
+    /// anyway.
+    Resize,
+    /// The event channel was full; events other than frames and the child
+    /// exit were dropped.
+    EventsDropped,
 }
 
 /// Commands that the runtime understands for mutating the terminal state.
@@ -69,108 +157,140 @@ pub enum TerminalRequest {
     /// Scroll the display viewport.
     ScrollDisplay(Scroll),
     /// Initialize the selection range on the surface.
+    ///
+    /// An additive start keeps the active selection, if it has the same
+    /// type, next to the new one instead of replacing it.
     StartSelection {
         ty: SelectionType,
         point: Point,
         direction: Side,
+        additive: bool,
     },
     /// Update the active selection range on the surface.
     UpdateSelection { point: Point, direction: Side },
-    /// Close the session and terminate the event loop.
+    /// Extend the active selection to a point, snapping its far end.
+    ExtendSelection {
+        point: Point,
+        granularity: SelectionGranularity,
+    },
+    /// Drop every selection on the surface.
+    ClearSelection,
+    /// Emit a fully damaged frame even if the surface did not change.
+    RequestFrame,
+    /// Update whether the terminal front-end currently has input focus.
+    SetFocus(bool),
+    /// Set the title from the front-end, e.g. when the user renames a tab,
+    /// or restore the default with `None`.
+    ///
+    /// The title is announced like one set by the child, which can still
+    /// replace it later.
+    SetTitle(Option<String>),
+    /// Enter keyboard driven copy mode at the terminal cursor.
+    EnterCopyMode,
+    /// Move the copy cursor, extending the copy mode selection if any.
+    CopyModeMove(Motion),
+    /// Start a selection at the copy cursor, or clear the active one.
+    CopyModeSelect(SelectionType),
+    /// Leave copy mode and report the selected text.
+    ExitCopyMode,
+    /// Update the pointer position used to resolve the hovered hyperlink.
+    HoverHyperlink(Option<Point>),
+    /// Empty the screen and scrollback and home the cursor without writing
+    /// to the child. Unlike a full reset (RIS), modes are kept.
+    ClearAll,
+    /// Search for a regex pattern and focus its most recent match, or clear
+    /// the search with `None`.
+    SetSearchPattern(Option<String>),
+    /// Focus the next search match, wrapping around at the bottom.
+    SearchNext,
+    /// Focus the previous search match, wrapping around at the top.
+    SearchPrevious,
+    /// Present the content as a plain grid or as blocks in later frames.
+    SetSurfaceLayout(SurfaceLayout),
+    /// Close the session and terminate the event loop, dropping input the
+    /// child has not accepted yet.
     Shutdown,
+    /// Like [`Self::Shutdown`], but first keep writing pending input to the
+    /// child for up to `timeout` and hand queued events to the consumer, so
+    /// a final write such as `exit\n` is not lost. The event loop keeps
+    /// running until the session is closed.
+    GracefulShutdown { timeout: Duration },
 }
 
-const MAX_SYNC_ACTIONS: usize = 10_000;
-const SYNC_TIMEOUT: Duration = Duration::from_millis(10);
-const IDLE_TICK: Duration = Duration::from_millis(10);
-
-pub(crate) struct SyncState {
-    active: bool,
-    buffer: Vec<Action>,
-    deadline: Option<Instant>,
-}
-
-impl SyncState {
-    /// Create a new sync state with a fresh deadline.
-    fn new() -> Self {
-        let mut state = Self {
-            active: false,
-            buffer: Vec::with_capacity(MAX_SYNC_ACTIONS),
-            deadline: None,
-        };
-        state.refresh_deadline();
-        state
-    }
-
-    /// Enter synchronized-update mode, buffering subsequent actions.
-    fn begin(&mut self) {
-        self.active = true;
-        self.buffer.clear();
-        self.refresh_deadline();
-    }
-
-    /// End synchronized-update mode and drain buffered actions.
-    fn end(&mut self) -> Vec<Action> {
-        self.active = false;
-        self.refresh_deadline();
-        std::mem::take(&mut self.buffer)
-    }
-
-    /// Cancel synchronized-update mode and drain buffered actions.
-    fn cancel(&mut self) -> Vec<Action> {
-        self.active = false;
-        self.refresh_deadline();
-        std::mem::take(&mut self.buffer)
-    }
-
-    /// Try to push a new action into the sync buffer.
-    ///
-    /// Returns the action back on overflow so that callers can fall back to
-    /// immediate processing.
-    #[allow(clippy::result_large_err)]
-    fn push(&mut self, action: Action) -> std::result::Result<(), Action> {
-        if self.buffer.len() >= MAX_SYNC_ACTIONS {
-            return Err(action);
-        }
-
-        self.buffer.push(action);
-        self.refresh_deadline();
-        Ok(())
-    }
-
-    /// Check whether synchronized-update mode is currently active.
-    fn is_active(&self) -> bool {
-        self.active
-    }
-
-    /// Check whether the current deadline has expired.
-    fn is_expired(&self) -> bool {
-        self.deadline
-            .is_some_and(|deadline| Instant::now() > deadline)
+impl TerminalRequest {
+    /// Whether the request terminates the event loop.
+    pub fn is_shutdown(&self) -> bool {
+        matches!(self, Self::Shutdown | Self::GracefulShutdown { .. })
     }
+}
 
-    /// Refresh the internal deadline based on the current mode.
-    fn refresh_deadline(&mut self) {
-        let timeout = if self.active { SYNC_TIMEOUT } else { IDLE_TICK };
-        self.deadline = Some(Instant::now() + timeout);
-    }
+/// Destination of the bytes the engine writes to the child.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Write to the session.
+    #[default]
+    Session,
+    /// Keep the bytes for [`TerminalEngine::drain_output_into`] instead of
+    /// writing them, e.g. for headless tests.
+    Drain,
+    /// Write to the session and keep a copy for
+    /// [`TerminalEngine::drain_output_into`], e.g. for recording.
+    Tee,
 }
 
 /// High level engine that connects a PTY session with the escape parser and
 /// in-memory surface model.
-pub struct TerminalEngine<P, E, S> {
+///
+/// Events and requests travel over flume channels by default; hosts can
+/// plug in their own [`EventSink`] / [`RequestSource`] with
+/// [`TerminalEngine::with_transport`].
+pub struct TerminalEngine<
+    P,
+    E,
+    S,
+    K = Sender<TerminalEvent>,
+    R = Receiver<TerminalRequest>,
+> {
     session: P,
     parser: E,
     surface: S,
     size: TerminalSize,
     read_buffer: Vec<u8>,
     exit_status: Option<ExitStatus>,
-    event_tx: Sender<TerminalEvent>,
-    request_rx: Receiver<TerminalRequest>,
+    event_sink: K,
+    request_source: R,
     pending_input: VecDeque<u8>,
+    output_mode: OutputMode,
+    drained_output: Vec<u8>,
     pending_requests: VecDeque<TerminalRequest>,
-    events: VecDeque<TerminalEvent>,
+    events: EventQueue,
     sync_state: SyncState,
+    memory_pressure: MemoryPressureState,
+    frame_batch: FrameBatchState,
+    focused: bool,
+    cursor_style: Option<CursorStyle>,
+    hovered_hyperlink: Option<HyperlinkSpan>,
+    /// Last progress reported through [`TerminalEvent::Progress`].
+    progress: (ProgressState, u8),
+    waker: WakerSlot,
+    dirty: DirtySignal,
+    term_profile: TermProfile,
+    termcaps: Termcaps,
+    write_chunk_size: usize,
+    utf8: bool,
+    /// Child output decoded from 8-bit bytes while UTF-8 is off.
+    decoded_output: Vec<u8>,
+    show_control_chars: bool,
+    /// Child output rewritten while control characters are shown.
+    visible_output: Vec<u8>,
+    flow_control: FlowControl,
+    clock: Arc<dyn Clock>,
+    /// Set while [`Self::process_requests`] holds frames back.
+    batching_frames: bool,
+    /// Whether a request of the current batch wanted a frame.
+    batched_frame: bool,
+    metrics: TerminalMetrics,
+    shutdown: ShutdownState,
 }
 
 impl<P, E, S> TerminalEngine<P, E, S>
@@ -179,6 +299,8 @@ where
     E: EscapeParser,
     S: SurfaceActor + SurfaceModel,
 {
+    /// Create an engine wired to flume channels, returning the request
+    /// handle and event receiver for the front-end.
     pub fn new(
         session: P,
         parser: E,
@@ -188,9 +310,34 @@ where
         let (event_tx, event_rx, request_tx, request_rx) =
             channel::build_channels(&options.channel_config);
 
-        let handle = TerminalHandle::new(request_tx);
-        let events = TerminalEvents::new(event_rx);
+        let engine = Self::with_transport(
+            session, parser, surface, options, event_tx, request_rx,
+        );
+        let handle = TerminalHandle::new(request_tx, engine.waker.clone());
+        let events = TerminalEvents::new(event_rx, engine.dirty.clone());
+
+        Ok((engine, handle, events))
+    }
+}
 
+impl<P, E, S, K, R> TerminalEngine<P, E, S, K, R>
+where
+    P: Session,
+    E: EscapeParser,
+    S: SurfaceActor + SurfaceModel,
+    K: EventSink,
+    R: RequestSource,
+{
+    /// Create an engine that delivers events to `event_sink` and drains
+    /// requests from `request_source`.
+    pub fn with_transport(
+        session: P,
+        parser: E,
+        surface: S,
+        options: TerminalOptions,
+        event_sink: K,
+        request_source: R,
+    ) -> Self {
         let mut read_buffer = vec![
             0u8;
             options
@@ -201,24 +348,56 @@ where
             read_buffer.resize(DEFAULT_READ_BUFFER_CAPACITY, 0);
         }
 
-        Ok((
-            Self {
-                session,
-                parser,
-                surface,
-                read_buffer,
-                size: TerminalSize::default(),
-                exit_status: None,
-                event_tx,
-                request_rx,
-                pending_input: VecDeque::new(),
-                pending_requests: VecDeque::new(),
-                events: VecDeque::new(),
-                sync_state: SyncState::new(),
-            },
-            handle,
-            events,
-        ))
+        Self {
+            session,
+            parser,
+            surface,
+            read_buffer,
+            size: TerminalSize::default(),
+            exit_status: None,
+            event_sink,
+            request_source,
+            pending_input: VecDeque::new(),
+            output_mode: OutputMode::default(),
+            drained_output: Vec::new(),
+            pending_requests: VecDeque::new(),
+            events: EventQueue::default(),
+            sync_state: SyncState::new(options.clock.clone()),
+            memory_pressure: MemoryPressureState::new(
+                options.memory_pressure_threshold,
+                options.memory_pressure_interval,
+            ),
+            frame_batch: FrameBatchState::new(options.output_frame_interval()),
+            focused: true,
+            cursor_style: None,
+            hovered_hyperlink: None,
+            progress: Default::default(),
+            waker: WakerSlot::default(),
+            dirty: DirtySignal::default(),
+            term_profile: options.term_profile,
+            termcaps: options.termcaps,
+            write_chunk_size: options.write_chunk_size.max(1),
+            utf8: options.utf8,
+            decoded_output: Vec::new(),
+            show_control_chars: options.show_control_chars,
+            visible_output: Vec::new(),
+            flow_control: FlowControl::new(options.flow_control),
+            clock: options.clock,
+            batching_frames: false,
+            batched_frame: false,
+            metrics: TerminalMetrics::default(),
+            shutdown: ShutdownState::default(),
+        }
+    }
+
+    /// Borrow the event sink, e.g. to inspect a host-provided transport.
+    pub fn event_sink(&self) -> &K {
+        &self.event_sink
+    }
+
+    /// Mutably borrow the request source, e.g. to queue host requests.
+    pub fn request_source_mut(&mut self) -> &mut R {
+        &mut self.request_source
     }
 
     /// Push a request into the engine for processing.
@@ -232,12 +411,26 @@ where
         self.process_pending_requests()?;
 
         let mut updated = false;
+        let mut read_errors = 0;
 
         loop {
             match self.session.read(self.read_buffer.as_mut_slice()) {
                 Ok(0) => break,
                 Ok(count) => {
-                    let chunk = &self.read_buffer[..count];
+                    read_errors = 0;
+                    let mut chunk = &self.read_buffer[..count];
+                    self.flow_control.observe_output(chunk);
+                    if !self.utf8 {
+                        eight_bit::decode(chunk, &mut self.decoded_output);
+                        chunk = &self.decoded_output;
+                    }
+                    if self.show_control_chars {
+                        control_chars::visualize(
+                            chunk,
+                            &mut self.visible_output,
+                        );
+                        chunk = &self.visible_output;
+                    }
                     let parser = &mut self.parser;
                     let surface = &mut self.surface;
                     {
@@ -246,6 +439,11 @@ where
                             events: &mut self.events,
                             pending_input: &mut self.pending_input,
                             sync_state: &mut self.sync_state,
+                            progress: &mut self.progress,
+                            term_profile: self.term_profile,
+                            termcaps: &self.termcaps,
+                            size: self.size,
+                            utf8: self.utf8,
                         };
                         parser.advance(chunk, &mut actor);
                         let _ = actor.flush_sync_timeout();
@@ -265,14 +463,29 @@ where
                 Err(SessionError::IO(ref err))
                     if is_session_closed_error(err) =>
                 {
-                    self.capture_exit()?;
+                    // The exit is captured below, once the final output
+                    // has been framed.
                     break;
                 },
+                Err(SessionError::IO(ref err))
+                    if err.kind() == ErrorKind::InvalidData
+                        && read_errors < MAX_CONSECUTIVE_READ_ERRORS =>
+                {
+                    read_errors += 1;
+                    self.report_error(TerminalErrorKind::Read, err);
+                },
                 Err(err) => return Err(err.into()),
             }
         }
 
-        if updated {
+        // Frames are withheld while a synchronized update is open or the
+        // user paused output; the end of the update or its timeout emits the
+        // next one, as does resuming output.
+        if updated
+            && !self.sync_state.is_active()
+            && !self.flow_control.output_paused()
+            && self.frame_batch.should_emit(self.clock.now())
+        {
             self.emit_frame()?;
         }
 
@@ -287,6 +500,7 @@ where
     pub fn on_writable(&mut self) -> Result<bool> {
         self.process_pending_requests()?;
         self.flush_pending_input()?;
+        self.advance_shutdown()?;
         self.flush_event_queue()?;
         Ok(!self.pending_input.is_empty())
     }
@@ -301,23 +515,105 @@ where
                 events: &mut self.events,
                 pending_input: &mut self.pending_input,
                 sync_state: &mut self.sync_state,
+                progress: &mut self.progress,
+                term_profile: self.term_profile,
+                termcaps: &self.termcaps,
+                size: self.size,
+                utf8: self.utf8,
             };
             actor.flush_sync_timeout()
         };
 
-        if flushed {
+        let due = !self.sync_state.is_active()
+            && !self.flow_control.output_paused()
+            && self.frame_batch.is_due(self.clock.now());
+        let repaint = self.events.is_repaint_owed()
+            && !self.sync_state.is_active()
+            && !self.flow_control.output_paused()
+            && self.events.has_room_for_frame(&self.event_sink);
+        if flushed || due || repaint {
             self.emit_frame()?;
         }
 
         self.capture_exit()?;
+        self.advance_shutdown()?;
 
         self.flush_event_queue()?;
 
         Ok(())
     }
 
+    /// Parse `bytes` as if the child had written them, without reading
+    /// from the session.
+    ///
+    /// Replies to queries are flushed right away, so with
+    /// [`OutputMode::Drain`] they can be collected from
+    /// [`Self::drain_output_into`].
+    pub fn inject(&mut self, bytes: &[u8]) -> Result<()> {
+        self.process_pending_requests()?;
+
+        self.flow_control.observe_output(bytes);
+        let bytes = if self.utf8 {
+            bytes
+        } else {
+            eight_bit::decode(bytes, &mut self.decoded_output);
+            &self.decoded_output
+        };
+        let bytes = if self.show_control_chars {
+            control_chars::visualize(bytes, &mut self.visible_output);
+            &self.visible_output
+        } else {
+            bytes
+        };
+
+        {
+            let mut actor = TerminalSurfaceActor {
+                surface: &mut self.surface,
+                events: &mut self.events,
+                pending_input: &mut self.pending_input,
+                sync_state: &mut self.sync_state,
+                progress: &mut self.progress,
+                term_profile: self.term_profile,
+                termcaps: &self.termcaps,
+                size: self.size,
+                utf8: self.utf8,
+            };
+            self.parser.advance(bytes, &mut actor);
+            let _ = actor.flush_sync_timeout();
+        }
+
+        if !self.sync_state.is_active()
+            && !self.flow_control.output_paused()
+            && self.frame_batch.should_emit(self.clock.now())
+        {
+            self.emit_frame()?;
+        }
+
+        self.flush_pending_input()?;
+        self.flush_event_queue()
+    }
+
+    /// Choose where bytes for the child go; see [`OutputMode`].
+    pub fn set_output_mode(&mut self, mode: OutputMode) {
+        self.output_mode = mode;
+    }
+
+    /// Append the bytes kept by [`OutputMode::Drain`] or [`OutputMode::Tee`]
+    /// to `out`, returning how many were appended.
+    pub fn drain_output_into(&mut self, out: &mut Vec<u8>) -> usize {
+        let count = self.drained_output.len();
+        out.append(&mut self.drained_output);
+        count
+    }
+
     /// Return whether there is buffered output waiting to be written.
+    ///
+    /// Output held back by an `XOFF` from the child does not count.
     pub fn has_pending_output(&self) -> bool {
+        if self.flow_control.input_paused() {
+            return false;
+        }
+
         !self.pending_input.is_empty()
             || self
                 .pending_requests
@@ -325,36 +621,131 @@ where
                 .any(|req| matches!(req, TerminalRequest::WriteBytes(_)))
     }
 
+    /// Bytes of input buffered for the child and not written yet.
+    ///
+    /// Hosts can check this before queueing more, e.g. to throttle a large
+    /// paste while the child is slow to read.
+    pub fn pending_input_len(&self) -> usize {
+        self.pending_input.len()
+    }
+
+    /// Requests queued on the engine and not processed yet.
+    ///
+    /// Requests still in flight on the request channel are not counted.
+    pub fn queued_request_count(&self) -> usize {
+        self.pending_requests.len()
+    }
+
+    /// Frame delivery counters since the engine was created.
+    pub fn metrics(&self) -> TerminalMetrics {
+        let mut metrics = self.metrics;
+        metrics.record_dropped(self.frame_batch.coalesced());
+        metrics
+    }
+
     /// Inspect the active terminal geometry.
     pub fn size(&self) -> TerminalSize {
         self.size
     }
 
-    /// Deadline for the next maintenance tick, based on sync mode.
+    /// Deadline for the next maintenance tick, based on sync mode, any
+    /// deferred output frame and a graceful shutdown in progress.
     pub fn next_deadline(&self) -> Option<Instant> {
-        self.sync_state.deadline
+        [
+            self.sync_state.deadline(),
+            self.frame_batch.deadline(),
+            self.shutdown.deadline(),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// Whether a [`TerminalRequest::GracefulShutdown`] is still writing
+    /// pending input before the session is closed.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_pending()
     }
 
     pub fn check_child_exit(&mut self) -> Result<Option<ExitStatus>> {
         self.capture_exit()
     }
 
+    /// Wake the given runtime whenever a [`TerminalHandle`] sends a request.
+    pub fn set_runtime_waker(&mut self, waker: Option<RuntimeWaker>) {
+        self.waker.set(waker);
+    }
+
     fn process_pending_requests(&mut self) -> Result<()> {
-        while let Ok(request) = self.request_rx.try_recv() {
+        while let Some(request) = self.request_source.try_recv() {
             self.pending_requests.push_back(request);
         }
 
         while let Some(request) = self.pending_requests.pop_front() {
+            let request = match request {
+                TerminalRequest::ScrollDisplay(Scroll::Delta(delta)) => {
+                    let delta = self.coalesce_scroll_delta(delta);
+                    TerminalRequest::ScrollDisplay(Scroll::Delta(delta))
+                },
+                request => request,
+            };
             self.process_request(request)?;
         }
         Ok(())
     }
 
+    /// Sum `delta` with the queued scroll deltas that directly follow it and
+    /// go the same way, so a burst of wheel events scrolls once.
+    fn coalesce_scroll_delta(&mut self, mut delta: i32) -> i32 {
+        while let Some(&TerminalRequest::ScrollDisplay(Scroll::Delta(next))) =
+            self.pending_requests.front()
+        {
+            if next.signum() != delta.signum() {
+                break;
+            }
+            delta = delta.saturating_add(next);
+            self.pending_requests.pop_front();
+        }
+        delta
+    }
+
+    /// Apply `requests` in order, emitting at most one frame for all of
+    /// them.
+    ///
+    /// The frames the requests would emit one by one are replaced with a
+    /// single frame taken after the last one, so a group of changes such
+    /// as a resize followed by a scroll reaches the client already applied
+    /// together. Like [`Self::process_request`], events are queued and not
+    /// flushed.
+    pub fn process_requests(
+        &mut self,
+        requests: impl IntoIterator<Item = TerminalRequest>,
+    ) -> Result<()> {
+        let nested = std::mem::replace(&mut self.batching_frames, true);
+        let result = requests
+            .into_iter()
+            .try_for_each(|request| self.process_request(request));
+        self.batching_frames = nested;
+
+        if nested {
+            return result;
+        }
+        let frame = std::mem::take(&mut self.batched_frame);
+        result?;
+        if frame {
+            self.emit_frame()?;
+        }
+        Ok(())
+    }
+
     pub fn process_request(&mut self, request: TerminalRequest) -> Result<()> {
         use TerminalRequest::*;
 
         match request {
-            WriteBytes(bytes) => {
+            WriteBytes(mut bytes) => {
+                if self.flow_control.filter_input(&mut bytes) {
+                    self.emit_frame()?;
+                }
                 debug!(
                     "terminal request write {} bytes: {:02X?} (utf8={})",
                     bytes.len(),
@@ -373,17 +764,86 @@ where
                 ty,
                 point,
                 direction,
+                additive,
             } => {
-                self.surface.start_selection(ty, point, direction);
+                if additive {
+                    self.surface.add_selection(ty, point, direction);
+                } else {
+                    self.surface.start_selection(ty, point, direction);
+                }
                 self.emit_frame()?;
             },
             UpdateSelection { point, direction } => {
                 self.surface.update_selection(point, direction);
                 self.emit_frame()?;
             },
+            ClearSelection => {
+                self.surface.clear_selection();
+                self.emit_frame()?;
+            },
+            ExtendSelection { point, granularity } => {
+                self.surface.extend_selection(point, granularity);
+                self.emit_frame()?;
+            },
+            RequestFrame => {
+                self.surface.mark_fully_damaged();
+                self.emit_frame()?;
+            },
+            SetFocus(focused) => self.set_focus(focused)?,
+            SetTitle(title) => {
+                self.surface.set_window_title(title.clone());
+                self.events.push(match title {
+                    Some(title) => TerminalEvent::TitleChanged { title },
+                    None => TerminalEvent::ResetTitle,
+                });
+            },
+            EnterCopyMode => {
+                self.surface.enter_copy_mode();
+                self.emit_frame()?;
+            },
+            CopyModeMove(motion) => {
+                self.surface.copy_mode_motion(motion);
+                self.emit_frame()?;
+            },
+            CopyModeSelect(ty) => {
+                self.surface.toggle_copy_selection(ty);
+                self.emit_frame()?;
+            },
+            ExitCopyMode => {
+                let selection_text = self.surface.exit_copy_mode();
+                self.events
+                    .push(TerminalEvent::CopyModeExited { selection_text });
+                self.emit_frame()?;
+            },
+            HoverHyperlink(point) => self.hover_hyperlink(point),
+            ClearAll => {
+                self.surface.clear_all();
+                self.emit_frame()?;
+            },
+            SetSearchPattern(pattern) => {
+                self.set_search_pattern(pattern.as_deref());
+                self.emit_frame()?;
+            },
+            SearchNext => {
+                self.surface.focus_next_match();
+                self.emit_frame()?;
+            },
+            SearchPrevious => {
+                self.surface.focus_previous_match();
+                self.emit_frame()?;
+            },
+            SetSurfaceLayout(layout) => {
+                self.surface.set_layout(layout);
+                self.emit_frame()?;
+            },
             Shutdown => {
+                self.pending_input.clear();
                 let _ = self.close();
             },
+            GracefulShutdown { timeout } => {
+                self.shutdown.begin(self.clock.now(), timeout);
+                self.advance_shutdown()?;
+            },
         }
 
         Ok(())
@@ -391,23 +851,50 @@ where
 
     /// Flush buffered output into the PTY session.
     fn flush_pending_input(&mut self) -> Result<()> {
+        if self.flow_control.input_paused() {
+            return Ok(());
+        }
+
+        if self.output_mode == OutputMode::Drain {
+            self.drained_output.extend(self.pending_input.drain(..));
+            return Ok(());
+        }
+
         while !self.pending_input.is_empty() {
-            let chunk = {
-                let slice = self.pending_input.make_contiguous();
-                slice.to_vec()
-            };
+            // Write straight from the ring buffer in bounded slices instead
+            // of copying the whole backlog.
+            let (front, _) = self.pending_input.as_slices();
+            let chunk = &front[..front.len().min(self.write_chunk_size)];
 
             if chunk.is_empty() {
                 break;
             }
 
             let total = chunk.len();
-            let written = self.write(&chunk)?;
+            let written = match Self::write(&mut self.session, chunk) {
+                Ok(written) => written,
+                Err(crate::Error::Session(SessionError::IO(err)))
+                    if !is_fatal_write_error(&err) =>
+                {
+                    let dropped = self.pending_input.len();
+                    self.pending_input.clear();
+                    self.report_error(
+                        TerminalErrorKind::Write,
+                        format!("dropped {dropped} bytes of input: {err}"),
+                    );
+                    break;
+                },
+                Err(err) => return Err(err),
+            };
 
             if written == 0 {
                 break;
             }
 
+            if self.output_mode == OutputMode::Tee {
+                self.drained_output.extend_from_slice(&chunk[..written]);
+            }
+
             self.pending_input
                 .drain(0..written.min(self.pending_input.len()));
 
@@ -419,8 +906,33 @@ where
         Ok(())
     }
 
+    /// Close the session of a graceful shutdown once the pending input has
+    /// been written or the shutdown deadline has passed.
+    ///
+    /// Until then the input keeps being written as the session becomes
+    /// writable, without blocking the event loop.
+    fn advance_shutdown(&mut self) -> Result<()> {
+        if !self.shutdown.is_pending() {
+            return Ok(());
+        }
+
+        if let Err(err) = self.flush_pending_input() {
+            debug!("dropping input on shutdown: {err}");
+            self.pending_input.clear();
+        }
+        let input_pending = !self.pending_input.is_empty();
+        if !self.shutdown.finish(self.clock.now(), input_pending) {
+            return Ok(());
+        }
+
+        self.pending_input.clear();
+        self.flush_event_queue()?;
+        let _ = self.close();
+        Ok(())
+    }
+
     /// Write a chunk of bytes to the PTY session.
-    fn write(&mut self, bytes: &[u8]) -> Result<usize> {
+    fn write(session: &mut P, bytes: &[u8]) -> Result<usize> {
         let mut written = 0usize;
         let total = bytes.len();
         debug!(
@@ -431,7 +943,7 @@ where
         );
 
         while written < bytes.len() {
-            match self.session.write(&bytes[written..]) {
+            match session.write(&bytes[written..]) {
                 Ok(0) => break,
                 Ok(count) => written += count,
                 Err(SessionError::IO(err))
@@ -454,12 +966,65 @@ where
 
     /// Request a PTY resize and mirror the new geometry in the surface model.
     fn resize(&mut self, size: TerminalSize) -> Result<()> {
-        self.session.resize(size.into())?;
+        if let Err(err) = self.session.resize(size.into()) {
+            self.report_error(TerminalErrorKind::Resize, err);
+        }
         self.surface.resize(size);
         self.size = size;
         self.emit_frame()
     }
 
+    /// Apply a focus change, reporting it to the application if requested.
+    fn set_focus(&mut self, focused: bool) -> Result<()> {
+        if self.focused == focused {
+            return Ok(());
+        }
+
+        self.focused = focused;
+        self.surface.set_focus(focused, &mut self.pending_input);
+        self.flush_pending_input()?;
+
+        // Re-announce a blinking style so front-ends stop or resume blinking.
+        if self.cursor_style.is_some_and(|style| style.blinking) {
+            self.events.push(TerminalEvent::CursorStyleChanged {
+                style: self.cursor_style,
+            });
+        }
+
+        self.emit_frame()
+    }
+
+    /// Resolve the hyperlink span under the pointer and report changes.
+    fn hover_hyperlink(&mut self, point: Option<Point>) {
+        let span =
+            point.and_then(|point| self.surface.hyperlink_span_at(point));
+        if span == self.hovered_hyperlink {
+            return;
+        }
+
+        self.hovered_hyperlink = span.clone();
+        self.events.push(TerminalEvent::HyperlinkSpan { span });
+    }
+
+    fn set_search_pattern(&mut self, pattern: Option<&str>) {
+        let search =
+            pattern
+                .filter(|pattern| !pattern.is_empty())
+                .and_then(|pattern| match RegexSearch::new(pattern) {
+                    Ok(search) => Some(search),
+                    Err(err) => {
+                        debug!("invalid search pattern {pattern:?}: {err}");
+                        None
+                    },
+                });
+
+        let has_search = search.is_some();
+        self.surface.set_search(search);
+        if has_search {
+            self.surface.focus_previous_match();
+        }
+    }
+
     /// Terminate the session and return the reported exit status code.
     fn close(&mut self) -> Result<i32> {
         let code = self.session.close()?;
@@ -491,6 +1056,13 @@ where
         }
     }
 
+    /// Queue a [`TerminalEvent::Error`] for a recoverable failure.
+    fn report_error(&mut self, kind: TerminalErrorKind, detail: impl ToString) {
+        let detail = detail.to_string();
+        debug!("recoverable terminal error ({kind:?}): {detail}");
+        self.events.push(TerminalEvent::Error { kind, detail });
+    }
+
     fn enqueue_input(&mut self, data: Vec<u8>) {
         if data.is_empty() {
             return;
@@ -499,37 +1071,104 @@ where
     }
 
     fn emit_frame(&mut self) -> Result<()> {
+        if self.batching_frames {
+            self.batched_frame = true;
+            return Ok(());
+        }
+
         let frame = self.surface.snapshot_owned();
         self.surface.reset_damage();
-        self.events.push_back(TerminalEvent::Frame {
+        self.frame_batch.record(self.clock.now());
+        self.events.push(TerminalEvent::Frame {
             frame: Arc::new(frame),
         });
+        for output in self.surface.take_block_output() {
+            self.events.push(match output {
+                BlockOutput::Appended { block_id, text } => {
+                    TerminalEvent::BlockOutput {
+                        block_id,
+                        appended_text: text,
+                    }
+                },
+                BlockOutput::Finished {
+                    block_id,
+                    exit_code,
+                } => TerminalEvent::BlockFinished {
+                    block_id,
+                    exit_code,
+                },
+            });
+        }
+
+        self.check_memory_pressure();
         Ok(())
     }
 
+    fn check_memory_pressure(&mut self) {
+        if !self.memory_pressure.is_enabled() {
+            return;
+        }
+
+        let bytes = self.surface.memory_usage();
+        if self.memory_pressure.should_emit(bytes, self.clock.now()) {
+            self.events.push(TerminalEvent::MemoryPressure { bytes });
+        }
+    }
+
     fn emit_child_exit(&mut self, status: ExitStatus) -> Result<()> {
-        self.events.push_back(TerminalEvent::ChildExit { status });
+        self.events.push(TerminalEvent::ChildExit { status });
         Ok(())
     }
 
+    /// Send queued events to the sink in the order documented on
+    /// [`TerminalEvent`].
+    ///
+    /// Events are queued as they happen, which already puts action-driven
+    /// events ahead of their frame. The one reordering is a frame deferred
+    /// by the frame interval: it is emitted ahead of a queued `ChildExit` so
+    /// the final output reaches clients before the exit.
     fn flush_event_queue(&mut self) -> Result<()> {
-        while let Some(event) = self.events.pop_front() {
-            match self.event_tx.try_send(event) {
-                Ok(()) => {},
-                Err(err) => match map_send_error(err) {
-                    ChannelSendError::Full => {
-                        return Err(crate::Error::EventChannelFull);
-                    },
-                    ChannelSendError::Disconnected => {
-                        return Err(crate::Error::EventChannelClosed);
-                    },
-                },
-            }
+        if self.frame_batch.is_pending()
+            && let Some(exit_events) = self.events.split_off_exit()
+        {
+            self.emit_frame()?;
+            self.events.extend(exit_events);
+        }
+
+        let cursor_style = &mut self.cursor_style;
+        let focused = self.focused;
+        let dropped_frame = self.events.flush(
+            &mut self.event_sink,
+            &self.dirty,
+            &mut self.metrics,
+            |event| gate_cursor_blink(cursor_style, focused, event),
+        )?;
+        if dropped_frame {
+            self.surface.mark_fully_damaged();
         }
         Ok(())
     }
 }
 
+/// Remember the requested cursor style and suppress blinking while the
+/// terminal is unfocused.
+fn gate_cursor_blink(
+    cursor_style: &mut Option<CursorStyle>,
+    focused: bool,
+    event: TerminalEvent,
+) -> TerminalEvent {
+    let TerminalEvent::CursorStyleChanged { style } = event else {
+        return event;
+    };
+
+    *cursor_style = style;
+    let style = style.map(|style| CursorStyle {
+        blinking: style.blinking && focused,
+        ..style
+    });
+    TerminalEvent::CursorStyleChanged { style }
+}
+
 fn to_exit_status(code: i32) -> ExitStatus {
     #[cfg(unix)]
     {
@@ -555,11 +1194,25 @@ fn is_session_closed_error(err: &std::io::Error) -> bool {
     err.kind() == ErrorKind::UnexpectedEof
 }
 
-impl<P, E, S> TerminalEngine<P, E, S>
+/// Whether a failed write means the session is gone rather than a
+/// transient failure worth reporting and moving on from.
+fn is_fatal_write_error(err: &std::io::Error) -> bool {
+    is_session_closed_error(err)
+        || matches!(
+            err.kind(),
+            ErrorKind::BrokenPipe
+                | ErrorKind::ConnectionReset
+                | ErrorKind::NotConnected
+        )
+}
+
+impl<P, E, S, K, R> TerminalEngine<P, E, S, K, R>
 where
     P: Session + Pollable,
     E: EscapeParser,
     S: SurfaceActor + SurfaceModel,
+    K: EventSink,
+    R: RequestSource,
 {
     /// Register the underlying session with a mio registry.
     pub fn register_session(
@@ -600,46 +1253,309 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::surface::{Surface, SurfaceConfig};
+    use crate::escape::{Action, NamedPrivateMode};
+    use crate::surface::{
+        BlockSurface, Column, Dimensions, Line, SnapshotDamage, Surface,
+        SurfaceConfig, SurfaceMode,
+    };
     use crate::terminal::channel::ChannelConfig;
+    use crate::terminal::sync::SYNC_TIMEOUT;
     use crate::tests::{
-        EioSession, FakeSession, PartialSession, StubParser, assert_frame,
-        collect_events, exit_ok,
+        EioSession, FakeSession, ManualClock, PartialSession, StubParser,
+        assert_frame, collect_events, exit_ok,
     };
-    use crate::{DefaultParser, Error};
+    use crate::{DefaultParser, Error, FastParser};
 
     #[test]
-    fn partial_writes_keep_pending_output_until_drained() -> Result<()> {
-        let session = PartialSession::with_behavior(4, true);
-        let parser = StubParser::default();
-        let surface =
-            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
-
-        let (mut engine, _handle, _events) = TerminalEngine::new(
+    fn surface_layout_requests_switch_the_frame_layout() -> Result<()> {
+        let preexec = r#"{"id":"1","phase":"preexec","cmd":"ls","time":1}"#;
+        let output = format!("\x1bPotty-dcs;block;{preexec}\x1b\\files");
+        let session = FakeSession::with_reads(vec![output.into_bytes()]);
+        let surface = BlockSurface::new(
+            SurfaceConfig::default(),
+            &TerminalSize::default(),
+        );
+        let (mut engine, handle, events) = TerminalEngine::new(
             session,
-            parser,
+            DefaultParser::default(),
             surface,
-            TerminalOptions {
-                channel_config: ChannelConfig::bounded(16),
-                ..TerminalOptions::default()
-            },
+            TerminalOptions::default(),
         )?;
+        let last_frame = || {
+            collect_events(&events)
+                .into_iter()
+                .filter_map(|event| match event {
+                    TerminalEvent::Frame { frame } => Some(frame),
+                    _ => None,
+                })
+                .last()
+                .expect("a frame")
+        };
+        let text = |frame: &SnapshotOwned| {
+            frame
+                .view()
+                .cells
+                .iter()
+                .map(|cell| cell.cell.c)
+                .collect::<String>()
+        };
 
-        engine
-            .queue_request(TerminalRequest::WriteBytes(b"abcdefgh".to_vec()))?;
+        engine.on_readable()?;
+        let blocks = last_frame();
+        assert_eq!(blocks.view().layout, SurfaceLayout::Blocks);
+        assert_eq!(blocks.view().blocks().len(), 2);
 
-        assert!(engine.has_pending_output());
+        handle
+            .send(TerminalRequest::SetSurfaceLayout(SurfaceLayout::Grid))
+            .expect("request channel open");
+        engine.tick()?;
+        let grid = last_frame();
+        assert_eq!(grid.view().layout, SurfaceLayout::Grid);
+        assert!(grid.view().blocks().is_empty());
+        assert_eq!(text(&grid), text(&blocks));
+        assert!(text(&grid).contains("files"));
 
-        let pending_after_first = engine.on_writable()?;
-        assert!(pending_after_first);
-        assert_eq!(engine.session.writes.len(), 1);
-        assert_eq!(engine.session.writes[0], b"abcd");
-        assert_eq!(engine.pending_input.len(), 4);
-        assert!(engine.has_pending_output());
+        handle
+            .send(TerminalRequest::SetSurfaceLayout(SurfaceLayout::Blocks))
+            .expect("request channel open");
+        engine.tick()?;
+        let blocks = last_frame();
+        let command = blocks.view().blocks().last().cloned().expect("a block");
+        assert_eq!(command.meta.cmd.as_deref(), Some("ls"));
+        assert_eq!(text(&blocks), text(&grid));
 
-        engine.session.blocked = false;
-        let pending_after_second = engine.on_writable()?;
-        assert!(!pending_after_second);
+        Ok(())
+    }
+
+    #[test]
+    fn additive_selection_starts_keep_earlier_ranges() -> Result<()> {
+        let session =
+            FakeSession::with_reads(vec![b"alpha beta\r\ngamma".to_vec()]);
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, handle, events) = TerminalEngine::new(
+            session,
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+        engine.on_readable()?;
+
+        let select = |line: i32, start: usize, end: usize, additive| {
+            handle
+                .send(TerminalRequest::StartSelection {
+                    ty: SelectionType::Simple,
+                    point: Point::new(Line(line), Column(start)),
+                    direction: Side::Left,
+                    additive,
+                })
+                .expect("request channel open");
+            handle
+                .send(TerminalRequest::UpdateSelection {
+                    point: Point::new(Line(line), Column(end)),
+                    direction: Side::Right,
+                })
+                .expect("request channel open");
+        };
+        let last_frame = |engine: &mut TerminalEngine<_, _, _>| {
+            engine.tick().expect("tick");
+            collect_events(&events)
+                .into_iter()
+                .filter_map(|event| match event {
+                    TerminalEvent::Frame { frame } => Some(frame),
+                    _ => None,
+                })
+                .last()
+                .expect("a frame")
+        };
+
+        select(0, 0, 4, false);
+        select(1, 0, 4, true);
+        let frame = last_frame(&mut engine);
+        let view = frame.view();
+        assert_eq!(view.extra_selections.len(), 1);
+        assert!(view.is_selected(Point::new(Line(0), Column(2))));
+        assert!(view.is_selected(Point::new(Line(1), Column(2))));
+        assert_eq!(view.selectable_content(), "alphagamma");
+
+        select(0, 6, 9, false);
+        let frame = last_frame(&mut engine);
+        let view = frame.view();
+        assert!(view.extra_selections.is_empty());
+        assert_eq!(view.selectable_content(), "beta");
+
+        Ok(())
+    }
+
+    #[test]
+    fn shutdown_writes_pending_input_only_when_draining() -> Result<()> {
+        let shutdown = |request: TerminalRequest, unblock: bool| {
+            let (mut engine, _handle, _events) = TerminalEngine::new(
+                PartialSession::with_behavior(4, true),
+                StubParser::default(),
+                Surface::new(
+                    SurfaceConfig::default(),
+                    &TerminalSize::default(),
+                ),
+                TerminalOptions::default(),
+            )?;
+            engine.process_request(TerminalRequest::WriteBytes(
+                b"exit\n".to_vec(),
+            ))?;
+            assert_eq!(engine.pending_input.len(), 1);
+
+            engine.session.blocked = !unblock;
+            engine.process_request(request)?;
+
+            assert!(engine.pending_input.is_empty());
+            assert!(engine.exit_status.is_some());
+            Ok::<_, Error>(engine.session.writes.concat())
+        };
+        let graceful = |timeout| TerminalRequest::GracefulShutdown { timeout };
+
+        assert_eq!(
+            shutdown(graceful(Duration::from_secs(1)), true)?,
+            b"exit\n"
+        );
+        assert_eq!(shutdown(TerminalRequest::Shutdown, true)?, b"exit");
+
+        Ok(())
+    }
+
+    #[test]
+    fn graceful_shutdown_waits_for_the_session_without_blocking() -> Result<()>
+    {
+        let clock = Arc::new(ManualClock::new());
+        let start = clock.now();
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            PartialSession::with_behavior(4, true),
+            StubParser::default(),
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default()),
+            TerminalOptions {
+                clock: clock.clone(),
+                ..TerminalOptions::default()
+            },
+        )?;
+        engine
+            .process_request(TerminalRequest::WriteBytes(b"exit\n".to_vec()))?;
+        engine.process_request(TerminalRequest::GracefulShutdown {
+            timeout: Duration::from_millis(5),
+        })?;
+
+        // The blocked session leaves the engine open for the event loop.
+        assert!(engine.is_shutting_down());
+        assert!(engine.has_pending_output());
+        assert_eq!(
+            engine.next_deadline(),
+            Some(start + Duration::from_millis(5))
+        );
+        engine.tick()?;
+        assert!(engine.exit_status.is_none());
+
+        engine.session.blocked = false;
+        engine.on_writable()?;
+        assert!(!engine.is_shutting_down());
+        assert_eq!(engine.session.writes.concat(), b"exit\n");
+        assert!(matches!(
+            collect_events(&events).as_slice(),
+            [TerminalEvent::ChildExit { .. }]
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn graceful_shutdown_closes_once_the_deadline_passes() -> Result<()> {
+        let clock = Arc::new(ManualClock::new());
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            PartialSession::with_behavior(4, true),
+            StubParser::default(),
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default()),
+            TerminalOptions {
+                clock: clock.clone(),
+                ..TerminalOptions::default()
+            },
+        )?;
+        engine
+            .process_request(TerminalRequest::WriteBytes(b"exit\n".to_vec()))?;
+        engine.process_request(TerminalRequest::GracefulShutdown {
+            timeout: Duration::from_millis(5),
+        })?;
+
+        clock.advance(Duration::from_millis(5));
+        engine.tick()?;
+
+        assert!(!engine.is_shutting_down());
+        assert!(engine.exit_status.is_some());
+        assert!(engine.pending_input.is_empty());
+        assert_eq!(engine.session.writes.concat(), b"exit");
+
+        Ok(())
+    }
+
+    #[test]
+    fn graceful_shutdown_without_a_deadline_waits_for_the_input() -> Result<()>
+    {
+        let clock = Arc::new(ManualClock::new());
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            PartialSession::with_behavior(4, true),
+            StubParser::default(),
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default()),
+            TerminalOptions {
+                clock: clock.clone(),
+                ..TerminalOptions::default()
+            },
+        )?;
+        engine
+            .process_request(TerminalRequest::WriteBytes(b"exit\n".to_vec()))?;
+        engine.process_request(TerminalRequest::GracefulShutdown {
+            timeout: Duration::MAX,
+        })?;
+
+        clock.advance(Duration::from_secs(3600));
+        engine.tick()?;
+        assert!(engine.is_shutting_down());
+
+        engine.session.blocked = false;
+        engine.tick()?;
+        assert!(engine.exit_status.is_some());
+        assert_eq!(engine.session.writes.concat(), b"exit\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn partial_writes_keep_pending_output_until_drained() -> Result<()> {
+        let session = PartialSession::with_behavior(4, true);
+        let parser = StubParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions {
+                channel_config: ChannelConfig::bounded(16),
+                ..TerminalOptions::default()
+            },
+        )?;
+
+        engine
+            .queue_request(TerminalRequest::WriteBytes(b"abcdefgh".to_vec()))?;
+
+        assert!(engine.has_pending_output());
+
+        let pending_after_first = engine.on_writable()?;
+        assert!(pending_after_first);
+        assert_eq!(engine.session.writes.len(), 1);
+        assert_eq!(engine.session.writes[0], b"abcd");
+        assert_eq!(engine.pending_input.len(), 4);
+        assert!(engine.has_pending_output());
+
+        engine.session.blocked = false;
+        let pending_after_second = engine.on_writable()?;
+        assert!(!pending_after_second);
         assert_eq!(engine.session.writes.len(), 2);
         assert_eq!(engine.session.writes[1], b"efgh");
         assert!(engine.pending_input.is_empty());
@@ -648,91 +1564,1740 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn dirty_token_resolves_once_events_are_queued() -> Result<()> {
+        let session = FakeSession::with_reads(vec![b"payload".to_vec()]);
+        let parser = StubParser::with_actions(vec![Action::Bell]);
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
+        assert!(!events.take_dirty());
+
+        engine.on_readable()?;
+        tokio::time::timeout(Duration::from_secs(1), events.dirty_token())
+            .await
+            .expect("dirty token resolves");
+
+        assert!(
+            collect_events(&events)
+                .iter()
+                .any(|event| matches!(event, TerminalEvent::Bell))
+        );
+        assert!(!events.take_dirty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn custom_transport_captures_events_without_channels() -> Result<()> {
+        let session = FakeSession::with_reads(vec![b"payload".to_vec()]);
+        let parser = StubParser::with_actions(vec![Action::Bell]);
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let requests = VecDeque::from([TerminalRequest::RequestFrame]);
+
+        let mut engine = TerminalEngine::with_transport(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+            Vec::new(),
+            requests,
+        );
+        engine.on_readable()?;
+
+        assert!(engine.request_source_mut().is_empty());
+        let events = engine.event_sink();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], TerminalEvent::Frame { .. }));
+        assert!(matches!(events[1], TerminalEvent::Bell));
+        assert!(matches!(events[2], TerminalEvent::Frame { .. }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn large_pending_input_is_written_in_bounded_chunks() -> Result<()> {
+        let session = PartialSession::with_behavior(usize::MAX, false);
+        let parser = StubParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions {
+                write_chunk_size: 1000,
+                ..TerminalOptions::default()
+            },
+        )?;
+
+        let payload = (0..10_500).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        engine.process_request(TerminalRequest::WriteBytes(payload.clone()))?;
+
+        let writes = &engine.session.writes;
+        assert_eq!(writes.len(), 11);
+        assert!(writes.iter().all(|write| write.len() <= 1000));
+        assert_eq!(writes.concat(), payload);
+        assert!(engine.pending_input.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn backlog_accessors_report_queued_requests_and_input() -> Result<()> {
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            PartialSession::with_behavior(4, true),
+            StubParser::default(),
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default()),
+            TerminalOptions::default(),
+        )?;
+
+        engine
+            .queue_request(TerminalRequest::WriteBytes(b"abcdef".to_vec()))?;
+        engine.queue_request(TerminalRequest::RequestFrame)?;
+        assert_eq!(engine.queued_request_count(), 2);
+        assert_eq!(engine.pending_input_len(), 0);
+
+        engine.on_writable()?;
+        assert_eq!(engine.queued_request_count(), 0);
+        assert_eq!(engine.pending_input_len(), 2);
+
+        engine.session.blocked = false;
+        engine.on_writable()?;
+        assert_eq!(engine.pending_input_len(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn has_pending_output_includes_queued_write_request() -> Result<()> {
+        let session = PartialSession::with_behavior(4, true);
+        let parser = StubParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+
+        let (mut engine, handle, _events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        handle
+            .send(TerminalRequest::WriteBytes(b"long-payload".to_vec()))
+            .expect("request channel open");
+
+        engine.process_pending_requests()?;
+        assert!(engine.has_pending_output());
+
+        engine.session.blocked = false;
+        engine.on_writable()?;
+        engine.session.blocked = false;
+        engine.on_writable()?;
+        assert!(!engine.has_pending_output());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sustained_output_frames_are_bounded_by_the_interval() -> Result<()> {
+        let interval = Duration::from_millis(20);
+        let clock = Arc::new(ManualClock::new());
+        let parser = DefaultParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            parser,
+            surface,
+            TerminalOptions {
+                frame_interval: Some(interval),
+                clock: clock.clone(),
+                ..TerminalOptions::default()
+            },
+        )?;
+
+        // One byte per millisecond for 200ms of simulated output.
+        for _ in 0..200 {
+            engine.session.push_read(b"x");
+            engine.on_readable()?;
+            clock.advance(Duration::from_millis(1));
+        }
+        let bound = (200 / interval.as_millis()) as usize + 1;
+
+        // Once output stops the deferred frame goes out on the next tick.
+        clock.advance(interval);
+        engine.tick()?;
+        assert_eq!(engine.frame_batch.deadline(), None);
+        engine.flush_event_queue()?;
+
+        let frames = collect_events(&events)
+            .into_iter()
+            .filter_map(|event| match event {
+                TerminalEvent::Frame { frame } => Some(frame),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(frames.len() <= bound + 1, "{} > {bound}", frames.len());
+        let printed = frames
+            .last()
+            .expect("at least one frame")
+            .view()
+            .cells
+            .iter()
+            .filter(|cell| cell.cell.c == 'x')
+            .count();
+        assert_eq!(printed, 200);
+
+        Ok(())
+    }
+
+    #[test]
+    fn index_controls_scroll_within_the_scroll_region() -> Result<()> {
+        // Label six lines, set the region to lines 2..=4 and park the
+        // cursor on the bottom margin.
+        let steps: [&[u8]; 4] = [
+            b"0\r\n1\r\n2\r\n3\r\n4\r\n5\x1b[2;4r\x1b[4;2H",
+            b"\x1bE",
+            b"\x1bD",
+            b"\x1b[2;1H\x1bM",
+        ];
+        let size = TerminalSize {
+            cols: 3,
+            rows: 6,
+            ..TerminalSize::default()
+        };
+        let surface = Surface::new(SurfaceConfig::default(), &size);
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        let mut snapshots = Vec::new();
+        for step in steps {
+            engine.session.push_read(step);
+            engine.on_readable()?;
+            let grid = engine.surface.grid();
+            let labels = (0..6)
+                .map(|line| grid[Point::new(Line(line), Column(0))].c)
+                .collect::<String>();
+            snapshots.push((labels, grid.cursor.point));
+        }
+
+        assert_eq!(
+            snapshots,
+            vec![
+                ("012345".into(), Point::new(Line(3), Column(1))),
+                ("023 45".into(), Point::new(Line(3), Column(0))),
+                ("03  45".into(), Point::new(Line(3), Column(0))),
+                ("0 3 45".into(), Point::new(Line(1), Column(0))),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn injected_queries_reply_into_the_drain_buffer() -> Result<()> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+        engine.set_output_mode(OutputMode::Drain);
+
+        engine.inject(b"ab\x1b[6n")?;
+
+        let mut output = Vec::new();
+        assert_eq!(engine.drain_output_into(&mut output), 6);
+        assert_eq!(output, b"\x1b[1;3R");
+        assert!(engine.session.writes.is_empty());
+        assert!(!engine.has_pending_output());
+
+        engine.set_output_mode(OutputMode::Tee);
+        engine
+            .process_request(TerminalRequest::WriteBytes(b"ls\r".to_vec()))?;
+
+        output.clear();
+        engine.drain_output_into(&mut output);
+        assert_eq!(output, b"ls\r");
+        assert_eq!(engine.session.writes, [b"ls\r".to_vec()]);
+
+        Ok(())
+    }
+
+    fn frame_texts(events: &TerminalEvents) -> Vec<String> {
+        frame_texts_from(&collect_events(events))
+    }
+
+    fn frame_texts_from(events: &[TerminalEvent]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                TerminalEvent::Frame { frame } => Some(
+                    frame
+                        .view()
+                        .cells
+                        .iter()
+                        .map(|cell| cell.cell.c)
+                        .collect::<String>()
+                        .trim_end()
+                        .to_string(),
+                ),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn control_chars_are_shown_as_glyphs_only_when_enabled() -> Result<()> {
+        let render = |show_control_chars| -> Result<Vec<String>> {
+            let session =
+                FakeSession::with_reads(vec![b"a\x03b\x1b[1mc".into()]);
+            let surface = Surface::new(
+                SurfaceConfig::default(),
+                &TerminalSize::default(),
+            );
+            let (mut engine, _handle, events) = TerminalEngine::new(
+                session,
+                DefaultParser::default(),
+                surface,
+                TerminalOptions {
+                    show_control_chars,
+                    ..TerminalOptions::default()
+                },
+            )?;
+            engine.on_readable()?;
+            Ok(frame_texts(&events))
+        };
+
+        assert_eq!(render(true)?, ["a^Cb\u{241B}[1mc"]);
+        assert_eq!(render(false)?, ["abc"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn high_bytes_are_characters_when_utf8_is_off() -> Result<()> {
+        // British set in G0, then `#`, `é` and `£` as Latin-1 bytes.
+        let output = b"\x1b(A#\xe9\xa3".to_vec();
+        let render = |utf8| -> Result<Vec<String>> {
+            let session = FakeSession::with_reads(vec![output.clone()]);
+            let surface = Surface::new(
+                SurfaceConfig::default(),
+                &TerminalSize::default(),
+            );
+            let (mut engine, _handle, events) = TerminalEngine::new(
+                session,
+                DefaultParser::default(),
+                surface,
+                TerminalOptions {
+                    utf8,
+                    ..TerminalOptions::default()
+                },
+            )?;
+            engine.on_readable()?;
+            Ok(frame_texts(&events))
+        };
+
+        assert_eq!(render(false)?, ["£é£"]);
+        // As UTF-8 the same bytes are an unfinished multi-byte sequence.
+        assert_eq!(render(true)?, ["£"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn high_bytes_map_through_the_set_invoked_into_gr() -> Result<()> {
+        let render = |output: &[u8]| -> Result<Vec<String>> {
+            let session = FakeSession::with_reads(vec![output.to_vec()]);
+            let surface = Surface::new(
+                SurfaceConfig::default(),
+                &TerminalSize::default(),
+            );
+            let (mut engine, _handle, events) = TerminalEngine::new(
+                session,
+                DefaultParser::default(),
+                surface,
+                TerminalOptions {
+                    utf8: false,
+                    ..TerminalOptions::default()
+                },
+            )?;
+            engine.on_readable()?;
+            Ok(frame_texts(&events))
+        };
+
+        // G1 is in GR: `q` as a high byte is a line, `#` is a pound sign.
+        assert_eq!(render(b"\x1b)0a\xf1")?, ["a─"]);
+        assert_eq!(render(b"\x1b)A\xa3\xe9")?, ["£i"]);
+        // GR maps on its own, whatever is invoked into GL.
+        assert_eq!(render(b"\x1b(0\x1b)A\xf1q")?, ["q─"]);
+        // LS2R invokes G2 into GR instead.
+        assert_eq!(render(b"\x1b)0\x1b*A\x1b}\xa3\xf1")?, ["£q"]);
+
+        Ok(())
+    }
+
+    fn flow_control_engine() -> Result<(
+        TerminalEngine<FakeSession, DefaultParser, Surface>,
+        TerminalEvents,
+    )> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions {
+                flow_control: true,
+                ..TerminalOptions::default()
+            },
+        )?;
+        Ok((engine, events))
+    }
+
+    #[test]
+    fn xoff_from_the_child_withholds_input_until_xon() -> Result<()> {
+        let (mut engine, _events) = flow_control_engine()?;
+
+        engine.inject(b"\x13")?;
+        engine
+            .process_request(TerminalRequest::WriteBytes(b"ls\r".to_vec()))?;
+        assert!(engine.session.writes.is_empty());
+        assert!(!engine.has_pending_output());
+
+        engine.inject(b"\x11")?;
+        assert_eq!(engine.session.writes.concat(), b"ls\r");
+        assert!(!engine.has_pending_output());
+
+        Ok(())
+    }
+
+    #[test]
+    fn xoff_is_ignored_without_flow_control() -> Result<()> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.inject(b"\x13")?;
+        engine
+            .process_request(TerminalRequest::WriteBytes(b"\x13ls".to_vec()))?;
+        assert_eq!(engine.session.writes.concat(), b"\x13ls");
+
+        Ok(())
+    }
+
+    #[test]
+    fn ctrl_s_and_ctrl_q_pause_and_resume_output_frames() -> Result<()> {
+        let (mut engine, events) = flow_control_engine()?;
+
+        engine
+            .process_request(TerminalRequest::WriteBytes(b"\x13".to_vec()))?;
+        engine.inject(b"hello")?;
+        engine.tick()?;
+        assert!(frame_texts(&events).is_empty());
+
+        engine
+            .process_request(TerminalRequest::WriteBytes(b"\x11".to_vec()))?;
+        engine.tick()?;
+        assert_eq!(frame_texts(&events), ["hello"]);
+        assert!(engine.session.writes.is_empty());
+
+        Ok(())
+    }
+
+    fn soft_errors(events: &[TerminalEvent]) -> Vec<TerminalErrorKind> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                TerminalEvent::Error { kind, .. } => Some(*kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn malformed_read_is_reported_and_reading_continues() -> Result<()> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.session.push_read(b"before ");
+        engine
+            .session
+            .push_read_error(std::io::ErrorKind::InvalidData);
+        engine.session.push_read(b"after");
+        assert!(engine.on_readable()?);
+
+        let events = collect_events(&events);
+        assert_eq!(soft_errors(&events), [TerminalErrorKind::Read]);
+        assert_eq!(frame_texts_from(&events), ["before after"]);
+
+        for _ in 0..=MAX_CONSECUTIVE_READ_ERRORS {
+            engine
+                .session
+                .push_read_error(std::io::ErrorKind::InvalidData);
+        }
+        assert!(engine.on_readable().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn failed_write_is_reported_without_stopping_the_engine() -> Result<()> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.session.fail_writes(std::io::ErrorKind::TimedOut);
+        engine
+            .process_request(TerminalRequest::WriteBytes(b"ls\r".to_vec()))?;
+        assert!(!engine.has_pending_output());
+
+        engine.inject(b"still running")?;
+        let events = collect_events(&events);
+        assert_eq!(soft_errors(&events), [TerminalErrorKind::Write]);
+        assert_eq!(frame_texts_from(&events), ["still running"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_to_a_closed_session_is_fatal() -> Result<()> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        for kind in [
+            std::io::ErrorKind::BrokenPipe,
+            std::io::ErrorKind::ConnectionReset,
+            std::io::ErrorKind::NotConnected,
+        ] {
+            engine.session.fail_writes(kind);
+            let result = engine
+                .process_request(TerminalRequest::WriteBytes(b"ls\r".to_vec()));
+            assert!(matches!(result, Err(Error::Session(_))), "{kind:?}");
+        }
+        assert!(soft_errors(&collect_events(&events)).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn synchronized_output_withholds_frames_until_the_end() -> Result<()> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.session.push_read(b"\x1b[?2026hhello");
+        engine.on_readable()?;
+        engine.inject(b" world")?;
+        assert!(frame_texts(&events).is_empty());
+        assert!(engine.sync_state.is_active());
+
+        engine.inject(b"\x1b[?2026l")?;
+        assert_eq!(frame_texts(&events), ["hello world"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn frame_rate_cap_coalesces_updates_within_a_window() -> Result<()> {
+        let clock = Arc::new(ManualClock::new());
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions {
+                max_fps: Some(10),
+                clock: clock.clone(),
+                ..TerminalOptions::default()
+            },
+        )?;
+
+        for byte in [b"a", b"b", b"c", b"d", b"e"] {
+            engine.session.push_read(byte);
+            engine.on_readable()?;
+            clock.advance(Duration::from_millis(10));
+        }
+        assert_eq!(frame_texts(&events), ["a"]);
+        assert_eq!(engine.metrics().frames_sent(), 1);
+        assert_eq!(engine.metrics().frames_dropped(), 4);
+
+        // Once output goes idle the coalesced frame follows the window.
+        engine.tick()?;
+        assert!(frame_texts(&events).is_empty());
+        clock.advance(Duration::from_millis(50));
+        engine.tick()?;
+        assert_eq!(frame_texts(&events), ["abcde"]);
+        assert_eq!(engine.metrics().frames_sent(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn synchronized_output_timeout_follows_the_injected_clock() -> Result<()> {
+        let clock = Arc::new(ManualClock::new());
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions {
+                clock: clock.clone(),
+                ..TerminalOptions::default()
+            },
+        )?;
+
+        engine.inject(b"\x1b[?2026hhello")?;
+        clock.advance(SYNC_TIMEOUT);
+        engine.tick()?;
+        assert!(frame_texts(&events).is_empty());
+        assert!(engine.sync_state.is_active());
+
+        clock.advance(Duration::from_millis(1));
+        engine.tick()?;
+        assert_eq!(frame_texts(&events), ["hello"]);
+        assert!(!engine.sync_state.is_active());
+
+        Ok(())
+    }
+
+    #[test]
+    fn block_output_is_streamed_before_the_block_finishes() -> Result<()> {
+        let surface = BlockSurface::new(
+            SurfaceConfig::default(),
+            &TerminalSize::default(),
+        );
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+        let block_events = || {
+            collect_events(&events)
+                .into_iter()
+                .filter_map(|event| match event {
+                    TerminalEvent::BlockOutput {
+                        block_id,
+                        appended_text,
+                    } => Some(format!("{block_id}+{appended_text}")),
+                    TerminalEvent::BlockFinished {
+                        block_id,
+                        exit_code,
+                    } => Some(format!("{block_id}={exit_code:?}")),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mark =
+            |payload: &str| format!("\x1bPotty-dcs;block;{payload}\x1b\\");
+
+        let preexec = mark(r#"{"id":"7","phase":"preexec","cmd":"build"}"#);
+        engine.inject(format!("$ build\r\n{preexec}").as_bytes())?;
+        engine.inject(b"compiling\r\nlinking")?;
+        assert_eq!(block_events(), ["7+compiling\n"]);
+
+        let exit = mark(r#"{"id":"7","phase":"exit","exit_code":0}"#);
+        engine.inject(format!("\r\n{exit}").as_bytes())?;
+        assert_eq!(block_events(), ["7+linking\n", "7=Some(0)"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn progress_reports_are_emitted_once_per_change() -> Result<()> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.inject(
+            b"\x1b]9;4;1;10\x07\x1b]9;4;1;10\x07\x1b]9;4;1;250\x07\
+              \x1b]9;4;2;100\x07\x1b]9;4;0\x07",
+        )?;
+
+        let progress = collect_events(&events)
+            .into_iter()
+            .filter_map(|event| match event {
+                TerminalEvent::Progress { state, percent } => {
+                    Some((state, percent))
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            progress,
+            [
+                (ProgressState::Normal, 10),
+                (ProgressState::Normal, 100),
+                (ProgressState::Error, 100),
+                (ProgressState::Remove, 0),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cursor_movements_clamp_at_screen_and_margins() -> Result<()> {
+        let steps: [(&[u8], (i32, usize)); 22] = [
+            (b"\x1b[3;4H\x1b[A", (1, 3)),
+            (b"\x1b[2B", (3, 3)),
+            (b"\x1b[C", (3, 4)),
+            (b"\x1b[99C", (3, 9)),
+            (b"\x1b[2D", (3, 7)),
+            (b"\x1b[99D", (3, 0)),
+            (b"\x1b[99A", (0, 0)),
+            (b"\x1b[99B", (5, 0)),
+            (b"\x1b[5G", (5, 4)),
+            (b"\x1b[G", (5, 0)),
+            (b"\x1b[2d", (1, 0)),
+            (b"\x1b[d", (0, 0)),
+            (b"\x1b[3;5H\x1b[E", (3, 0)),
+            (b"\x1b[2F", (1, 0)),
+            (b"\x1b[3a", (1, 3)),
+            (b"\x1b[2e", (3, 3)),
+            (b"\x1b[99e", (5, 3)),
+            // Scroll region on lines 2..=4 (indices 1..=3).
+            (b"\x1b[2;4r\x1b[3;1H\x1b[9A", (1, 0)),
+            (b"\x1b[9B", (3, 0)),
+            (b"\x1b[6;1H\x1b[9B", (5, 0)),
+            (b"\x1b[9A", (1, 0)),
+            (b"\x1b[1;1H\x1b[9B", (3, 0)),
+        ];
+        let size = TerminalSize {
+            cols: 10,
+            rows: 6,
+            ..TerminalSize::default()
+        };
+        let surface = Surface::new(SurfaceConfig::default(), &size);
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        for (bytes, (line, column)) in steps {
+            engine.session.push_read(bytes);
+            engine.on_readable()?;
+            assert_eq!(
+                engine.surface.grid().cursor.point,
+                Point::new(Line(line), Column(column)),
+                "after {:?}",
+                String::from_utf8_lossy(bytes)
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn backspace_moves_back_and_del_changes_nothing() -> Result<()> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+        let first_row = |engine: &TerminalEngine<_, _, Surface>| {
+            engine.surface.grid()[Line(0)][..Column(3)]
+                .iter()
+                .map(|cell| cell.c)
+                .collect::<String>()
+        };
+
+        engine.inject(b"abc\x08\x08")?;
+        assert_eq!(first_row(&engine), "abc");
+        assert_eq!(
+            engine.surface.grid().cursor.point,
+            Point::new(Line(0), Column(1))
+        );
+
+        engine.inject(b"\x7f")?;
+        assert_eq!(first_row(&engine), "abc");
+        assert_eq!(
+            engine.surface.grid().cursor.point,
+            Point::new(Line(0), Column(1))
+        );
+
+        // Backspace is not an erase: printing overwrites in place.
+        engine.inject(b"X")?;
+        assert_eq!(first_row(&engine), "aXc");
+
+        Ok(())
+    }
+
+    #[test]
+    fn sixel_images_reserve_cells_from_the_cell_pixel_size() -> Result<()> {
+        let size = TerminalSize {
+            cell_width: 10,
+            cell_height: 20,
+            cols: 8,
+            rows: 4,
+        };
+        let surface = Surface::new(SurfaceConfig::default(), &size);
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+        engine.process_request(TerminalRequest::Resize(size))?;
+
+        engine.session.push_read(b"ab\x1bPq\"1;1;25;30#0~\x1b\\");
+        engine.on_readable()?;
+
+        let regions = engine.surface.snapshot_owned().view().image_regions();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].start, Point::new(Line(0), Column(2)));
+        assert_eq!((regions[0].columns, regions[0].lines), (3, 2));
+        assert_eq!(
+            engine.surface.grid().cursor.point,
+            Point::new(Line(2), Column(2))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fast_parser_matches_default_parser_surface_state() -> Result<()> {
+        let chunks: [&[u8]; 4] = [
+            b"$ ls\r\n\x1b[1;34mdir\x1b[0m  file.txt  \xe7\x95",
+            b"\x8c wide \xe7\x95\x8c and more text that wraps\r\n",
+            b"\x1b[2;5Hover\x1b[Kwrite\x1b[3@\x1b[4bx\ty\x08z\x1b[",
+            b"7mrev\x1b[27m\x1b]2;title\x07\x1b[?1049hin alt\x1b[?1049l!",
+        ];
+        let size = TerminalSize {
+            cols: 16,
+            rows: 6,
+            ..TerminalSize::default()
+        };
+
+        let (mut default_engine, _handle, _events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            Surface::new(SurfaceConfig::default(), &size),
+            TerminalOptions::default(),
+        )?;
+        let (mut fast_engine, _handle, _events) = TerminalEngine::new(
+            FakeSession::default(),
+            FastParser::default(),
+            Surface::new(SurfaceConfig::default(), &size),
+            TerminalOptions::default(),
+        )?;
+
+        for chunk in chunks {
+            default_engine.session.push_read(chunk);
+            default_engine.on_readable()?;
+            fast_engine.session.push_read(chunk);
+            fast_engine.on_readable()?;
+        }
+
+        let (expected, actual) =
+            (default_engine.surface.grid(), fast_engine.surface.grid());
+        for line in expected.topmost_line().0..=expected.bottommost_line().0 {
+            assert_eq!(actual[Line(line)], expected[Line(line)], "line {line}");
+        }
+        assert_eq!(actual.cursor, expected.cursor);
+        assert_eq!(fast_engine.surface.mode(), default_engine.surface.mode());
+
+        Ok(())
+    }
+
+    #[test]
+    fn one_private_mode_sequence_toggles_every_listed_mode() -> Result<()> {
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default()),
+            TerminalOptions::default(),
+        )?;
+
+        engine.inject(b"\x1b[?1;2004;2026h")?;
+        let mode = *engine.surface.mode();
+        assert!(mode.contains(SurfaceMode::APP_CURSOR));
+        assert!(mode.contains(SurfaceMode::BRACKETED_PASTE));
+        assert!(engine.sync_state.is_active());
+
+        engine.inject(b"\x1b[?1;2004;2026l")?;
+        let mode = *engine.surface.mode();
+        assert!(!mode.contains(SurfaceMode::APP_CURSOR));
+        assert!(!mode.contains(SurfaceMode::BRACKETED_PASTE));
+        assert!(!engine.sync_state.is_active());
+
+        Ok(())
+    }
+
+    #[test]
+    fn clear_all_empties_screen_and_scrollback_keeping_modes() -> Result<()> {
+        let size = TerminalSize {
+            cols: 8,
+            rows: 3,
+            ..TerminalSize::default()
+        };
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            Surface::new(SurfaceConfig::default(), &size),
+            TerminalOptions::default(),
+        )?;
+
+        engine
+            .session
+            .push_read(b"\x1b[?1h\x1b[4h1\r\n2\r\n3\r\n4\r\n5ab");
+        engine.on_readable()?;
+        engine.surface.scroll_display(Scroll::Delta(1));
+        let modes = *engine.surface.mode();
+        assert!(engine.surface.grid().history_size() > 0);
+        collect_events(&events);
+
+        engine.process_request(TerminalRequest::ClearAll)?;
+        engine.flush_event_queue()?;
+
+        let grid = engine.surface.grid();
+        assert_eq!(grid.history_size(), 0);
+        assert_eq!(grid.display_offset(), 0);
+        assert!(grid.display_iter().all(|indexed| indexed.cell.c == ' '));
+        assert_eq!(grid.cursor.point, Point::new(Line(0), Column(0)));
+        assert_eq!(*engine.surface.mode(), modes);
+        assert!(!engine.has_pending_output());
+
+        let events = collect_events(&events);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], TerminalEvent::Frame { .. }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn request_batches_emit_one_frame_with_every_change() -> Result<()> {
+        let session = FakeSession::with_reads(vec![
+            b"1\r\n2\r\n3\r\n4\r\n5\r\n6\r\n7\r\n8\r\n9".to_vec(),
+        ]);
+        let size = TerminalSize {
+            rows: 4,
+            cols: 10,
+            ..TerminalSize::default()
+        };
+        let surface = Surface::new(SurfaceConfig::default(), &size);
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            session,
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+        engine.on_readable()?;
+        collect_events(&events);
+
+        let resized = TerminalSize { rows: 3, ..size };
+        engine.process_requests([
+            TerminalRequest::Resize(resized),
+            TerminalRequest::ScrollDisplay(Scroll::Delta(2)),
+        ])?;
+        engine.flush_event_queue()?;
+
+        let frames = collect_events(&events)
+            .into_iter()
+            .filter_map(|event| match event {
+                TerminalEvent::Frame { frame } => Some(frame),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(frames.len(), 1);
+        let view = frames[0].view();
+        assert_eq!(view.size.screen_lines, 3);
+        assert_eq!(view.display_offset, 2);
+
+        // Requests that need no frame emit none.
+        engine.process_requests([TerminalRequest::HoverHyperlink(None)])?;
+        engine.process_requests([])?;
+        engine.flush_event_queue()?;
+        assert!(collect_events(&events).is_empty());
+
+        Ok(())
+    }
+
+    /// Scripted output mixing a title, bell, cursor shape, hyperlink and text.
+    const MIXED_OUTPUT: &[u8] = b"\x1b]0;title\x07\x07\x1b[5 q\x1b]8;;https://otty.sh\x1b\\link\x1b]8;;\x1b\\ text";
+
+    /// Check the ordering contract documented on [`TerminalEvent`].
+    fn assert_event_order(events: &[TerminalEvent]) {
+        let mut unframed = None;
+        let mut framed = false;
+        for (index, event) in events.iter().enumerate() {
+            match event {
+                TerminalEvent::Frame { .. } => {
+                    unframed = None;
+                    framed = true;
+                },
+                TerminalEvent::ChildExit { .. } => {
+                    assert!(
+                        framed,
+                        "ChildExit at {index} precedes every frame"
+                    );
+                    assert_eq!(
+                        unframed, None,
+                        "event not framed before ChildExit"
+                    );
+                    assert_eq!(index, events.len() - 1, "events after exit");
+                },
+                TerminalEvent::MemoryPressure { .. } => {},
+                _ => {
+                    unframed.get_or_insert(index);
+                },
+            }
+        }
+        assert_eq!(unframed, None, "event after the last frame");
+    }
+
+    fn mixed_output_event_count(events: &[TerminalEvent]) -> usize {
+        events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    TerminalEvent::TitleChanged { .. }
+                        | TerminalEvent::Bell
+                        | TerminalEvent::CursorStyleChanged { .. }
+                        | TerminalEvent::Hyperlink { .. }
+                )
+            })
+            .count()
+    }
+
+    #[test]
+    fn scripted_events_precede_frame_and_frame_precedes_exit() -> Result<()> {
+        let session = FakeSession::with_reads(vec![MIXED_OUTPUT.to_vec()])
+            .with_exit(exit_ok());
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            session,
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.on_readable()?;
+
+        let events = collect_events(&events);
+        assert_event_order(&events);
+        assert_eq!(mixed_output_event_count(&events), 5);
+        assert!(matches!(
+            events.last(),
+            Some(TerminalEvent::ChildExit { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn deferred_frame_is_flushed_before_child_exit() -> Result<()> {
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::with_reads(vec![b"first".to_vec()]),
+            DefaultParser::default(),
+            surface,
+            TerminalOptions {
+                frame_interval: Some(Duration::from_secs(3600)),
+                ..TerminalOptions::default()
+            },
+        )?;
+
+        engine.on_readable()?;
+        engine.session.push_read(MIXED_OUTPUT);
+        engine.on_readable()?;
+        assert!(engine.frame_batch.is_pending());
+
+        engine.session.set_exit(exit_ok());
+        engine.on_readable()?;
+
+        let events = collect_events(&events);
+        assert_event_order(&events);
+        assert_eq!(mixed_output_event_count(&events), 5);
+        let frames = events
+            .iter()
+            .filter(|event| matches!(event, TerminalEvent::Frame { .. }))
+            .count();
+        assert_eq!(frames, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn emits_frame_before_child_exit() -> Result<()> {
+        let session = FakeSession::with_reads(vec![b"data".to_vec()])
+            .with_exit(exit_ok());
+        let parser = StubParser::with_actions(vec![Action::Print('a')]);
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let options = TerminalOptions {
+            channel_config: ChannelConfig::default(),
+            ..TerminalOptions::default()
+        };
+        let (mut engine, _handle, events) =
+            TerminalEngine::new(session, parser, surface, options)?;
+
+        engine.on_readable()?;
+
+        let first = events.recv().expect("first event");
+        match first {
+            TerminalEvent::Frame { frame } => assert_frame(frame),
+            _ => panic!("expected frame first"),
+        }
+
+        let second = events.recv().expect("second event");
+        assert!(matches!(second, TerminalEvent::ChildExit { .. }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn full_event_channel_drops_and_reports_events_but_keeps_the_exit()
+    -> Result<()> {
+        let session = FakeSession::with_reads(vec![b"payload".to_vec()])
+            .with_exit(exit_ok());
+        // Frames give way, but only one of the bells fits.
+        let parser = StubParser::with_actions(vec![
+            Action::Print('x'),
+            Action::Bell,
+            Action::Bell,
+        ]);
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let options = TerminalOptions {
+            channel_config: ChannelConfig {
+                event_capacity: Some(1),
+                request_capacity: None,
+            },
+            ..TerminalOptions::default()
+        };
+
+        let (mut engine, _handle, events) =
+            TerminalEngine::new(session, parser, surface, options)?;
+
+        engine.on_readable()?;
+        let delivered = collect_events(&events);
+        assert!(matches!(delivered.as_slice(), [TerminalEvent::Bell]));
+
+        engine.tick()?;
+        let delivered = collect_events(&events);
+        assert_eq!(soft_errors(&delivered), [TerminalErrorKind::EventsDropped]);
+
+        engine.tick()?;
+        let delivered = collect_events(&events);
+        assert!(matches!(
+            delivered.as_slice(),
+            [TerminalEvent::ChildExit { .. }]
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn frames_give_way_to_a_child_exit_on_a_full_channel() -> Result<()> {
+        let session = FakeSession::with_reads(vec![b"payload".to_vec()])
+            .with_exit(exit_ok());
+        let parser =
+            StubParser::with_actions(vec![Action::Print('x'), Action::Bell]);
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let options = TerminalOptions {
+            channel_config: ChannelConfig {
+                event_capacity: Some(3),
+                request_capacity: None,
+            },
+            ..TerminalOptions::default()
+        };
+
+        let (mut engine, handle, events) =
+            TerminalEngine::new(session, parser, surface, options)?;
+        for _ in 0..10 {
+            handle.request_frame().expect("request channel open");
+        }
+        engine.on_readable()?;
+
+        let delivered = collect_events(&events);
+        assert_eq!(delivered.len(), 3);
+        assert!(matches!(delivered[0], TerminalEvent::Frame { .. }));
+        assert!(matches!(delivered[1], TerminalEvent::Bell));
+        assert!(matches!(delivered[2], TerminalEvent::ChildExit { .. }));
+
+        // The dropped frames are made up for once the channel has room.
+        engine.tick()?;
+        let repaint = collect_events(&events);
+        assert!(matches!(repaint.first(), Some(TerminalEvent::Frame { .. })));
+
+        Ok(())
+    }
+
+    #[test]
+    fn owed_repaint_waits_for_room_in_the_channel() -> Result<()> {
+        let session = FakeSession::default();
+        let parser = StubParser::with_actions(vec![Action::Print('x')]);
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let options = TerminalOptions {
+            channel_config: ChannelConfig {
+                event_capacity: Some(1),
+                request_capacity: None,
+            },
+            ..TerminalOptions::default()
+        };
+
+        let (mut engine, _handle, events) =
+            TerminalEngine::new(session, parser, surface, options)?;
+        engine.inject(b"a")?;
+        engine.inject(b"b")?;
+        assert_eq!(engine.metrics().frames_sent(), 1);
+        assert_eq!(engine.metrics().frames_dropped(), 1);
+
+        // No repaint is attempted while the channel stays full.
+        for _ in 0..5 {
+            engine.tick()?;
+        }
+        assert_eq!(engine.metrics().frames_sent(), 1);
+        assert_eq!(engine.metrics().frames_dropped(), 1);
+
+        assert_eq!(collect_events(&events).len(), 1);
+        engine.tick()?;
+        let repaint = collect_events(&events);
+        assert!(matches!(repaint.as_slice(), [TerminalEvent::Frame { .. }]));
+        assert_eq!(engine.metrics().frames_sent(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn handle_requests_flow_into_engine() -> Result<()> {
+        let session = FakeSession::default();
+        let parser = StubParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, handle, events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        handle
+            .send(TerminalRequest::Resize(TerminalSize::default()))
+            .expect("request channel open");
+
+        engine.tick()?;
+
+        let event = events.recv().expect("frame after resize");
+        match event {
+            TerminalEvent::Frame { frame } => assert_frame(frame),
+            _ => panic!("expected frame"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn request_frame_emits_full_frame_on_idle_engine() -> Result<()> {
+        let session = FakeSession::default();
+        let parser = StubParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, handle, events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.surface.reset_damage();
+        handle.request_frame().expect("request channel open");
+        engine.tick()?;
+
+        let event = events.try_recv().expect("frame after request");
+        match event {
+            TerminalEvent::Frame { frame } => {
+                assert!(matches!(frame.view().damage, SnapshotDamage::Full));
+            },
+            _ => panic!("expected frame"),
+        }
+        assert!(events.try_recv().is_err());
+
+        Ok(())
+    }
+
     #[test]
-    fn has_pending_output_includes_queued_write_request() -> Result<()> {
-        let session = PartialSession::with_behavior(4, true);
-        let parser = StubParser::default();
+    fn unfocusing_stops_blink_and_marks_frame_unfocused() -> Result<()> {
+        let session = FakeSession::with_reads(vec![b"\x1b[1 q".to_vec()]);
+        let parser = DefaultParser::default();
         let surface =
             Surface::new(SurfaceConfig::default(), &TerminalSize::default());
-
-        let (mut engine, handle, _events) = TerminalEngine::new(
+        let (mut engine, handle, events) = TerminalEngine::new(
             session,
             parser,
             surface,
             TerminalOptions::default(),
         )?;
 
+        engine.on_readable()?;
+        let blinking = collect_events(&events).into_iter().any(|event| {
+            matches!(
+                event,
+                TerminalEvent::CursorStyleChanged { style: Some(style) }
+                    if style.blinking
+            )
+        });
+        assert!(blinking);
+
         handle
-            .send(TerminalRequest::WriteBytes(b"long-payload".to_vec()))
+            .send(TerminalRequest::SetFocus(false))
             .expect("request channel open");
+        engine.tick()?;
 
-        engine.process_pending_requests()?;
-        assert!(engine.has_pending_output());
+        let collected = collect_events(&events);
+        assert!(collected.iter().any(|event| matches!(
+            event,
+            TerminalEvent::CursorStyleChanged { style: Some(style) }
+                if !style.blinking
+        )));
+        assert!(!collected.iter().any(|event| matches!(
+            event,
+            TerminalEvent::CursorStyleChanged { style: Some(style) }
+                if style.blinking
+        )));
+        match collected.last() {
+            Some(TerminalEvent::Frame { frame }) => {
+                assert!(!frame.view().focused)
+            },
+            _ => panic!("expected frame after focus change"),
+        }
 
-        engine.session.blocked = false;
-        engine.on_writable()?;
-        engine.session.blocked = false;
-        engine.on_writable()?;
-        assert!(!engine.has_pending_output());
+        Ok(())
+    }
+
+    #[test]
+    fn focus_change_is_reported_when_enabled() -> Result<()> {
+        let session = PartialSession::with_behavior(64, false);
+        let parser = DefaultParser::default();
+        let mut surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        surface.set_private_mode(NamedPrivateMode::ReportFocusInOut.into());
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.process_request(TerminalRequest::SetFocus(false))?;
+        engine.process_request(TerminalRequest::SetFocus(false))?;
+        engine.process_request(TerminalRequest::SetFocus(true))?;
+
+        assert_eq!(
+            engine.session.writes,
+            vec![b"\x1b[O".to_vec(), b"\x1b[I".to_vec()]
+        );
 
         Ok(())
     }
 
     #[test]
-    fn emits_frame_before_child_exit() -> Result<()> {
-        let session = FakeSession::with_reads(vec![b"data".to_vec()])
-            .with_exit(exit_ok());
-        let parser = StubParser::with_actions(vec![Action::Print('a')]);
+    fn cursor_position_query_replies_with_cpr() -> Result<()> {
+        let session =
+            FakeSession::with_reads(vec![b"\x1b[3;5H\x1b[6n\x1b[5n".to_vec()]);
+        let parser = DefaultParser::default();
         let surface =
             Surface::new(SurfaceConfig::default(), &TerminalSize::default());
-        let options = TerminalOptions {
-            channel_config: ChannelConfig::default(),
-            ..TerminalOptions::default()
-        };
-        let (mut engine, _handle, events) =
-            TerminalEngine::new(session, parser, surface, options)?;
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
 
         engine.on_readable()?;
 
-        let first = events.recv().expect("first event");
-        match first {
-            TerminalEvent::Frame { frame } => assert_frame(frame),
-            _ => panic!("expected frame first"),
-        }
+        let reply: Vec<u8> = engine.pending_input.iter().copied().collect();
+        assert_eq!(reply, b"\x1b[3;5R\x1b[0n");
 
-        let second = events.recv().expect("second event");
-        assert!(matches!(second, TerminalEvent::ChildExit { .. }));
+        Ok(())
+    }
+
+    #[test]
+    fn termcap_query_replies_for_known_and_unknown_caps() -> Result<()> {
+        let session =
+            FakeSession::with_reads(vec![b"\x1bP+q436f;7878\x1b\\".to_vec()]);
+        let parser = DefaultParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.on_readable()?;
+
+        let reply: Vec<u8> = engine.pending_input.iter().copied().collect();
+        assert_eq!(reply, b"\x1bP1+r436F=323536\x1b\\\x1bP0+r7878\x1b\\");
 
         Ok(())
     }
 
     #[test]
-    fn bounded_event_channel_surfaces_backpressure() {
-        let session = FakeSession::with_reads(vec![b"payload".to_vec()])
-            .with_exit(exit_ok());
-        let parser = StubParser::with_actions(vec![Action::Print('x')]);
+    fn device_attributes_follow_the_term_profile() -> Result<()> {
+        let cases = [
+            (TermProfile::Xterm, &b"\x1b[?6c\x1bP0+r524742\x1b\\"[..]),
+            (TermProfile::Xterm256Color, b"\x1b[?6c\x1bP0+r524742\x1b\\"),
+            (
+                TermProfile::XtermDirect,
+                b"\x1b[?62;22c\x1bP1+r524742\x1b\\",
+            ),
+        ];
+
+        for (profile, expected) in cases {
+            let session = FakeSession::with_reads(vec![
+                b"\x1b[c\x1bP+q524742\x1b\\".to_vec(),
+            ]);
+            let surface = Surface::new(
+                SurfaceConfig::default(),
+                &TerminalSize::default(),
+            );
+            let (mut engine, _handle, _events) = TerminalEngine::new(
+                session,
+                DefaultParser::default(),
+                surface,
+                TerminalOptions::default().with_term_profile(profile),
+            )?;
+
+            engine.on_readable()?;
+
+            let reply: Vec<u8> = engine.pending_input.iter().copied().collect();
+            assert_eq!(reply, expected, "{profile:?}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn frames_report_which_screen_is_shown() -> Result<()> {
+        let session = FakeSession::with_reads(vec![b"\x1b[?1049h".to_vec()]);
         let surface =
             Surface::new(SurfaceConfig::default(), &TerminalSize::default());
-        let options = TerminalOptions {
-            channel_config: ChannelConfig {
-                event_capacity: Some(1),
-                request_capacity: None,
-            },
-            ..TerminalOptions::default()
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            session,
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+        let last_frame = || {
+            collect_events(&events)
+                .into_iter()
+                .filter_map(|event| match event {
+                    TerminalEvent::Frame { frame } => Some(frame),
+                    _ => None,
+                })
+                .last()
+                .expect("a frame")
         };
 
-        let (mut engine, _handle, _events) =
-            TerminalEngine::new(session, parser, surface, options)
-                .expect("construct engine");
+        engine.on_readable()?;
+        assert!(last_frame().view().alt_screen);
+
+        engine.session.push_read(b"\x1b[?1049l");
+        engine.on_readable()?;
+        assert!(!last_frame().view().alt_screen);
 
-        let err = engine.on_readable().expect_err("channel backpressure");
-        assert!(matches!(err, Error::EventChannelFull));
+        Ok(())
     }
 
     #[test]
-    fn handle_requests_flow_into_engine() -> Result<()> {
-        let session = FakeSession::default();
-        let parser = StubParser::default();
+    fn mode_queries_report_the_current_state() -> Result<()> {
+        let session = FakeSession::with_reads(vec![
+            b"\x1b[?2004h\x1b[?2004$p\x1b[4$p\x1b[?9999$p\x1b[99$p".to_vec(),
+        ]);
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            session,
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.on_readable()?;
+
+        let reply: Vec<u8> = engine.pending_input.iter().copied().collect();
+        assert_eq!(reply, b"\x1b[?2004;1$y\x1b[4;2$y\x1b[?9999;0$y\x1b[99;0$y");
+
+        Ok(())
+    }
+
+    #[test]
+    fn sync_update_queries_are_answered_inside_the_update() -> Result<()> {
+        let session = FakeSession::with_reads(vec![
+            b"\x1b[?2026$p\x1b[?2026hx\x1b[?2026$p".to_vec(),
+        ]);
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, _events) = TerminalEngine::new(
+            session,
+            DefaultParser::default(),
+            surface,
+            TerminalOptions::default(),
+        )?;
+
+        engine.on_readable()?;
+
+        assert!(engine.sync_state.is_active());
+        let reply: Vec<u8> = engine.pending_input.iter().copied().collect();
+        assert_eq!(reply, b"\x1b[?2026;2$y\x1b[?2026;1$y");
+
+        Ok(())
+    }
+
+    #[test]
+    fn hover_reports_full_hyperlink_spans() -> Result<()> {
+        let session = FakeSession::with_reads(vec![
+            b"\x1b]8;id=a;https://a.test\x1b\\first\x1b]8;;\x1b\\ \
+              \x1b]8;;https://b.test\x1b\\second\x1b]8;;\x1b\\"
+                .to_vec(),
+        ]);
+        let parser = DefaultParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
+        engine.on_readable()?;
+        let _ = collect_events(&events);
+
+        let hover = |column| Some(Point::new(Line(0), Column(column)));
+        engine.process_request(TerminalRequest::HoverHyperlink(hover(2)))?;
+        engine.process_request(TerminalRequest::HoverHyperlink(hover(4)))?;
+        engine.process_request(TerminalRequest::HoverHyperlink(hover(8)))?;
+        engine.process_request(TerminalRequest::HoverHyperlink(hover(5)))?;
+        engine.flush_event_queue()?;
+
+        let spans = collect_events(&events)
+            .into_iter()
+            .filter_map(|event| match event {
+                TerminalEvent::HyperlinkSpan { span } => {
+                    Some(span.map(|span| {
+                        (span.link.uri().to_owned(), span.start, span.end)
+                    }))
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            vec![
+                Some((
+                    "https://a.test".to_owned(),
+                    Point::new(Line(0), Column(0)),
+                    Point::new(Line(0), Column(4)),
+                )),
+                Some((
+                    "https://b.test".to_owned(),
+                    Point::new(Line(0), Column(6)),
+                    Point::new(Line(0), Column(11)),
+                )),
+                None,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn search_focuses_matches_and_scrolls_them_into_view() -> Result<()> {
+        let mut output = b"needle one\r\n".to_vec();
+        output.extend(b"filler\r\n".repeat(60));
+        output.extend(b"needle two\r\nneedle three");
+        let session = FakeSession::with_reads(vec![output]);
+        let parser = DefaultParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
+        engine.on_readable()?;
+        let _ = collect_events(&events);
+
+        engine.process_request(TerminalRequest::SetSearchPattern(Some(
+            "needle \\w+".into(),
+        )))?;
+        engine.process_request(TerminalRequest::SearchPrevious)?;
+        engine.process_request(TerminalRequest::SearchPrevious)?;
+        engine.process_request(TerminalRequest::SearchNext)?;
+        engine.process_request(TerminalRequest::SearchNext)?;
+        engine.process_request(TerminalRequest::SetSearchPattern(None))?;
+        engine.flush_event_queue()?;
+
+        let focused = collect_events(&events)
+            .into_iter()
+            .filter_map(|event| match event {
+                TerminalEvent::Frame { frame } => {
+                    let view = frame.view();
+                    Some((
+                        view.search_match.map(|m| (*m.start(), *m.end())),
+                        view.display_offset,
+                    ))
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let first = (
+            Point::new(Line(-13), Column(0)),
+            Point::new(Line(-13), Column(9)),
+        );
+        let second = (
+            Point::new(Line(48), Column(0)),
+            Point::new(Line(48), Column(9)),
+        );
+        let third = (
+            Point::new(Line(49), Column(0)),
+            Point::new(Line(49), Column(11)),
+        );
+        assert_eq!(
+            focused,
+            vec![
+                (Some(third), 0),
+                (Some(second), 0),
+                (Some(first), 13),
+                (Some(second), 1),
+                (Some(third), 0),
+                (None, 0),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn copy_mode_selects_by_word_and_line() -> Result<()> {
+        let session = FakeSession::with_reads(vec![
+            b"hello world again\r\nsecond line\x1b[H".to_vec(),
+        ]);
+        let parser = DefaultParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions::default(),
+        )?;
+        engine.on_readable()?;
+
+        engine.process_request(TerminalRequest::EnterCopyMode)?;
+        engine.process_request(TerminalRequest::CopyModeMove(
+            Motion::WordRight,
+        ))?;
+        engine.process_request(TerminalRequest::CopyModeSelect(
+            SelectionType::Simple,
+        ))?;
+        engine.process_request(TerminalRequest::CopyModeMove(
+            Motion::WordRight,
+        ))?;
+        engine.process_request(TerminalRequest::CopyModeMove(Motion::Down))?;
+        engine.process_request(TerminalRequest::ExitCopyMode)?;
+        engine.flush_event_queue()?;
+
+        let events = collect_events(&events);
+        let copy_cursors = events
+            .iter()
+            .filter_map(|event| match event {
+                TerminalEvent::Frame { frame } => {
+                    let cursor = frame.view().cursor.clone();
+                    cursor.copy_mode.then_some(cursor.point)
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            copy_cursors,
+            vec![
+                Point::new(Line(0), Column(0)),
+                Point::new(Line(0), Column(6)),
+                Point::new(Line(0), Column(6)),
+                Point::new(Line(0), Column(12)),
+                Point::new(Line(1), Column(12)),
+            ]
+        );
+
+        let selection_text = events.into_iter().find_map(|event| match event {
+            TerminalEvent::CopyModeExited { selection_text } => selection_text,
+            _ => None,
+        });
+        assert_eq!(selection_text.as_deref(), Some("world again\nsecond line"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn consecutive_scrolls_in_one_direction_emit_one_frame() -> Result<()> {
+        let session = FakeSession::with_reads(vec![b"line\r\n".repeat(60)]);
+        let parser = DefaultParser::default();
         let surface =
             Surface::new(SurfaceConfig::default(), &TerminalSize::default());
         let (mut engine, handle, events) = TerminalEngine::new(
@@ -741,18 +3306,69 @@ mod tests {
             surface,
             TerminalOptions::default(),
         )?;
+        engine.on_readable()?;
+        let _ = collect_events(&events);
+
+        for scroll in [
+            Scroll::Delta(3),
+            Scroll::Delta(3),
+            Scroll::Delta(4),
+            Scroll::Delta(-2),
+            Scroll::Delta(-1),
+            Scroll::Top,
+            Scroll::Delta(-5),
+        ] {
+            handle
+                .send(TerminalRequest::ScrollDisplay(scroll))
+                .expect("request channel open");
+        }
+        engine.tick()?;
 
-        handle
-            .send(TerminalRequest::Resize(TerminalSize::default()))
-            .expect("request channel open");
+        let offsets = collect_events(&events)
+            .into_iter()
+            .filter_map(|event| match event {
+                TerminalEvent::Frame { frame } => {
+                    Some(frame.view().display_offset)
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![10, 7, 11, 6]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn memory_pressure_fires_once_per_interval() -> Result<()> {
+        let session = FakeSession::with_reads(vec![b"\n".repeat(200)]);
+        let parser = DefaultParser::default();
+        let surface =
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default());
+        let threshold = surface.memory_usage() + 1;
+        let (mut engine, handle, events) = TerminalEngine::new(
+            session,
+            parser,
+            surface,
+            TerminalOptions {
+                memory_pressure_threshold: Some(threshold),
+                memory_pressure_interval: Duration::from_secs(60),
+                ..TerminalOptions::default()
+            },
+        )?;
 
+        engine.on_readable()?;
+        handle.request_frame().expect("request channel open");
         engine.tick()?;
 
-        let event = events.recv().expect("frame after resize");
-        match event {
-            TerminalEvent::Frame { frame } => assert_frame(frame),
-            _ => panic!("expected frame"),
-        }
+        let pressure = collect_events(&events)
+            .into_iter()
+            .filter_map(|event| match event {
+                TerminalEvent::MemoryPressure { bytes } => Some(bytes),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(pressure.len(), 1);
+        assert!(pressure[0] >= threshold);
 
         Ok(())
     }
@@ -780,6 +3396,47 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn front_end_titles_are_announced_and_yield_to_the_child() -> Result<()> {
+        let (mut engine, _handle, events) = TerminalEngine::new(
+            FakeSession::default(),
+            DefaultParser::default(),
+            Surface::new(SurfaceConfig::default(), &TerminalSize::default()),
+            TerminalOptions::default(),
+        )?;
+        let titles = |events: &TerminalEvents| {
+            collect_events(events)
+                .into_iter()
+                .filter_map(|event| match event {
+                    TerminalEvent::TitleChanged { title } => Some(Some(title)),
+                    TerminalEvent::ResetTitle => Some(None),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        engine
+            .process_request(TerminalRequest::SetTitle(Some("logs".into())))?;
+        engine.process_request(TerminalRequest::SetTitle(None))?;
+        engine.flush_event_queue()?;
+        assert_eq!(titles(&events), [Some("logs".to_string()), None]);
+
+        // The renamed title is the one a child saves and restores.
+        engine
+            .process_request(TerminalRequest::SetTitle(Some("logs".into())))?;
+        engine.inject(b"\x1b[22t\x1b]0;vim\x07\x1b[23t")?;
+        assert_eq!(
+            titles(&events),
+            [
+                Some("logs".to_string()),
+                Some("vim".to_string()),
+                Some("logs".to_string())
+            ]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn parses_bytes_into_title_event_and_frame() -> anyhow::Result<()> {
         let session =
@@ -1,4 +1,10 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use crate::ChannelConfig;
+use crate::terminal::clock::{Clock, SystemClock};
+use crate::terminal::profile::TermProfile;
+use crate::terminal::termcap::Termcaps;
 
 /// Configuration knobs that influence how the terminal runtime behaves.
 #[derive(Clone, Debug)]
@@ -7,6 +13,53 @@ pub struct TerminalOptions {
     pub read_buffer_capacity: usize,
     /// Channel sizing options for terminal request/event plumbing.
     pub channel_config: ChannelConfig,
+    /// Surface memory estimate (in bytes) above which
+    /// [`TerminalEvent::MemoryPressure`](crate::TerminalEvent::MemoryPressure)
+    /// is emitted. Disabled when `None`.
+    pub memory_pressure_threshold: Option<usize>,
+    /// Minimum delay between two consecutive memory pressure events.
+    pub memory_pressure_interval: Duration,
+    /// `TERM`/`COLORTERM` profile set on spawned sessions and advertised in
+    /// device attribute replies.
+    pub term_profile: TermProfile,
+    /// Capabilities reported to XTGETTCAP queries.
+    ///
+    /// [`Self::with_term_profile`] replaces them with the profile's set.
+    pub termcaps: Termcaps,
+    /// Upper bound for the bytes handed to a single PTY write.
+    pub write_chunk_size: usize,
+    /// Minimum spacing between frames emitted for PTY output. Reads landing
+    /// inside the interval are coalesced into one frame on the next tick.
+    /// Every read emits a frame when `None`.
+    pub frame_interval: Option<Duration>,
+    /// Upper bound on output frames per second, coalescing the reads of
+    /// one `1 / max_fps` window like [`Self::frame_interval`]. The longer
+    /// of the two spacings applies.
+    pub max_fps: Option<u32>,
+    /// Print control characters from the child as visible glyphs (`^C`,
+    /// `␛`) instead of interpreting them.
+    ///
+    /// Only the display changes: the bytes the child writes and the input
+    /// sent to it stay the same.
+    pub show_control_chars: bool,
+    /// Decode child output as UTF-8.
+    ///
+    /// When off, every byte is one character, as on serial consoles and
+    /// other legacy devices: the low half of the code table goes through
+    /// the set invoked into GL and the high half through the set invoked
+    /// into GR, G1 unless shifted with LS1R–LS3R. A GR set of ASCII, the
+    /// default, shows ISO Latin-1. Input sent to the child is not
+    /// transcoded.
+    pub utf8: bool,
+    /// Honor software flow control (XON/XOFF).
+    ///
+    /// Input for the child is held back after it writes `XOFF` until it
+    /// writes `XON`. Ctrl-S and Ctrl-Q typed by the user pause and resume
+    /// output frames instead of reaching the child.
+    pub flow_control: bool,
+    /// Time source for sync-mode timeouts, frame batching and the memory
+    /// pressure interval.
+    pub clock: Arc<dyn Clock>,
 }
 
 impl Default for TerminalOptions {
@@ -14,6 +67,39 @@ impl Default for TerminalOptions {
         Self {
             read_buffer_capacity: 4096,
             channel_config: ChannelConfig::default(),
+            memory_pressure_threshold: None,
+            memory_pressure_interval: Duration::from_secs(5),
+            term_profile: TermProfile::default(),
+            termcaps: Termcaps::default(),
+            write_chunk_size: 64 * 1024,
+            frame_interval: None,
+            max_fps: None,
+            show_control_chars: false,
+            utf8: true,
+            flow_control: false,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl TerminalOptions {
+    /// Present the terminal with `profile`, also answering XTGETTCAP
+    /// queries with the capabilities of the profile.
+    pub fn with_term_profile(mut self, profile: TermProfile) -> Self {
+        self.term_profile = profile;
+        self.termcaps = profile.termcaps();
+        self
+    }
+
+    /// Spacing between output frames from the interval and the rate cap.
+    pub(crate) fn output_frame_interval(&self) -> Option<Duration> {
+        let cap = self
+            .max_fps
+            .filter(|&fps| fps > 0)
+            .map(|fps| Duration::from_secs(1) / fps);
+        match (self.frame_interval, cap) {
+            (Some(interval), Some(cap)) => Some(interval.max(cap)),
+            (interval, cap) => interval.or(cap),
         }
     }
 }
@@ -0,0 +1,73 @@
+//! Terminal identity advertised to child programs.
+
+use crate::terminal::termcap::Termcaps;
+
+/// `TERM`/`COLORTERM` capability profile of a terminal.
+///
+/// The profile decides the environment of spawned sessions and which
+/// features the engine advertises in query replies (DA, XTGETTCAP), so
+/// child programs pick a matching terminfo entry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TermProfile {
+    /// Plain `xterm` with the 8 ANSI colors and no 24-bit color.
+    Xterm,
+    /// `xterm-256color`, advertising 24-bit color through `COLORTERM` and
+    /// the `Tc` capability.
+    #[default]
+    Xterm256Color,
+    /// `xterm-direct`, where color indices are 24-bit RGB values.
+    XtermDirect,
+}
+
+impl TermProfile {
+    /// Value of the `TERM` variable.
+    pub fn term(self) -> &'static str {
+        match self {
+            Self::Xterm => "xterm",
+            Self::Xterm256Color => "xterm-256color",
+            Self::XtermDirect => "xterm-direct",
+        }
+    }
+
+    /// Value of the `COLORTERM` variable, if 24-bit color is advertised.
+    pub fn colorterm(self) -> Option<&'static str> {
+        self.truecolor().then_some("truecolor")
+    }
+
+    /// Whether 24-bit color is advertised to child programs.
+    pub fn truecolor(self) -> bool {
+        !matches!(self, Self::Xterm)
+    }
+
+    /// Capabilities reported to XTGETTCAP queries.
+    pub fn termcaps(self) -> Termcaps {
+        let colors = match self {
+            Self::Xterm => "8",
+            Self::Xterm256Color => "256",
+            Self::XtermDirect => "16777216",
+        };
+
+        let mut caps = Termcaps::empty();
+        caps.insert("TN", self.term());
+        caps.insert("name", self.term());
+        caps.insert("Co", colors);
+        caps.insert("colors", colors);
+        if self.truecolor() {
+            caps.insert("Tc", "");
+        }
+        if self == Self::XtermDirect {
+            caps.insert("RGB", "");
+        }
+        caps
+    }
+
+    /// Reply to a primary device attributes (DA1) request.
+    pub(crate) fn primary_device_attributes(self) -> &'static str {
+        match self {
+            // VT102.
+            Self::Xterm | Self::Xterm256Color => "\x1b[?6c",
+            // VT220 with ANSI color, like a modern xterm.
+            Self::XtermDirect => "\x1b[?62;22c",
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Graceful shutdown that writes pending input before the session closes.
+
+use std::time::{Duration, Instant};
+
+/// Progress of a graceful shutdown waiting for pending input to reach the
+/// child.
+#[derive(Debug, Default)]
+pub(crate) struct ShutdownState {
+    /// Set while a shutdown is in progress.
+    pending: Option<PendingShutdown>,
+}
+
+impl ShutdownState {
+    /// Whether a shutdown is waiting for pending input.
+    pub(crate) fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// When the session is closed regardless of pending input.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.pending.and_then(|pending| pending.deadline)
+    }
+
+    /// Start a shutdown at `now` that gives pending input `timeout` to be
+    /// written; a timeout too far out waits for the input to drain.
+    pub(crate) fn begin(&mut self, now: Instant, timeout: Duration) {
+        self.pending = Some(PendingShutdown {
+            deadline: now.checked_add(timeout),
+        });
+    }
+
+    /// Return whether the session should be closed at `now`, ending the
+    /// shutdown once the input has drained or the deadline has passed.
+    pub(crate) fn finish(&mut self, now: Instant, input_pending: bool) -> bool {
+        let Some(pending) = self.pending else {
+            return false;
+        };
+
+        let expired = pending.deadline.is_some_and(|deadline| now >= deadline);
+        if input_pending && !expired {
+            return false;
+        }
+
+        self.pending = None;
+        true
+    }
+}
+
+/// Graceful shutdown in progress.
+#[derive(Debug, Clone, Copy)]
+struct PendingShutdown {
+    /// When to close regardless; `None` waits for the input to drain.
+    deadline: Option<Instant>,
+}
@@ -3,7 +3,7 @@ use otty_pty::PtySize;
 use crate::surface::{Column, Dimensions, Line};
 
 /// Geometry of the terminal grid and the size of individual cells.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct TerminalSize {
     /// Width of a single terminal cell in pixels.
     pub cell_width: u16,
@@ -58,3 +58,56 @@ impl From<TerminalSize> for PtySize {
         }
     }
 }
+
+impl From<PtySize> for TerminalSize {
+    fn from(val: PtySize) -> Self {
+        TerminalSize {
+            cell_width: val.cell_width,
+            cell_height: val.cell_height,
+            cols: val.cols,
+            rows: val.rows,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminal_size_round_trips_through_pty_size() {
+        let size = TerminalSize {
+            cell_width: 9,
+            cell_height: 18,
+            cols: 132,
+            rows: 43,
+        };
+
+        let pty: PtySize = size.into();
+        assert_eq!(
+            pty,
+            PtySize {
+                rows: 43,
+                cols: 132,
+                cell_width: 9,
+                cell_height: 18,
+            }
+        );
+        assert_eq!(TerminalSize::from(pty), size);
+    }
+
+    #[test]
+    fn pty_size_round_trip_keeps_zero_pixel_dimensions() {
+        let pty = PtySize {
+            rows: 24,
+            cols: 80,
+            cell_width: 0,
+            cell_height: 0,
+        };
+
+        let size = TerminalSize::from(pty);
+        assert_eq!(size.cell_width, 0);
+        assert_eq!(size.cell_height, 0);
+        assert_eq!(PtySize::from(size), pty);
+    }
+}
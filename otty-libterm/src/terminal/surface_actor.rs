@@ -2,29 +2,66 @@ use std::collections::VecDeque;
 
 use log::debug;
 
-use crate::escape::{Action, EscapeActor};
+use crate::escape::{
+    Action, EscapeActor, ImageSize, NamedPrivateMode, PrivateMode,
+    ProgressState,
+};
 use crate::surface::SurfaceActor;
-use crate::terminal::{SyncState, TerminalEvent};
+use crate::terminal::event_queue::EventQueue;
+use crate::terminal::profile::TermProfile;
+use crate::terminal::size::TerminalSize;
+use crate::terminal::sync::SyncState;
+use crate::terminal::termcap::Termcaps;
+use crate::terminal::{TerminalEvent, eight_bit};
 
 /// Adapter that applies parsed escape [`Action`]s to a [`SurfaceActor`]
 /// implementation and emits high-level [`TerminalEvent`]s.
 pub(super) struct TerminalSurfaceActor<'a, S> {
     pub surface: &'a mut S,
-    pub events: &'a mut VecDeque<TerminalEvent>,
+    pub events: &'a mut EventQueue,
     pub pending_input: &'a mut VecDeque<u8>,
     pub sync_state: &'a mut SyncState,
+    pub progress: &'a mut (ProgressState, u8),
+    pub term_profile: TermProfile,
+    pub termcaps: &'a Termcaps,
+    pub size: TerminalSize,
+    pub utf8: bool,
 }
 
 impl<'a, S: SurfaceActor> TerminalSurfaceActor<'a, S> {
     fn dispatch_event(&mut self, event: TerminalEvent) {
-        self.events.push_back(event);
+        self.events.push(event);
+    }
+
+    /// Print decoded 8-bit output, handing characters of the right half of
+    /// the code table to the set invoked into GR.
+    fn print_eight_bit(&mut self, mut text: &str) {
+        while let Some((index, ch)) = text
+            .char_indices()
+            .find(|&(_, ch)| eight_bit::is_right_half(ch))
+        {
+            if index > 0 {
+                self.surface.print_str(&text[..index]);
+            }
+            self.surface.print_right_half(ch);
+            text = &text[index + ch.len_utf8()..];
+        }
+
+        if !text.is_empty() {
+            self.surface.print_str(text);
+        }
     }
 
     fn process_action(&mut self, action: Action) {
         use Action::*;
 
         match action {
+            Print(ch) if !self.utf8 && eight_bit::is_right_half(ch) => {
+                self.surface.print_right_half(ch)
+            },
             Print(ch) => self.surface.print(ch),
+            PrintString(text) if !self.utf8 => self.print_eight_bit(&text),
+            PrintString(text) => self.surface.print_str(&text),
             Bell => {
                 self.dispatch_event(TerminalEvent::Bell);
             },
@@ -59,6 +96,9 @@ impl<'a, S: SurfaceActor> TerminalSurfaceActor<'a, S> {
             SetActiveCharsetIndex(index) => {
                 self.surface.set_active_charset_index(index);
             },
+            SetRightCharsetIndex(index) => {
+                self.surface.set_right_charset_index(index);
+            },
             ConfigureCharset(charset, index) => {
                 self.surface.configure_charset(charset, index);
             },
@@ -102,6 +142,10 @@ impl<'a, S: SurfaceActor> TerminalSurfaceActor<'a, S> {
             Goto(row, col) => self.surface.goto(row, col),
             GotoRow(row) => self.surface.goto_row(row),
             GotoColumn(col) => self.surface.goto_column(col),
+            IdentifyTerminal(None) => {
+                let reply = self.term_profile.primary_device_attributes();
+                self.pending_input.extend(reply.as_bytes());
+            },
             IdentifyTerminal(response) => {
                 self.surface.identify_terminal(response, self.pending_input);
             },
@@ -109,6 +153,9 @@ impl<'a, S: SurfaceActor> TerminalSurfaceActor<'a, S> {
                 self.surface
                     .report_device_status(status, self.pending_input);
             },
+            QueryTermcap(names) => {
+                self.termcaps.reply(&names, self.pending_input);
+            },
             SetKeypadApplicationMode => {
                 self.surface.set_keypad_application_mode(true);
             },
@@ -158,10 +205,48 @@ impl<'a, S: SurfaceActor> TerminalSurfaceActor<'a, S> {
             BlockEvent(event) => {
                 self.surface.handle_block_event(event);
             },
+            PlaceImage(placement) => {
+                let (columns, lines) = self.image_cells(placement.size);
+                self.surface.place_image(placement, columns, lines);
+            },
+            RectFill { area, c } => self.surface.fill_rect(area, c),
+            RectAttr { area, change } => {
+                self.surface.change_rect_attributes(area, change)
+            },
+            SetAttributeChangeExtent(extent) => {
+                self.surface.set_attribute_change_extent(extent)
+            },
+            Progress { state, percent } => {
+                if *self.progress != (state, percent) {
+                    *self.progress = (state, percent);
+                    self.dispatch_event(TerminalEvent::Progress {
+                        state,
+                        percent,
+                    });
+                }
+            },
+            SemanticPrompt(mark) => self.surface.semantic_prompt(mark),
             action => debug!("unsupported action: {action:?}"),
         }
     }
 
+    /// Number of cells covered by an image of the given size.
+    fn image_cells(&self, size: ImageSize) -> (usize, usize) {
+        match size {
+            ImageSize::Cells { columns, rows } => {
+                (columns as usize, rows as usize)
+            },
+            ImageSize::Pixels { width, height } => {
+                let cell_width = u32::from(self.size.cell_width.max(1));
+                let cell_height = u32::from(self.size.cell_height.max(1));
+                (
+                    width.div_ceil(cell_width) as usize,
+                    height.div_ceil(cell_height) as usize,
+                )
+            },
+        }
+    }
+
     /// Apply a batch of actions, typically collected during a sync update.
     fn flush_buffered_actions(&mut self, actions: Vec<Action>) {
         for action in actions {
@@ -169,6 +254,16 @@ impl<'a, S: SurfaceActor> TerminalSurfaceActor<'a, S> {
         }
     }
 
+    /// Answer DECRQM for mode 2026 from the engine's own sync state.
+    ///
+    /// The surface never sees synchronized updates, and a query sent
+    /// inside one must not wait in the buffer until the update ends.
+    fn report_sync_update(&mut self) {
+        let state = if self.sync_state.is_active() { 1 } else { 2 };
+        self.pending_input
+            .extend(format!("\x1b[?2026;{state}$y").as_bytes());
+    }
+
     fn abort_sync(&mut self) -> bool {
         if !self.sync_state.is_active() {
             return false;
@@ -193,6 +288,14 @@ impl<'a, S: SurfaceActor> TerminalSurfaceActor<'a, S> {
 impl<'a, S: SurfaceActor> EscapeActor for TerminalSurfaceActor<'a, S> {
     /// Handle a single escape action, optionally buffering it in sync mode.
     fn handle(&mut self, action: Action) {
+        if let Action::ReportPrivateMode(PrivateMode::Named(
+            NamedPrivateMode::SyncUpdate,
+        )) = action
+        {
+            self.report_sync_update();
+            return;
+        }
+
         if self.sync_state.is_active() {
             if self.sync_state.is_expired() {
                 self.abort_sync();
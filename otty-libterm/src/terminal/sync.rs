@@ -0,0 +1,94 @@
+//! Synchronized output (mode 2026) buffering of parsed actions.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::escape::Action;
+use crate::terminal::clock::Clock;
+
+const MAX_SYNC_ACTIONS: usize = 10_000;
+pub(crate) const SYNC_TIMEOUT: Duration = Duration::from_millis(10);
+const IDLE_TICK: Duration = Duration::from_millis(10);
+
+/// Actions withheld during synchronized output, until its end or timeout.
+pub(crate) struct SyncState {
+    active: bool,
+    buffer: Vec<Action>,
+    deadline: Option<Instant>,
+    clock: Arc<dyn Clock>,
+}
+
+impl SyncState {
+    /// Create a new sync state with a fresh deadline read from `clock`.
+    pub(crate) fn new(clock: Arc<dyn Clock>) -> Self {
+        let mut state = Self {
+            active: false,
+            buffer: Vec::with_capacity(MAX_SYNC_ACTIONS),
+            deadline: None,
+            clock,
+        };
+        state.refresh_deadline();
+        state
+    }
+
+    /// Enter synchronized-update mode, buffering subsequent actions.
+    pub(crate) fn begin(&mut self) {
+        self.active = true;
+        self.buffer.clear();
+        self.refresh_deadline();
+    }
+
+    /// End synchronized-update mode and drain buffered actions.
+    pub(crate) fn end(&mut self) -> Vec<Action> {
+        self.active = false;
+        self.refresh_deadline();
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Cancel synchronized-update mode and drain buffered actions.
+    pub(crate) fn cancel(&mut self) -> Vec<Action> {
+        self.active = false;
+        self.refresh_deadline();
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Try to push a new action into the sync buffer.
+    ///
+    /// Returns the action back on overflow so that callers can fall back to
+    /// immediate processing. The deadline keeps counting from the start of
+    /// the update, so a missing end flushes even under steady output.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn push(
+        &mut self,
+        action: Action,
+    ) -> std::result::Result<(), Action> {
+        if self.buffer.len() >= MAX_SYNC_ACTIONS {
+            return Err(action);
+        }
+
+        self.buffer.push(action);
+        Ok(())
+    }
+
+    /// Check whether synchronized-update mode is currently active.
+    pub(crate) fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Instant at which the current deadline expires.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Check whether the current deadline has expired.
+    pub(crate) fn is_expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| self.clock.now() > deadline)
+    }
+
+    /// Refresh the internal deadline based on the current mode.
+    pub(crate) fn refresh_deadline(&mut self) {
+        let timeout = if self.active { SYNC_TIMEOUT } else { IDLE_TICK };
+        self.deadline = Some(self.clock.now() + timeout);
+    }
+}
@@ -0,0 +1,102 @@
+//! Terminfo capabilities reported in reply to XTGETTCAP queries.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+
+use crate::terminal::profile::TermProfile;
+
+/// Capability set answered for XTGETTCAP (`DCS + q Pt ST`) queries.
+///
+/// Boolean capabilities are stored with an empty value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Termcaps {
+    caps: BTreeMap<String, String>,
+}
+
+/// Capabilities of the default [`TermProfile`].
+impl Default for Termcaps {
+    fn default() -> Self {
+        TermProfile::default().termcaps()
+    }
+}
+
+impl Termcaps {
+    /// Create a set that answers every query with "not found".
+    pub fn empty() -> Self {
+        Self {
+            caps: BTreeMap::new(),
+        }
+    }
+
+    /// Add or replace a capability value.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) {
+        self.caps.insert(name.into(), value.into());
+    }
+
+    /// Remove a capability, returning its previous value.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.caps.remove(name)
+    }
+
+    /// Value of a capability, if known.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.caps.get(name).map(String::as_str)
+    }
+
+    /// Write one DCS reply per queried capability.
+    ///
+    /// Known capabilities are answered with `DCS 1 + r name=value ST`,
+    /// unknown ones with `DCS 0 + r name ST`.
+    pub(crate) fn reply(&self, names: &[String], out: &mut VecDeque<u8>) {
+        for name in names {
+            let reply = match self.get(name) {
+                Some("") => format!("\x1bP1+r{}\x1b\\", hex(name)),
+                Some(value) => {
+                    format!("\x1bP1+r{}={}\x1b\\", hex(name), hex(value))
+                },
+                None => format!("\x1bP0+r{}\x1b\\", hex(name)),
+            };
+            out.extend(reply.bytes());
+        }
+    }
+}
+
+fn hex(text: &str) -> String {
+    text.bytes().fold(String::new(), |mut out, byte| {
+        let _ = write!(out, "{byte:02X}");
+        out
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(caps: &Termcaps, names: &[&str]) -> Vec<u8> {
+        let names: Vec<String> = names.iter().map(|&n| n.to_owned()).collect();
+        let mut out = VecDeque::new();
+        caps.reply(&names, &mut out);
+        out.into_iter().collect()
+    }
+
+    #[test]
+    fn replies_with_known_value() {
+        let reply = reply(&Termcaps::default(), &["Co"]);
+
+        assert_eq!(reply, b"\x1bP1+r436F=323536\x1b\\");
+    }
+
+    #[test]
+    fn replies_not_found_for_unknown_and_removed_caps() {
+        let mut caps = Termcaps::default();
+        caps.remove("Co");
+
+        let reply = reply(&caps, &["Co", "Tc"]);
+
+        assert_eq!(reply, b"\x1bP0+r436F\x1b\\\x1bP1+r5463\x1b\\");
+    }
+}
@@ -1,4 +1,5 @@
 use std::io;
+use std::path::PathBuf;
 use std::time::Duration;
 
 #[cfg(unix)]
@@ -20,9 +21,19 @@ pub enum SessionError {
     #[error("failed to resize pty: {0}")]
     Resize(io::Error),
 
+    #[error("working directory {0:?} does not exist or is not a directory")]
+    InvalidWorkingDirectory(PathBuf),
+
     #[error("ssh host resolved to no addresses")]
     NoAddresses,
 
+    #[error("unsupported ssh {kind} {name:?}, supported: {supported}")]
+    UnsupportedAlgorithm {
+        kind: &'static str,
+        name: String,
+        supported: String,
+    },
+
     #[error("session launch cancelled")]
     Cancelled,
 
@@ -16,9 +16,11 @@ mod unix;
 use std::process::ExitStatus;
 
 use mio::Token;
+#[cfg(unix)]
+pub use nix::unistd::Pid;
 pub use ssh::{SSHAuth, SSHSession, SSHSessionBuilder, ssh};
 #[cfg(unix)]
-pub use unix::{LocalSession, LocalSessionBuilder, local};
+pub use unix::{AttachedSession, LocalSession, LocalSessionBuilder, local};
 
 pub use crate::errors::SessionError;
 pub use crate::size::PtySize;
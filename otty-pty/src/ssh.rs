@@ -12,7 +12,8 @@ use std::time::{Duration, Instant};
 use log::debug;
 use mio::{Events, Interest, Poll, Token, Waker};
 use ssh2::{
-    Channel, Error as SshError, ErrorCode, ExtendedData, Session as Ssh2Session,
+    Channel, Error as SshError, ErrorCode, ExtendedData, MethodType,
+    Session as Ssh2Session,
 };
 
 use crate::{Pollable, PtySize, Session, SessionError};
@@ -37,11 +38,73 @@ pub enum SSHAuth {
     },
 }
 
-/// Interactive SSH session backed by `ssh2` crate
-/// and integrated with Mio's poll loop.
+/// Channel operations used by [`SSHSession`], implemented by `ssh2`'s
+/// [`Channel`] and by test doubles.
+trait SSHChannel: Read + Write + Send {
+    fn request_pty_size(
+        &mut self,
+        width: u32,
+        height: u32,
+        width_px: Option<u32>,
+        height_px: Option<u32>,
+    ) -> Result<(), SshError>;
+    fn eof(&self) -> bool;
+    fn exit_status(&self) -> Result<i32, SshError>;
+    fn send_eof(&mut self) -> Result<(), SshError>;
+    fn wait_eof(&mut self) -> Result<(), SshError>;
+    fn close(&mut self) -> Result<(), SshError>;
+    fn wait_close(&mut self) -> Result<(), SshError>;
+}
+
+/// One step of the graceful channel teardown.
+type TeardownStep = fn(&mut dyn SSHChannel) -> Result<(), SshError>;
+
+impl SSHChannel for Channel {
+    fn request_pty_size(
+        &mut self,
+        width: u32,
+        height: u32,
+        width_px: Option<u32>,
+        height_px: Option<u32>,
+    ) -> Result<(), SshError> {
+        Channel::request_pty_size(self, width, height, width_px, height_px)
+    }
+
+    fn eof(&self) -> bool {
+        Channel::eof(self)
+    }
+
+    fn exit_status(&self) -> Result<i32, SshError> {
+        Channel::exit_status(self)
+    }
+
+    fn send_eof(&mut self) -> Result<(), SshError> {
+        Channel::send_eof(self)
+    }
+
+    fn wait_eof(&mut self) -> Result<(), SshError> {
+        Channel::wait_eof(self)
+    }
+
+    fn close(&mut self) -> Result<(), SshError> {
+        Channel::close(self)
+    }
+
+    fn wait_close(&mut self) -> Result<(), SshError> {
+        Channel::wait_close(self)
+    }
+}
+
+/// SSH session backed by `ssh2` crate and integrated with Mio's poll loop.
+///
+/// The session runs either an interactive shell or a single command, with
+/// or without a remote PTY, see [`SSHSessionBuilder::exec`] and
+/// [`SSHSessionBuilder::request_pty`].
 pub struct SSHSession {
-    _session: Ssh2Session,
-    channel: Channel,
+    _session: Option<Ssh2Session>,
+    channel: Box<dyn SSHChannel>,
+    /// Whether a remote PTY was allocated for the channel.
+    pty: bool,
 
     io: mio::net::TcpStream,
     waker: Option<mio::Waker>,
@@ -53,13 +116,15 @@ pub struct SSHSession {
 impl SSHSession {
     /// Construct a new SSH session wrapper with paired exit notification pipes.
     fn new(
-        session: Ssh2Session,
-        channel: Channel,
+        session: Option<Ssh2Session>,
+        channel: Box<dyn SSHChannel>,
+        pty: bool,
         io: mio::net::TcpStream,
     ) -> Self {
         Self {
             _session: session,
             channel,
+            pty,
             io,
             waker: None,
             exit_status: None,
@@ -138,7 +203,14 @@ impl Session for SSHSession {
 
     /// Request a resize of the remote PTY dimensions, propagating both
     /// character and pixel sizes when provided.
+    ///
+    /// Sessions opened without a PTY have nothing to resize, so this is a
+    /// no-op for them.
     fn resize(&mut self, size: PtySize) -> Result<(), SessionError> {
+        if !self.pty {
+            return Ok(());
+        }
+
         let pixel_width =
             (size.cell_width as u32).checked_mul(size.cols as u32);
         let pixel_height =
@@ -157,13 +229,14 @@ impl Session for SSHSession {
     /// Drive a graceful SSH channel teardown and surface the remote exit code
     /// when available.
     fn close(&mut self) -> Result<i32, SessionError> {
-        for step in [
-            Channel::send_eof,
-            Channel::wait_eof,
-            Channel::close,
-            Channel::wait_close,
-        ] {
-            if let Err(err) = step(&mut self.channel) {
+        let steps: [TeardownStep; 4] = [
+            |channel| channel.send_eof(),
+            |channel| channel.wait_eof(),
+            |channel| channel.close(),
+            |channel| channel.wait_close(),
+        ];
+        for step in steps {
+            if let Err(err) = step(self.channel.as_mut()) {
                 if !is_would_block(&err) {
                     return Err(SessionError::SSH2(err));
                 }
@@ -243,6 +316,12 @@ pub struct SSHSessionBuilder {
     user: String,
     auth: SSHAuth,
     size: PtySize,
+    term: Option<String>,
+    command: Option<String>,
+    skip_pty: bool,
+    compress: bool,
+    ciphers: Vec<String>,
+    kex: Vec<String>,
     timeout: Option<Duration>,
     cancel: Option<Arc<AtomicBool>>,
 }
@@ -276,6 +355,57 @@ impl SSHSessionBuilder {
         self
     }
 
+    /// Override the terminal type requested for the remote PTY.
+    ///
+    /// Defaults to `xterm-256color`.
+    pub fn with_term(mut self, term: &str) -> Self {
+        self.term = Some(term.into());
+        self
+    }
+
+    /// Run `command` on an exec channel instead of an interactive shell.
+    ///
+    /// The command output is read through [`Session::read`] and the
+    /// session exits with the command.
+    pub fn exec(mut self, command: &str) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    /// Choose whether a remote PTY is allocated for the channel.
+    ///
+    /// Defaults to `true`. Without a PTY the output can be piped as is,
+    /// and resizing the session does nothing.
+    pub fn request_pty(mut self, request: bool) -> Self {
+        self.skip_pty = !request;
+        self
+    }
+
+    /// Ask the server to compress the transport, which helps on slow links.
+    ///
+    /// Defaults to `false`.
+    pub fn compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Offer only these ciphers, most preferred first, in both directions.
+    ///
+    /// An empty list keeps the libssh2 defaults. Names libssh2 does not
+    /// support make [`spawn`](Self::spawn) fail before connecting.
+    pub fn preferred_ciphers(mut self, ciphers: Vec<String>) -> Self {
+        self.ciphers = ciphers;
+        self
+    }
+
+    /// Offer only these key exchange methods, most preferred first.
+    ///
+    /// Validated like [`preferred_ciphers`](Self::preferred_ciphers).
+    pub fn preferred_kex(mut self, kex: Vec<String>) -> Self {
+        self.kex = kex;
+        self
+    }
+
     /// Set an overall timeout for connecting and authenticating the session.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = Some(timeout);
@@ -288,18 +418,30 @@ impl SSHSessionBuilder {
         self
     }
 
-    /// Establish the SSH connection, negotiate a PTY, and return an interactive
-    /// session that can be registered with Mio.
+    /// Establish the SSH connection, negotiate a PTY unless disabled, and
+    /// return a session running the shell or the exec command that can be
+    /// registered with Mio.
     pub fn spawn(self) -> Result<SSHSession, SessionError> {
         let SSHSessionBuilder {
             host,
             user,
             auth,
             size,
+            term,
+            command,
+            skip_pty,
+            compress,
+            ciphers,
+            kex,
             timeout,
             cancel,
         } = self;
 
+        // Method preferences only take effect before the handshake, and a
+        // typo is reported before any network traffic happens.
+        let mut session = Ssh2Session::new()?;
+        configure_transport(&session, compress, &ciphers, &kex)?;
+
         let start = Instant::now();
         let cancel = cancel.as_ref();
         let executor = RetryableExecutor {
@@ -311,7 +453,6 @@ impl SSHSessionBuilder {
         stream.set_nonblocking(true)?;
         stream.set_nodelay(true)?;
 
-        let mut session = Ssh2Session::new()?;
         session.set_tcp_stream(stream.try_clone()?);
         session.set_blocking(false);
         executor.exec("ssh handshake", || session.handshake())?;
@@ -371,30 +512,88 @@ impl SSHSessionBuilder {
             channel.handle_extended_data(ExtendedData::Merge)
         })?;
 
-        let pixel_width =
-            (size.cell_width as u32).checked_mul(size.cols as u32);
-        let pixel_height =
-            (size.cell_height as u32).checked_mul(size.rows as u32);
-
-        let pty_size = Some((
-            size.cols as u32,
-            size.rows as u32,
-            pixel_width.unwrap_or(0),
-            pixel_height.unwrap_or(0),
-        ));
+        if !skip_pty {
+            let pixel_width =
+                (size.cell_width as u32).checked_mul(size.cols as u32);
+            let pixel_height =
+                (size.cell_height as u32).checked_mul(size.rows as u32);
+
+            let pty_size = Some((
+                size.cols as u32,
+                size.rows as u32,
+                pixel_width.unwrap_or(0),
+                pixel_height.unwrap_or(0),
+            ));
+
+            let term = term.as_deref().unwrap_or(REQUEST_PTY_TAG);
+            executor.exec("ssh request pty", || {
+                channel.request_pty(term, None, pty_size)
+            })?;
+        }
 
-        executor.exec("ssh request pty", || {
-            channel.request_pty(REQUEST_PTY_TAG, None, pty_size)
-        })?;
-        executor.exec("ssh shell", || channel.shell())?;
+        match command.as_deref() {
+            Some(command) => {
+                executor.exec("ssh exec", || channel.exec(command))?
+            },
+            None => executor.exec("ssh shell", || channel.shell())?,
+        }
 
         let mio_stream = mio::net::TcpStream::from_std(stream);
         mio_stream.set_nodelay(true)?;
 
-        Ok(SSHSession::new(session, channel, mio_stream))
+        Ok(SSHSession::new(
+            Some(session),
+            Box::new(channel),
+            !skip_pty,
+            mio_stream,
+        ))
     }
 }
 
+/// Apply the compression flag and the algorithm preferences to `session`.
+fn configure_transport(
+    session: &Ssh2Session,
+    compress: bool,
+    ciphers: &[String],
+    kex: &[String],
+) -> Result<(), SessionError> {
+    session.set_compress(compress);
+    for method in [MethodType::CryptCs, MethodType::CryptSc] {
+        set_method_pref(session, method, "cipher", ciphers)?;
+    }
+    set_method_pref(session, MethodType::Kex, "key exchange", kex)
+}
+
+/// Restrict `method` to `names`, rejecting names libssh2 does not know.
+///
+/// libssh2 silently drops unknown names, which would leave the connection
+/// with a different algorithm than requested.
+fn set_method_pref(
+    session: &Ssh2Session,
+    method: MethodType,
+    kind: &'static str,
+    names: &[String],
+) -> Result<(), SessionError> {
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    let supported = session.supported_algs(method)?;
+    if let Some(name) = names
+        .iter()
+        .find(|name| !supported.contains(&name.as_str()))
+    {
+        return Err(SessionError::UnsupportedAlgorithm {
+            kind,
+            name: name.clone(),
+            supported: supported.join(", "),
+        });
+    }
+
+    session.method_pref(method, &names.join(","))?;
+    Ok(())
+}
+
 /// Check whether a libssh2 error represents a non-blocking retry condition.
 fn is_would_block(err: &SshError) -> bool {
     matches!(err.code(), ErrorCode::Session(code) if code == LIBSSH2_ERROR_EAGAIN)
@@ -553,3 +752,188 @@ fn exit_status_from_code(code: i32) -> ExitStatus {
 fn exit_status_from_code(code: i32) -> ExitStatus {
     std::os::windows::process::ExitStatusExt::from_raw(code as u32)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// In-memory channel replaying the output of a finished command.
+    struct MockChannel {
+        output: io::Cursor<Vec<u8>>,
+        exit_code: i32,
+        resizes: Arc<Mutex<Vec<(u32, u32)>>>,
+    }
+
+    impl MockChannel {
+        fn new(output: &[u8], exit_code: i32) -> Self {
+            Self {
+                output: io::Cursor::new(output.to_vec()),
+                exit_code,
+                resizes: Arc::default(),
+            }
+        }
+    }
+
+    impl Read for MockChannel {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.output.read(buf)
+        }
+    }
+
+    impl Write for MockChannel {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SSHChannel for MockChannel {
+        fn request_pty_size(
+            &mut self,
+            width: u32,
+            height: u32,
+            _: Option<u32>,
+            _: Option<u32>,
+        ) -> Result<(), SshError> {
+            self.resizes.lock().unwrap().push((width, height));
+            Ok(())
+        }
+
+        fn eof(&self) -> bool {
+            self.output.position() == self.output.get_ref().len() as u64
+        }
+
+        fn exit_status(&self) -> Result<i32, SshError> {
+            Ok(self.exit_code)
+        }
+
+        fn send_eof(&mut self) -> Result<(), SshError> {
+            Ok(())
+        }
+
+        fn wait_eof(&mut self) -> Result<(), SshError> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> Result<(), SshError> {
+            Ok(())
+        }
+
+        fn wait_close(&mut self) -> Result<(), SshError> {
+            Ok(())
+        }
+    }
+
+    fn loopback_stream() -> mio::net::TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let stream = TcpStream::connect(listener.local_addr().expect("addr"))
+            .expect("connect");
+        mio::net::TcpStream::from_std(stream)
+    }
+
+    fn read_all(session: &mut SSHSession) -> Vec<u8> {
+        let mut output = Vec::new();
+        let mut buf = [0u8; 4];
+        loop {
+            match session.read(&mut buf).expect("read") {
+                0 => return output,
+                n => output.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+
+    #[test]
+    fn exec_channel_output_flows_through_read() {
+        let channel = MockChannel::new(b"total 0\nREADME.md\n", 3);
+        let mut session =
+            SSHSession::new(None, Box::new(channel), false, loopback_stream());
+
+        assert_eq!(read_all(&mut session), b"total 0\nREADME.md\n");
+        let status = session
+            .try_get_child_exit_status()
+            .expect("exit status")
+            .expect("command exited");
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(session.close().expect("close"), 3);
+    }
+
+    #[test]
+    fn transport_options_are_kept_by_the_builder() {
+        let builder = ssh()
+            .compression(true)
+            .preferred_ciphers(vec!["aes256-ctr".into(), "aes128-ctr".into()])
+            .preferred_kex(vec!["diffie-hellman-group14-sha256".into()]);
+
+        assert!(builder.compress);
+        assert_eq!(builder.ciphers, ["aes256-ctr", "aes128-ctr"]);
+        assert_eq!(builder.kex, ["diffie-hellman-group14-sha256"]);
+    }
+
+    #[test]
+    fn supported_algorithms_are_applied_to_the_session() {
+        let session = Ssh2Session::new().expect("session");
+        let cipher = session.supported_algs(MethodType::CryptCs).expect("algs")
+            [0]
+        .to_string();
+        let kex = session.supported_algs(MethodType::Kex).expect("algs")[0]
+            .to_string();
+
+        configure_transport(&session, true, &[cipher], &[kex])
+            .expect("known algorithms are accepted");
+        configure_transport(&session, false, &[], &[])
+            .expect("empty preferences keep the defaults");
+    }
+
+    #[test]
+    fn unknown_cipher_is_rejected_with_the_supported_list() {
+        let session = Ssh2Session::new().expect("session");
+        let supported =
+            session.supported_algs(MethodType::CryptCs).expect("algs");
+
+        let err = configure_transport(
+            &session,
+            false,
+            &[supported[0].to_string(), "rot13".into()],
+            &[],
+        )
+        .expect_err("unknown cipher");
+
+        assert!(matches!(
+            &err,
+            SessionError::UnsupportedAlgorithm { kind: "cipher", name, .. }
+                if name == "rot13"
+        ));
+        let message = err.to_string();
+        assert!(message.starts_with("unsupported ssh cipher \"rot13\""));
+        assert!(supported.iter().all(|alg| message.contains(alg)));
+    }
+
+    #[test]
+    fn resize_is_ignored_without_a_pty() {
+        let size = PtySize {
+            rows: 40,
+            cols: 120,
+            ..PtySize::default()
+        };
+
+        let channel = MockChannel::new(b"", 0);
+        let resizes = channel.resizes.clone();
+        let mut session =
+            SSHSession::new(None, Box::new(channel), false, loopback_stream());
+        session.resize(size).expect("resize");
+        assert!(resizes.lock().unwrap().is_empty());
+
+        let channel = MockChannel::new(b"", 0);
+        let resizes = channel.resizes.clone();
+        let mut session =
+            SSHSession::new(None, Box::new(channel), true, loopback_stream());
+        session.resize(size).expect("resize");
+        assert_eq!(*resizes.lock().unwrap(), [(120, 40)]);
+    }
+}
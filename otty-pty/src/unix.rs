@@ -2,30 +2,41 @@
 //! through the shared `Session` abstraction.
 
 use std::convert::TryFrom;
-use std::env;
 use std::fs::File;
 use std::io::{self, Read, Write};
-use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd};
+use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::os::unix::net::UnixStream;
-use std::os::unix::process::CommandExt;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, ExitStatus};
+use std::{env, thread};
 
+use log::warn;
 use mio::Token;
 use mio::unix::SourceFd;
 use nix::libc;
 use nix::pty::{Winsize, openpty};
+use nix::sys::wait::{WaitPidFlag, WaitStatus, waitpid};
+use nix::unistd::Pid;
 use signal_hook::SigId;
 use signal_hook::low_level::{self, pipe};
 
 use crate::{Pollable, PtySize, Session, SessionError};
 
 /// Local pseudo terminal session that owns the spawned child process.
+///
+/// [`Session::close`] always terminates and reaps the child, so it never
+/// lingers as a zombie. Dropping the session without closing it does the
+/// same by default; with [`LocalSessionBuilder::kill_on_drop`] disabled the
+/// child is detached instead and reaped by a background thread once it
+/// exits on its own.
 pub struct LocalSession {
     master: File,
     child: Child,
     signal_pipe: UnixStream,
     signal_pipe_id: SigId,
+    controlling_tty: bool,
+    kill_on_drop: bool,
 }
 
 impl Session for LocalSession {
@@ -44,52 +55,25 @@ impl Session for LocalSession {
 
     /// Read bytes produced by the child process from the PTY master.
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
-        loop {
-            match self.master.read(buf) {
-                Ok(n) => return Ok(n),
-                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
-                    continue;
-                },
-                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
-                    return Err(SessionError::IO(err));
-                },
-                Err(err) => return Err(SessionError::IO(err)),
-            }
-        }
+        read_master(&mut self.master, buf)
     }
 
     /// Write bytes into the PTY master so the child process receives them on
     /// its stdin.
     fn write(&mut self, input: &[u8]) -> Result<usize, SessionError> {
-        loop {
-            match self.master.write(input) {
-                Ok(n) => return Ok(n),
-                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
-                    continue;
-                },
-                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
-                    return Err(SessionError::IO(err));
-                },
-                Err(err) => return Err(SessionError::IO(err)),
-            }
-        }
+        write_master(&mut self.master, input)
     }
 
     /// Resize the pseudo terminal to match the front-end viewport.
+    ///
+    /// The kernel signals `SIGWINCH` only to processes the PTY controls, so
+    /// a child spawned without a controlling terminal is signalled
+    /// explicitly, once the new size is in place.
     fn resize(&mut self, size: PtySize) -> Result<(), SessionError> {
-        let size: Winsize = size.into();
-        let res = unsafe {
-            libc::ioctl(
-                self.master.as_raw_fd(),
-                libc::TIOCSWINSZ,
-                &size as *const _,
-            )
-        };
-
-        if res < 0 {
-            return Err(SessionError::Resize(io::Error::last_os_error()));
+        resize_master(&self.master, size)?;
+        if !self.controlling_tty {
+            self.notify_resize()?;
         }
-
         Ok(())
     }
 
@@ -198,7 +182,15 @@ impl Pollable for LocalSession {
 
 impl Drop for LocalSession {
     fn drop(&mut self) {
-        let _ = self.close().expect("failed to close unix session");
+        if self.kill_on_drop {
+            if let Err(err) = self.close() {
+                warn!("failed to close unix session on drop: {err}");
+            }
+            return;
+        }
+
+        low_level::unregister(self.signal_pipe_id);
+        self.detach_child();
     }
 }
 
@@ -208,13 +200,254 @@ impl LocalSession {
         child: Child,
         signal_pipe: UnixStream,
         signal_pipe_id: SigId,
+        controlling_tty: bool,
+        kill_on_drop: bool,
     ) -> Self {
         Self {
             master,
             child,
             signal_pipe,
             signal_pipe_id,
+            controlling_tty,
+            kill_on_drop,
+        }
+    }
+
+    /// Send `SIGWINCH` to the foreground process group of the PTY.
+    ///
+    /// Lets hosts that proxy resizes from a parent terminal make the child
+    /// re-read its size even when the size did not change, since the kernel
+    /// stays silent then. Without a foreground process group the child
+    /// itself is signalled. A child that already exited is not an error.
+    pub fn notify_resize(&self) -> Result<(), SessionError> {
+        let group = unsafe { libc::tcgetpgrp(self.master.as_raw_fd()) };
+        let result = if group > 0 {
+            unsafe { libc::killpg(group, libc::SIGWINCH) }
+        } else {
+            let pid = i32::try_from(self.child.id())
+                .map_err(|err| SessionError::Internal(err.to_string()))?;
+            unsafe { libc::kill(pid, libc::SIGWINCH) }
+        };
+
+        if result != 0 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::ESRCH) {
+                return Err(SessionError::IO(err));
+            }
+        }
+        Ok(())
+    }
+
+    /// Leave the child running and reap it from a background thread.
+    fn detach_child(&mut self) {
+        match self.child.try_wait() {
+            Ok(Some(_)) => {},
+            Ok(None) => {
+                let pid = self.child.id();
+                let spawned = thread::Builder::new()
+                    .name(format!("otty-pty-reaper-{pid}"))
+                    .spawn(move || reap_detached(pid));
+                if let Err(err) = spawned {
+                    warn!("failed to spawn reaper for child {pid}: {err}");
+                }
+            },
+            Err(err) => warn!("failed to poll detached child: {err}"),
+        }
+    }
+}
+
+/// PTY session over a master fd opened elsewhere, e.g. one received over a
+/// unix socket from a multiplexer.
+///
+/// Nothing is spawned. When the pid of a child of this process is known its
+/// exit is reported, otherwise [`Session::try_get_child_exit_status`] always
+/// returns `None`. [`Session::close`] only hangs up the master fd.
+pub struct AttachedSession {
+    master: Option<File>,
+    child: Option<AttachedChild>,
+}
+
+/// Child process watched by an [`AttachedSession`].
+struct AttachedChild {
+    pid: Pid,
+    signal_pipe: UnixStream,
+    signal_pipe_id: SigId,
+    status: Option<ExitStatus>,
+}
+
+impl AttachedSession {
+    /// Wrap an existing PTY master fd, taking ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open PTY master fd that is not owned elsewhere, as
+    /// with [`FromRawFd::from_raw_fd`].
+    pub unsafe fn from_raw_fd(
+        fd: RawFd,
+        child_pid: Option<Pid>,
+    ) -> Result<Self, SessionError> {
+        let master = unsafe { File::from_raw_fd(fd) };
+        set_nonblocking(master.as_raw_fd())?;
+
+        let child = match child_pid {
+            Some(pid) => {
+                let (signal_pipe, signal_pipe_id) = register_signal_handler()?;
+                Some(AttachedChild {
+                    pid,
+                    signal_pipe,
+                    signal_pipe_id,
+                    status: None,
+                })
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            master: Some(master),
+            child,
+        })
+    }
+
+    fn master(&mut self) -> Result<&mut File, SessionError> {
+        self.master.as_mut().ok_or_else(|| {
+            SessionError::IO(io::Error::from(io::ErrorKind::NotConnected))
+        })
+    }
+}
+
+impl Session for AttachedSession {
+    /// Report the exit of the watched child, if there is one.
+    fn try_get_child_exit_status(
+        &mut self,
+    ) -> Result<Option<ExitStatus>, SessionError> {
+        let Some(child) = self.child.as_mut() else {
+            return Ok(None);
+        };
+
+        let mut tmp = [0u8; 1];
+        match child.signal_pipe.read(&mut tmp) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(SessionError::IO(e)),
+            _ => Ok(child.try_wait()),
+        }
+    }
+
+    /// Read bytes from the attached PTY master.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        read_master(self.master()?, buf)
+    }
+
+    /// Write bytes into the attached PTY master.
+    fn write(&mut self, input: &[u8]) -> Result<usize, SessionError> {
+        write_master(self.master()?, input)
+    }
+
+    /// Resize the attached pseudo terminal.
+    fn resize(&mut self, size: PtySize) -> Result<(), SessionError> {
+        resize_master(self.master()?, size)
+    }
+
+    /// Close the master fd and report the child's exit code if known.
+    fn close(&mut self) -> Result<i32, SessionError> {
+        self.master = None;
+
+        let status = self.child.as_mut().and_then(AttachedChild::try_wait);
+        Ok(status.and_then(|status| status.code()).unwrap_or_default())
+    }
+}
+
+impl Pollable for AttachedSession {
+    /// Register the PTY master and, with a known child, the SIGCHLD
+    /// notification pipe with Mio.
+    fn register(
+        &mut self,
+        registry: &mio::Registry,
+        interest: mio::Interest,
+        io_token: Token,
+        child_token: Token,
+    ) -> Result<(), SessionError> {
+        let master_fd = self.master()?.as_raw_fd();
+        registry.register(&mut SourceFd(&master_fd), io_token, interest)?;
+
+        if let Some(child) = &self.child {
+            let signal_pipe = child.signal_pipe.as_raw_fd();
+            registry.register(
+                &mut SourceFd(&signal_pipe),
+                child_token,
+                mio::Interest::READABLE,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Update Mio's interest set for the tracked file descriptors.
+    fn reregister(
+        &mut self,
+        registry: &mio::Registry,
+        interest: mio::Interest,
+        io_token: Token,
+        child_token: Token,
+    ) -> Result<(), SessionError> {
+        let master_fd = self.master()?.as_raw_fd();
+        registry.reregister(&mut SourceFd(&master_fd), io_token, interest)?;
+
+        if let Some(child) = &self.child {
+            let signal_pipe = child.signal_pipe.as_raw_fd();
+            registry.reregister(
+                &mut SourceFd(&signal_pipe),
+                child_token,
+                mio::Interest::READABLE,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove the tracked file descriptors from the Mio registry.
+    fn deregister(
+        &mut self,
+        registry: &mio::Registry,
+    ) -> Result<(), SessionError> {
+        if let Some(master) = &self.master {
+            registry.deregister(&mut SourceFd(&master.as_raw_fd()))?;
+        }
+
+        if let Some(child) = &self.child {
+            registry
+                .deregister(&mut SourceFd(&child.signal_pipe.as_raw_fd()))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for AttachedChild {
+    fn drop(&mut self) {
+        low_level::unregister(self.signal_pipe_id);
+    }
+}
+
+impl AttachedChild {
+    /// Poll the child without blocking, caching its exit status.
+    fn try_wait(&mut self) -> Option<ExitStatus> {
+        if self.status.is_none() {
+            self.status = match waitpid(self.pid, Some(WaitPidFlag::WNOHANG)) {
+                Ok(WaitStatus::Exited(_, code)) => {
+                    Some(ExitStatus::from_raw(code << 8))
+                },
+                Ok(WaitStatus::Signaled(_, signal, _)) => {
+                    Some(ExitStatus::from_raw(signal as i32))
+                },
+                Ok(_) => None,
+                Err(err) => {
+                    warn!("failed to poll attached child {}: {err}", self.pid);
+                    None
+                },
+            };
         }
+
+        self.status
     }
 }
 
@@ -224,6 +457,7 @@ pub struct LocalSessionBuilder {
     size: PtySize,
     work_dir: Option<PathBuf>,
     controlling_tty: bool,
+    kill_on_drop: bool,
 }
 
 /// Start building a Unix PTY session for the provided executable.
@@ -233,6 +467,7 @@ pub fn local(program: &str) -> LocalSessionBuilder {
         size: PtySize::default(),
         work_dir: None,
         controlling_tty: false,
+        kill_on_drop: true,
     }
 }
 
@@ -243,6 +478,12 @@ impl Default for LocalSessionBuilder {
 }
 
 impl LocalSessionBuilder {
+    /// Whether an environment variable was explicitly set or removed for
+    /// the child process.
+    pub fn has_env(&self, key: &str) -> bool {
+        self.cmd.get_envs().any(|(name, _)| name == key)
+    }
+
     /// Append a single argument to the command line.
     pub fn with_arg(mut self, arg: &str) -> Self {
         self.cmd.arg(arg);
@@ -277,6 +518,12 @@ impl LocalSessionBuilder {
     }
 
     /// Change the working directory of the spawned child process.
+    ///
+    /// [`spawn`](Self::spawn) fails with
+    /// [`SessionError::InvalidWorkingDirectory`] when the path is missing or
+    /// is not a directory. A directory that exists but cannot be inspected
+    /// or entered is left to the child, which reports the failure to change
+    /// into it.
     pub fn with_cwd(mut self, path: &Path) -> Self {
         self.work_dir = Some(path.to_path_buf());
         self
@@ -289,9 +536,22 @@ impl LocalSessionBuilder {
         self
     }
 
+    /// Choose whether dropping the session terminates the child process.
+    ///
+    /// Enabled by default. When disabled the child keeps running after the
+    /// session is dropped and is reaped in the background once it exits.
+    pub fn kill_on_drop(mut self, enabled: bool) -> Self {
+        self.kill_on_drop = enabled;
+        self
+    }
+
     /// Spawn the configured command and return an interactive PTY session that
     /// can be registered with Mio.
     pub fn spawn(mut self) -> Result<LocalSession, SessionError> {
+        if let Some(dir) = &self.work_dir {
+            validate_work_dir(dir)?;
+        }
+
         let result = openpty(Some(&self.size.into()), None)?;
         let master = unsafe { File::from_raw_fd(result.master.into_raw_fd()) };
         let slave = unsafe { File::from_raw_fd(result.slave.into_raw_fd()) };
@@ -356,6 +616,8 @@ impl LocalSessionBuilder {
             child,
             signal_pipe,
             signal_pipe_id,
+            self.controlling_tty,
+            self.kill_on_drop,
         ))
     }
 }
@@ -367,6 +629,68 @@ fn register_signal_handler() -> Result<(UnixStream, SigId), SessionError> {
     Ok((pipe, pipe_id))
 }
 
+fn reap_detached(pid: u32) {
+    let Ok(pid_raw) = i32::try_from(pid) else {
+        return;
+    };
+
+    let mut status = 0;
+    loop {
+        let result = unsafe { libc::waitpid(pid_raw, &mut status, 0) };
+        if result >= 0 {
+            return;
+        }
+
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return;
+        }
+    }
+}
+
+fn read_master(
+    master: &mut File,
+    buf: &mut [u8],
+) -> Result<usize, SessionError> {
+    loop {
+        match master.read(buf) {
+            Ok(n) => return Ok(n),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
+                continue;
+            },
+            Err(err) => return Err(SessionError::IO(err)),
+        }
+    }
+}
+
+fn write_master(
+    master: &mut File,
+    input: &[u8],
+) -> Result<usize, SessionError> {
+    loop {
+        match master.write(input) {
+            Ok(n) => return Ok(n),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
+                continue;
+            },
+            Err(err) => return Err(SessionError::IO(err)),
+        }
+    }
+}
+
+fn resize_master(master: &File, size: PtySize) -> Result<(), SessionError> {
+    let size: Winsize = size.into();
+    let res = unsafe {
+        libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &size as *const _)
+    };
+
+    if res < 0 {
+        return Err(SessionError::Resize(io::Error::last_os_error()));
+    }
+
+    Ok(())
+}
+
 fn set_nonblocking(raw_fd: i32) -> Result<(), SessionError> {
     unsafe {
         let flags = libc::fcntl(raw_fd, libc::F_GETFL, 0);
@@ -380,15 +704,56 @@ fn set_nonblocking(raw_fd: i32) -> Result<(), SessionError> {
     }
 }
 
+/// Reject a working directory that is missing or is not a directory.
+///
+/// Other lookup failures, such as a permission error, are not checked here:
+/// the child still tries to change into the directory.
+fn validate_work_dir(dir: &Path) -> Result<(), SessionError> {
+    match std::fs::metadata(dir) {
+        Ok(metadata) if !metadata.is_dir() => {
+            Err(SessionError::InvalidWorkingDirectory(dir.to_path_buf()))
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            Err(SessionError::InvalidWorkingDirectory(dir.to_path_buf()))
+        },
+        _ => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::io::ErrorKind;
-    use std::thread;
+    use std::fs::File;
+    use std::io::{ErrorKind, Read, Write};
+    use std::os::fd::{FromRawFd, IntoRawFd};
+    use std::path::PathBuf;
+    use std::process::Command;
     use std::time::Duration;
+    use std::{env, fs, thread};
 
     use nix::errno::Errno;
+    use nix::libc;
+    use nix::pty::{OpenptyResult, openpty};
+    use nix::unistd::Pid;
 
-    use super::{Session, SessionError, local};
+    use super::{AttachedSession, PtySize, Session, SessionError, local};
+
+    fn open_pty() -> Option<OpenptyResult> {
+        match openpty(None, None) {
+            Ok(pty) => Some(pty),
+            Err(Errno::EACCES) => {
+                eprintln!("skipping test; PTY allocation denied (EACCES)");
+                None
+            },
+            Err(err) => panic!("failed to open pty: {err:?}"),
+        }
+    }
+
+    fn child_is_gone(pid: i32) -> bool {
+        let mut status = 0;
+        let result = unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) };
+        let alive = unsafe { libc::kill(pid, 0) } == 0;
+        result == -1 && !alive
+    }
 
     fn read_output(session: &mut impl Session) -> Result<String, SessionError> {
         let mut buffer = [0u8; 1024];
@@ -475,6 +840,42 @@ mod test {
         assert_eq!(session.close().expect("failed to close"), 0);
     }
 
+    fn scratch_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("otty-pty-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn spawn_rejects_a_missing_working_directory() {
+        let dir = scratch_path("missing-dir");
+        let result = local("/bin/true").with_cwd(&dir).spawn();
+
+        assert!(
+            matches!(
+                &result,
+                Err(SessionError::InvalidWorkingDirectory(path)) if *path == dir
+            ),
+            "unexpected spawn result: {:?}",
+            result.err()
+        );
+    }
+
+    #[test]
+    fn spawn_rejects_a_file_as_working_directory() {
+        let file = scratch_path("cwd-file");
+        fs::write(&file, b"").expect("failed to create scratch file");
+        let result = local("/bin/true").with_cwd(&file).spawn();
+        let _ = fs::remove_file(&file);
+
+        assert!(
+            matches!(
+                &result,
+                Err(SessionError::InvalidWorkingDirectory(path)) if *path == file
+            ),
+            "unexpected spawn result: {:?}",
+            result.err()
+        );
+    }
+
     #[test]
     fn unix_session_respects_environment()
     -> Result<(), Box<dyn std::error::Error>> {
@@ -498,4 +899,168 @@ mod test {
         assert_eq!(session.close()?, 0);
         Ok(())
     }
+
+    fn read_until(
+        session: &mut impl Session,
+        needle: &str,
+    ) -> Result<String, SessionError> {
+        let mut output = String::new();
+        for _ in 0..100 {
+            output.push_str(&read_output(session)?);
+            if output.contains(needle) {
+                break;
+            }
+        }
+        Ok(output)
+    }
+
+    #[test]
+    fn resize_delivers_sigwinch_to_the_child() {
+        let script = "trap 'echo \"winch $(stty size)\"' WINCH; echo ready; \
+                      while :; do sleep 0.05; done";
+        for controlling_tty in [false, true] {
+            let mut builder = local("/bin/sh").with_arg("-c").with_arg(script);
+            if controlling_tty {
+                builder = builder.set_controling_tty_enable();
+            }
+            let mut session = match builder.spawn() {
+                Ok(session) => session,
+                Err(SessionError::Nix(Errno::EACCES)) => {
+                    eprintln!("skipping test; PTY allocation denied (EACCES)");
+                    return;
+                },
+                Err(err) => panic!("failed to spawn session: {err:?}"),
+            };
+
+            let output = read_until(&mut session, "ready").expect("read");
+            assert!(output.contains("ready"), "no prompt: {output:?}");
+
+            session
+                .resize(PtySize {
+                    rows: 33,
+                    cols: 101,
+                    ..PtySize::default()
+                })
+                .expect("failed to resize");
+            let output =
+                read_until(&mut session, "winch 33 101").expect("read");
+            assert!(
+                output.contains("winch 33 101"),
+                "resize not reported (controlling tty: {controlling_tty}): \
+                 {output:?}"
+            );
+
+            session.close().expect("failed to close");
+        }
+    }
+
+    #[test]
+    fn attached_session_round_trips_bytes_over_existing_master() {
+        let Some(pty) = open_pty() else {
+            return;
+        };
+        let mut slave = unsafe { File::from_raw_fd(pty.slave.into_raw_fd()) };
+        let mut session = unsafe {
+            AttachedSession::from_raw_fd(pty.master.into_raw_fd(), None)
+        }
+        .expect("failed to attach to master");
+
+        slave
+            .write_all(b"from-slave")
+            .expect("failed to write slave");
+        let output = read_output(&mut session).expect("failed to read");
+        assert_eq!(output, "from-slave");
+
+        write_input(&mut session, b"from-master\n")
+            .expect("failed to write master");
+        let mut buf = [0u8; 64];
+        let n = slave.read(&mut buf).expect("failed to read slave");
+        assert_eq!(&buf[..n], b"from-master\n");
+
+        session
+            .resize(PtySize::default())
+            .expect("failed to resize attached pty");
+        assert!(matches!(session.try_get_child_exit_status(), Ok(None)));
+        assert_eq!(session.close().expect("failed to close"), 0);
+        assert!(session.read(&mut buf).is_err());
+    }
+
+    #[test]
+    fn attached_session_reports_known_child_exit() {
+        let Some(pty) = open_pty() else {
+            return;
+        };
+        // The attached session reaps the child through its pid.
+        #[allow(clippy::zombie_processes)]
+        let child = Command::new("/bin/sh")
+            .args(["-c", "sleep 0.1; exit 3"])
+            .spawn()
+            .expect("failed to spawn child");
+        let pid = Pid::from_raw(child.id() as i32);
+        let mut session = unsafe {
+            AttachedSession::from_raw_fd(pty.master.into_raw_fd(), Some(pid))
+        }
+        .expect("failed to attach to master");
+
+        let mut status = None;
+        for _ in 0..100 {
+            status = session
+                .try_get_child_exit_status()
+                .expect("failed to poll child");
+            if status.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        assert_eq!(status.and_then(|status| status.code()), Some(3));
+        assert_eq!(session.close().expect("failed to close"), 3);
+    }
+
+    #[test]
+    fn dropping_session_kills_and_reaps_child() {
+        let session = match local("/bin/sleep").with_arg("30").spawn() {
+            Ok(session) => session,
+            Err(SessionError::Nix(Errno::EACCES)) => {
+                eprintln!("skipping test; PTY allocation denied (EACCES)");
+                return;
+            },
+            Err(err) => panic!("failed to spawn session: {err:?}"),
+        };
+        let pid = session.child.id() as i32;
+
+        drop(session);
+
+        assert!(child_is_gone(pid), "child {pid} should be reaped on drop");
+    }
+
+    #[test]
+    fn detached_child_survives_drop_and_is_reaped() {
+        let session = match local("/bin/sleep")
+            .with_arg("0.2")
+            .kill_on_drop(false)
+            .spawn()
+        {
+            Ok(session) => session,
+            Err(SessionError::Nix(Errno::EACCES)) => {
+                eprintln!("skipping test; PTY allocation denied (EACCES)");
+                return;
+            },
+            Err(err) => panic!("failed to spawn session: {err:?}"),
+        };
+        let pid = session.child.id() as i32;
+
+        drop(session);
+        assert_eq!(unsafe { libc::kill(pid, 0) }, 0, "child should survive");
+
+        let mut reaped = false;
+        for _ in 0..100 {
+            if child_is_gone(pid) {
+                reaped = true;
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(reaped, "detached child {pid} should be reaped after exit");
+    }
 }
@@ -0,0 +1,77 @@
+//! Compare literal `find_all` searches with and without the scrollback index.
+//!
+//! Run with `cargo bench -p otty-surface --bench history_search`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use otty_surface::{
+    Dimensions, RegexSearch, Surface, SurfaceActor, SurfaceConfig,
+};
+
+const HISTORY: usize = 50_000;
+const ROUNDS: u32 = 10;
+
+struct Size;
+
+impl Dimensions for Size {
+    fn total_lines(&self) -> usize {
+        self.screen_lines()
+    }
+
+    fn screen_lines(&self) -> usize {
+        40
+    }
+
+    fn columns(&self) -> usize {
+        120
+    }
+}
+
+fn filled_surface(history_search_index: bool) -> Surface {
+    let config = SurfaceConfig {
+        scrolling_history: HISTORY,
+        history_search_index,
+        ..SurfaceConfig::default()
+    };
+    let mut surface = Surface::new(config, &Size);
+
+    for i in 0..HISTORY {
+        let line = if i % 5_000 == 0 {
+            format!("{i}: the needle is here")
+        } else {
+            format!("{i}: lorem ipsum dolor sit amet, consectetur adipiscing")
+        };
+        for c in line.chars() {
+            surface.print(c);
+        }
+        surface.carriage_return();
+        surface.line_feed();
+    }
+
+    surface
+}
+
+fn measure(surface: &Surface) -> (Duration, usize) {
+    let mut regex = RegexSearch::new("needle").expect("valid pattern");
+    let start = Instant::now();
+    let mut matches = 0;
+    for _ in 0..ROUNDS {
+        matches = black_box(surface.find_all(&mut regex)).len();
+    }
+    (start.elapsed() / ROUNDS, matches)
+}
+
+fn main() {
+    let (naive, naive_matches) = measure(&filled_surface(false));
+    let (indexed, indexed_matches) = measure(&filled_surface(true));
+    assert_eq!(naive_matches, indexed_matches);
+
+    println!("scrollback lines: {HISTORY}, matches: {naive_matches}");
+    println!("naive   find_all: {naive:?}");
+    println!("indexed find_all: {indexed:?}");
+    println!(
+        "speedup: {:.1}x",
+        naive.as_secs_f64() / indexed.as_secs_f64().max(f64::EPSILON)
+    );
+}
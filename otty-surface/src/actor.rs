@@ -6,14 +6,19 @@
 
 use std::collections::VecDeque;
 
+use crate::block::SurfaceLayout;
+use crate::copy_mode::Motion;
 use crate::escape::{
-    CharacterAttribute, Charset, CharsetIndex, ClearMode, CursorShape,
-    CursorStyle, Hyperlink, KeyboardMode, KeyboardModeApplyBehavior,
-    LineClearMode, Mode, PrivateMode, Rgb, TabClearMode,
+    AttributeChangeExtent, CharacterAttribute, Charset, CharsetIndex,
+    ClearMode, CursorShape, CursorStyle, Hyperlink, ImagePlacement,
+    KeyboardMode, KeyboardModeApplyBehavior, LineClearMode, Mode, PrivateMode,
+    RectArea, RectAttrChange, Rgb, SemanticPromptMark, TabClearMode,
 };
 use crate::grid::{Dimensions, Scroll};
+use crate::hyperlink::HyperlinkSpan;
 use crate::index::Point;
-use crate::{SelectionType, Side};
+use crate::search::RegexSearch;
+use crate::{SelectionGranularity, SelectionType, Side};
 
 /// Consumer of semantic terminal actions.
 ///
@@ -29,6 +34,20 @@ pub trait SurfaceActor {
     /// Render a single Unicode scalar value at the current cursor position.
     fn print(&mut self, _: char) {}
 
+    /// Render a run of characters, exactly as if each was passed to
+    /// [`SurfaceActor::print`] in turn.
+    fn print_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.print(c);
+        }
+    }
+
+    /// Render a character of the right half of an 8-bit code table, given
+    /// as its ISO Latin-1 equivalent, through the set invoked into GR.
+    fn print_right_half(&mut self, c: char) {
+        self.print(c);
+    }
+
     /// Resize the underlying surface to the given dimensions.
     ///
     /// Implementations typically reflow scrollback and viewport content to
@@ -77,6 +96,13 @@ pub trait SurfaceActor {
     /// Clear the screen according to the provided clear mode.
     fn clear_screen(&mut self, _: ClearMode) {}
 
+    /// Empty the screen and scrollback and home the cursor, leaving modes
+    /// untouched.
+    fn clear_all(&mut self) {
+        self.clear_screen(ClearMode::Saved);
+        self.goto(0, 0);
+    }
+
     /// Clear the current line according to the provided clear mode.
     fn clear_line(&mut self, _: LineClearMode) {}
 
@@ -98,6 +124,9 @@ pub trait SurfaceActor {
     /// Select the active character set index (G0–G3).
     fn set_active_charset_index(&mut self, _: CharsetIndex) {}
 
+    /// Select the character set index (G0–G3) invoked into GR.
+    fn set_right_charset_index(&mut self, _: CharsetIndex) {}
+
     /// Configure the character set mapped at a given index.
     fn configure_charset(&mut self, _: Charset, _: CharsetIndex) {}
 
@@ -125,6 +154,37 @@ pub trait SurfaceActor {
     /// Set the hyperlink associated with subsequent printed cells.
     fn set_hyperlink(&mut self, _: Option<Hyperlink>) {}
 
+    /// Reserve a `columns` × `lines` region for an image at the cursor.
+    ///
+    /// The caller resolves the image size into cells; `placement` decides
+    /// the image id and where the cursor ends up.
+    fn place_image(
+        &mut self,
+        _placement: ImagePlacement,
+        _columns: usize,
+        _lines: usize,
+    ) {
+    }
+
+    /// Fill `area` with `c` in the current rendition (DECFRA).
+    fn fill_rect(&mut self, _area: RectArea, _c: char) {}
+
+    /// Change the attributes of the cells in `area` (DECCARA, DECRARA).
+    fn change_rect_attributes(
+        &mut self,
+        _area: RectArea,
+        _change: RectAttrChange,
+    ) {
+    }
+
+    /// Select which cells area attribute changes cover (DECSACE).
+    fn set_attribute_change_extent(&mut self, _: AttributeChangeExtent) {}
+
+    /// Track a semantic prompt mark (`OSC 133`).
+    ///
+    /// Lines take the role of the latest mark when text is written to them.
+    fn semantic_prompt(&mut self, _mark: SemanticPromptMark) {}
+
     /// Apply a single Select Graphic Rendition (SGR) attribute.
     fn sgr(&mut self, _: CharacterAttribute) {}
 
@@ -253,9 +313,65 @@ pub trait SurfaceActor {
     /// Init the selection range
     fn start_selection(&mut self, _: SelectionType, _: Point, _: Side) {}
 
+    /// Start a selection while keeping the active one, if it has the same
+    /// type, as an extra selection. Other selections are cleared.
+    ///
+    /// Surfaces without multi-range selections start a plain selection.
+    fn add_selection(&mut self, ty: SelectionType, point: Point, side: Side) {
+        self.start_selection(ty, point, side);
+    }
+
     /// Update the selection range
     fn update_selection(&mut self, _: Point, _: Side) {}
 
+    /// Extend the current selection to a point, snapping to `granularity`.
+    fn extend_selection(&mut self, _: Point, _: SelectionGranularity) {}
+
+    /// Drop the active selection together with any extra ones.
+    fn clear_selection(&mut self) {}
+
+    /// Enter copy mode with the copy cursor at the terminal cursor.
+    fn enter_copy_mode(&mut self) {}
+
+    /// Move the copy cursor, extending the selection if one is active.
+    fn copy_mode_motion(&mut self, _: Motion) {}
+
+    /// Start a selection at the copy cursor, or clear the active one.
+    fn toggle_copy_selection(&mut self, _: SelectionType) {}
+
+    /// Leave copy mode, returning the selected text if any.
+    fn exit_copy_mode(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Resolve the full OSC 8 hyperlink span covering the given point.
+    fn hyperlink_span_at(&self, _: Point) -> Option<HyperlinkSpan> {
+        None
+    }
+
+    /// Search: replace the active regex and drop the focused match.
+    fn set_search(&mut self, _: Option<RegexSearch>) {}
+
+    /// Search: focus the next match below the current one, wrapping around.
+    fn focus_next_match(&mut self) {}
+
+    /// Search: focus the previous match above the current one, wrapping
+    /// around.
+    fn focus_previous_match(&mut self) {}
+
+    /// Track window focus, reporting changes when focus reporting is on.
+    fn set_focus(
+        &mut self,
+        _focused: bool,
+        _report_channel: &mut VecDeque<u8>,
+    ) {
+    }
+
     /// Handle high‑level block lifecycle events coming from the parser.
     fn handle_block_event(&mut self, _: crate::escape::BlockEvent) {}
+
+    /// Choose how snapshots present the content.
+    ///
+    /// Surfaces without block support always export a plain grid.
+    fn set_layout(&mut self, _: SurfaceLayout) {}
 }
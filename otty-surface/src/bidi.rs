@@ -0,0 +1,182 @@
+//! Unicode BiDi annotation for snapshot rows.
+//!
+//! The stored grid is always kept in logical order. This module resolves
+//! embedding levels per cell so renderers can lay out right-to-left runs
+//! without the surface reordering its content.
+
+use unicode_bidi::{Level, ParagraphBidiInfo};
+
+use crate::cell::Flags;
+use crate::index::{Column, Line};
+use crate::snapshot::SnapshotCell;
+
+/// Resolved writing direction of a run of cells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Contiguous cells of a row sharing one resolved embedding level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BidiRun {
+    /// First column of the run (inclusive).
+    pub start: Column,
+    /// Last column of the run (inclusive).
+    pub end: Column,
+    /// Resolved Unicode BiDi embedding level.
+    pub level: u8,
+}
+
+impl BidiRun {
+    /// Writing direction implied by the embedding level.
+    pub fn direction(&self) -> TextDirection {
+        if self.level % 2 == 1 {
+            TextDirection::RightToLeft
+        } else {
+            TextDirection::LeftToRight
+        }
+    }
+}
+
+/// BiDi resolution for a single visible row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BidiRow {
+    /// Grid line the row belongs to.
+    pub line: Line,
+    /// Direction runs in logical (storage) order.
+    pub runs: Vec<BidiRun>,
+    visual_columns: Vec<Column>,
+}
+
+impl BidiRow {
+    /// Check whether the whole row is laid out left to right.
+    pub fn is_pure_ltr(&self) -> bool {
+        self.runs
+            .iter()
+            .all(|run| run.direction() == TextDirection::LeftToRight)
+    }
+
+    /// Map a logical column to the column it occupies on screen.
+    pub fn visual_column(&self, column: Column) -> Column {
+        self.visual_columns.get(column.0).copied().unwrap_or(column)
+    }
+
+    /// Resolve direction runs for the cells of one row.
+    pub(crate) fn resolve(line: Line, cells: &[SnapshotCell]) -> Self {
+        let mut text = String::with_capacity(cells.len());
+        let mut previous = ' ';
+        for indexed in cells {
+            // Spacers inherit the wide character so they share its level.
+            let c = if indexed.cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                previous
+            } else {
+                indexed.cell.c
+            };
+            text.push(c);
+            previous = c;
+        }
+
+        let info = ParagraphBidiInfo::new(&text, None);
+        let levels = info.reordered_levels_per_char(0..text.len());
+
+        let runs = collect_runs(&levels);
+
+        let mut visual_columns = vec![Column(0); levels.len()];
+        for (visual, logical) in ParagraphBidiInfo::reorder_visual(&levels)
+            .into_iter()
+            .enumerate()
+        {
+            visual_columns[logical] = Column(visual);
+        }
+
+        Self {
+            line,
+            runs,
+            visual_columns,
+        }
+    }
+}
+
+fn collect_runs(levels: &[Level]) -> Vec<BidiRun> {
+    let mut runs: Vec<BidiRun> = Vec::new();
+    for (column, level) in levels.iter().enumerate() {
+        let level = level.number();
+        match runs.last_mut() {
+            Some(run) if run.level == level => run.end = Column(column),
+            _ => runs.push(BidiRun {
+                start: Column(column),
+                end: Column(column),
+                level,
+            }),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+    use crate::index::Point;
+
+    fn row(text: &str) -> Vec<SnapshotCell> {
+        text.chars()
+            .enumerate()
+            .map(|(column, c)| SnapshotCell {
+                point: Point::new(Line(0), Column(column)),
+                cell: Cell {
+                    c,
+                    ..Cell::default()
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mixed_line_resolves_direction_runs() {
+        let row = BidiRow::resolve(Line(0), &row("abc אבג def"));
+
+        assert_eq!(
+            row.runs,
+            vec![
+                BidiRun {
+                    start: Column(0),
+                    end: Column(3),
+                    level: 0,
+                },
+                BidiRun {
+                    start: Column(4),
+                    end: Column(6),
+                    level: 1,
+                },
+                BidiRun {
+                    start: Column(7),
+                    end: Column(10),
+                    level: 0,
+                },
+            ]
+        );
+        assert_eq!(row.runs[1].direction(), TextDirection::RightToLeft);
+        assert!(!row.is_pure_ltr());
+    }
+
+    #[test]
+    fn rtl_run_is_mirrored_in_visual_columns() {
+        let row = BidiRow::resolve(Line(0), &row("abc אבג def"));
+
+        assert_eq!(row.visual_column(Column(0)), Column(0));
+        assert_eq!(row.visual_column(Column(4)), Column(6));
+        assert_eq!(row.visual_column(Column(6)), Column(4));
+        assert_eq!(row.visual_column(Column(8)), Column(8));
+    }
+
+    #[test]
+    fn ascii_line_is_pure_ltr() {
+        let row = BidiRow::resolve(Line(0), &row("plain text"));
+
+        assert!(row.is_pure_ltr());
+        assert_eq!(row.runs.len(), 1);
+        assert_eq!(row.visual_column(Column(3)), Column(3));
+    }
+}
@@ -4,9 +4,10 @@ use std::sync::Arc;
 use crate::cell::Cell;
 use crate::escape::{
     BlockKind as EscapeBlockKind, BlockMeta as EscapeBlockMeta, BlockPhase,
+    PromptKind, SemanticPromptMark,
 };
-use crate::grid::{Grid, Scroll};
-use crate::hyperlink::HyperlinkMap;
+use crate::grid::{Grid, LineKind, Scroll};
+use crate::hyperlink::{HyperlinkMap, HyperlinkSpan};
 use crate::index::{Column, Line, Point};
 use crate::selection::SelectionRange;
 use crate::snapshot::{
@@ -78,6 +79,17 @@ impl From<EscapeBlockMeta> for BlockMeta {
     }
 }
 
+/// How a [`BlockSurface`] presents its content.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SurfaceLayout {
+    /// A plain terminal grid with no block boundaries, as exported by a
+    /// bare [`Surface`].
+    #[default]
+    Grid,
+    /// Output split into prompt and command blocks at the shell marks.
+    Blocks,
+}
+
 /// Snapshot entry describing a block's extent within the viewport.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct BlockSnapshot {
@@ -96,6 +108,20 @@ pub struct BlockSnapshot {
     pub is_alt_screen: bool,
 }
 
+/// Output of a command block, collected while the command runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockOutput {
+    /// Lines the block gained since the previous report.
+    ///
+    /// Every line ends with a newline; soft-wrapped rows are joined.
+    Appended { block_id: String, text: String },
+    /// The block finished, no more output follows for it.
+    Finished {
+        block_id: String,
+        exit_code: Option<i32>,
+    },
+}
+
 /// In‑memory representation of a single block.
 struct Block {
     /// Metadata describing the block's identity and lifecycle.
@@ -104,6 +130,12 @@ struct Block {
     pub surface: Surface,
     /// Cached textual contents for finished blocks.
     pub cached_text: Option<Arc<str>>,
+    /// Absolute number of the first line not reported as output yet, or
+    /// `None` when the block does not stream.
+    ///
+    /// Lines are numbered from the first line the surface ever held, see
+    /// [`Grid::dropped_history`], so the count survives history rotation.
+    streamed_lines: Option<usize>,
 }
 
 impl Block {
@@ -118,7 +150,94 @@ impl Block {
             meta,
             surface: Surface::new(config.clone(), dimensions),
             cached_text: None,
+            streamed_lines: None,
+        }
+    }
+
+    /// Stream the output written from the cursor line on.
+    fn start_output_stream(&mut self) {
+        let grid = self.surface.grid();
+        let cursor_line = grid.cursor.point.line.0.max(0) as usize;
+        let top = grid.dropped_history() + grid.history_size();
+        self.streamed_lines = Some(top + cursor_line);
+    }
+
+    /// Take the output lines written since the previous call.
+    ///
+    /// Lines are taken once the cursor leaves their logical line, since
+    /// the cursor line and the rows wrapping into it may still be
+    /// rewritten, and all remaining lines once the block is finished.
+    /// Lines that rotated out of the scrollback before they were taken are
+    /// lost.
+    fn take_output(&mut self) -> Option<String> {
+        let streamed = self.streamed_lines?;
+        let grid = self.surface.grid();
+        let history = grid.history_size();
+        let columns = grid.columns();
+        let top = grid.dropped_history() + history;
+        let end = if self.meta.is_finished {
+            top + BlockSurface::viewport_content_bounds(grid).1
+        } else {
+            top.saturating_add_signed(Self::logical_line_start(grid) as isize)
+        };
+        let start = streamed.max(top - history);
+        if end <= start || columns == 0 {
+            return None;
+        }
+
+        let mut lines = Vec::new();
+        let mut buffer = String::new();
+        let mut buffer_start = start;
+        for line in start..end {
+            let row = &grid[Line(line as i32 - top as i32)];
+            for column in 0..columns {
+                let cell = &row[Column(column)];
+                if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    buffer.push(cell.c);
+                }
+            }
+            if !row[Column(columns - 1)].flags.contains(Flags::WRAPLINE) {
+                lines.push(buffer.trim_end_matches(' ').to_string());
+                buffer.clear();
+                buffer_start = line + 1;
+            }
+        }
+
+        // A wrapped line still running is only complete once the block is.
+        if self.meta.is_finished {
+            if !buffer.is_empty() {
+                lines.push(buffer.trim_end_matches(' ').to_string());
+            }
+            while lines.last().is_some_and(String::is_empty) {
+                lines.pop();
+            }
+            buffer_start = end;
+        }
+
+        self.streamed_lines = Some(buffer_start);
+        if lines.is_empty() {
+            return None;
         }
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+        Some(text)
+    }
+
+    /// Viewport line where the logical line holding the cursor starts,
+    /// following soft wraps up into the history.
+    fn logical_line_start(grid: &Grid<Cell>) -> i32 {
+        let last_column = Column(grid.columns().saturating_sub(1));
+        let topmost = -(grid.history_size() as i32);
+        let mut line = grid.cursor.point.line.0;
+        while line > topmost
+            && grid[Line(line - 1)][last_column]
+                .flags
+                .contains(Flags::WRAPLINE)
+        {
+            line -= 1;
+        }
+        line
     }
 
     fn update_cached_text(&mut self) {
@@ -246,6 +365,8 @@ pub struct BlockSurface {
     selection_anchor: Option<GlobalPoint>,
     /// Selection that spans multiple blocks in global coordinates.
     global_selection: Option<GlobalSelection>,
+    /// Presentation exported in snapshots.
+    layout: SurfaceLayout,
 }
 
 impl Dimensions for BlockSurface {
@@ -294,14 +415,25 @@ impl BlockSurface {
             selection_anchor: None,
             selection_block: None,
             global_selection: None,
+            layout: SurfaceLayout::Blocks,
         }
     }
 
+    /// Return how snapshots present the content.
+    pub fn layout(&self) -> SurfaceLayout {
+        self.layout
+    }
+
     /// Return the index of the last block, clamping to zero if no blocks exist.
     fn last_block_idx(&self) -> usize {
         self.blocks.len().saturating_sub(1)
     }
 
+    /// Return a reference to the active block.
+    fn active_block(&self) -> &Block {
+        &self.blocks[self.last_block_idx()]
+    }
+
     /// Return a mutable reference to the active block.
     fn active_block_mut(&mut self) -> &mut Block {
         let idx = self.last_block_idx();
@@ -489,15 +621,18 @@ impl BlockSurface {
         }
 
         // A new block is created with the current surface dimensions.
-        let size = {
+        let (size, is_focused) = {
             let surface = &self.blocks[self.last_block_idx()].surface;
-            BlockDimensions {
+            let size = BlockDimensions {
                 columns: surface.columns(),
                 screen_lines: surface.screen_lines(),
-            }
+            };
+            (size, surface.is_focused)
         };
 
-        self.blocks.push(Block::new(&self.config, &size, meta));
+        let mut block = Block::new(&self.config, &size, meta);
+        block.surface.is_focused = is_focused;
+        self.blocks.push(block);
 
         self.enforce_max_blocks();
         self.calculate_display_offset();
@@ -552,6 +687,208 @@ impl BlockSurface {
         snapshot
     }
 
+    /// Stitch the visible lines of all blocks into a single viewport.
+    fn merged_snapshot(&mut self) -> SnapshotOwned {
+        if self.blocks.is_empty() {
+            return SnapshotOwned::default();
+        }
+
+        if self.is_alt_screen_active() {
+            return self.snapshot_active_alt_screen_block();
+        }
+
+        self.calculate_display_offset();
+
+        let columns = self.columns();
+        let viewport_lines = self.screen_lines();
+        let slices = self.block_slices();
+        let context = self.viewport_context(&slices);
+        let start = context.start;
+        let viewport_end = context.viewport_end;
+
+        let mut cells =
+            Vec::with_capacity(columns.saturating_mul(viewport_lines));
+        let mut line_kinds = vec![LineKind::Output; viewport_lines];
+        let bottom_padding = context.bottom_padding;
+        let effective_start = context.effective_start;
+
+        let mut slice_idx = 0;
+        let mut current_slice = slices.get(slice_idx);
+        for (row, line_kind) in line_kinds.iter_mut().enumerate() {
+            let global_index = effective_start + row as isize;
+            if global_index < 0
+                || global_index as usize >= context.content_lines
+                || current_slice.is_none()
+            {
+                Self::push_blank_row(
+                    &mut cells,
+                    columns,
+                    row,
+                    self.display_offset,
+                );
+                continue;
+            }
+
+            let global_index = global_index as usize;
+            while let Some(slice) = current_slice {
+                if global_index < slice.end {
+                    break;
+                }
+                slice_idx += 1;
+                current_slice = slices.get(slice_idx);
+            }
+
+            let Some(slice) = current_slice else {
+                Self::push_blank_row(
+                    &mut cells,
+                    columns,
+                    row,
+                    self.display_offset,
+                );
+                continue;
+            };
+
+            if global_index < slice.start {
+                Self::push_blank_row(
+                    &mut cells,
+                    columns,
+                    row,
+                    self.display_offset,
+                );
+                continue;
+            }
+
+            let block_index = slice.index;
+            let block = &self.blocks[block_index];
+            let grid = block.surface.grid();
+            let local_index = global_index - slice.start;
+            let line = slice.top_line + local_index;
+            *line_kind = grid[line].kind;
+
+            for col in 0..columns {
+                let column = Column(col);
+                let cell = grid[line][column].clone();
+                let point_line = row as i32 - self.display_offset as i32;
+                let point = Point::new(Line(point_line), column);
+                cells.push(SnapshotCell { point, cell });
+            }
+        }
+
+        let idx = self.last_block_idx();
+        let active_block = &self.blocks[idx].surface;
+        let mut cursor = CursorSnapshot::new(active_block);
+        let active_slice = slices.iter().find(|slice| slice.index == idx);
+        if let Some(slice) = active_slice {
+            if let Some(point) =
+                self.convert_point_to_view(slice, cursor.point, start)
+            {
+                cursor.point = point;
+            }
+        }
+
+        let mut search_match = active_slice.and_then(|slice| {
+            let focused = active_block.search_match()?;
+            let first =
+                self.convert_point_to_view(slice, *focused.start(), start)?;
+            let last =
+                self.convert_point_to_view(slice, *focused.end(), start)?;
+            Some(first..=last)
+        });
+
+        let mut selection = None;
+        if let Some(index) =
+            self.selection_block.filter(|&idx| idx < self.blocks.len())
+        {
+            if let Some(slice) =
+                slices.iter().find(|slice| slice.index == index)
+            {
+                if let Some(range) = self.blocks[index]
+                    .surface
+                    .selection
+                    .as_ref()
+                    .and_then(|s| s.to_range(&self.blocks[index].surface))
+                {
+                    selection =
+                        self.convert_selection_to_view(range, slice, start);
+                }
+            }
+        }
+
+        let size = SnapshotSize {
+            columns,
+            screen_lines: viewport_lines,
+            total_lines: self.total_lines(),
+        };
+        let visible_cell_count = size.columns * size.screen_lines;
+
+        let hyperlinks = HyperlinkMap::build(&cells, size, self.display_offset);
+
+        if selection.is_none() {
+            if let Some(global_selection) = &self.global_selection {
+                selection = self.global_selection_to_view(
+                    global_selection,
+                    &context,
+                    viewport_lines,
+                );
+            }
+        }
+
+        let padding_adjustment = bottom_padding as i32;
+        if padding_adjustment > 0 {
+            cursor.point.line += padding_adjustment;
+            if let Some(range) = selection.as_mut() {
+                range.start.line += padding_adjustment;
+                range.end.line += padding_adjustment;
+            }
+            if let Some(focused) = search_match.as_mut() {
+                let mut first = *focused.start();
+                let mut last = *focused.end();
+                first.line += padding_adjustment;
+                last.line += padding_adjustment;
+                *focused = first..=last;
+            }
+        }
+
+        let mut block_snapshots = Vec::with_capacity(self.blocks.len());
+        for slice in &slices {
+            let block = &self.blocks[slice.index];
+            let block_start = slice.start;
+            let block_end = slice.end;
+            let visible_start = max(block_start, start);
+            let visible_end = min(block_end, viewport_end);
+            let line_count = visible_end.saturating_sub(visible_start);
+            let line_offset = visible_start.saturating_sub(start);
+            let start_line = line_offset as i32 - self.display_offset as i32
+                + padding_adjustment;
+
+            block_snapshots.push(BlockSnapshot {
+                meta: block.meta.clone(),
+                start_line,
+                line_count,
+                cached_text: block.cached_text.clone(),
+                is_alt_screen: block.meta.is_alt_screen,
+            });
+        }
+
+        let mut snapshot = SnapshotOwned::from_parts(
+            cells,
+            selection,
+            search_match,
+            hyperlinks,
+            cursor,
+            self.display_offset,
+            *active_block.colors(),
+            *active_block.mode(),
+            active_block.is_focused,
+            size,
+            SnapshotDamage::Full,
+            visible_cell_count,
+            block_snapshots,
+        );
+        snapshot.line_kinds = line_kinds;
+        snapshot
+    }
+
     /// Return the number of visible lines from a block, including trimmed
     /// viewport content and scrollback.
     fn block_visible_line_count(block: &Block) -> usize {
@@ -805,6 +1142,16 @@ impl SurfaceActor for BlockSurface {
         self.active_block_mut().surface.print(c);
     }
 
+    /// Write a run of printable characters into the active block surface.
+    fn print_str(&mut self, text: &str) {
+        self.active_block_mut().surface.print_str(text);
+    }
+
+    /// Write a right-half 8-bit character into the active block surface.
+    fn print_right_half(&mut self, c: char) {
+        self.active_block_mut().surface.print_right_half(c);
+    }
+
     /// Resize every block surface so they stay aligned when the viewport changes.
     fn resize<S: Dimensions>(&mut self, size: S) {
         let columns = size.columns();
@@ -885,6 +1232,15 @@ impl SurfaceActor for BlockSurface {
         self.active_block_mut().surface.clear_screen(mode);
     }
 
+    /// Drop every earlier block and clear the active one.
+    fn clear_all(&mut self) {
+        while self.blocks.len() > 1 {
+            self.remove_block_at(0);
+        }
+        self.active_block_mut().surface.clear_all();
+        self.display_offset = 0;
+    }
+
     /// Clear part of the current line in the active block.
     fn clear_line(&mut self, mode: crate::escape::LineClearMode) {
         self.active_block_mut().surface.clear_line(mode);
@@ -922,6 +1278,13 @@ impl SurfaceActor for BlockSurface {
             .set_active_charset_index(index);
     }
 
+    /// Select which charset slot maps the right half of the code table.
+    fn set_right_charset_index(&mut self, index: crate::escape::CharsetIndex) {
+        self.active_block_mut()
+            .surface
+            .set_right_charset_index(index);
+    }
+
     /// Configure a charset mapping for the given slot on the active surface.
     fn configure_charset(
         &mut self,
@@ -992,6 +1355,49 @@ impl SurfaceActor for BlockSurface {
         self.active_block_mut().surface.set_hyperlink(link);
     }
 
+    /// Reserve the cells of an image in the active block.
+    fn place_image(
+        &mut self,
+        placement: crate::escape::ImagePlacement,
+        columns: usize,
+        lines: usize,
+    ) {
+        self.active_block_mut()
+            .surface
+            .place_image(placement, columns, lines);
+    }
+
+    /// Fill a rectangle of the active block.
+    fn fill_rect(&mut self, area: crate::escape::RectArea, c: char) {
+        self.active_block_mut().surface.fill_rect(area, c);
+    }
+
+    /// Change the attributes of a rectangle of the active block.
+    fn change_rect_attributes(
+        &mut self,
+        area: crate::escape::RectArea,
+        change: crate::escape::RectAttrChange,
+    ) {
+        self.active_block_mut()
+            .surface
+            .change_rect_attributes(area, change);
+    }
+
+    /// Select the extent of area attribute changes for the active block.
+    fn set_attribute_change_extent(
+        &mut self,
+        extent: crate::escape::AttributeChangeExtent,
+    ) {
+        self.active_block_mut()
+            .surface
+            .set_attribute_change_extent(extent);
+    }
+
+    /// Track a semantic prompt mark on the active surface.
+    fn semantic_prompt(&mut self, mark: SemanticPromptMark) {
+        self.active_block_mut().surface.semantic_prompt(mark);
+    }
+
     /// Apply an SGR attribute to the active surface.
     fn sgr(&mut self, attr: crate::escape::CharacterAttribute) {
         self.active_block_mut().surface.sgr(attr);
@@ -1124,6 +1530,50 @@ impl SurfaceActor for BlockSurface {
         self.active_block_mut().surface.unset_private_mode(mode);
     }
 
+    /// Enter copy mode on the active surface.
+    fn enter_copy_mode(&mut self) {
+        self.active_block_mut().surface.enter_copy_mode();
+    }
+
+    fn copy_mode_motion(&mut self, motion: crate::Motion) {
+        self.active_block_mut().surface.copy_mode_motion(motion);
+    }
+
+    fn toggle_copy_selection(&mut self, ty: crate::SelectionType) {
+        self.active_block_mut().surface.toggle_copy_selection(ty);
+    }
+
+    fn exit_copy_mode(&mut self) -> Option<String> {
+        self.active_block_mut().surface.exit_copy_mode()
+    }
+
+    fn hyperlink_span_at(&self, point: Point) -> Option<HyperlinkSpan> {
+        self.active_block().surface.hyperlink_span_at(point)
+    }
+
+    fn set_search(&mut self, search: Option<crate::RegexSearch>) {
+        self.active_block_mut().surface.set_search(search);
+    }
+
+    fn focus_next_match(&mut self) {
+        self.active_block_mut().surface.focus_next_match();
+    }
+
+    fn focus_previous_match(&mut self) {
+        self.active_block_mut().surface.focus_previous_match();
+    }
+
+    /// Track focus on the active surface and report transitions.
+    fn set_focus(
+        &mut self,
+        focused: bool,
+        report_channel: &mut std::collections::VecDeque<u8>,
+    ) {
+        self.active_block_mut()
+            .surface
+            .set_focus(focused, report_channel);
+    }
+
     /// Report a DEC private mode through the response channel.
     fn report_private_mode(
         &mut self,
@@ -1262,6 +1712,48 @@ impl SurfaceActor for BlockSurface {
         }
     }
 
+    /// Extend a selection inside its block with snapping; selections that
+    /// span blocks extend cell by cell.
+    fn extend_selection(
+        &mut self,
+        point: Point,
+        granularity: crate::SelectionGranularity,
+    ) {
+        let slices = self.block_slices();
+        let context = self.viewport_context(&slices);
+        let resolved = self.resolve_block_point_with(&slices, &context, point);
+
+        if let (Some((index, local_point, _)), Some(block_index), None) =
+            (resolved, self.selection_block, &self.global_selection)
+            && index == block_index
+        {
+            if let Some(block) = self.blocks.get_mut(index) {
+                block.surface.extend_selection(local_point, granularity);
+            }
+            return;
+        }
+
+        self.update_selection(point, crate::Side::Right);
+    }
+
+    /// Drop the selection of every block and any selection spanning them.
+    fn clear_selection(&mut self) {
+        self.global_selection = None;
+        self.selection_block = None;
+        self.selection_anchor = None;
+        for block in &mut self.blocks {
+            block.surface.clear_selection();
+        }
+    }
+
+    /// Switch between the block and plain grid layouts.
+    fn set_layout(&mut self, layout: SurfaceLayout) {
+        if self.layout != layout {
+            self.layout = layout;
+            self.mark_fully_damaged();
+        }
+    }
+
     /// React to prompt/command lifecycle events emitted by the parser.
     fn handle_block_event(&mut self, event: crate::escape::BlockEvent) {
         let escape_meta = event.meta;
@@ -1286,6 +1778,11 @@ impl SurfaceActor for BlockSurface {
                 } else {
                     self.begin_block(meta);
                 }
+                let block = self.active_block_mut();
+                block
+                    .surface
+                    .semantic_prompt(SemanticPromptMark::OutputStart);
+                block.start_output_stream();
             },
             BlockPhase::Exit => {
                 self.end_block_by_id(&meta);
@@ -1306,6 +1803,11 @@ impl SurfaceActor for BlockSurface {
                 }
 
                 self.begin_block(meta);
+                self.active_block_mut().surface.semantic_prompt(
+                    SemanticPromptMark::PromptStart {
+                        kind: PromptKind::Initial,
+                    },
+                );
             },
         }
     }
@@ -1313,193 +1815,67 @@ impl SurfaceActor for BlockSurface {
 
 impl SurfaceModel for BlockSurface {
     /// Build a full snapshot of all blocks merged into a single viewport.
+    ///
+    /// In the grid layout the block metadata is left out. Blocks are still
+    /// recorded at the shell marks, so switching back to the block layout
+    /// segments all captured output again.
     fn snapshot_owned(&mut self) -> SnapshotOwned {
-        if self.blocks.is_empty() {
-            return SnapshotOwned::default();
+        let mut snapshot = self.merged_snapshot();
+        if self.layout == SurfaceLayout::Grid {
+            snapshot.blocks.clear();
         }
+        snapshot.layout = self.layout;
+        snapshot
+    }
 
-        if self.is_alt_screen_active() {
-            return self.snapshot_active_alt_screen_block();
-        }
-
-        self.calculate_display_offset();
-
-        let columns = self.columns();
-        let viewport_lines = self.screen_lines();
-        let slices = self.block_slices();
-        let context = self.viewport_context(&slices);
-        let start = context.start;
-        let viewport_end = context.viewport_end;
-
-        let mut cells =
-            Vec::with_capacity(columns.saturating_mul(viewport_lines));
-        let bottom_padding = context.bottom_padding;
-        let effective_start = context.effective_start;
-
-        let mut slice_idx = 0;
-        let mut current_slice = slices.get(slice_idx);
-        for row in 0..viewport_lines {
-            let global_index = effective_start + row as isize;
-            if global_index < 0
-                || global_index as usize >= context.content_lines
-                || current_slice.is_none()
-            {
-                Self::push_blank_row(
-                    &mut cells,
-                    columns,
-                    row,
-                    self.display_offset,
-                );
-                continue;
-            }
+    /// Propagate damage reset to the active block surface.
+    fn reset_damage(&mut self) {
+        self.active_block_mut().surface.reset_damage();
+    }
 
-            let global_index = global_index as usize;
-            while let Some(slice) = current_slice {
-                if global_index < slice.end {
-                    break;
-                }
-                slice_idx += 1;
-                current_slice = slices.get(slice_idx);
-            }
+    /// Propagate full damage to the active block surface.
+    fn mark_fully_damaged(&mut self) {
+        self.active_block_mut().surface.mark_fully_damaged();
+    }
 
-            let Some(slice) = current_slice else {
-                Self::push_blank_row(
-                    &mut cells,
-                    columns,
-                    row,
-                    self.display_offset,
-                );
-                continue;
-            };
+    /// Sum the memory held by every retained block surface.
+    fn memory_usage(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.surface.memory_usage())
+            .sum()
+    }
 
-            if global_index < slice.start {
-                Self::push_blank_row(
-                    &mut cells,
-                    columns,
-                    row,
-                    self.display_offset,
-                );
+    /// Report the output of running commands, from their `Preexec` mark to
+    /// the end of the block.
+    fn take_block_output(&mut self) -> Vec<BlockOutput> {
+        let mut output = Vec::new();
+        for block in &mut self.blocks {
+            if block.streamed_lines.is_none() {
                 continue;
             }
 
-            let block_index = slice.index;
-            let block = &self.blocks[block_index];
-            let grid = block.surface.grid();
-            let local_index = global_index - slice.start;
-            let line = slice.top_line + local_index;
-
-            for col in 0..columns {
-                let column = Column(col);
-                let cell = grid[line][column].clone();
-                let point_line = row as i32 - self.display_offset as i32;
-                let point = Point::new(Line(point_line), column);
-                cells.push(SnapshotCell { point, cell });
-            }
-        }
-
-        let idx = self.last_block_idx();
-        let active_block = &self.blocks[idx].surface;
-        let mut cursor = CursorSnapshot::new(active_block);
-        let active_slice = slices.iter().find(|slice| slice.index == idx);
-        if let Some(slice) = active_slice {
-            if let Some(point) =
-                self.convert_point_to_view(slice, cursor.point, start)
-            {
-                cursor.point = point;
-            }
-        }
-
-        let mut selection = None;
-        if let Some(index) =
-            self.selection_block.filter(|&idx| idx < self.blocks.len())
-        {
-            if let Some(slice) =
-                slices.iter().find(|slice| slice.index == index)
-            {
-                if let Some(range) = self.blocks[index]
-                    .surface
-                    .selection
-                    .as_ref()
-                    .and_then(|s| s.to_range(&self.blocks[index].surface))
-                {
-                    selection =
-                        self.convert_selection_to_view(range, slice, start);
-                }
-            }
-        }
-
-        let size = SnapshotSize {
-            columns,
-            screen_lines: viewport_lines,
-            total_lines: self.total_lines(),
-        };
-        let visible_cell_count = size.columns * size.screen_lines;
-
-        let hyperlinks = HyperlinkMap::build(&cells, size, self.display_offset);
-
-        if selection.is_none() {
-            if let Some(global_selection) = &self.global_selection {
-                selection = self.global_selection_to_view(
-                    global_selection,
-                    &context,
-                    viewport_lines,
-                );
+            if let Some(text) = block.take_output() {
+                output.push(BlockOutput::Appended {
+                    block_id: block.meta.id.clone(),
+                    text,
+                });
             }
-        }
-
-        let padding_adjustment = bottom_padding as i32;
-        if padding_adjustment > 0 {
-            cursor.point.line += padding_adjustment;
-            if let Some(range) = selection.as_mut() {
-                range.start.line += padding_adjustment;
-                range.end.line += padding_adjustment;
+            if block.meta.is_finished {
+                block.streamed_lines = None;
+                output.push(BlockOutput::Finished {
+                    block_id: block.meta.id.clone(),
+                    exit_code: block.meta.exit_code,
+                });
             }
         }
-
-        let mut block_snapshots = Vec::with_capacity(self.blocks.len());
-        for slice in &slices {
-            let block = &self.blocks[slice.index];
-            let block_start = slice.start;
-            let block_end = slice.end;
-            let visible_start = max(block_start, start);
-            let visible_end = min(block_end, viewport_end);
-            let line_count = visible_end.saturating_sub(visible_start);
-            let line_offset = visible_start.saturating_sub(start);
-            let start_line = line_offset as i32 - self.display_offset as i32
-                + padding_adjustment;
-
-            block_snapshots.push(BlockSnapshot {
-                meta: block.meta.clone(),
-                start_line,
-                line_count,
-                cached_text: block.cached_text.clone(),
-                is_alt_screen: block.meta.is_alt_screen,
-            });
-        }
-
-        SnapshotOwned::from_parts(
-            cells,
-            selection,
-            hyperlinks,
-            cursor,
-            self.display_offset,
-            *active_block.colors(),
-            *active_block.mode(),
-            size,
-            SnapshotDamage::Full,
-            visible_cell_count,
-            block_snapshots,
-        )
-    }
-
-    /// Propagate damage reset to the active block surface.
-    fn reset_damage(&mut self) {
-        self.active_block_mut().surface.reset_damage();
+        output
     }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::escape::BlockEvent;
     use crate::snapshot::SurfaceModel;
     use crate::{Dimensions, Line, SurfaceConfig};
 
@@ -1560,6 +1936,42 @@ mod tests {
         assert!(view.cells.iter().any(|cell| cell.cell.c == 'X'));
     }
 
+    #[test]
+    fn grid_layout_hides_blocks_and_keeps_their_content() {
+        let dims = TestDimensions::new(6, 4);
+        let mut surface = BlockSurface::new(SurfaceConfig::default(), &dims);
+        let command = |id: &str| BlockMeta {
+            id: id.into(),
+            ..BlockMeta::default()
+        };
+        surface.print_str("$ ls");
+        surface.begin_block(command("1"));
+        surface.print_str("out");
+        let block_snapshot = surface.snapshot_owned();
+        assert_eq!(block_snapshot.view().layout, SurfaceLayout::Blocks);
+        assert_eq!(block_snapshot.view().blocks().len(), 2);
+
+        surface.set_layout(SurfaceLayout::Grid);
+        let grid_snapshot = surface.snapshot_owned();
+        let grid_view = grid_snapshot.view();
+        assert_eq!(grid_view.layout, SurfaceLayout::Grid);
+        assert!(grid_view.blocks().is_empty());
+        assert_eq!(grid_view.cells, block_snapshot.view().cells);
+
+        // Output captured in the grid layout is still split at the marks.
+        surface.begin_block(command("2"));
+        surface.print_str("more");
+        surface.set_layout(SurfaceLayout::Blocks);
+        let snapshot = surface.snapshot_owned();
+        let ids: Vec<_> = snapshot
+            .view()
+            .blocks()
+            .iter()
+            .map(|block| block.meta.id.clone())
+            .collect();
+        assert_eq!(ids, ["", "1", "2"]);
+    }
+
     #[test]
     fn block_surface_begin_block_creates_new_active_block() {
         let dims = TestDimensions::new(4, 2);
@@ -1678,6 +2090,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn clear_all_drops_earlier_blocks_and_keeps_the_active_one() {
+        let dims = TestDimensions::new(4, 2);
+        let mut surface = BlockSurface::new(SurfaceConfig::default(), &dims);
+
+        for i in 0..3 {
+            surface.begin_block(BlockMeta {
+                id: format!("{i}"),
+                kind: BlockKind::Command,
+                ..BlockMeta::default()
+            });
+            surface.print('A');
+            surface.new_line();
+            surface.print('B');
+        }
+        surface.clear_all();
+
+        assert_eq!(surface.blocks.len(), 1);
+        assert_eq!(surface.blocks[0].meta.id, "2");
+        assert_eq!(surface.display_offset, 0);
+        let active = &surface.blocks[0].surface;
+        assert_eq!(active.grid().history_size(), 0);
+        assert_eq!(active.grid().cursor.point, Point::new(Line(0), Column(0)));
+        assert!(
+            surface
+                .snapshot_owned()
+                .view()
+                .cells
+                .iter()
+                .all(|cell| cell.cell.c == ' ')
+        );
+    }
+
     #[test]
     fn snapshot_includes_multiple_blocks() {
         use crate::grid::Scroll;
@@ -1885,4 +2330,142 @@ mod tests {
 
         assert_eq!(snapshot.block_text("block-1"), Some(String::from("A\nB")));
     }
+
+    fn block_event(phase: BlockPhase, exit_code: Option<i32>) -> BlockEvent {
+        BlockEvent {
+            phase,
+            meta: EscapeBlockMeta {
+                id: String::from("cmd-1"),
+                kind: EscapeBlockKind::Command,
+                cmd: Some(String::from("make")),
+                cwd: None,
+                started_at: None,
+                finished_at: None,
+                exit_code,
+                shell: None,
+                is_alt_screen: false,
+            },
+        }
+    }
+
+    fn print_text(surface: &mut BlockSurface, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    surface.carriage_return();
+                    surface.line_feed();
+                },
+                c => surface.print(c),
+            }
+        }
+    }
+
+    #[test]
+    fn command_output_streams_line_by_line_until_exit() {
+        let dims = TestDimensions::new(6, 3);
+        let mut surface = BlockSurface::new(SurfaceConfig::default(), &dims);
+        print_text(&mut surface, "$ make\n");
+        surface.handle_block_event(block_event(BlockPhase::Preexec, None));
+        assert!(surface.take_block_output().is_empty());
+
+        let appended = |text: &str| BlockOutput::Appended {
+            block_id: String::from("cmd-1"),
+            text: String::from(text),
+        };
+
+        print_text(&mut surface, "one\ntw");
+        assert_eq!(surface.take_block_output(), [appended("one\n")]);
+
+        // The cursor line is still being written.
+        print_text(&mut surface, "o");
+        assert!(surface.take_block_output().is_empty());
+
+        print_text(&mut surface, "\nwrapped\nfour");
+        assert_eq!(surface.take_block_output(), [appended("two\nwrapped\n")]);
+
+        surface.handle_block_event(block_event(BlockPhase::Exit, Some(2)));
+        assert_eq!(
+            surface.take_block_output(),
+            [
+                appended("four\n"),
+                BlockOutput::Finished {
+                    block_id: String::from("cmd-1"),
+                    exit_code: Some(2),
+                },
+            ]
+        );
+        assert!(surface.take_block_output().is_empty());
+    }
+
+    #[test]
+    fn wrapped_output_line_is_streamed_once_complete() {
+        let dims = TestDimensions::new(4, 3);
+        let mut surface = BlockSurface::new(SurfaceConfig::default(), &dims);
+        print_text(&mut surface, "$\n");
+        surface.handle_block_event(block_event(BlockPhase::Preexec, None));
+
+        print_text(&mut surface, "abcdef");
+        assert!(surface.take_block_output().is_empty());
+
+        print_text(&mut surface, "gh\nij");
+        assert_eq!(
+            surface.take_block_output(),
+            [BlockOutput::Appended {
+                block_id: String::from("cmd-1"),
+                text: String::from("abcdefgh\n"),
+            }]
+        );
+    }
+
+    #[test]
+    fn output_longer_than_the_scrollback_keeps_streaming() {
+        let dims = TestDimensions::new(6, 3);
+        let config = SurfaceConfig {
+            scrolling_history: 2,
+            ..SurfaceConfig::default()
+        };
+        let mut surface = BlockSurface::new(config, &dims);
+        print_text(&mut surface, "$\n");
+        surface.handle_block_event(block_event(BlockPhase::Preexec, None));
+
+        let mut streamed = String::new();
+        for line in 0..10 {
+            print_text(&mut surface, &format!("line{line}\n"));
+            for output in surface.take_block_output() {
+                if let BlockOutput::Appended { text, .. } = output {
+                    streamed.push_str(&text);
+                }
+            }
+        }
+
+        let expected: String =
+            (0..10).map(|line| format!("line{line}\n")).collect();
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn block_phases_assign_prompt_and_output_line_kinds() {
+        let dims = TestDimensions::new(10, 4);
+        let mut surface = BlockSurface::new(SurfaceConfig::default(), &dims);
+        surface.handle_block_event(block_event(BlockPhase::Precmd, None));
+        print_text(&mut surface, "$ make\n");
+        surface.handle_block_event(block_event(BlockPhase::Preexec, None));
+        print_text(&mut surface, "built\ndone");
+
+        let frame = surface.snapshot_owned();
+        let view = frame.view();
+        let block = view
+            .blocks()
+            .iter()
+            .find(|block| block.meta.id == "cmd-1")
+            .expect("command block");
+        assert_eq!(block.line_count, 3);
+        let kinds: Vec<_> = (0..3)
+            .map(|line| view.line_kind(Line(block.start_line + line)))
+            .collect();
+        assert_eq!(
+            kinds,
+            [LineKind::Prompt, LineKind::Output, LineKind::Output]
+        );
+    }
 }
@@ -221,11 +221,13 @@ mod tests {
         SnapshotOwned::from_parts(
             cells,
             None::<SelectionRange>,
+            None,
             HyperlinkMap::default(),
             CursorSnapshot::default(),
             0,
             Colors::default(),
             SurfaceMode::default(),
+            true,
             SnapshotSize {
                 columns: 80,
                 screen_lines: 2,
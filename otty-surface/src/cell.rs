@@ -5,13 +5,14 @@ use bitflags::bitflags;
 
 use crate::escape::{Color, Hyperlink as VTHyperlink, StdColor};
 use crate::grid::{GridCell, Row};
+use crate::image::ImageCell;
 use crate::index::Column;
 
 bitflags! {
     /// Per‑cell attribute flags used by the surface grid.
     ///
     /// These flags encode visual attributes (bold, italic, underline,
-    /// strikeout), layout information (line wrapping, wide characters and
+    /// strikeout, overline), layout information (line wrapping, wide characters and
     /// their spacers) and other state that affects rendering.
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct Flags: u16 {
@@ -32,6 +33,7 @@ bitflags! {
         const UNDERCURL                 = 0b0001_0000_0000_0000;
         const DOTTED_UNDERLINE          = 0b0010_0000_0000_0000;
         const DASHED_UNDERLINE          = 0b0100_0000_0000_0000;
+        const OVERLINE                  = 0b1000_0000_0000_0000;
         const ALL_UNDERLINES            = Self::UNDERLINE.bits() | Self::DOUBLE_UNDERLINE.bits()
                                         | Self::UNDERCURL.bits() | Self::DOTTED_UNDERLINE.bits()
                                         | Self::DASHED_UNDERLINE.bits();
@@ -44,9 +46,11 @@ static HYPERLINK_ID_SUFFIX: AtomicU32 = AtomicU32::new(0);
 /// Hyperlink associated with a cell.
 ///
 /// The surface stores hyperlinks separately from the raw escape layer and
-/// provides cheap cloning via [`Arc`]. A unique identifier is generated for
-/// links without an explicit ID so that backends can distinguish different
-/// links pointing to the same URI.
+/// provides cheap cloning via [`Arc`]. Every cell printed while a link is
+/// active shares the same allocation, which is released once the last cell
+/// referencing it is overwritten or cleared. A unique identifier is generated
+/// for links without an explicit ID so that backends can distinguish
+/// different links pointing to the same URI.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Hyperlink {
     inner: Arc<HyperlinkInner>,
@@ -65,6 +69,24 @@ impl Hyperlink {
     pub fn uri(&self) -> &str {
         &self.inner.uri
     }
+
+    /// Number of live references to this link.
+    #[cfg(test)]
+    pub(crate) fn ref_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    /// Check whether adjacent cells carrying `other` belong to this link.
+    ///
+    /// Links with an explicit OSC 8 id match by id and URI, id-less links
+    /// only match other id-less links with an identical URI.
+    pub(crate) fn is_same_link(&self, other: &Hyperlink) -> bool {
+        match (self.inner.has_explicit_id, other.inner.has_explicit_id) {
+            (true, true) => self == other,
+            (false, false) => self.uri() == other.uri(),
+            _ => false,
+        }
+    }
 }
 
 impl From<VTHyperlink> for Hyperlink {
@@ -89,10 +111,14 @@ struct HyperlinkInner {
 
     /// Resource identifier of the hyperlink.
     uri: String,
+
+    /// Whether the id was provided by the application.
+    has_explicit_id: bool,
 }
 
 impl HyperlinkInner {
     pub fn new<T: ToString>(id: Option<T>, uri: String) -> Self {
+        let has_explicit_id = id.is_some();
         let id = match id {
             Some(id) => id.to_string(),
             None => {
@@ -104,36 +130,18 @@ impl HyperlinkInner {
             },
         };
 
-        Self { id, uri }
-    }
-}
-
-/// Trait for determining if a reset should be performed.
-///
-/// Implementations return a lightweight "discriminant" value which is used to
-/// detect changes in the template cell. If the discriminant differs from the
-/// previous template, the entire row needs to be reset.
-pub trait ResetDiscriminant<T> {
-    /// Value based on which equality for the reset will be determined.
-    fn discriminant(&self) -> T;
-}
-
-impl<T: Copy> ResetDiscriminant<T> for T {
-    fn discriminant(&self) -> T {
-        *self
-    }
-}
-
-impl ResetDiscriminant<Color> for Cell {
-    fn discriminant(&self) -> Color {
-        self.bg
+        Self {
+            id,
+            uri,
+            has_explicit_id,
+        }
     }
 }
 
 /// Dynamically allocated cell content.
 ///
 /// This storage is reserved for cell attributes which are rarely set
-/// (zerowidth codepoints, underline color, hyperlink). Keeping them in a
+/// (zerowidth codepoints, underline color, hyperlink, image). Keeping them in a
 /// separate structure allows the hot path for "simple" cells to stay compact
 /// while still supporting rich attributes when needed.
 #[derive(Default, Debug, Clone, Eq, PartialEq)]
@@ -141,6 +149,7 @@ pub struct CellExtra {
     zerowidth: Vec<char>,
     underline_color: Option<Color>,
     hyperlink: Option<Hyperlink>,
+    image: Option<ImageCell>,
 }
 
 /// Content and attributes of a single cell in the terminal grid.
@@ -168,6 +177,37 @@ impl Default for Cell {
 }
 
 impl Cell {
+    /// Whether the cell holds the first half of a double-width character.
+    #[inline]
+    pub fn is_wide(&self) -> bool {
+        self.flags.contains(Flags::WIDE_CHAR)
+    }
+
+    /// Whether the cell is the second half of a double-width character,
+    /// which the character in the previous cell covers.
+    #[inline]
+    pub fn is_wide_spacer(&self) -> bool {
+        self.flags.contains(Flags::WIDE_CHAR_SPACER)
+    }
+
+    /// Whether the line continues on the next row (the cell is the last
+    /// one of a soft-wrapped row).
+    #[inline]
+    pub fn is_wrapped(&self) -> bool {
+        self.flags.contains(Flags::WRAPLINE)
+    }
+
+    /// Whether the cell draws no glyph: a space or tab without combining
+    /// characters or an image. Its background and decorations such as an
+    /// underline are still drawn.
+    #[inline]
+    pub fn is_blank(&self) -> bool {
+        (self.c == ' ' || self.c == '\t')
+            && self.extra.as_ref().is_none_or(|extra| {
+                extra.zerowidth.is_empty() && extra.image.is_none()
+            })
+    }
+
     /// Zero‑width characters stored in this cell.
     ///
     /// These are combining marks which should be rendered on top of the
@@ -199,7 +239,9 @@ impl Cell {
         // If we reset color and we don't have zerowidth we should drop extra storage.
         if color.is_none()
             && self.extra.as_ref().is_none_or(|extra| {
-                extra.zerowidth.is_empty() && extra.hyperlink.is_none()
+                extra.zerowidth.is_empty()
+                    && extra.hyperlink.is_none()
+                    && extra.image.is_none()
             })
         {
             self.extra = None;
@@ -219,7 +261,9 @@ impl Cell {
     pub fn set_hyperlink(&mut self, hyperlink: Option<Hyperlink>) {
         let should_drop = hyperlink.is_none()
             && self.extra.as_ref().is_none_or(|extra| {
-                extra.zerowidth.is_empty() && extra.underline_color.is_none()
+                extra.zerowidth.is_empty()
+                    && extra.underline_color.is_none()
+                    && extra.image.is_none()
             });
 
         if should_drop {
@@ -235,6 +279,29 @@ impl Cell {
     pub fn hyperlink(&self) -> Option<Hyperlink> {
         self.extra.as_ref()?.hyperlink.clone()
     }
+
+    /// Mark this cell as covered by an image, or clear the placeholder.
+    pub fn set_image(&mut self, image: Option<ImageCell>) {
+        let should_drop = image.is_none()
+            && self.extra.as_ref().is_none_or(|extra| {
+                extra.zerowidth.is_empty()
+                    && extra.underline_color.is_none()
+                    && extra.hyperlink.is_none()
+            });
+
+        if should_drop {
+            self.extra = None;
+        } else {
+            let extra = self.extra.get_or_insert(Default::default());
+            Arc::make_mut(extra).image = image;
+        }
+    }
+
+    /// Image placeholder stored in this cell.
+    #[inline]
+    pub fn image(&self) -> Option<&ImageCell> {
+        self.extra.as_ref()?.image.as_ref()
+    }
 }
 
 impl GridCell for Cell {
@@ -247,12 +314,14 @@ impl GridCell for Cell {
                 Flags::INVERSE
                     | Flags::ALL_UNDERLINES
                     | Flags::STRIKEOUT
+                    | Flags::OVERLINE
                     | Flags::WRAPLINE
                     | Flags::WIDE_CHAR_SPACER
                     | Flags::LEADING_WIDE_CHAR_SPACER,
             )
-            && self.extra.as_ref().map(|extra| extra.zerowidth.is_empty())
-                != Some(false)
+            && self.extra.as_ref().is_none_or(|extra| {
+                extra.zerowidth.is_empty() && extra.image.is_none()
+            })
     }
 
     #[inline]
@@ -266,10 +335,10 @@ impl GridCell for Cell {
     }
 
     #[inline]
-    fn reset(&mut self, template: &Self) {
+    fn reset(&mut self, template: &Self, blank: &Self) {
         *self = Cell {
             bg: template.bg,
-            ..Cell::default()
+            ..blank.clone()
         };
     }
 }
@@ -303,8 +372,9 @@ impl LineLength for Row<Cell> {
 
         for (index, cell) in self[..].iter().rev().enumerate() {
             if cell.c != ' '
-                || cell.extra.as_ref().map(|extra| extra.zerowidth.is_empty())
-                    == Some(false)
+                || cell.extra.as_ref().is_some_and(|extra| {
+                    !extra.zerowidth.is_empty() || extra.image.is_some()
+                })
             {
                 length = Column(self.len() - index);
                 break;
@@ -331,6 +401,41 @@ mod tests {
         assert!(mem::size_of::<Cell>() <= EXPECTED_CELL_SIZE);
     }
 
+    #[test]
+    fn predicates_follow_the_cell_flags() {
+        let cell_with = |flags| Cell {
+            c: 'a',
+            flags,
+            ..Cell::default()
+        };
+
+        let wide = cell_with(Flags::WIDE_CHAR);
+        assert!(wide.is_wide() && !wide.is_wide_spacer() && !wide.is_wrapped());
+
+        assert!(cell_with(Flags::WIDE_CHAR_SPACER).is_wide_spacer());
+        // The filler before a wrapped wide character is an ordinary blank.
+        assert!(!cell_with(Flags::LEADING_WIDE_CHAR_SPACER).is_wide_spacer());
+        assert!(!cell_with(Flags::WIDE_CHAR_SPACER).is_wide());
+
+        let wrapped = cell_with(Flags::WRAPLINE);
+        assert!(wrapped.is_wrapped() && !wrapped.is_wide());
+
+        assert!(!cell_with(Flags::empty()).is_blank());
+    }
+
+    #[test]
+    fn blank_cells_draw_no_glyph() {
+        let mut cell = Cell {
+            bg: Color::Std(StdColor::Red),
+            flags: Flags::UNDERLINE,
+            ..Cell::default()
+        };
+        assert!(cell.is_blank());
+
+        cell.push_zerowidth('\u{301}');
+        assert!(!cell.is_blank());
+    }
+
     #[test]
     fn line_length_works() {
         let mut row = Row::<Cell>::new(10);
@@ -19,7 +19,7 @@ use crate::escape::{Rgb, StdColor};
 /// * `268` – dim background
 pub const COUNT: usize = 269;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Colors([Option<Rgb>; COUNT]);
 
 impl Default for Colors {
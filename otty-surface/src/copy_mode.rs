@@ -0,0 +1,270 @@
+//! Keyboard driven copy mode.
+//!
+//! While copy mode is active the [`crate::Surface`] tracks a copy cursor that
+//! is moved with vi-like [`Motion`]s independently of the terminal cursor.
+
+use std::cmp;
+
+use crate::cell::Flags;
+use crate::grid::{Dimensions, GridCell};
+use crate::index::{Boundary, Column, Point};
+use crate::surface::Surface;
+
+/// Copy cursor motion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Motion {
+    /// Move one cell to the left.
+    Left,
+    /// Move one cell to the right.
+    Right,
+    /// Move one line up.
+    Up,
+    /// Move one line down.
+    Down,
+    /// Move to the start of the previous word.
+    WordLeft,
+    /// Move to the start of the next word.
+    WordRight,
+    /// Move one screen up.
+    PageUp,
+    /// Move one screen down.
+    PageDown,
+    /// Move to the first column of the line.
+    LineStart,
+    /// Move to the last occupied column of the line.
+    LineEnd,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Space,
+    Separator,
+    Word,
+}
+
+/// Resolve where `motion` takes the copy cursor at `point`.
+pub(crate) fn apply_motion(
+    surface: &Surface,
+    point: Point,
+    motion: Motion,
+) -> Point {
+    let grid = surface.grid();
+    let page = grid.screen_lines() as i32;
+
+    match motion {
+        Motion::Left => {
+            Point::new(point.line, Column(point.column.saturating_sub(1)))
+        },
+        Motion::Right => Point::new(
+            point.line,
+            cmp::min(point.column + 1, grid.last_column()),
+        ),
+        Motion::Up => move_lines(surface, point, -1),
+        Motion::Down => move_lines(surface, point, 1),
+        Motion::PageUp => move_lines(surface, point, -page),
+        Motion::PageDown => move_lines(surface, point, page),
+        Motion::WordLeft => word_left(surface, point),
+        Motion::WordRight => word_right(surface, point),
+        Motion::LineStart => surface.line_search_left(point),
+        Motion::LineEnd => line_end(surface, point),
+    }
+}
+
+fn move_lines(surface: &Surface, point: Point, delta: i32) -> Point {
+    let grid = surface.grid();
+    let line = (point.line + delta)
+        .max(grid.topmost_line())
+        .min(grid.bottommost_line());
+    Point::new(line, point.column)
+}
+
+fn word_right(surface: &Surface, mut point: Point) -> Point {
+    let start_class = class_at(surface, point);
+
+    // Leave the current word, then skip any blanks after it.
+    while let Some(next) = step(surface, point, 1) {
+        point = next;
+        let class = class_at(surface, point);
+        if class != start_class && class != CharClass::Space {
+            return point;
+        }
+        if class == CharClass::Space {
+            break;
+        }
+    }
+
+    while class_at(surface, point) == CharClass::Space {
+        match step(surface, point, 1) {
+            Some(next) => point = next,
+            None => break,
+        }
+    }
+
+    point
+}
+
+fn word_left(surface: &Surface, mut point: Point) -> Point {
+    // Skip blanks directly before the cursor.
+    loop {
+        match step(surface, point, -1) {
+            Some(prev) => point = prev,
+            None => return point,
+        }
+        if class_at(surface, point) != CharClass::Space {
+            break;
+        }
+    }
+
+    let class = class_at(surface, point);
+    while let Some(prev) = step(surface, point, -1) {
+        if class_at(surface, prev) != class {
+            break;
+        }
+        point = prev;
+    }
+
+    point
+}
+
+fn line_end(surface: &Surface, point: Point) -> Point {
+    let mut point = surface.line_search_right(point);
+    while point.column > 0 && surface.grid()[point].is_empty() {
+        point.column -= 1;
+    }
+    point
+}
+
+/// Move one cell forwards or backwards, skipping wide char spacers.
+fn step(surface: &Surface, point: Point, direction: i32) -> Option<Point> {
+    let grid = surface.grid();
+    let mut next = point;
+    loop {
+        next = if direction < 0 {
+            if next == Point::new(grid.topmost_line(), Column(0)) {
+                return None;
+            }
+            next.sub(grid, Boundary::Grid, 1)
+        } else {
+            if next == Point::new(grid.bottommost_line(), grid.last_column()) {
+                return None;
+            }
+            next.add(grid, Boundary::Grid, 1)
+        };
+
+        if !grid[next].flags.contains(Flags::WIDE_CHAR_SPACER) {
+            return Some(next);
+        }
+    }
+}
+
+fn class_at(surface: &Surface, point: Point) -> CharClass {
+    let c = surface.grid()[point].c;
+    if c == ' ' || c == '\t' {
+        CharClass::Space
+    } else if surface.semantic_escape_chars().contains(c) {
+        CharClass::Separator
+    } else {
+        CharClass::Word
+    }
+}
+
+/// Clamp a copy cursor to the grid after a resize or content change.
+pub(crate) fn clamp_point(surface: &Surface, point: Point) -> Point {
+    let grid = surface.grid();
+    let line = point
+        .line
+        .max(grid.topmost_line())
+        .min(grid.bottommost_line());
+    Point::new(line, cmp::min(point.column, grid.last_column()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SurfaceConfig;
+    use crate::index::Line;
+
+    struct Size {
+        columns: usize,
+        lines: usize,
+    }
+
+    impl Dimensions for Size {
+        fn total_lines(&self) -> usize {
+            self.lines
+        }
+
+        fn screen_lines(&self) -> usize {
+            self.lines
+        }
+
+        fn columns(&self) -> usize {
+            self.columns
+        }
+    }
+
+    fn surface_with(lines: &[&str]) -> Surface {
+        let size = Size {
+            columns: 20,
+            lines: 4,
+        };
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        for (line, text) in lines.iter().enumerate() {
+            for (column, c) in text.chars().enumerate() {
+                surface.grid_mut()[Line(line as i32)][Column(column)].c = c;
+            }
+        }
+        surface
+    }
+
+    #[test]
+    fn word_motions_stop_at_word_starts() {
+        let surface = surface_with(&["foo bar(baz)", "next"]);
+        let origin = Point::new(Line(0), Column(0));
+
+        let point = apply_motion(&surface, origin, Motion::WordRight);
+        assert_eq!(point, Point::new(Line(0), Column(4)));
+        let point = apply_motion(&surface, point, Motion::WordRight);
+        assert_eq!(point, Point::new(Line(0), Column(7)));
+        let point = apply_motion(&surface, point, Motion::WordRight);
+        assert_eq!(point, Point::new(Line(0), Column(8)));
+
+        let point = apply_motion(&surface, point, Motion::WordLeft);
+        assert_eq!(point, Point::new(Line(0), Column(7)));
+        let point = apply_motion(&surface, point, Motion::WordLeft);
+        assert_eq!(point, Point::new(Line(0), Column(4)));
+    }
+
+    #[test]
+    fn word_right_crosses_trailing_blanks_into_next_line() {
+        let surface = surface_with(&["foo", "next"]);
+
+        let point = apply_motion(
+            &surface,
+            Point::new(Line(0), Column(0)),
+            Motion::WordRight,
+        );
+
+        assert_eq!(point, Point::new(Line(1), Column(0)));
+    }
+
+    #[test]
+    fn line_motions_are_clamped_to_the_grid() {
+        let surface = surface_with(&["some text"]);
+        let origin = Point::new(Line(0), Column(3));
+
+        assert_eq!(apply_motion(&surface, origin, Motion::Up), origin);
+        assert_eq!(
+            apply_motion(&surface, origin, Motion::PageDown),
+            Point::new(Line(3), Column(3))
+        );
+        assert_eq!(
+            apply_motion(&surface, origin, Motion::LineEnd),
+            Point::new(Line(0), Column(8))
+        );
+        assert_eq!(
+            apply_motion(&surface, origin, Motion::LineStart),
+            Point::new(Line(0), Column(0))
+        );
+    }
+}
@@ -9,6 +9,10 @@ use std::{cmp, slice};
 
 use crate::index::Point;
 
+/// Maximum number of merged damage regions kept before the whole surface is
+/// treated as damaged.
+pub(crate) const MAX_DAMAGE_RECTS: usize = 32;
+
 /// Damage bounds for a single line in the grid.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct LineDamageBounds {
@@ -59,6 +63,54 @@ impl LineDamageBounds {
     }
 }
 
+/// Rectangular damaged region spanning one or more consecutive lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DamageRect {
+    /// First damaged line.
+    pub top: usize,
+
+    /// Last damaged line (inclusive).
+    pub bottom: usize,
+
+    /// Leftmost damaged column.
+    pub left: usize,
+
+    /// Rightmost damaged column (inclusive).
+    pub right: usize,
+}
+
+impl DamageRect {
+    /// Create a region covering the damaged span of a single line.
+    #[inline]
+    pub fn from_line(bounds: LineDamageBounds) -> Self {
+        Self {
+            top: bounds.line,
+            bottom: bounds.line,
+            left: bounds.left,
+            right: bounds.right,
+        }
+    }
+
+    /// Grow this region to cover `bounds` when they overlap or touch.
+    ///
+    /// Returns `false` and leaves the region untouched otherwise.
+    fn try_merge(&mut self, bounds: &LineDamageBounds) -> bool {
+        let touches_lines =
+            bounds.line + 1 >= self.top && bounds.line <= self.bottom + 1;
+        let touches_columns =
+            bounds.left <= self.right + 1 && self.left <= bounds.right + 1;
+        if !touches_lines || !touches_columns {
+            return false;
+        }
+
+        self.top = cmp::min(self.top, bounds.line);
+        self.bottom = cmp::max(self.bottom, bounds.line);
+        self.left = cmp::min(self.left, bounds.left);
+        self.right = cmp::max(self.right, bounds.right);
+        true
+    }
+}
+
 /// Terminal damage information collected since the last
 /// [`crate::surface::Surface::reset_damage`] call.
 #[derive(Debug)]
@@ -158,6 +210,9 @@ impl SurfaceDamageState {
     }
 
     /// Expand `line`'s damage to span at least `left` to `right` column.
+    ///
+    /// Nothing is tracked once the whole surface is damaged, so a repaint
+    /// following a full clear until the next reset costs no bookkeeping.
     #[inline]
     pub(crate) fn damage_line(
         &mut self,
@@ -165,6 +220,9 @@ impl SurfaceDamageState {
         left: usize,
         right: usize,
     ) {
+        if self.full {
+            return;
+        }
         self.lines[line].expand(left, right);
     }
 
@@ -174,3 +232,101 @@ impl SurfaceDamageState {
         self.lines.iter_mut().for_each(|line| line.reset(num_cols));
     }
 }
+
+/// Coalesce damaged lines into a small list of rectangles.
+///
+/// Overlapping or adjacent spans on neighbouring lines are merged into one
+/// region. Returns `None` when more than `cap` regions remain, signalling
+/// that the caller should fall back to full damage.
+pub(crate) fn merge_line_damage(
+    lines: impl IntoIterator<Item = LineDamageBounds>,
+    cap: usize,
+) -> Option<Vec<DamageRect>> {
+    let mut rects: Vec<DamageRect> = Vec::new();
+
+    for bounds in lines.into_iter().filter(LineDamageBounds::is_damaged) {
+        // Only regions ending on the previous or current line can still
+        // touch this span, and those are always at the tail.
+        let merged = rects
+            .iter_mut()
+            .rev()
+            .take_while(|rect| rect.bottom + 1 >= bounds.line)
+            .any(|rect| rect.try_merge(&bounds));
+        if merged {
+            continue;
+        }
+
+        if rects.len() == cap {
+            return None;
+        }
+        rects.push(DamageRect::from_line(bounds));
+    }
+
+    Some(rects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn damaged(line: usize, column: usize) -> LineDamageBounds {
+        LineDamageBounds::new(line, column, column)
+    }
+
+    #[test]
+    fn scattered_cells_merge_into_spans() {
+        let lines = [
+            damaged(0, 3),
+            damaged(1, 4),
+            damaged(2, 2),
+            damaged(4, 10),
+            damaged(5, 30),
+            LineDamageBounds::undamaged(6, 80),
+            damaged(7, 30),
+        ];
+
+        let rects = merge_line_damage(lines, MAX_DAMAGE_RECTS)
+            .expect("damage fits under the cap");
+
+        assert_eq!(
+            rects,
+            vec![
+                DamageRect {
+                    top: 0,
+                    bottom: 2,
+                    left: 2,
+                    right: 4,
+                },
+                DamageRect {
+                    top: 4,
+                    bottom: 4,
+                    left: 10,
+                    right: 10,
+                },
+                DamageRect {
+                    top: 5,
+                    bottom: 5,
+                    left: 30,
+                    right: 30,
+                },
+                DamageRect {
+                    top: 7,
+                    bottom: 7,
+                    left: 30,
+                    right: 30,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn exceeding_cap_collapses_to_full_damage() {
+        let lines = (0..10).map(|line| damaged(line * 2, 0));
+
+        assert_eq!(
+            merge_line_damage(lines.clone(), 10).map(|r| r.len()),
+            Some(10)
+        );
+        assert_eq!(merge_line_damage(lines, 9), None);
+    }
+}
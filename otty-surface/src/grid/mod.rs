@@ -14,10 +14,11 @@ mod tests;
 use std::cmp::{max, min};
 use std::ops::{Bound, Deref, Index, IndexMut, Range, RangeBounds};
 
+pub use row::LineKind;
 pub(crate) use row::Row;
 use storage::Storage;
 
-use crate::cell::{Flags, ResetDiscriminant};
+use crate::cell::Flags;
 use crate::escape::{Charset, CharsetIndex};
 use crate::index::{Column, Line, Point};
 
@@ -30,8 +31,9 @@ pub trait GridCell: Sized {
     /// Check if the cell contains any content.
     fn is_empty(&self) -> bool;
 
-    /// Perform an opinionated cell reset based on a template cell.
-    fn reset(&mut self, template: &Self);
+    /// Reset the cell to `blank`, keeping what erasing carries over from
+    /// the template cell.
+    fn reset(&mut self, template: &Self, blank: &Self);
 
     fn flags(&self) -> &Flags;
     fn flags_mut(&mut self) -> &mut Flags;
@@ -132,6 +134,10 @@ pub struct Grid<T> {
     /// Last saved cursor.
     pub saved_cursor: Cursor<T>,
 
+    /// Active charset and origin mode saved along with `saved_cursor`.
+    pub saved_charset: CharsetIndex,
+    pub saved_origin: bool,
+
     /// Lines in the grid. Each row holds a list of cells corresponding to the
     /// columns in that row.
     raw: Storage<T>,
@@ -151,9 +157,16 @@ pub struct Grid<T> {
 
     /// Maximum number of lines in history.
     max_scroll_limit: usize,
+
+    /// Lines dropped from the top of the history since the grid was
+    /// created, by rotation out of a full scrollback or by shrinking it.
+    dropped_history: usize,
+
+    /// Cell that cleared and newly added cells are reset to.
+    blank: T,
 }
 
-impl<T: GridCell + Default + PartialEq> Grid<T> {
+impl<T: GridCell + Default + Clone + PartialEq> Grid<T> {
     /// Create a new grid with the given viewport size and scrollback limit.
     pub fn new(
         lines: usize,
@@ -165,17 +178,34 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
             max_scroll_limit,
             display_offset: 0,
             saved_cursor: Cursor::default(),
+            saved_charset: CharsetIndex::default(),
+            saved_origin: false,
             cursor: Cursor::default(),
             lines,
             columns,
+            dropped_history: 0,
+            blank: T::default(),
+        }
+    }
+
+    /// Reset cleared cells to `blank` instead of the default cell.
+    ///
+    /// The visible lines are reset to it right away.
+    pub fn with_blank(mut self, blank: T) -> Self {
+        self.blank = blank;
+        for line in (0..self.lines as i32).map(Line::from) {
+            self.raw[line].reset(&self.cursor.template, &self.blank);
         }
+        self
     }
 
     /// Update the size of the scrollback history.
     pub fn update_history(&mut self, history_size: usize) {
         let current_history_size = self.history_size();
         if current_history_size > history_size {
-            self.raw.shrink_lines(current_history_size - history_size);
+            let dropped = current_history_size - history_size;
+            self.raw.shrink_lines(dropped);
+            self.dropped_history += dropped;
         }
         self.display_offset = min(self.display_offset, history_size);
         self.max_scroll_limit = history_size;
@@ -206,21 +236,18 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
     fn decrease_scroll_limit(&mut self, count: usize) {
         let count = min(count, self.history_size());
         if count != 0 {
-            self.raw.shrink_lines(min(count, self.history_size()));
+            self.raw.shrink_lines(count);
+            self.dropped_history += count;
             self.display_offset = min(self.display_offset, self.history_size());
         }
     }
 
     #[inline]
-    pub fn scroll_down<D>(&mut self, region: &Range<Line>, positions: usize)
-    where
-        T: ResetDiscriminant<D>,
-        D: PartialEq,
-    {
+    pub fn scroll_down(&mut self, region: &Range<Line>, positions: usize) {
         // When rotating the entire region, just reset everything.
         if region.end - region.start <= positions {
             for i in (region.start.0..region.end.0).map(Line::from) {
-                self.raw[i].reset(&self.cursor.template);
+                self.raw[i].reset(&self.cursor.template, &self.blank);
             }
 
             return;
@@ -249,7 +276,7 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
 
             // Ensure all new lines are fully cleared.
             for i in (0..positions).map(Line::from) {
-                self.raw[i].reset(&self.cursor.template);
+                self.raw[i].reset(&self.cursor.template, &self.blank);
             }
 
             // Swap the fixed lines at the top back into position.
@@ -265,7 +292,7 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
 
             let range = region.start.0..(region.start + positions).0;
             for line in range.rev().map(Line::from) {
-                self.raw[line].reset(&self.cursor.template);
+                self.raw[line].reset(&self.cursor.template, &self.blank);
             }
         }
     }
@@ -273,15 +300,11 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
     /// Move lines at the bottom toward the top.
     ///
     /// This is the performance-sensitive part of scrolling.
-    pub fn scroll_up<D>(&mut self, region: &Range<Line>, positions: usize)
-    where
-        T: ResetDiscriminant<D>,
-        D: PartialEq,
-    {
+    pub fn scroll_up(&mut self, region: &Range<Line>, positions: usize) {
         // When rotating the entire region with fixed lines at the top, just reset everything.
         if region.end - region.start <= positions && region.start != 0 {
             for i in (region.start.0..region.end.0).map(Line::from) {
-                self.raw[i].reset(&self.cursor.template);
+                self.raw[i].reset(&self.cursor.template, &self.blank);
             }
 
             return;
@@ -295,7 +318,10 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
 
         // Only rotate the entire history if the active region starts at the top.
         if region.start == 0 {
-            // Create scrollback for the new lines.
+            // Create scrollback for the new lines, the oldest ones rotate
+            // out once it is full.
+            let room = self.max_scroll_limit - self.history_size();
+            self.dropped_history += positions.saturating_sub(room);
             self.increase_scroll_limit(positions);
 
             // Swap the lines fixed at the top to their target positions after rotation.
@@ -330,15 +356,11 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
         // Ensure all new lines are fully cleared.
         for i in (region.end.0 - positions as i32..region.end.0).map(Line::from)
         {
-            self.raw[i].reset(&self.cursor.template);
+            self.raw[i].reset(&self.cursor.template, &self.blank);
         }
     }
 
-    pub fn clear_viewport<D>(&mut self)
-    where
-        T: ResetDiscriminant<D>,
-        D: PartialEq,
-    {
+    pub fn clear_viewport(&mut self) {
         // Determine how many lines to scroll up by.
         let end =
             Point::new(Line(self.lines as i32 - 1), Column(self.columns()));
@@ -357,36 +379,33 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
 
         // Reset rotated lines.
         for line in (0..(self.lines - positions)).map(Line::from) {
-            self.raw[line].reset(&self.cursor.template);
+            self.raw[line].reset(&self.cursor.template, &self.blank);
         }
     }
 
     /// Completely reset the grid state.
-    pub fn reset<D>(&mut self)
-    where
-        T: ResetDiscriminant<D>,
-        D: PartialEq,
-    {
+    pub fn reset(&mut self) {
         self.clear_history();
 
         self.saved_cursor = Cursor::default();
+        self.saved_charset = CharsetIndex::default();
+        self.saved_origin = false;
         self.cursor = Cursor::default();
         self.display_offset = 0;
 
         // Reset all visible lines.
         let range = self.topmost_line().0..(self.screen_lines() as i32);
         for line in range.map(Line::from) {
-            self.raw[line].reset(&self.cursor.template);
+            self.raw[line].reset(&self.cursor.template, &self.blank);
         }
     }
 }
 
 impl<T> Grid<T> {
     /// Reset a visible region within the grid.
-    pub fn reset_region<D, R: RangeBounds<Line>>(&mut self, bounds: R)
+    pub fn reset_region<R: RangeBounds<Line>>(&mut self, bounds: R)
     where
-        T: ResetDiscriminant<D> + GridCell + Default,
-        D: PartialEq,
+        T: GridCell + Default + Clone + PartialEq,
     {
         let start = match bounds.start_bound() {
             Bound::Included(line) => *line,
@@ -404,13 +423,14 @@ impl<T> Grid<T> {
         debug_assert!(end <= self.screen_lines() as i32);
 
         for line in (start.0..end.0).map(Line::from) {
-            self.raw[line].reset(&self.cursor.template);
+            self.raw[line].reset(&self.cursor.template, &self.blank);
         }
     }
 
     #[inline]
     pub fn clear_history(&mut self) {
         // Explicitly purge all lines from history.
+        self.dropped_history += self.history_size();
         self.raw.shrink_lines(self.history_size());
 
         // Reset display offset.
@@ -475,11 +495,38 @@ impl<T> Grid<T> {
         self.display_offset
     }
 
+    /// Lines dropped from the top of the history since the grid was
+    /// created.
+    ///
+    /// Adding it to the history size and a viewport line gives a line
+    /// number that stays put while output scrolls.
+    #[inline]
+    pub fn dropped_history(&self) -> usize {
+        self.dropped_history
+    }
+
+    /// Estimate the heap bytes held by the visible lines and scrollback.
+    #[inline]
+    pub fn memory_usage(&self) -> usize {
+        self.raw.memory_usage()
+    }
+
     #[inline]
     pub fn cursor_cell(&mut self) -> &mut T {
         let point = self.cursor.point;
         &mut self[point.line][point.column]
     }
+
+    /// Iterate over the logical lines of the whole grid, oldest first.
+    ///
+    /// Rows joined by a soft wrap form one logical line; a row that does
+    /// not wrap is a logical line of its own.
+    pub fn logical_lines(&self) -> LogicalLines<'_, T> {
+        LogicalLines {
+            grid: self,
+            next: self.topmost_line(),
+        }
+    }
 }
 
 impl<T: PartialEq> PartialEq for Grid<T> {
@@ -679,6 +726,73 @@ impl<'a, T> Iterator for GridIterator<'a, T> {
     }
 }
 
+/// Iterator over the logical lines of a grid.
+#[derive(Clone)]
+pub struct LogicalLines<'a, T> {
+    grid: &'a Grid<T>,
+    /// First row of the next logical line.
+    next: Line,
+}
+
+impl<'a, T: GridCell> Iterator for LogicalLines<'a, T> {
+    type Item = LogicalLine<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bottommost_line = self.grid.bottommost_line();
+        if self.next > bottommost_line {
+            return None;
+        }
+
+        let start = self.next;
+        let mut end = start;
+        let last_column = self.grid.last_column();
+        while end < bottommost_line
+            && self.grid[end][last_column]
+                .flags()
+                .contains(Flags::WRAPLINE)
+        {
+            end += 1;
+        }
+        self.next = end + 1;
+
+        Some(LogicalLine {
+            grid: self.grid,
+            next: start,
+            end,
+        })
+    }
+}
+
+/// Physical rows forming one logical line, from top to bottom.
+#[derive(Clone)]
+pub struct LogicalLine<'a, T> {
+    grid: &'a Grid<T>,
+    next: Line,
+    /// Last row of the logical line, inclusive.
+    end: Line,
+}
+
+impl<T> LogicalLine<'_, T> {
+    /// Lines of the rows not yielded yet.
+    pub fn lines(&self) -> Range<Line> {
+        self.next..self.end + 1
+    }
+}
+
+impl<'a, T> Iterator for LogicalLine<'a, T> {
+    type Item = &'a Row<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next > self.end {
+            return None;
+        }
+
+        let row = &self.grid[self.next];
+        self.next += 1;
+        Some(row)
+    }
+}
+
 /// Bidirectional iterator.
 pub trait BidirectionalIterator: Iterator {
     fn prev(&mut self) -> Option<Self::Item>;
@@ -9,21 +9,17 @@ use std::mem;
 
 use super::row::Row;
 use super::{Dimensions, Grid, GridCell};
-use crate::cell::{Flags, ResetDiscriminant};
+use crate::cell::Flags;
 use crate::index::{Boundary, Column, Line};
 
-impl<T: GridCell + Default + PartialEq> Grid<T> {
+impl<T: GridCell + Default + Clone + PartialEq> Grid<T> {
     /// Resize the grid's width and/or height.
     ///
     /// * `reflow = true` enables logical rewrapping of lines when the number
     ///   of columns changes, similar to GUI terminals.
     /// * `reflow = false` keeps cell positions fixed and may truncate or pad
     ///   rows instead.
-    pub fn resize<D>(&mut self, reflow: bool, lines: usize, columns: usize)
-    where
-        T: ResetDiscriminant<D>,
-        D: PartialEq,
-    {
+    pub fn resize(&mut self, reflow: bool, lines: usize, columns: usize) {
         // Use empty template cell for resetting cells due to resize.
         let template = mem::take(&mut self.cursor.template);
 
@@ -48,11 +44,7 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
     /// Alacritty keeps the cursor at the bottom of the terminal as long as there
     /// is scrollback available. Once scrollback is exhausted, new lines are
     /// simply added to the bottom of the screen.
-    fn grow_lines<D>(&mut self, target: usize)
-    where
-        T: ResetDiscriminant<D>,
-        D: PartialEq,
-    {
+    fn grow_lines(&mut self, target: usize) {
         let lines_added = target - self.lines;
 
         // Need to resize before updating buffer.
@@ -83,11 +75,7 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
     /// of the terminal window.
     ///
     /// Alacritty takes the same approach.
-    fn shrink_lines<D>(&mut self, target: usize)
-    where
-        T: ResetDiscriminant<D>,
-        D: PartialEq,
-    {
+    fn shrink_lines(&mut self, target: usize) {
         // Scroll up to keep content inside the window.
         let required_scrolling =
             (self.cursor.point.line.0 as usize + 1).saturating_sub(target);
@@ -236,7 +224,11 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
             let delta = (self.lines - reversed.len()) as i32;
             self.cursor.point.line =
                 max(self.cursor.point.line - delta, Line(0));
-            reversed.resize_with(self.lines, || Row::new(columns));
+            reversed.resize_with(self.lines, || {
+                let mut row = Row::new(columns);
+                row.reset(&self.cursor.template, &self.blank);
+                row
+            });
         }
 
         // Pull content down to put cursor in correct position, or move cursor up if there's no
@@ -256,7 +248,7 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
         let mut new_raw = Vec::with_capacity(reversed.len());
         for mut row in reversed.drain(..).rev() {
             if row.len() < columns {
-                row.grow(columns);
+                row.grow(columns, &self.blank);
             }
             new_raw.push(row);
         }
@@ -295,6 +287,8 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
                 row.append_front(buffered);
             }
 
+            // Lines split off by the reflow keep the role of their source.
+            let kind = row.kind;
             loop {
                 // Remove all cells which require reflowing.
                 let mut wrapped = match row.shrink(columns) {
@@ -398,9 +392,10 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
                     // Make sure new row is at least as long as new width.
                     let occ = wrapped.len();
                     if occ < columns {
-                        wrapped.resize_with(columns, T::default);
+                        wrapped.resize(columns, self.blank.clone());
                     }
                     row = Row::from_vec(wrapped, occ);
+                    row.kind = kind;
 
                     if i < self.display_offset {
                         // Since we added a new line, rotate up the viewport.
@@ -4,12 +4,23 @@ use std::cmp::{max, min};
 use std::ops::{
     Index, IndexMut, Range, RangeFrom, RangeFull, RangeTo, RangeToInclusive,
 };
-use std::{ptr, slice};
+use std::{mem, ptr, slice};
 
 use super::GridCell;
-use crate::cell::ResetDiscriminant;
 use crate::index::Column;
 
+/// Semantic role of a grid line, taken from the shell integration marks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineKind {
+    /// Prompt drawn by the shell.
+    Prompt,
+    /// Command line typed at the prompt.
+    Command,
+    /// Anything else, including lines written without any marks.
+    #[default]
+    Output,
+}
+
 /// A single logical row of cells in the grid.
 ///
 /// The `Row` keeps track of an "occupied" prefix (`occ`) to speed up mass
@@ -23,6 +34,9 @@ pub struct Row<T> {
     /// This is the upper bound on the number of elements in the row, which have been modified
     /// since the last reset. All cells after this point are guaranteed to be equal.
     pub(crate) occ: usize,
+
+    /// Semantic role of the line, reset together with its cells.
+    pub(crate) kind: LineKind,
 }
 
 impl<T: PartialEq> PartialEq for Row<T> {
@@ -54,17 +68,24 @@ impl<T: Default> Row<T> {
             inner.set_len(columns);
         }
 
-        Row { inner, occ: 0 }
+        Row {
+            inner,
+            occ: 0,
+            kind: LineKind::Output,
+        }
     }
 
-    /// Increase the number of columns in the row.
+    /// Increase the number of columns in the row with `blank` cells.
     #[inline]
-    pub fn grow(&mut self, columns: usize) {
+    pub fn grow(&mut self, columns: usize, blank: &T)
+    where
+        T: Clone,
+    {
         if self.inner.len() >= columns {
             return;
         }
 
-        self.inner.resize_with(columns, T::default);
+        self.inner.resize(columns, blank.clone());
     }
 
     /// Reduce the number of columns in the row.
@@ -95,27 +116,31 @@ impl<T: Default> Row<T> {
         }
     }
 
-    /// Reset all cells in the row to the `template` cell.
+    /// Reset all cells in the row to the `blank` cell, based on the
+    /// `template` cell.
     #[inline]
-    pub fn reset<D>(&mut self, template: &T)
+    pub fn reset(&mut self, template: &T, blank: &T)
     where
-        T: ResetDiscriminant<D> + GridCell,
-        D: PartialEq,
+        T: GridCell + Clone + PartialEq,
     {
         debug_assert!(!self.inner.is_empty());
 
-        // Mark all cells as dirty if template cell changed.
+        // Mark all cells as dirty if the unoccupied cells differ from the
+        // reset cell, like after a template change.
         let len = self.inner.len();
-        if self.inner[len - 1].discriminant() != template.discriminant() {
+        let mut cleared = blank.clone();
+        cleared.reset(template, blank);
+        if self.inner[len - 1] != cleared {
             self.occ = len;
         }
 
         // Reset every dirty cell in the row.
         for item in &mut self.inner[0..self.occ] {
-            item.reset(template);
+            *item = cleared.clone();
         }
 
         self.occ = 0;
+        self.kind = LineKind::Output;
     }
 }
 
@@ -123,7 +148,11 @@ impl<T: Default> Row<T> {
 impl<T> Row<T> {
     #[inline]
     pub fn from_vec(vec: Vec<T>, occ: usize) -> Row<T> {
-        Row { inner: vec, occ }
+        Row {
+            inner: vec,
+            occ,
+            kind: LineKind::Output,
+        }
     }
 
     /// Total number of columns in this row.
@@ -132,6 +161,12 @@ impl<T> Row<T> {
         self.inner.len()
     }
 
+    /// Estimate the heap bytes held by the row's cells.
+    #[inline]
+    pub fn memory_usage(&self) -> usize {
+        self.inner.capacity() * mem::size_of::<T>()
+    }
+
     #[inline]
     pub fn last(&self) -> Option<&T> {
         self.inner.last()
@@ -145,16 +145,22 @@ impl<T> Storage<T> {
         self.len
     }
 
+    /// Estimate the heap bytes held by all allocated rows, including the
+    /// cached rows beyond the active length.
+    pub fn memory_usage(&self) -> usize {
+        let rows = self.inner.capacity() * mem::size_of::<Row<T>>();
+        self.inner
+            .iter()
+            .fold(rows, |total, row| total + row.memory_usage())
+    }
+
     /// Swap implementation for Row<T>.
     ///
     /// Exploits the known size of Row<T> to produce a slightly more efficient
-    /// swap than going through slice::swap.
-    ///
-    /// The default implementation from swap generates 8 movups and 4 movaps
-    /// instructions. This implementation achieves the swap in only 8 movups
-    /// instructions.
+    /// swap than going through slice::swap, copying one qword at a time.
     pub fn swap(&mut self, a: Line, b: Line) {
-        debug_assert_eq!(mem::size_of::<Row<T>>(), mem::size_of::<usize>() * 4);
+        let words = mem::size_of::<Row<T>>() / mem::size_of::<usize>();
+        debug_assert_eq!(mem::size_of::<Row<T>>(), mem::size_of::<usize>() * 5);
 
         let a = self.compute_index(a);
         let b = self.compute_index(b);
@@ -172,7 +178,7 @@ impl<T> Storage<T> {
             //
             // The optimizer unrolls this loop and vectorizes it.
             let mut tmp: MaybeUninit<usize>;
-            for i in 0..4 {
+            for i in 0..words as isize {
                 tmp = *a_ptr.offset(i);
                 *a_ptr.offset(i) = *b_ptr.offset(i);
                 *b_ptr.offset(i) = tmp;
@@ -285,7 +291,7 @@ mod tests {
             *self == ' ' || *self == '\t'
         }
 
-        fn reset(&mut self, template: &Self) {
+        fn reset(&mut self, template: &Self, _blank: &Self) {
             *self = *template;
         }
 
@@ -8,7 +8,7 @@ impl GridCell for usize {
         *self == 0
     }
 
-    fn reset(&mut self, template: &Self) {
+    fn reset(&mut self, template: &Self, _blank: &Self) {
         *self = *template;
     }
 
@@ -29,7 +29,7 @@ fn scroll_up() {
         grid[Line(i as i32)][Column(0)] = i;
     }
 
-    grid.scroll_up::<usize>(&(Line(0)..Line(10)), 2);
+    grid.scroll_up(&(Line(0)..Line(10)), 2);
 
     assert_eq!(grid[Line(0)][Column(0)], 2);
     assert_eq!(grid[Line(0)].occ, 1);
@@ -61,7 +61,7 @@ fn scroll_down() {
         grid[Line(i as i32)][Column(0)] = i;
     }
 
-    grid.scroll_down::<usize>(&(Line(0)..Line(10)), 2);
+    grid.scroll_down(&(Line(0)..Line(10)), 2);
 
     assert_eq!(grid[Line(0)][Column(0)], 0); // was 8.
     assert_eq!(grid[Line(0)].occ, 0);
@@ -93,7 +93,7 @@ fn scroll_down_with_history() {
         grid[Line(i as i32)][Column(0)] = i;
     }
 
-    grid.scroll_down::<usize>(&(Line(0)..Line(10)), 2);
+    grid.scroll_down(&(Line(0)..Line(10)), 2);
 
     assert_eq!(grid[Line(0)][Column(0)], 0); // was 8.
     assert_eq!(grid[Line(0)].occ, 0);
@@ -390,3 +390,33 @@ fn wrap_cell(c: char) -> Cell {
     cell.flags.insert(Flags::WRAPLINE);
     cell
 }
+
+#[test]
+fn logical_lines_group_wrapped_rows() {
+    let mut grid = Grid::<Cell>::new(5, 2, 0);
+    for (line, text) in ["ab", "cd", "ef", "gh", "ij"].iter().enumerate() {
+        for (column, c) in text.chars().enumerate() {
+            grid[Line(line as i32)][Column(column)] = cell(c);
+        }
+    }
+    grid[Line(0)][Column(1)] = wrap_cell('b');
+    grid[Line(1)][Column(1)] = wrap_cell('d');
+
+    let groups: Vec<(Range<Line>, String)> = grid
+        .logical_lines()
+        .map(|logical| {
+            let lines = logical.lines();
+            let text = logical.flat_map(|row| row[..].iter().map(|c| c.c));
+            (lines, text.collect())
+        })
+        .collect();
+
+    assert_eq!(
+        groups,
+        [
+            (Line(0)..Line(3), "abcdef".to_string()),
+            (Line(3)..Line(4), "gh".to_string()),
+            (Line(4)..Line(5), "ij".to_string()),
+        ]
+    );
+}
@@ -6,7 +6,11 @@ use regex_automata::hybrid::regex::{
 use regex_automata::util::syntax::Config as RegexSyntaxConfig;
 
 use crate::cell::Hyperlink;
-use crate::{Flags, Point, SnapshotCell, SnapshotSize, point_to_viewport};
+use crate::grid::Dimensions;
+use crate::index::Column;
+use crate::{
+    Cell, Flags, Grid, Point, SnapshotCell, SnapshotSize, point_to_viewport,
+};
 
 /// Regex pattern for detecting URLs in terminal content.
 ///
@@ -208,7 +212,7 @@ impl HyperlinkMap {
             return false;
         };
 
-        if span.link != *new_link {
+        if !span.link.is_same_link(new_link) {
             return false;
         }
 
@@ -337,6 +341,68 @@ impl HyperlinkMap {
     }
 }
 
+/// Find the OSC 8 hyperlink span covering `point` in the grid.
+///
+/// Neighbouring cells are grouped while they carry the same link, following
+/// soft wraps onto adjacent lines.
+pub(crate) fn osc_span_at(
+    grid: &Grid<Cell>,
+    point: Point,
+) -> Option<HyperlinkSpan> {
+    let in_grid = point.line >= grid.topmost_line()
+        && point.line <= grid.bottommost_line()
+        && point.column < grid.columns();
+    if !in_grid {
+        return None;
+    }
+
+    let link = grid[point].hyperlink()?;
+    let shares_link = |point: &Point| {
+        grid[*point]
+            .hyperlink()
+            .is_some_and(|other| link.is_same_link(&other))
+    };
+
+    let mut start = point;
+    while let Some(prev) = previous_cell(grid, start).filter(shares_link) {
+        start = prev;
+    }
+
+    let mut end = point;
+    while let Some(next) = next_cell(grid, end).filter(shares_link) {
+        end = next;
+    }
+
+    Some(HyperlinkSpan { link, start, end })
+}
+
+fn previous_cell(grid: &Grid<Cell>, point: Point) -> Option<Point> {
+    if point.column > 0 {
+        return Some(Point::new(point.line, point.column - 1));
+    }
+
+    let line = point.line - 1;
+    if line < grid.topmost_line() {
+        return None;
+    }
+
+    let last = grid.last_column();
+    grid[Point::new(line, last)]
+        .flags
+        .contains(Flags::WRAPLINE)
+        .then_some(Point::new(line, last))
+}
+
+fn next_cell(grid: &Grid<Cell>, point: Point) -> Option<Point> {
+    if point.column < grid.last_column() {
+        return Some(Point::new(point.line, point.column + 1));
+    }
+
+    let wraps = grid[point].flags.contains(Flags::WRAPLINE);
+    (wraps && point.line < grid.bottommost_line())
+        .then(|| Point::new(point.line + 1, Column(0)))
+}
+
 #[derive(Default)]
 struct LogicalLine {
     text: String,
@@ -537,4 +603,97 @@ mod tests {
         assert_eq!(first.link.uri(), "https://otty.sh");
         assert_eq!(second.link.uri(), "https://otty.sh");
     }
+
+    #[test]
+    fn build_keeps_osc_links_over_detected_urls() {
+        let columns = 40;
+        let mut cells =
+            cells_from_rows(&["https://shown.test https://otty.sh"], columns);
+        let link = Hyperlink::new(Some("docs"), "https://target.test".into());
+        for indexed in &mut cells[..18] {
+            indexed.cell.set_hyperlink(Some(link.clone()));
+        }
+        let size = SnapshotSize {
+            columns,
+            screen_lines: 1,
+            total_lines: 1,
+        };
+        let map = HyperlinkMap::build(&cells, size, 0);
+
+        let span = map
+            .span_for_point(0, Point::new(Line(0), Column(3)))
+            .expect("osc span");
+        assert_eq!(span.link.uri(), "https://target.test");
+        assert_eq!(span.end, Point::new(Line(0), Column(17)));
+
+        let span = map
+            .span_for_point(0, Point::new(Line(0), Column(20)))
+            .expect("detected span");
+        assert_eq!(span.link.uri(), "https://otty.sh");
+    }
+
+    fn link_cells(
+        grid: &mut Grid<Cell>,
+        columns: std::ops::Range<usize>,
+        link: &Hyperlink,
+    ) {
+        for column in columns {
+            grid[Line(0)][Column(column)].set_hyperlink(Some(link.clone()));
+        }
+    }
+
+    #[test]
+    fn osc_span_covers_each_distinct_link() {
+        let mut grid = Grid::<Cell>::new(2, 20, 0);
+        let first = Hyperlink::new(Some("a"), "https://a.test".into());
+        let second = Hyperlink::new(Some("b"), "https://b.test".into());
+        link_cells(&mut grid, 2..6, &first);
+        link_cells(&mut grid, 6..10, &second);
+
+        let span = osc_span_at(&grid, Point::new(Line(0), Column(3)))
+            .expect("first link span");
+        assert_eq!(span.link.uri(), "https://a.test");
+        assert_eq!(span.start, Point::new(Line(0), Column(2)));
+        assert_eq!(span.end, Point::new(Line(0), Column(5)));
+
+        let span = osc_span_at(&grid, Point::new(Line(0), Column(9)))
+            .expect("second link span");
+        assert_eq!(span.link.uri(), "https://b.test");
+        assert_eq!(span.start, Point::new(Line(0), Column(6)));
+        assert_eq!(span.end, Point::new(Line(0), Column(9)));
+
+        assert!(osc_span_at(&grid, Point::new(Line(0), Column(10))).is_none());
+    }
+
+    #[test]
+    fn osc_span_groups_contiguous_id_less_links_by_uri() {
+        let mut grid = Grid::<Cell>::new(2, 20, 0);
+        let uri = "https://otty.sh".to_string();
+        link_cells(
+            &mut grid,
+            0..3,
+            &Hyperlink::new(None::<String>, uri.clone()),
+        );
+        link_cells(
+            &mut grid,
+            3..6,
+            &Hyperlink::new(None::<String>, uri.clone()),
+        );
+        link_cells(&mut grid, 7..9, &Hyperlink::new(None::<String>, uri));
+        link_cells(
+            &mut grid,
+            9..11,
+            &Hyperlink::new(None::<String>, "https://other.sh".into()),
+        );
+
+        let span = osc_span_at(&grid, Point::new(Line(0), Column(1)))
+            .expect("grouped span");
+        assert_eq!(span.start, Point::new(Line(0), Column(0)));
+        assert_eq!(span.end, Point::new(Line(0), Column(5)));
+
+        let span = osc_span_at(&grid, Point::new(Line(0), Column(8)))
+            .expect("detached span");
+        assert_eq!(span.start, Point::new(Line(0), Column(7)));
+        assert_eq!(span.end, Point::new(Line(0), Column(8)));
+    }
 }
@@ -0,0 +1,53 @@
+//! Grid cells reserved for inline images.
+//!
+//! Image pixels are not stored on the surface. Each covered cell instead
+//! carries an [`ImageCell`] placeholder, so image regions scroll, clear and
+//! get overwritten together with the text around them.
+
+use crate::index::{Column, Point};
+
+/// Offset added to generated ids, keeping them apart from the 32-bit ids
+/// chosen by applications.
+pub(crate) const GENERATED_IMAGE_ID_BASE: u64 = 1 << 32;
+
+/// Placeholder stored in a cell covered by an image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageCell {
+    /// Image id, either chosen by the application or generated.
+    pub id: u64,
+    /// Width of the whole image region, in cells.
+    pub columns: usize,
+    /// Height of the whole image region, in lines.
+    pub lines: usize,
+    /// Line of this cell relative to the top of the region.
+    pub line_offset: usize,
+    /// Column of this cell relative to the left edge of the region.
+    pub column_offset: usize,
+}
+
+/// Region of the grid covered by an image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageRegion {
+    pub id: u64,
+    /// Top-left cell of the region in grid coordinates.
+    ///
+    /// The line may lie above the viewport while the image scrolls out.
+    pub start: Point,
+    pub columns: usize,
+    pub lines: usize,
+}
+
+impl ImageCell {
+    /// Region this placeholder belongs to, given the cell location.
+    pub fn region(&self, point: Point) -> ImageRegion {
+        ImageRegion {
+            id: self.id,
+            start: Point::new(
+                point.line - self.line_offset,
+                Column(point.column.0.saturating_sub(self.column_offset)),
+            ),
+            columns: self.columns,
+            lines: self.lines,
+        }
+    }
+}
@@ -1,34 +1,53 @@
 //! Terminal surface abstraction for grid‑based terminal content.
 
 mod actor;
+#[cfg(feature = "bidi")]
+mod bidi;
 mod block;
 mod block_text;
 mod cell;
 mod color;
+mod copy_mode;
 mod damage;
 mod grid;
 mod hyperlink;
+mod image;
 mod index;
 mod mode;
 mod search;
+mod search_index;
 mod selection;
+mod semantic_zone;
 mod snapshot;
 mod surface;
 
 pub use actor::SurfaceActor;
-pub use block::{BlockKind, BlockMeta, BlockSnapshot, BlockSurface};
+#[cfg(feature = "bidi")]
+pub use bidi::{BidiRow, BidiRun, TextDirection};
+pub use block::{
+    BlockKind, BlockMeta, BlockOutput, BlockSnapshot, BlockSurface,
+    SurfaceLayout,
+};
 pub use block_text::collect_block_text;
-pub use cell::{Cell, Flags};
+pub use cell::{Cell, Flags, Hyperlink as HyperlinkRef};
 pub use color::Colors;
-pub use grid::{Dimensions, Grid, Scroll};
+pub use copy_mode::Motion;
+pub use damage::DamageRect;
+pub use grid::{Dimensions, Grid, LineKind, LogicalLine, LogicalLines, Scroll};
+pub use hyperlink::HyperlinkSpan;
+pub use image::{ImageCell, ImageRegion};
 pub use index::{Column, Line, Point, Side};
 pub use mode::SurfaceMode;
 pub(crate) use otty_escape as escape;
 pub use search::{Match, RegexIter, RegexSearch};
-pub use selection::{SelectionRange, SelectionType};
+pub use selection::{
+    SelectionGranularity, SelectionRange, SelectionSpan, SelectionType,
+    VisibleSelection,
+};
+pub use semantic_zone::SemanticZone;
 pub use snapshot::{
     CursorSnapshot, SnapshotCell, SnapshotDamage, SnapshotOwned, SnapshotSize,
-    SnapshotView, SurfaceModel,
+    SnapshotView, SurfaceModel, VerticalDir,
 };
 pub use surface::{
     Surface, SurfaceConfig, point_to_viewport, viewport_to_point,
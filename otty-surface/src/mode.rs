@@ -35,6 +35,7 @@ bitflags! {
         const REPORT_ALTERNATE_KEYS   = 1 << 19;
         const REPORT_ALL_KEYS_AS_ESC  = 1 << 20;
         const REPORT_ASSOCIATED_TEXT  = 1 << 21;
+        const REVERSE_WRAP            = 1 << 22;
         /// Convenience mask for all mouse reporting modes.
         const MOUSE_MODE              = Self::MOUSE_REPORT_CLICK.bits() | Self::MOUSE_MOTION.bits() | Self::MOUSE_DRAG.bits();
         /// Convenience mask for all Kitty keyboard protocol modes.
@@ -24,6 +24,9 @@ pub struct RegexSearch {
     left_rdfa: LazyDfa,
     right_rdfa: LazyDfa,
     right_fdfa: LazyDfa,
+    /// Pattern text when it is a plain ASCII literal.
+    literal: Option<String>,
+    case_insensitive: bool,
 }
 
 impl RegexSearch {
@@ -74,15 +77,46 @@ impl RegexSearch {
             true,
         )?;
 
+        let is_literal = !search.is_empty()
+            && search
+                .chars()
+                .all(|c| c.is_ascii() && !is_meta_character(c));
+
         Ok(RegexSearch {
             left_fdfa,
             left_rdfa,
             right_fdfa,
             right_rdfa,
+            literal: is_literal.then(|| search.to_owned()),
+            case_insensitive: !has_uppercase,
         })
     }
 }
 
+/// Characters with a special meaning in the regex syntax.
+fn is_meta_character(c: char) -> bool {
+    matches!(
+        c,
+        '\\' | '.'
+            | '+'
+            | '*'
+            | '?'
+            | '('
+            | ')'
+            | '|'
+            | '['
+            | ']'
+            | '{'
+            | '}'
+            | '^'
+            | '$'
+            | '#'
+            | '&'
+            | '-'
+            | '~'
+    )
+}
+
 /// Runtime-evaluated DFA used for a single direction.
 #[derive(Clone, Debug)]
 struct LazyDfa {
@@ -128,6 +162,62 @@ impl LazyDfa {
 }
 
 impl Surface {
+    /// Collect every match from the top of the scrollback downwards.
+    ///
+    /// With [`crate::SurfaceConfig::history_search_index`] enabled, literal
+    /// patterns skip scrollback lines that can't contain them.
+    pub fn find_all(&self, regex: &mut RegexSearch) -> Vec<Match> {
+        let topmost_line = self.topmost_line();
+        let bottommost_line = self.bottommost_line();
+        let last_column = self.last_column();
+        let index = self.history_search_index();
+
+        let (Some(index), Some(literal)) = (index, regex.literal.clone())
+        else {
+            let start = Point::new(topmost_line, Column(0));
+            let end = Point::new(bottommost_line, last_column);
+            return RegexIter::new(start, end, Direction::Right, self, regex)
+                .collect();
+        };
+
+        let mut matches = Vec::new();
+        let mut line = topmost_line;
+        while line <= bottommost_line {
+            // Literals can't match across unwrapped line breaks, so every
+            // logical line is searched on its own.
+            let mut end_line = line;
+            while end_line < bottommost_line
+                && self.grid()[end_line][last_column]
+                    .flags
+                    .contains(Flags::WRAPLINE)
+            {
+                end_line += 1;
+            }
+
+            let skip = end_line < 0
+                && !index.may_contain(
+                    line..=end_line,
+                    &literal,
+                    regex.case_insensitive,
+                );
+            if !skip {
+                let start = Point::new(line, Column(0));
+                let end = Point::new(end_line, last_column);
+                matches.extend(RegexIter::new(
+                    start,
+                    end,
+                    Direction::Right,
+                    self,
+                    regex,
+                ));
+            }
+
+            line = end_line + 1;
+        }
+
+        matches
+    }
+
     /// Find next search match relative to `origin` in `direction`.
     pub fn search_next(
         &self,
@@ -529,9 +619,11 @@ impl Iterator for RegexIter<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::escape::ClearMode;
     use crate::grid::Dimensions;
     use crate::index::{Column, Line};
-    use crate::{Surface, SurfaceConfig};
+    use crate::search_index::HistorySearchIndex;
+    use crate::{Surface, SurfaceActor, SurfaceConfig};
 
     struct TestDimensions {
         columns: usize,
@@ -586,6 +678,83 @@ mod tests {
         }
     }
 
+    fn scrollback_surface(history_search_index: bool) -> Surface {
+        let config = SurfaceConfig {
+            scrolling_history: 64,
+            history_search_index,
+            ..SurfaceConfig::default()
+        };
+        Surface::new(config, &TestDimensions::new(12, 4))
+    }
+
+    fn feed(surface: &mut Surface, text: &str) {
+        for c in text.chars() {
+            if c == '\n' {
+                surface.carriage_return();
+                surface.line_feed();
+            } else {
+                surface.print(c);
+            }
+        }
+    }
+
+    fn scrollback_text() -> String {
+        let mut text = String::new();
+        for i in 0..120 {
+            let line = match i % 7 {
+                0 => format!("needle {i}"),
+                1 => format!("wrapped text nee{i}dle across needle"),
+                2 => format!("NEEDLE {i}"),
+                3 => format!("ünïcode needle {i}"),
+                _ => format!("hay {i}"),
+            };
+            text.push_str(&line);
+            text.push('\n');
+        }
+        text
+    }
+
+    fn assert_index_matches_naive(indexed: &Surface, naive: &Surface) {
+        for pattern in ["needle", "NEEDLE", "dle acr", "ne+dle", "hay 1"] {
+            let mut regex = RegexSearch::new(pattern).unwrap();
+            let expected = naive.find_all(&mut regex);
+            assert!(!expected.is_empty(), "no matches for {pattern}");
+            assert_eq!(indexed.find_all(&mut regex), expected, "{pattern}");
+        }
+    }
+
+    #[test]
+    fn indexed_find_all_matches_naive_search() {
+        let mut indexed = scrollback_surface(true);
+        let mut naive = scrollback_surface(false);
+        let text = scrollback_text();
+        feed(&mut indexed, &text);
+        feed(&mut naive, &text);
+
+        assert!(indexed.history_search_index().is_some());
+        assert!(naive.history_search_index().is_none());
+        assert_index_matches_naive(&indexed, &naive);
+    }
+
+    #[test]
+    fn history_index_stays_consistent_as_lines_scroll_off() {
+        let mut surface = scrollback_surface(true);
+        feed(&mut surface, &scrollback_text());
+        let mut rebuilt = HistorySearchIndex::default();
+        rebuilt.rebuild(surface.grid());
+        assert_eq!(surface.history_search_index(), Some(&rebuilt));
+
+        surface.resize(TestDimensions::new(9, 5));
+        feed(&mut surface, "after resize needle\n");
+        rebuilt.rebuild(surface.grid());
+        assert_eq!(surface.history_search_index(), Some(&rebuilt));
+
+        surface.clear_screen(ClearMode::Saved);
+        rebuilt.rebuild(surface.grid());
+        assert_eq!(surface.history_search_index(), Some(&rebuilt));
+        assert_eq!(surface.grid().history_size(), 0);
+    }
+
     #[test]
     fn test_regex_search_new_creates_valid_regex() {
         let result = RegexSearch::new("test");
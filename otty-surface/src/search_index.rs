@@ -0,0 +1,110 @@
+//! Plain-text index over scrollback rows.
+//!
+//! Rows are recorded once they scroll into history, where they no longer
+//! change. Literal searches use the index to skip history lines that cannot
+//! contain the pattern instead of feeding every cell through the regex DFA.
+
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+
+use crate::cell::{Cell, Flags};
+use crate::grid::{Dimensions, Grid};
+use crate::index::{Column, Line};
+
+/// Text of every scrollback row, ordered from the oldest to the newest.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct HistorySearchIndex {
+    /// Row text, or `None` for rows with content the index can't reason
+    /// about (non-ASCII, wide or combining characters).
+    rows: VecDeque<Option<Box<str>>>,
+}
+
+impl HistorySearchIndex {
+    /// Re-read the whole scrollback of `grid`.
+    pub(crate) fn rebuild(&mut self, grid: &Grid<Cell>) {
+        self.rows.clear();
+        let history_size = grid.history_size() as i32;
+        self.rows
+            .extend((1..=history_size).rev().map(|n| row_text(grid, Line(-n))));
+    }
+
+    /// Record `count` rows that just scrolled into the history of `grid`.
+    ///
+    /// Rows pushed out of a full scrollback are dropped from the front.
+    pub(crate) fn push_scrolled(&mut self, grid: &Grid<Cell>, count: usize) {
+        let history_size = grid.history_size();
+        let count = count.min(history_size) as i32;
+        self.rows
+            .extend((1..=count).rev().map(|n| row_text(grid, Line(-n))));
+
+        while self.rows.len() > history_size {
+            self.rows.pop_front();
+        }
+    }
+
+    /// Check whether the history `lines` may contain `literal`.
+    ///
+    /// Lines outside of the index, or ones it can't reason about, always
+    /// report a possible match.
+    pub(crate) fn may_contain(
+        &self,
+        lines: RangeInclusive<Line>,
+        literal: &str,
+        case_insensitive: bool,
+    ) -> bool {
+        let mut text = String::new();
+        for line in lines.start().0..=lines.end().0 {
+            let Some(Some(row)) = self.row(Line(line)) else {
+                return true;
+            };
+            text.push_str(row);
+        }
+
+        let (text, literal) = (text.as_bytes(), literal.as_bytes());
+        if literal.is_empty() {
+            return true;
+        }
+
+        text.windows(literal.len()).any(|window| {
+            if case_insensitive {
+                window.eq_ignore_ascii_case(literal)
+            } else {
+                window == literal
+            }
+        })
+    }
+
+    fn row(&self, line: Line) -> Option<&Option<Box<str>>> {
+        if line.0 >= 0 {
+            return None;
+        }
+
+        let index = self
+            .rows
+            .len()
+            .checked_sub(line.0.unsigned_abs() as usize)?;
+        self.rows.get(index)
+    }
+}
+
+/// Extract the text of a row if it only holds plain ASCII cells.
+fn row_text(grid: &Grid<Cell>, line: Line) -> Option<Box<str>> {
+    let row = &grid[line];
+    let mut text = String::with_capacity(grid.columns());
+    for column in 0..grid.columns() {
+        let cell = &row[Column(column)];
+        if !cell.c.is_ascii()
+            || cell.zerowidth().is_some()
+            || cell.flags.intersects(
+                Flags::WIDE_CHAR
+                    | Flags::WIDE_CHAR_SPACER
+                    | Flags::LEADING_WIDE_CHAR_SPACER,
+            )
+        {
+            return None;
+        }
+        text.push(cell.c);
+    }
+
+    Some(text.into_boxed_str())
+}
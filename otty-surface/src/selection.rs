@@ -39,6 +39,24 @@ pub struct SelectionRange {
     pub is_block: bool,
 }
 
+/// Columns of one viewport row covered by a selection.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SelectionSpan {
+    /// Viewport row, counted from the top of the viewport.
+    pub line: usize,
+    /// First selected column.
+    pub start: Column,
+    /// Last selected column, inclusive.
+    pub end: Column,
+}
+
+/// Part of a [`SelectionRange`] intersecting the viewport.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VisibleSelection {
+    /// Selected spans, one per covered row, ordered from the top.
+    pub spans: Vec<SelectionSpan>,
+}
+
 impl SelectionRange {
     pub fn new(start: Point, end: Point, is_block: bool) -> Self {
         assert!(start <= end);
@@ -61,6 +79,61 @@ impl SelectionRange {
                 || (self.end.line != point.line && !self.is_block))
     }
 
+    /// Clip the selection to a viewport of `rows` × `columns` cells
+    /// scrolled `display_offset` lines into the history.
+    ///
+    /// Rows between the first and the last line of a non-block selection
+    /// are covered up to the last column, as they continue on the next row.
+    /// Returns `None` if no part of the selection is visible.
+    pub fn clip_to_viewport(
+        &self,
+        display_offset: usize,
+        rows: usize,
+        columns: usize,
+    ) -> Option<VisibleSelection> {
+        if rows == 0 || columns == 0 {
+            return None;
+        }
+
+        let top = Line(0) - display_offset;
+        let bottom = top + (rows - 1);
+        let first = self.start.line.max(top);
+        let last = self.end.line.min(bottom);
+        if first > last {
+            return None;
+        }
+
+        let last_column = Column(columns - 1);
+        let spans = (first.0..=last.0)
+            .map(Line)
+            .map(|line| {
+                let (start, end) = if self.is_block {
+                    (self.start.column, self.end.column)
+                } else {
+                    let start = if line == self.start.line {
+                        self.start.column
+                    } else {
+                        Column(0)
+                    };
+                    let end = if line == self.end.line {
+                        self.end.column
+                    } else {
+                        last_column
+                    };
+                    (start, end)
+                };
+
+                SelectionSpan {
+                    line: (line.0 + display_offset as i32) as usize,
+                    start: start.min(last_column),
+                    end: end.min(last_column),
+                }
+            })
+            .collect();
+
+        Some(VisibleSelection { spans })
+    }
+
     /// Check if the cell at a point is part of the selection.
     pub fn contains_cell(
         &self,
@@ -109,6 +182,17 @@ pub enum SelectionType {
     Lines,
 }
 
+/// Unit the far end of an extended selection snaps to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SelectionGranularity {
+    /// Extend to the exact cell.
+    Char,
+    /// Extend to the word boundary beyond the point.
+    Word,
+    /// Extend to the line boundary beyond the point.
+    Line,
+}
+
 /// Describes a region of a 2-dimensional area.
 ///
 /// Used to track a text selection. There are four supported modes, each with its own constructor:
@@ -143,6 +227,41 @@ impl Selection {
         self.region.end = Anchor::new(point, side);
     }
 
+    /// Move the far end to `point`, snapping it away from the anchor.
+    ///
+    /// The anchor cell stays selected when the end crosses over it.
+    pub fn extend(
+        &mut self,
+        surface: &Surface,
+        point: Point,
+        granularity: SelectionGranularity,
+    ) {
+        let backwards = point < self.region.start.point;
+        let point = match (granularity, backwards) {
+            (SelectionGranularity::Char, _) => point,
+            (SelectionGranularity::Word, true) => {
+                surface.semantic_search_left(point)
+            },
+            (SelectionGranularity::Word, false) => {
+                surface.semantic_search_right(point)
+            },
+            (SelectionGranularity::Line, true) => {
+                surface.line_search_left(point)
+            },
+            (SelectionGranularity::Line, false) => {
+                surface.line_search_right(point)
+            },
+        };
+
+        let (start_side, end_side) = if backwards {
+            (Side::Right, Side::Left)
+        } else {
+            (Side::Left, Side::Right)
+        };
+        self.region.start.side = start_side;
+        self.region.end = Anchor::new(point, end_side);
+    }
+
     pub fn rotate<D: Dimensions>(
         mut self,
         dimensions: &D,
@@ -636,6 +755,78 @@ mod tests {
         );
     }
 
+    fn term_with_text(text: &str) -> Surface {
+        let mut surface = term(2, 12);
+        for (column, c) in text.chars().enumerate() {
+            surface.grid_mut()[Line(0)][Column(column)].c = c;
+        }
+        surface
+    }
+
+    #[test]
+    fn extend_snaps_far_end_to_word_boundary() {
+        let surface = term_with_text("foo bar baz");
+        let mut selection = Selection::new(
+            SelectionType::Simple,
+            Point::new(Line(0), Column(4)),
+            Side::Left,
+        );
+
+        selection.extend(
+            &surface,
+            Point::new(Line(0), Column(9)),
+            SelectionGranularity::Word,
+        );
+
+        assert_eq!(
+            selection.to_range(&surface).unwrap(),
+            SelectionRange::new(
+                Point::new(Line(0), Column(4)),
+                Point::new(Line(0), Column(10)),
+                false,
+            )
+        );
+    }
+
+    #[test]
+    fn extend_before_anchor_flips_and_keeps_anchor_cell() {
+        let surface = term_with_text("foo bar baz");
+        let mut selection = Selection::new(
+            SelectionType::Simple,
+            Point::new(Line(0), Column(4)),
+            Side::Left,
+        );
+        selection.update(Point::new(Line(0), Column(6)), Side::Right);
+
+        selection.extend(
+            &surface,
+            Point::new(Line(0), Column(2)),
+            SelectionGranularity::Char,
+        );
+        assert_eq!(
+            selection.to_range(&surface).unwrap(),
+            SelectionRange::new(
+                Point::new(Line(0), Column(2)),
+                Point::new(Line(0), Column(4)),
+                false,
+            )
+        );
+
+        selection.extend(
+            &surface,
+            Point::new(Line(0), Column(1)),
+            SelectionGranularity::Word,
+        );
+        assert_eq!(
+            selection.to_range(&surface).unwrap(),
+            SelectionRange::new(
+                Point::new(Line(0), Column(0)),
+                Point::new(Line(0), Column(4)),
+                false,
+            )
+        );
+    }
+
     #[test]
     fn semantic_selection() {
         let size = (10, 5);
@@ -827,4 +1018,70 @@ mod tests {
         assert!(!selection.intersects_range(..=Line(2)));
         assert!(!selection.intersects_range(Line(7)..=Line(8)));
     }
+
+    #[test]
+    fn clip_to_viewport_keeps_rows_below_the_top() {
+        // Rows -3..=1 are selected and the viewport shows lines -1..=2.
+        let range = SelectionRange::new(
+            Point::new(Line(-3), Column(4)),
+            Point::new(Line(1), Column(2)),
+            false,
+        );
+
+        let visible = range.clip_to_viewport(1, 4, 6).expect("visible");
+
+        assert_eq!(
+            visible.spans,
+            [
+                SelectionSpan {
+                    line: 0,
+                    start: Column(0),
+                    end: Column(5),
+                },
+                SelectionSpan {
+                    line: 1,
+                    start: Column(0),
+                    end: Column(5),
+                },
+                SelectionSpan {
+                    line: 2,
+                    start: Column(0),
+                    end: Column(2),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn clip_to_viewport_keeps_block_columns() {
+        let range = SelectionRange::new(
+            Point::new(Line(-2), Column(1)),
+            Point::new(Line(0), Column(3)),
+            true,
+        );
+
+        let visible = range.clip_to_viewport(0, 3, 6).expect("visible");
+
+        assert_eq!(
+            visible.spans,
+            [SelectionSpan {
+                line: 0,
+                start: Column(1),
+                end: Column(3),
+            }]
+        );
+    }
+
+    #[test]
+    fn clip_to_viewport_skips_selections_off_screen() {
+        let range = SelectionRange::new(
+            Point::new(Line(-5), Column(0)),
+            Point::new(Line(-3), Column(2)),
+            false,
+        );
+
+        assert_eq!(range.clip_to_viewport(0, 4, 6), None);
+        assert_eq!(range.clip_to_viewport(2, 4, 6), None);
+        assert!(range.clip_to_viewport(3, 4, 6).is_some());
+    }
 }
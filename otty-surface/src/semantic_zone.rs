@@ -0,0 +1,112 @@
+//! Command zones recorded from the `OSC 133` semantic prompt marks.
+//!
+//! A zone spans one command: its prompt, the command line and the output.
+//! Zones let the surface find the output of a command and its exit status
+//! without the DCS block protocol.
+
+use std::ops::Range;
+
+use crate::index::Line;
+
+/// Upper bound for the zones kept by a surface, oldest dropped first.
+pub(crate) const MAX_SEMANTIC_ZONES: usize = 256;
+
+/// One command delimited by semantic prompt marks, in grid coordinates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemanticZone {
+    /// Line where the prompt of the command starts.
+    pub prompt_start: Line,
+    /// Line where the command output starts, once the command was
+    /// submitted.
+    pub output_start: Option<Line>,
+    /// Line after the last output line, once the command finished.
+    pub output_end: Option<Line>,
+    /// Exit status reported with the end of the command.
+    pub exit_code: Option<i32>,
+}
+
+impl SemanticZone {
+    fn new(prompt_start: Line) -> Self {
+        Self {
+            prompt_start,
+            output_start: None,
+            output_end: None,
+            exit_code: None,
+        }
+    }
+
+    /// Lines of the command output, once the command finished.
+    pub fn output_lines(&self) -> Option<Range<Line>> {
+        Some(self.output_start?..self.output_end?)
+    }
+
+    /// Whether the command finished.
+    pub fn is_finished(&self) -> bool {
+        self.output_end.is_some()
+    }
+
+    fn shift(&mut self, delta: i32) {
+        self.prompt_start -= delta;
+        if let Some(line) = self.output_start.as_mut() {
+            *line -= delta;
+        }
+        if let Some(line) = self.output_end.as_mut() {
+            *line -= delta;
+        }
+    }
+}
+
+/// Zones of a surface, oldest first.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SemanticZones {
+    zones: Vec<SemanticZone>,
+}
+
+impl SemanticZones {
+    pub(crate) fn as_slice(&self) -> &[SemanticZone] {
+        &self.zones
+    }
+
+    /// Open a zone for the prompt starting at `line`.
+    pub(crate) fn start_prompt(&mut self, line: Line) {
+        if self.zones.len() == MAX_SEMANTIC_ZONES {
+            self.zones.remove(0);
+        }
+        self.zones.push(SemanticZone::new(line));
+    }
+
+    /// Record where the output of the latest command starts.
+    pub(crate) fn start_output(&mut self, line: Line) {
+        if let Some(zone) = self.open_zone() {
+            zone.output_start = Some(line);
+        }
+    }
+
+    /// Close the latest command, its output ending before `line`.
+    pub(crate) fn finish(&mut self, line: Line, exit_code: Option<i32>) {
+        if let Some(zone) = self.open_zone() {
+            let start = *zone.output_start.get_or_insert(line);
+            zone.output_end = Some(line.max(start));
+            zone.exit_code = exit_code;
+        }
+    }
+
+    /// Move the zones with text scrolled up by `delta` lines, dropping the
+    /// ones that left the grid above `topmost`.
+    pub(crate) fn scroll_up(&mut self, delta: i32, topmost: Line) {
+        for zone in &mut self.zones {
+            zone.shift(delta);
+        }
+        self.zones.retain(|zone| {
+            zone.output_end.unwrap_or(zone.prompt_start) >= topmost
+        });
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.zones.clear();
+    }
+
+    fn open_zone(&mut self) -> Option<&mut SemanticZone> {
+        self.zones.last_mut().filter(|zone| !zone.is_finished())
+    }
+}
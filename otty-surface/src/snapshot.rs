@@ -1,28 +1,48 @@
-use crate::block::BlockSnapshot;
-use crate::cell::{Cell, Flags};
+use std::borrow::Cow;
+
+#[cfg(feature = "bidi")]
+use crate::bidi::BidiRow;
+use crate::block::{BlockOutput, BlockSnapshot, SurfaceLayout};
+use crate::cell::{Cell, Flags, Hyperlink};
 use crate::color::Colors;
-use crate::damage::{LineDamageBounds, SurfaceDamage};
+use crate::damage::{
+    DamageRect, MAX_DAMAGE_RECTS, SurfaceDamage, merge_line_damage,
+};
 use crate::escape::CursorShape;
-use crate::grid::Dimensions;
+use crate::grid::{Dimensions, LineKind};
 use crate::hyperlink::{HyperlinkMap, HyperlinkSpan};
-use crate::index::Point;
+use crate::image::ImageRegion;
+use crate::index::{Column, Line, Point};
 use crate::mode::SurfaceMode;
+use crate::search::Match;
 use crate::selection::SelectionRange;
 use crate::surface::Surface;
 
 /// Terminal cursor rendering information.
-#[derive(Default, Clone, PartialEq, Eq)]
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct CursorSnapshot {
     pub shape: CursorShape,
     pub cell: Cell,
     pub point: Point,
+    /// Whether `point` is the copy mode cursor rather than the terminal one.
+    pub copy_mode: bool,
 }
 
 impl CursorSnapshot {
     /// Construct a renderable cursor description from the given surface.
     ///
-    /// This accounts for wide characters and cursor visibility modes.
+    /// This accounts for wide characters and cursor visibility modes. While
+    /// copy mode is active the copy cursor is reported as a block instead.
     pub(crate) fn new(surface: &Surface) -> Self {
+        if let Some(point) = surface.copy_cursor() {
+            return Self {
+                shape: CursorShape::Block,
+                point,
+                cell: surface.grid().cursor.template.clone(),
+                copy_mode: true,
+            };
+        }
+
         // Cursor position.
         let mut point = surface.grid().cursor.point;
         if surface.grid()[point]
@@ -43,10 +63,18 @@ impl CursorSnapshot {
             shape,
             point,
             cell: surface.grid().cursor.template.clone(),
+            copy_mode: false,
         }
     }
 }
 
+/// Side of the viewport an off-screen cursor lies on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerticalDir {
+    Above,
+    Below,
+}
+
 /// Visible terminal content.
 ///
 /// This contains all content required to render the current terminal view.
@@ -57,6 +85,38 @@ pub struct SnapshotCell {
     pub cell: Cell,
 }
 
+impl SnapshotCell {
+    /// Shared reference to the OSC 8 hyperlink stored on this cell.
+    #[inline]
+    pub fn hyperlink(&self) -> Option<Hyperlink> {
+        self.cell.hyperlink()
+    }
+
+    /// See [`Cell::is_wide`].
+    #[inline]
+    pub fn is_wide(&self) -> bool {
+        self.cell.is_wide()
+    }
+
+    /// See [`Cell::is_wide_spacer`].
+    #[inline]
+    pub fn is_wide_spacer(&self) -> bool {
+        self.cell.is_wide_spacer()
+    }
+
+    /// See [`Cell::is_wrapped`].
+    #[inline]
+    pub fn is_wrapped(&self) -> bool {
+        self.cell.is_wrapped()
+    }
+
+    /// See [`Cell::is_blank`].
+    #[inline]
+    pub fn is_blank(&self) -> bool {
+        self.cell.is_blank()
+    }
+}
+
 /// Geometry captured alongside an owned snapshot.
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SnapshotSize {
@@ -70,23 +130,33 @@ pub struct SnapshotSize {
 pub enum SnapshotDamage {
     #[default]
     Full,
-    Partial(Vec<LineDamageBounds>),
+    /// Merged damaged regions; too many regions collapse into [`Self::Full`].
+    Partial(Vec<DamageRect>),
 }
 
 /// Owned snapshot capturing all renderable surface state.
-#[derive(Default, Clone)]
+///
+/// Equality compares the rendered state and ignores the damage, which only
+/// tells how the snapshot was reached.
+#[derive(Default, Clone, Debug)]
 pub struct SnapshotOwned {
     cells: Vec<SnapshotCell>,
     selection: Option<SelectionRange>,
+    extra_selections: Vec<SelectionRange>,
+    search_match: Option<Match>,
     hyperlinks: HyperlinkMap,
     cursor: CursorSnapshot,
     display_offset: usize,
     colors: Colors,
     mode: SurfaceMode,
+    focused: bool,
     size: SnapshotSize,
     damage: SnapshotDamage,
     visible_cell_count: usize,
     pub blocks: Vec<BlockSnapshot>,
+    pub(crate) layout: SurfaceLayout,
+    /// Role of every viewport row, from top to bottom.
+    pub(crate) line_kinds: Vec<LineKind>,
 }
 
 /// View over an owned snapshot suitable for rendering.
@@ -95,16 +165,34 @@ pub struct SnapshotView<'a> {
     pub cells: &'a [SnapshotCell],
     /// Resolved selection range in grid coordinates, if any.
     pub selection: Option<&'a SelectionRange>,
+    /// Earlier ranges of a multi-range selection, oldest first.
+    pub extra_selections: &'a [SelectionRange],
+    /// Search match currently under focus, in grid coordinates.
+    pub search_match: Option<&'a Match>,
     /// Hyperlink mapping for the visible viewport.
     pub(crate) hyperlinks: &'a HyperlinkMap,
     /// Cursor state suitable for rendering.
     pub cursor: &'a CursorSnapshot,
-    /// Current scrollback display offset.
+    /// Cursor location relative to the top of the viewport, `None` while
+    /// the viewport is scrolled away from it.
+    pub cursor_viewport: Option<Point<usize>>,
+    /// Side of the viewport the cursor lies on when it is off-screen, so
+    /// UIs can point at it.
+    pub cursor_direction: Option<VerticalDir>,
+    /// Lines the viewport is scrolled above the bottom, zero while
+    /// following the live output.
     pub display_offset: usize,
+    /// Scrollback plus screen lines the viewport can scroll across.
+    pub total_lines: usize,
     /// Effective color palette.
     pub colors: &'a Colors,
     /// Active surface modes.
     pub mode: SurfaceMode,
+    /// Whether the frame shows the alternate screen rather than the
+    /// primary one with its scrollback.
+    pub alt_screen: bool,
+    /// Whether the terminal had input focus at capture time.
+    pub focused: bool,
     /// Grid geometry at capture time.
     pub size: SnapshotSize,
     /// Damage collected since last reset.
@@ -113,34 +201,70 @@ pub struct SnapshotView<'a> {
     pub visible_cell_count: usize,
     /// Ordered list of block metadata captured alongside this snapshot.
     pub(crate) blocks: &'a [BlockSnapshot],
+    /// Whether the content is presented as a plain grid or as blocks.
+    pub layout: SurfaceLayout,
+    /// Role of every viewport row, from top to bottom.
+    pub line_kinds: &'a [LineKind],
 }
 
 impl SnapshotOwned {
+    /// Compare only the content of two snapshots: cells, geometry and
+    /// blocks, ignoring the cursor, selection, modes and damage.
+    pub fn content_eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && self.cells == other.cells
+            && self.line_kinds == other.line_kinds
+            && self.blocks == other.blocks
+    }
+
     /// Borrow this owned snapshot as a lightweight view.
     pub fn view(&self) -> SnapshotView<'_> {
+        let cursor_line = self.cursor.point.line.0 + self.display_offset as i32;
+        let cursor_direction = if cursor_line < 0 {
+            Some(VerticalDir::Above)
+        } else if cursor_line as usize >= self.size.screen_lines {
+            Some(VerticalDir::Below)
+        } else {
+            None
+        };
+        let cursor_viewport = cursor_direction.is_none().then(|| {
+            Point::new(cursor_line as usize, self.cursor.point.column)
+        });
+
         SnapshotView {
             cells: &self.cells,
             selection: self.selection.as_ref(),
+            extra_selections: &self.extra_selections,
+            search_match: self.search_match.as_ref(),
             hyperlinks: &self.hyperlinks,
             cursor: &self.cursor,
+            cursor_viewport,
+            cursor_direction,
             display_offset: self.display_offset,
+            total_lines: self.size.total_lines,
             colors: &self.colors,
             mode: self.mode,
+            alt_screen: self.mode.contains(SurfaceMode::ALT_SCREEN),
+            focused: self.focused,
             size: self.size,
             damage: &self.damage,
             visible_cell_count: self.visible_cell_count,
             blocks: &self.blocks,
+            layout: self.layout,
+            line_kinds: &self.line_kinds,
         }
     }
 
     pub(crate) fn from_parts(
         cells: Vec<SnapshotCell>,
         selection: Option<SelectionRange>,
+        search_match: Option<Match>,
         hyperlinks: HyperlinkMap,
         cursor: CursorSnapshot,
         display_offset: usize,
         colors: Colors,
         mode: SurfaceMode,
+        focused: bool,
         size: SnapshotSize,
         damage: SnapshotDamage,
         visible_cell_count: usize,
@@ -149,19 +273,29 @@ impl SnapshotOwned {
         SnapshotOwned {
             cells,
             selection,
+            extra_selections: Vec::new(),
+            search_match,
             hyperlinks,
             cursor,
             display_offset,
             colors,
             mode,
+            focused,
             size,
             damage,
             visible_cell_count,
             blocks,
+            layout: SurfaceLayout::Blocks,
+            line_kinds: Vec::new(),
         }
     }
 
     pub fn from_surface(surface: &mut Surface) -> SnapshotOwned {
+        let damage = SnapshotDamage::from(surface.damage());
+        Self::capture(surface, damage)
+    }
+
+    fn capture(surface: &Surface, damage: SnapshotDamage) -> SnapshotOwned {
         let mut cells =
             Vec::with_capacity(surface.grid().display_iter().count());
         for indexed in surface.grid().display_iter() {
@@ -173,10 +307,17 @@ impl SnapshotOwned {
 
         let selection =
             surface.selection.as_ref().and_then(|s| s.to_range(surface));
+        let extra_selections = surface
+            .extra_selections()
+            .iter()
+            .filter_map(|s| s.to_range(surface))
+            .collect();
+        let search_match = surface.search_match().cloned();
         let cursor = CursorSnapshot::new(surface);
         let display_offset = surface.grid().display_offset();
         let colors = *surface.colors();
         let mode = *surface.mode();
+        let focused = surface.is_focused;
         let size = SnapshotSize {
             columns: surface.grid().columns(),
             screen_lines: surface.grid().screen_lines(),
@@ -185,24 +326,91 @@ impl SnapshotOwned {
         let visible_cell_count = size.columns * size.screen_lines;
         let hyperlinks = HyperlinkMap::build(&cells, size, display_offset);
 
-        let damage = SnapshotDamage::from(surface.damage());
+        let grid = surface.grid();
+        let line_kinds = (0..size.screen_lines)
+            .map(|row| grid[Line(row as i32 - display_offset as i32)].kind)
+            .collect();
 
         SnapshotOwned {
             cells,
             selection,
+            extra_selections,
+            search_match,
             hyperlinks,
             cursor,
             display_offset,
             colors,
             mode,
+            focused,
             size,
             damage,
             visible_cell_count,
             blocks: Vec::new(),
+            layout: SurfaceLayout::Grid,
+            line_kinds,
         }
     }
 }
 
+impl Surface {
+    /// Capture the visible rows at the current scroll position, in viewport
+    /// coordinates.
+    ///
+    /// Line `0` is the top row of the viewport, and the cursor, selections
+    /// and search match are shifted the same way. The snapshot knows nothing
+    /// of the scrollback: its display offset is `0` and it holds exactly
+    /// `screen_lines` lines. Damage is always [`SnapshotDamage::Full`] and
+    /// the surface damage is left untouched.
+    pub fn snapshot_viewport(&self) -> SnapshotOwned {
+        let mut snapshot = SnapshotOwned::capture(self, SnapshotDamage::Full);
+        let offset = snapshot.display_offset as i32;
+        let shift = |point: &mut Point| point.line += offset;
+
+        for indexed in &mut snapshot.cells {
+            shift(&mut indexed.point);
+        }
+        shift(&mut snapshot.cursor.point);
+        for range in snapshot
+            .selection
+            .iter_mut()
+            .chain(snapshot.extra_selections.iter_mut())
+        {
+            shift(&mut range.start);
+            shift(&mut range.end);
+        }
+        if let Some(search_match) = snapshot.search_match.as_mut() {
+            let (mut start, mut end) =
+                (*search_match.start(), *search_match.end());
+            shift(&mut start);
+            shift(&mut end);
+            *search_match = start..=end;
+        }
+
+        snapshot.display_offset = 0;
+        snapshot.size.total_lines = snapshot.size.screen_lines;
+        snapshot.hyperlinks =
+            HyperlinkMap::build(&snapshot.cells, snapshot.size, 0);
+        snapshot
+    }
+}
+
+impl PartialEq for SnapshotOwned {
+    fn eq(&self, other: &Self) -> bool {
+        self.content_eq(other)
+            && self.cursor == other.cursor
+            && self.selection == other.selection
+            && self.extra_selections == other.extra_selections
+            && self.search_match == other.search_match
+            && self.display_offset == other.display_offset
+            && self.colors == other.colors
+            && self.mode == other.mode
+            && self.focused == other.focused
+            && self.layout == other.layout
+    }
+}
+
+impl Eq for SnapshotOwned {}
+
 impl From<&mut Surface> for SnapshotOwned {
     fn from(surface: &mut Surface) -> Self {
         SnapshotOwned::from_surface(surface)
@@ -213,7 +421,12 @@ impl From<SurfaceDamage<'_>> for SnapshotDamage {
     fn from(damage: SurfaceDamage<'_>) -> Self {
         match damage {
             SurfaceDamage::Full => Self::Full,
-            SurfaceDamage::Partial(iter) => Self::Partial(iter.collect()),
+            SurfaceDamage::Partial(iter) => {
+                match merge_line_damage(iter, MAX_DAMAGE_RECTS) {
+                    Some(rects) => Self::Partial(rects),
+                    None => Self::Full,
+                }
+            },
         }
     }
 }
@@ -225,6 +438,21 @@ pub trait SurfaceModel {
 
     /// Reset any accumulated damage bookkeeping after a frame is consumed.
     fn reset_damage(&mut self) {}
+
+    /// Mark the whole surface as damaged so the next frame repaints fully.
+    fn mark_fully_damaged(&mut self) {}
+
+    /// Estimate the bytes held by the surface contents and scrollback.
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    /// Take the block output produced since the previous call.
+    ///
+    /// Surfaces without blocks have nothing to report.
+    fn take_block_output(&mut self) -> Vec<BlockOutput> {
+        Vec::new()
+    }
 }
 
 impl SurfaceModel for Surface {
@@ -235,6 +463,14 @@ impl SurfaceModel for Surface {
     fn reset_damage(&mut self) {
         Surface::reset_damage(self);
     }
+
+    fn mark_fully_damaged(&mut self) {
+        Surface::mark_fully_damaged(self);
+    }
+
+    fn memory_usage(&self) -> usize {
+        Surface::memory_usage(self)
+    }
 }
 
 impl<'a> SnapshotView<'a> {
@@ -251,23 +487,108 @@ impl<'a> SnapshotView<'a> {
             .span_id_for_point(self.display_offset, point)
     }
 
+    /// Whether `point` lies in the active or in an extra selection.
+    #[inline]
+    pub fn is_selected(&self, point: Point) -> bool {
+        self.selection.is_some_and(|range| range.contains(point))
+            || self
+                .extra_selections
+                .iter()
+                .any(|range| range.contains(point))
+    }
+
+    /// Columns of the active selection on every viewport row, from top to
+    /// bottom, as inclusive `(first, last)` pairs.
+    ///
+    /// Block selections cover the same columns on each row, while rows
+    /// inside a linear selection run to the last column. Rows outside the
+    /// selection are `None`.
+    pub fn selection_spans(&self) -> Vec<Option<(Column, Column)>> {
+        let rows = self.size.screen_lines;
+        let mut spans = vec![None; rows];
+        let visible = self.selection.and_then(|range| {
+            range.clip_to_viewport(self.display_offset, rows, self.size.columns)
+        });
+        for span in visible.into_iter().flat_map(|visible| visible.spans) {
+            spans[span.line] = Some((span.start, span.end));
+        }
+        spans
+    }
+
     /// Get selected content into one string buffer
     #[inline]
     pub fn selectable_content(&self) -> String {
         let mut result = String::new();
-        if let Some(range) = self.selection {
-            for indexed in self.cells {
-                if range.contains(indexed.point) {
-                    result.push(indexed.cell.c);
-                    if let Some(zerowidth) = indexed.cell.zerowidth() {
-                        result.extend(zerowidth.iter());
-                    }
+        if self.selection.is_none() && self.extra_selections.is_empty() {
+            return result;
+        }
+
+        for indexed in self.cells {
+            if self.is_selected(indexed.point) {
+                result.push(indexed.cell.c);
+                if let Some(zerowidth) = indexed.cell.zerowidth() {
+                    result.extend(zerowidth.iter());
                 }
             }
         }
         result
     }
 
+    /// Text of the viewport line `line`, in grid coordinates.
+    ///
+    /// Wide char spacers are skipped and trailing blanks kept. Lines outside
+    /// the viewport are empty.
+    pub fn text_line(&self, line: Line) -> Cow<'_, str> {
+        self.line_text(line, false)
+    }
+
+    /// Text of the viewport line `line` like [`Self::text_line`], without
+    /// the trailing blanks.
+    pub fn trimmed_text_line(&self, line: Line) -> Cow<'_, str> {
+        self.line_text(line, true)
+    }
+
+    fn line_text(&self, line: Line, trim: bool) -> Cow<'_, str> {
+        let columns = self.size.columns;
+        let row = usize::try_from(line.0 + self.display_offset as i32).ok();
+        let cells = row
+            .and_then(|row| self.cells.get(row * columns..(row + 1) * columns))
+            .filter(|cells| {
+                cells.first().is_some_and(|c| c.point.line == line)
+            });
+        let Some(cells) = cells else {
+            return Cow::Borrowed("");
+        };
+
+        let mut text = String::with_capacity(columns);
+        for indexed in cells {
+            if indexed.cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                continue;
+            }
+            text.push(indexed.cell.c);
+            if let Some(zerowidth) = indexed.cell.zerowidth() {
+                text.extend(zerowidth.iter());
+            }
+        }
+        if trim {
+            text.truncate(text.trim_end_matches(' ').len());
+        }
+        Cow::Owned(text)
+    }
+
+    /// Role of the viewport line `line`, in grid coordinates.
+    ///
+    /// Lines outside the viewport count as output.
+    #[inline]
+    pub fn line_kind(&self, line: Line) -> LineKind {
+        let row = line.0 + self.display_offset as i32;
+        usize::try_from(row)
+            .ok()
+            .and_then(|row| self.line_kinds.get(row))
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Return the list of block metadata captured in this snapshot.
     #[inline]
     pub fn blocks(&self) -> &[BlockSnapshot] {
@@ -286,13 +607,66 @@ impl<'a> SnapshotView<'a> {
             point.line.0 >= start && point.line.0 < end
         })
     }
+
+    /// Regions of the visible cells covered by images.
+    ///
+    /// Each image is reported once, including images only partly visible.
+    pub fn image_regions(&self) -> Vec<ImageRegion> {
+        let mut regions: Vec<ImageRegion> = Vec::new();
+        for indexed in self.cells {
+            if let Some(image) = indexed.cell.image() {
+                let region = image.region(indexed.point);
+                if !regions.contains(&region) {
+                    regions.push(region);
+                }
+            }
+        }
+        regions
+    }
+
+    /// Resolve BiDi direction runs for every visible row.
+    ///
+    /// The stored grid stays in logical order; only the annotations are
+    /// computed here.
+    #[cfg(feature = "bidi")]
+    pub fn bidi_rows(&self) -> Vec<BidiRow> {
+        self.cells
+            .chunk_by(|a, b| a.point.line == b.point.line)
+            .map(|row| BidiRow::resolve(row[0].point.line, row))
+            .collect()
+    }
+
+    /// Cursor position mapped to its on-screen column after BiDi reordering.
+    #[cfg(feature = "bidi")]
+    pub fn cursor_visual_point(&self) -> Point {
+        let point = self.cursor.point;
+        let row = self
+            .cells
+            .chunk_by(|a, b| a.point.line == b.point.line)
+            .find(|row| row[0].point.line == point.line);
+
+        match row {
+            Some(row) => {
+                let column = BidiRow::resolve(point.line, row)
+                    .visual_column(point.column);
+                Point::new(point.line, column)
+            },
+            None => point,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::actor::SurfaceActor;
-    use crate::cell::Hyperlink;
+    use crate::copy_mode::Motion;
+    use crate::escape::{
+        ClearMode, Hyperlink as VTHyperlink, ImagePlacement, ImageProtocol,
+        ImageSize, LineClearMode, PromptKind, SemanticPromptMark,
+    };
+    use crate::grid::Scroll;
+    use crate::image::GENERATED_IMAGE_ID_BASE;
     use crate::index::{Column, Line, Side};
     use crate::selection::SelectionType;
     use crate::{
@@ -331,6 +705,21 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "bidi")]
+    #[test]
+    fn cursor_visual_point_follows_rtl_run() {
+        let dims = TestDimensions::new(11, 2);
+        let mut surface = Surface::new(SurfaceConfig::default(), &dims);
+        set_text(&mut surface, 0, "abc אבג def");
+        surface.grid_mut().cursor.point = Point::new(Line(0), Column(4));
+
+        let frame = surface.snapshot_owned();
+        let view = frame.view();
+
+        assert_eq!(view.bidi_rows().len(), 2);
+        assert_eq!(view.cursor_visual_point(), Point::new(Line(0), Column(6)));
+    }
+
     #[test]
     fn captures_owned_frame_with_damage() {
         let dims = TestDimensions::new(4, 2);
@@ -356,6 +745,160 @@ mod tests {
         }
     }
 
+    #[test]
+    fn view_reports_scroll_position_for_scrollbars() {
+        let dims = TestDimensions::new(4, 3);
+        let config = SurfaceConfig {
+            scrolling_history: 5,
+            ..SurfaceConfig::default()
+        };
+        let mut surface = Surface::new(config, &dims);
+        let feed = |surface: &mut Surface, lines: usize| {
+            for _ in 0..lines {
+                surface.print('x');
+                surface.carriage_return();
+                surface.new_line();
+            }
+        };
+
+        feed(&mut surface, 4);
+        let frame = surface.snapshot_owned();
+        assert_eq!(frame.view().display_offset, 0);
+        assert_eq!(frame.view().total_lines, 5);
+
+        surface.scroll_display(Scroll::Delta(2));
+        let frame = surface.snapshot_owned();
+        assert_eq!(frame.view().display_offset, 2);
+        assert_eq!(frame.view().total_lines, 5);
+
+        // Scrolling past the top clamps to the whole history.
+        surface.scroll_display(Scroll::Delta(10));
+        assert_eq!(surface.snapshot_owned().view().display_offset, 2);
+
+        // Overflowing the scrollback trims it to the configured limit.
+        surface.scroll_display(Scroll::Bottom);
+        feed(&mut surface, 20);
+        let frame = surface.snapshot_owned();
+        assert_eq!(frame.view().display_offset, 0);
+        assert_eq!(frame.view().total_lines, 8);
+
+        surface.scroll_display(Scroll::Delta(3));
+        let frame = surface.snapshot_owned();
+        assert_eq!(frame.view().display_offset, 3);
+        assert_eq!(frame.view().total_lines, 8);
+
+        surface.scroll_display(Scroll::Top);
+        assert_eq!(surface.snapshot_owned().view().display_offset, 5);
+    }
+
+    #[test]
+    fn viewport_snapshot_holds_the_visible_rows_only() {
+        let dims = TestDimensions::new(4, 3);
+        let config = SurfaceConfig {
+            scrolling_history: 10,
+            ..SurfaceConfig::default()
+        };
+        let mut surface = Surface::new(config, &dims);
+        for ch in ['a', 'b', 'c', 'd', 'e', 'f'] {
+            surface.print(ch);
+            surface.carriage_return();
+            surface.new_line();
+        }
+        surface.scroll_display(Scroll::Delta(2));
+        surface.start_selection(
+            SelectionType::Simple,
+            Point::new(Line(-1), Column(0)),
+            Side::Left,
+        );
+        surface.update_selection(Point::new(Line(-1), Column(0)), Side::Right);
+
+        let frame = surface.snapshot_viewport();
+        let view = frame.view();
+
+        assert_eq!(view.cells.len(), 3 * 4);
+        assert_eq!(view.cells[0].point, Point::new(Line(0), Column(0)));
+        assert_eq!(view.size.total_lines, 3);
+        assert_eq!(view.display_offset, 0);
+        let rows = (0..3)
+            .map(|line| view.trimmed_text_line(Line(line)).into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, ["c", "d", "e"]);
+        // The live cursor sits on the screen row hidden below the viewport.
+        assert_eq!(view.cursor.point, Point::new(Line(4), Column(0)));
+        assert_eq!(view.cursor_direction, Some(VerticalDir::Below));
+        assert!(view.is_selected(Point::new(Line(1), Column(0))));
+        assert!(!view.is_selected(Point::new(Line(0), Column(0))));
+
+        // The full snapshot is unaffected.
+        let frame = surface.snapshot_owned();
+        assert_eq!(frame.view().display_offset, 2);
+        assert_eq!(frame.view().trimmed_text_line(Line(-2)).into_owned(), "c");
+    }
+
+    #[test]
+    fn selection_spans_cover_the_selected_columns_of_each_row() {
+        let dims = TestDimensions::new(6, 4);
+        let config = SurfaceConfig {
+            scrolling_history: 10,
+            ..SurfaceConfig::default()
+        };
+        let mut surface = Surface::new(config, &dims);
+        let select = |surface: &mut Surface, ty, start, end| {
+            surface.start_selection(ty, start, Side::Left);
+            surface.update_selection(end, Side::Right);
+            surface.snapshot_owned().view().selection_spans()
+        };
+
+        let linear = select(
+            &mut surface,
+            SelectionType::Simple,
+            Point::new(Line(0), Column(4)),
+            Point::new(Line(2), Column(1)),
+        );
+        assert_eq!(
+            linear,
+            [
+                Some((Column(4), Column(5))),
+                Some((Column(0), Column(5))),
+                Some((Column(0), Column(1))),
+                None,
+            ]
+        );
+
+        let block = select(
+            &mut surface,
+            SelectionType::Block,
+            Point::new(Line(1), Column(1)),
+            Point::new(Line(3), Column(3)),
+        );
+        assert_eq!(
+            block,
+            [
+                None,
+                Some((Column(1), Column(3))),
+                Some((Column(1), Column(3))),
+                Some((Column(1), Column(3))),
+            ]
+        );
+
+        // Rows are counted from the top of a scrolled viewport.
+        for _ in 0..6 {
+            surface.new_line();
+        }
+        surface.scroll_display(Scroll::Delta(2));
+        let scrolled = select(
+            &mut surface,
+            SelectionType::Simple,
+            Point::new(Line(-2), Column(2)),
+            Point::new(Line(-2), Column(3)),
+        );
+        assert_eq!(scrolled, [Some((Column(2), Column(3))), None, None, None]);
+
+        surface.selection = None;
+        let frame = surface.snapshot_owned();
+        assert_eq!(frame.view().selection_spans(), [None; 4]);
+    }
+
     #[test]
     fn view_exposes_selection_and_cursor() {
         let dims = TestDimensions::new(3, 2);
@@ -376,6 +919,39 @@ mod tests {
         assert_eq!(view.cursor.point, surface.grid().cursor.point);
     }
 
+    #[test]
+    fn off_screen_cursor_reports_its_direction() {
+        let dims = TestDimensions::new(4, 3);
+        let mut surface = Surface::new(SurfaceConfig::default(), &dims);
+        for _ in 0..5 {
+            surface.print('x');
+            surface.carriage_return();
+            surface.new_line();
+        }
+
+        let frame = surface.snapshot_owned();
+        let view = frame.view();
+        assert_eq!(view.cursor_viewport, Some(Point::new(2, Column(0))));
+        assert_eq!(view.cursor_direction, None);
+
+        // Scrolling into history leaves the live cursor below the viewport.
+        surface.scroll_display(Scroll::Delta(3));
+        let frame = surface.snapshot_owned();
+        assert_eq!(frame.view().cursor_viewport, None);
+        assert_eq!(frame.view().cursor_direction, Some(VerticalDir::Below));
+
+        // A copy cursor left in history ends up above it.
+        surface.scroll_display(Scroll::Bottom);
+        surface.enter_copy_mode();
+        for _ in 0..4 {
+            surface.copy_mode_motion(Motion::Up);
+        }
+        surface.scroll_display(Scroll::Bottom);
+        let frame = surface.snapshot_owned();
+        assert_eq!(frame.view().cursor_viewport, None);
+        assert_eq!(frame.view().cursor_direction, Some(VerticalDir::Above));
+    }
+
     #[test]
     fn selectable_content_preserves_zero_width_marks() {
         let mut surface =
@@ -425,6 +1001,53 @@ mod tests {
         assert!(span.contains(Point::new(Line(0), Column(1))));
     }
 
+    #[test]
+    fn cells_printed_inside_osc_link_carry_it() {
+        let mut surface =
+            Surface::new(SurfaceConfig::default(), &TestDimensions::new(10, 2));
+        surface.print('<');
+        surface.set_hyperlink(Some(VTHyperlink {
+            id: Some("docs".into()),
+            uri: "https://otty.sh".into(),
+        }));
+        for c in "otty".chars() {
+            surface.print(c);
+        }
+        surface.set_hyperlink(None);
+        surface.print('>');
+
+        let snapshot = surface.snapshot_owned();
+        let links = snapshot.view().cells[..7]
+            .iter()
+            .map(|cell| cell.hyperlink().map(|link| link.uri().to_owned()))
+            .collect::<Vec<_>>();
+        let otty = Some("https://otty.sh".to_owned());
+        assert_eq!(
+            links,
+            [
+                None,
+                otty.clone(),
+                otty.clone(),
+                otty.clone(),
+                otty,
+                None,
+                None
+            ]
+        );
+
+        // Every linked cell shares the same interned link.
+        let link = snapshot.view().cells[1].hyperlink().expect("link");
+        let first = snapshot.view().cells[4].hyperlink();
+        assert_eq!(first.as_ref(), Some(&link));
+        drop(first);
+        drop(snapshot);
+        assert!(link.ref_count() > 1);
+
+        // Clearing the cells releases the link.
+        surface.clear_line(LineClearMode::All);
+        assert_eq!(link.ref_count(), 1);
+    }
+
     #[test]
     fn regex_detects_plain_url() {
         let mut surface =
@@ -462,4 +1085,161 @@ mod tests {
                 .is_none()
         );
     }
+
+    fn kitty_image(id: u32) -> ImagePlacement {
+        ImagePlacement {
+            protocol: ImageProtocol::Kitty,
+            id: Some(id),
+            size: ImageSize::Cells {
+                columns: 3,
+                rows: 2,
+            },
+            move_cursor: true,
+        }
+    }
+
+    #[test]
+    fn image_region_scrolls_with_its_cells() {
+        let dims = TestDimensions::new(6, 4);
+        let mut surface = Surface::new(SurfaceConfig::default(), &dims);
+        surface.goto(1, 1);
+        surface.place_image(kitty_image(7), 3, 2);
+
+        assert_eq!(surface.grid().cursor.point, Point::new(Line(2), Column(4)));
+        let region = ImageRegion {
+            id: 7,
+            start: Point::new(Line(1), Column(1)),
+            columns: 3,
+            lines: 2,
+        };
+        assert_eq!(surface.snapshot_owned().view().image_regions(), [region]);
+
+        for _ in 0..3 {
+            surface.line_feed();
+        }
+
+        let region = ImageRegion {
+            start: Point::new(Line(-1), Column(1)),
+            ..region
+        };
+        assert_eq!(surface.snapshot_owned().view().image_regions(), [region]);
+        assert!(surface.grid()[Line(-1)][Column(1)].image().is_some());
+    }
+
+    #[test]
+    fn clearing_removes_image_regions() {
+        let dims = TestDimensions::new(6, 4);
+        let mut surface = Surface::new(SurfaceConfig::default(), &dims);
+        let sixel = ImagePlacement {
+            protocol: ImageProtocol::Sixel,
+            id: None,
+            ..kitty_image(0)
+        };
+        surface.place_image(sixel, 2, 2);
+        surface.place_image(kitty_image(3), 3, 1);
+
+        assert_eq!(surface.grid().cursor.point, Point::new(Line(2), Column(3)));
+        let regions = surface.snapshot_owned().view().image_regions();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].id, GENERATED_IMAGE_ID_BASE + 1);
+        assert_eq!(regions[0].start, Point::new(Line(0), Column(0)));
+
+        surface.clear_line(LineClearMode::All);
+        let regions = surface.snapshot_owned().view().image_regions();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].start, Point::new(Line(0), Column(0)));
+
+        surface.clear_screen(ClearMode::All);
+        assert!(surface.snapshot_owned().view().image_regions().is_empty());
+    }
+
+    fn surface_with_text(text: &str) -> Surface {
+        let mut surface =
+            Surface::new(SurfaceConfig::default(), &TestDimensions::new(8, 2));
+        text.chars().for_each(|c| surface.print(c));
+        surface
+    }
+
+    #[test]
+    fn snapshots_of_identical_surfaces_are_equal() {
+        let mut first = surface_with_text("ab");
+        let mut second = surface_with_text("ab");
+        // Damage differs but does not take part in the comparison.
+        second.reset_damage();
+
+        assert_eq!(first.snapshot_owned(), second.snapshot_owned());
+    }
+
+    #[test]
+    fn snapshots_differing_in_one_cell_are_not_equal() {
+        let mut first = surface_with_text("ab");
+        let mut second = surface_with_text("ac");
+        assert_ne!(first.snapshot_owned(), second.snapshot_owned());
+        assert!(!first.snapshot_owned().content_eq(&second.snapshot_owned()));
+    }
+
+    #[test]
+    fn content_eq_ignores_the_cursor() {
+        let mut first = surface_with_text("ab");
+        let mut second = surface_with_text("ab");
+        second.goto(1, 5);
+
+        let (first, second) = (first.snapshot_owned(), second.snapshot_owned());
+        assert_ne!(first, second);
+        assert!(first.content_eq(&second));
+    }
+
+    #[test]
+    fn lines_take_the_kind_of_the_semantic_prompt_marks() {
+        let dims = TestDimensions::new(10, 5);
+        let mut surface = Surface::new(SurfaceConfig::default(), &dims);
+        let write_line = |surface: &mut Surface, text: &str| {
+            surface.print_str(text);
+            surface.carriage_return();
+            surface.line_feed();
+        };
+
+        write_line(&mut surface, "boot");
+        surface.semantic_prompt(SemanticPromptMark::PromptStart {
+            kind: PromptKind::Initial,
+        });
+        write_line(&mut surface, "~/src");
+        surface.print_str("$ ");
+        surface.semantic_prompt(SemanticPromptMark::CommandStart);
+        write_line(&mut surface, "ls");
+        surface.semantic_prompt(SemanticPromptMark::OutputStart);
+        write_line(&mut surface, "Cargo.toml");
+        surface.semantic_prompt(SemanticPromptMark::CommandFinished {
+            exit_code: Some(0),
+        });
+
+        let frame = surface.snapshot_owned();
+        let view = frame.view();
+        let kinds: Vec<_> =
+            (0..5).map(|line| view.line_kind(Line(line))).collect();
+        assert_eq!(
+            kinds,
+            [
+                LineKind::Output,
+                LineKind::Prompt,
+                LineKind::Command,
+                LineKind::Output,
+                LineKind::Output,
+            ]
+        );
+        assert_eq!(view.line_kind(Line(-1)), LineKind::Output);
+    }
+
+    #[test]
+    fn text_line_skips_wide_char_spacers() {
+        let mut surface = surface_with_text("a漢b  ");
+        let frame = surface.snapshot_owned();
+        let view = frame.view();
+
+        assert_eq!(view.text_line(Line(0)), "a漢b    ");
+        assert_eq!(view.trimmed_text_line(Line(0)), "a漢b");
+        assert_eq!(view.trimmed_text_line(Line(1)), "");
+        assert_eq!(view.text_line(Line(2)), "");
+        assert_eq!(view.text_line(Line(-1)), "");
+    }
 }
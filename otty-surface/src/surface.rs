@@ -12,7 +12,7 @@
 
 use std::cmp::max;
 use std::collections::VecDeque;
-use std::ops::{Index, IndexMut, Range};
+use std::ops::{Index, IndexMut, Range, RangeBounds};
 use std::{cmp, mem, ptr, str};
 
 use log::{debug, trace};
@@ -22,16 +22,28 @@ use crate::Side;
 use crate::actor::SurfaceActor;
 use crate::cell::{Cell, Flags, LineLength};
 use crate::color::Colors;
-use crate::damage::{SurfaceDamage, SurfaceDamageIterator, SurfaceDamageState};
+use crate::copy_mode::{self, Motion};
+use crate::damage::{
+    LineDamageBounds, SurfaceDamage, SurfaceDamageIterator, SurfaceDamageState,
+};
 use crate::escape::{
-    CharacterAttribute, Charset, CharsetIndex, ClearMode, Color, CursorStyle,
-    Hyperlink, KeyboardMode, KeyboardModeApplyBehavior, LineClearMode, Mode,
-    NamedMode, NamedPrivateMode, PrivateMode, Rgb, StdColor, TabClearMode,
+    AttributeChangeExtent, CharacterAttribute, Charset, CharsetIndex,
+    ClearMode, Color, CursorStyle, Hyperlink, ImagePlacement, ImageProtocol,
+    KeyboardMode, KeyboardModeApplyBehavior, LineClearMode, Mode, NamedMode,
+    NamedPrivateMode, PrivateMode, PromptKind, RectArea, RectAttrChange, Rgb,
+    SemanticPromptMark, StdColor, TabClearMode,
 };
-use crate::grid::{BidirectionalIterator, Dimensions, Grid, Scroll};
+use crate::grid::{BidirectionalIterator, Dimensions, Grid, LineKind, Scroll};
+use crate::hyperlink::{self, HyperlinkSpan};
+use crate::image::{GENERATED_IMAGE_ID_BASE, ImageCell};
 use crate::index::{Boundary, Column, Direction, Line, Point};
 use crate::mode::SurfaceMode;
-use crate::selection::{Selection, SelectionRange, SelectionType};
+use crate::search::{Match, RegexSearch};
+use crate::search_index::HistorySearchIndex;
+use crate::selection::{
+    Selection, SelectionGranularity, SelectionRange, SelectionType,
+};
+use crate::semantic_zone::{SemanticZone, SemanticZones};
 
 /// Max size of the window title stack.
 const TITLE_STACK_MAX_DEPTH: usize = 4096;
@@ -79,6 +91,23 @@ pub struct Surface {
     pub is_focused: bool,
 
     pub selection: Option<Selection>,
+
+    /// Earlier selections kept alongside [`Self::selection`] by additive
+    /// starts, oldest first.
+    extra_selections: Vec<Selection>,
+
+    /// Copy mode cursor, present while copy mode is active.
+    copy_cursor: Option<Point>,
+
+    /// Active search regex, if a pattern is set.
+    search: Option<RegexSearch>,
+
+    /// Search match currently under focus.
+    search_match: Option<Match>,
+
+    /// Scrollback text index, when enabled in the config.
+    search_index: Option<HistorySearchIndex>,
+
     /// Currently active grid.
     ///
     /// Tracks the screen buffer currently in use. While the alternate screen
@@ -96,6 +125,10 @@ pub struct Surface {
     /// Index into `charsets`, pointing to what ASCII is currently being mapped to.
     active_charset: CharsetIndex,
 
+    /// Index into `charsets` of the set invoked into the right half (GR)
+    /// of 8-bit code tables.
+    right_charset: CharsetIndex,
+
     /// Tabstops.
     tabs: TabStops,
 
@@ -132,6 +165,19 @@ pub struct Surface {
 
     /// Static configuration for this surface.
     config: SurfaceConfig,
+
+    /// Number of image ids generated for images sent without one.
+    generated_image_ids: u64,
+
+    /// Cells covered by area attribute changes (DECSACE).
+    attribute_change_extent: AttributeChangeExtent,
+
+    /// Role given to the lines written from now on, following the
+    /// semantic prompt marks.
+    line_kind: LineKind,
+
+    /// Commands delimited by the semantic prompt marks.
+    semantic_zones: SemanticZones,
 }
 
 /// Configuration options for the [`Surface`].
@@ -149,6 +195,18 @@ pub struct SurfaceConfig {
 
     /// Whether to enable kitty keyboard protocol.
     pub kitty_keyboard: bool,
+
+    /// Whether line feed/new line mode (LNM) is enabled initially and
+    /// after a reset, making LF also perform a carriage return.
+    pub line_feed_new_line: bool,
+
+    /// Whether to keep a text index of the scrollback, letting literal
+    /// searches skip lines that can't match.
+    pub history_search_index: bool,
+
+    /// Cell left behind by erasing, scrolling and resizing, drawn on the
+    /// current SGR background. The default is a plain blank space.
+    pub default_cell: Cell,
 }
 
 impl Default for SurfaceConfig {
@@ -158,6 +216,9 @@ impl Default for SurfaceConfig {
             semantic_escape_chars: SEMANTIC_ESCAPE_CHARS.to_owned(),
             default_cursor_style: Default::default(),
             kitty_keyboard: Default::default(),
+            line_feed_new_line: Default::default(),
+            history_search_index: Default::default(),
+            default_cell: Cell::default(),
         }
     }
 }
@@ -172,8 +233,10 @@ impl Surface {
         let num_lines = dimensions.screen_lines();
 
         let history_size = config.scrolling_history;
-        let grid = Grid::new(num_lines, num_cols, history_size);
-        let inactive_grid = Grid::new(num_lines, num_cols, 0);
+        let blank = config.default_cell.clone();
+        let grid = Grid::new(num_lines, num_cols, history_size)
+            .with_blank(blank.clone());
+        let inactive_grid = Grid::new(num_lines, num_cols, 0).with_blank(blank);
 
         let tabs = TabStops::new(grid.columns());
 
@@ -182,6 +245,13 @@ impl Surface {
         // Initialize terminal damage, covering the entire surface upon launch.
         let damage = SurfaceDamageState::new(num_cols, num_lines);
 
+        let mut mode = SurfaceMode::default();
+        mode.set(SurfaceMode::LINE_FEED_NEW_LINE, config.line_feed_new_line);
+
+        let search_index = config
+            .history_search_index
+            .then(HistorySearchIndex::default);
+
         Surface {
             inactive_grid,
             scroll_region,
@@ -192,13 +262,23 @@ impl Surface {
             inactive_keyboard_mode_stack: Default::default(),
             keyboard_mode_stack: Default::default(),
             active_charset: Default::default(),
+            right_charset: CharsetIndex::G1,
             cursor_style: Default::default(),
             colors: Colors::default(),
             title_stack: Default::default(),
-            is_focused: Default::default(),
+            is_focused: true,
             selection: Default::default(),
+            extra_selections: Vec::new(),
+            copy_cursor: None,
+            search: None,
+            search_match: None,
+            search_index,
             title: Default::default(),
-            mode: Default::default(),
+            mode,
+            generated_image_ids: 0,
+            attribute_change_extent: AttributeChangeExtent::default(),
+            line_kind: LineKind::default(),
+            semantic_zones: SemanticZones::default(),
         }
     }
 
@@ -248,11 +328,37 @@ impl Surface {
         self.damage.reset(self.columns());
     }
 
+    /// Mark the entire surface as damaged.
     #[inline]
-    fn mark_fully_damaged(&mut self) {
+    pub fn mark_fully_damaged(&mut self) {
         self.damage.full = true;
     }
 
+    /// Earlier selections kept alongside [`Self::selection`] by additive
+    /// starts, oldest first.
+    #[inline]
+    pub fn extra_selections(&self) -> &[Selection] {
+        &self.extra_selections
+    }
+
+    /// Position of the copy mode cursor, if copy mode is active.
+    #[inline]
+    pub fn copy_cursor(&self) -> Option<Point> {
+        self.copy_cursor
+    }
+
+    /// Search match currently under focus, if any.
+    #[inline]
+    pub fn search_match(&self) -> Option<&Match> {
+        self.search_match.as_ref()
+    }
+
+    /// Commands delimited by semantic prompt marks, oldest first.
+    #[inline]
+    pub fn semantic_zones(&self) -> &[SemanticZone] {
+        self.semantic_zones.as_slice()
+    }
+
     /// Convert the active selection to a String.
     #[inline]
     pub fn selection_to_string(&self) -> Option<String> {
@@ -408,6 +514,11 @@ impl Surface {
         &mut self.grid
     }
 
+    /// Estimate the bytes held by both grids, including scrollback.
+    pub fn memory_usage(&self) -> usize {
+        self.grid.memory_usage() + self.inactive_grid.memory_usage()
+    }
+
     /// Active surface modes.
     #[inline]
     pub fn mode(&self) -> &SurfaceMode {
@@ -424,6 +535,34 @@ impl Surface {
         &self.config.semantic_escape_chars
     }
 
+    /// Drop the active selection together with the extra ones.
+    fn clear_selections(&mut self) {
+        self.selection = None;
+        self.extra_selections.clear();
+    }
+
+    /// Move every selection along with text scrolled inside `range`.
+    fn rotate_selections(&mut self, range: &Range<Line>, delta: i32) {
+        self.selection = self
+            .selection
+            .take()
+            .and_then(|s| s.rotate(self, range, delta));
+        self.extra_selections = mem::take(&mut self.extra_selections)
+            .into_iter()
+            .filter_map(|s| s.rotate(self, range, delta))
+            .collect();
+    }
+
+    /// Drop the selections touching the cleared `range` of lines.
+    fn clear_selections_in<R: RangeBounds<Line> + Clone>(&mut self, range: R) {
+        self.selection = self
+            .selection
+            .take()
+            .filter(|s| !s.intersects_range(range.clone()));
+        self.extra_selections
+            .retain(|s| !s.intersects_range(range.clone()));
+    }
+
     /// Scroll screen down.
     ///
     /// Text moves down; clear at bottom
@@ -441,10 +580,7 @@ impl Surface {
         let region = origin..self.scroll_region.end;
 
         // Scroll selection.
-        self.selection = self
-            .selection
-            .take()
-            .and_then(|s| s.rotate(self, &region, -(lines as i32)));
+        self.rotate_selections(&region, -(lines as i32));
 
         // Scroll between origin and bottom
         self.grid.scroll_down(&region, lines);
@@ -467,15 +603,41 @@ impl Surface {
         let region = origin..self.scroll_region.end;
 
         // Scroll selection.
-        self.selection = self
-            .selection
-            .take()
-            .and_then(|s| s.rotate(self, &region, lines as i32));
+        self.rotate_selections(&region, lines as i32);
 
         self.grid.scroll_up(&region, lines);
+        if region.start == 0 && !self.mode.contains(SurfaceMode::ALT_SCREEN) {
+            let topmost = self.topmost_line();
+            self.semantic_zones.scroll_up(lines as i32, topmost);
+            if let Some(index) = self.search_index.as_mut() {
+                index.push_scrolled(&self.grid, lines);
+            }
+        }
         self.mark_fully_damaged();
     }
 
+    /// Scrollback index usable for searching the active grid.
+    pub(crate) fn history_search_index(&self) -> Option<&HistorySearchIndex> {
+        if self.mode.contains(SurfaceMode::ALT_SCREEN) {
+            return None;
+        }
+
+        self.search_index.as_ref()
+    }
+
+    /// Re-read the primary grid's scrollback into the search index.
+    fn rebuild_search_index(&mut self) {
+        let grid = if self.mode.contains(SurfaceMode::ALT_SCREEN) {
+            &self.inactive_grid
+        } else {
+            &self.grid
+        };
+
+        if let Some(index) = self.search_index.as_mut() {
+            index.rebuild(grid);
+        }
+    }
+
     /// Scroll display to point if it is outside of viewport.
     pub fn scroll_to_point(&mut self, point: Point) {
         let display_offset = self.grid.display_offset() as i32;
@@ -558,16 +720,117 @@ impl Surface {
         self.damage_cursor();
     }
 
+    /// Print `ch`, already mapped through its charset, at the cursor.
+    fn print_mapped(&mut self, ch: char) {
+        // Number of cells the char will occupy.
+        let width = match ch.width() {
+            Some(width) => width,
+            None => return,
+        };
+
+        // Handle zero-width characters.
+        if width == 0 {
+            // Get previous column.
+            let mut column = self.grid.cursor.point.column;
+            if !self.grid.cursor.input_needs_wrap {
+                column.0 = column.saturating_sub(1);
+            }
+
+            // Put zerowidth characters over first fullwidth character cell.
+            let line = self.grid.cursor.point.line;
+            if self.grid[line][column]
+                .flags
+                .contains(Flags::WIDE_CHAR_SPACER)
+            {
+                column.0 = column.saturating_sub(1);
+            }
+
+            self.grid[line][column].push_zerowidth(ch);
+            return;
+        }
+
+        // Move cursor to next line.
+        if self.grid.cursor.input_needs_wrap {
+            self.wrapline();
+        }
+
+        // If in insert mode, first shift cells to the right.
+        let columns = self.columns();
+        if self.mode.contains(SurfaceMode::INSERT)
+            && self.grid.cursor.point.column + width < columns
+        {
+            let line = self.grid.cursor.point.line;
+            let col = self.grid.cursor.point.column;
+            let row = &mut self.grid[line][..];
+
+            for col in (col.0..(columns - width)).rev() {
+                row.swap(col + width, col);
+            }
+        }
+
+        if width == 1 {
+            self.write_at_cursor(ch);
+        } else {
+            if self.grid.cursor.point.column + 1 >= columns {
+                if self.mode.contains(SurfaceMode::LINE_WRAP) {
+                    // Insert placeholder before wide char if glyph does not fit in this row.
+                    self.grid
+                        .cursor
+                        .template
+                        .flags
+                        .insert(Flags::LEADING_WIDE_CHAR_SPACER);
+                    self.write_at_cursor(' ');
+                    self.grid
+                        .cursor
+                        .template
+                        .flags
+                        .remove(Flags::LEADING_WIDE_CHAR_SPACER);
+                    self.wrapline();
+                } else {
+                    // Prevent out of bounds crash when linewrapping is disabled.
+                    self.grid.cursor.input_needs_wrap = true;
+                    return;
+                }
+            }
+
+            // Write full width glyph to current cursor cell.
+            self.grid.cursor.template.flags.insert(Flags::WIDE_CHAR);
+            self.write_at_cursor(ch);
+            self.grid.cursor.template.flags.remove(Flags::WIDE_CHAR);
+
+            // Write spacer to cell following the wide glyph.
+            self.grid.cursor.point.column += 1;
+            self.grid
+                .cursor
+                .template
+                .flags
+                .insert(Flags::WIDE_CHAR_SPACER);
+            self.write_at_cursor(' ');
+            self.grid
+                .cursor
+                .template
+                .flags
+                .remove(Flags::WIDE_CHAR_SPACER);
+        }
+
+        if self.grid.cursor.point.column + 1 < columns {
+            self.grid.cursor.point.column += 1;
+        } else {
+            self.grid.cursor.input_needs_wrap = true;
+        }
+    }
+
     /// Write `c` to the cell at the cursor position.
     #[inline(always)]
     fn write_at_cursor(&mut self, c: char) {
-        // TODO:
-        let c = self.grid.cursor.charsets[self.active_charset].map(c);
         let fg = self.grid.cursor.template.fg;
         let bg = self.grid.cursor.template.bg;
         let flags = self.grid.cursor.template.flags;
         let extra = self.grid.cursor.template.extra.clone();
 
+        let line = self.grid.cursor.point.line;
+        self.grid[line].kind = self.line_kind;
+
         let mut cursor_cell = self.grid.cursor_cell();
 
         // Clear all related cells when overwriting a fullwidth cell.
@@ -604,6 +867,83 @@ impl Surface {
         cursor_cell.extra = extra;
     }
 
+    /// Move the cursor to `line`, optionally returning to the first column.
+    fn move_vertically(&mut self, line: Line, carriage_return: bool) {
+        self.damage_cursor();
+        self.grid.cursor.point.line = line;
+        if carriage_return {
+            self.grid.cursor.point.column = Column(0);
+        }
+        self.damage_cursor();
+        self.grid.cursor.input_needs_wrap = false;
+    }
+
+    /// Inclusive `(top, left, bottom, right)` corners of `area` on screen.
+    ///
+    /// In origin mode the area is relative to, and limited by, the scroll
+    /// region. Returns `None` when the area starts outside the screen.
+    fn rect_bounds(
+        &self,
+        area: RectArea,
+    ) -> Option<(Line, Column, Line, Column)> {
+        let (offset, max_line) = if self.mode.contains(SurfaceMode::ORIGIN) {
+            (self.scroll_region.start, self.scroll_region.end - 1)
+        } else {
+            (Line(0), self.bottommost_line())
+        };
+        let to_line = |value: usize| {
+            let value = i32::try_from(value).unwrap_or(i32::MAX);
+            Line(offset.0.saturating_add(value))
+        };
+
+        let top = to_line(area.top);
+        let left = Column(area.left);
+        let last_column = self.last_column();
+        if top > max_line || left > last_column {
+            return None;
+        }
+
+        let bottom = area
+            .bottom
+            .map_or(max_line, |bottom| cmp::min(to_line(bottom), max_line));
+        let right = area
+            .right
+            .map_or(last_column, |right| cmp::min(Column(right), last_column));
+        (top <= bottom).then_some((top, left, bottom, right))
+    }
+
+    /// Cell written by erase operations: the configured default cell on
+    /// the current SGR background.
+    fn blank_cell(&self) -> Cell {
+        Cell {
+            bg: self.grid.cursor.template.bg,
+            ..self.config.default_cell.clone()
+        }
+    }
+
+    /// Line above the cursor that wrapped into its line, when reverse
+    /// wraparound lets the cursor step back onto it.
+    fn reverse_wrap_line(&self) -> Option<Line> {
+        let line = self.grid.cursor.point.line;
+        if !self.mode.contains(SurfaceMode::REVERSE_WRAP) || line <= Line(0) {
+            return None;
+        }
+
+        let previous = Line(line.0 - 1);
+        self.grid[previous][self.last_column()]
+            .flags
+            .contains(Flags::WRAPLINE)
+            .then_some(previous)
+    }
+
+    /// Move the cursor to the last column of `line`.
+    fn wrap_to_previous_line(&mut self, line: Line) {
+        self.damage_cursor();
+        self.grid.cursor.point = Point::new(line, self.last_column());
+        self.grid.cursor.input_needs_wrap = false;
+        self.damage_cursor();
+    }
+
     #[inline]
     fn damage_cursor(&mut self) {
         // The normal cursor coordinates are always in viewport.
@@ -614,6 +954,108 @@ impl Surface {
         self.damage.damage_point(point);
     }
 
+    /// Replace the full damage of a resize with the lines it changed.
+    ///
+    /// Lines whose kept cells did not move only get the columns the resize
+    /// added, on top of their pending damage. Lines that reflowed, scrolled
+    /// or are new are damaged entirely. Giving up cells damages nothing.
+    fn damage_resized_viewport(&mut self, old: ResizedViewport) {
+        if self.grid.display_offset() != 0 {
+            return;
+        }
+
+        let columns = self.columns();
+        let kept = Column(old.columns.min(columns));
+        self.damage.full = false;
+        for line in 0..self.screen_lines() {
+            let current = &self.grid[Line(line as i32)][..kept];
+            let unmoved = old
+                .rows
+                .get(line)
+                .is_some_and(|row| row[..kept.0] == *current);
+            if !unmoved {
+                self.damage.damage_line(line, 0, columns - 1);
+                continue;
+            }
+
+            if let Some(bounds) = old.damage.get(line)
+                && bounds.is_damaged()
+                && bounds.left < columns
+            {
+                let right = bounds.right.min(columns - 1);
+                self.damage.damage_line(line, bounds.left, right);
+            }
+            if columns > old.columns {
+                self.damage.damage_line(line, old.columns, columns - 1);
+            }
+        }
+
+        let cursor = old.last_cursor;
+        if cursor.line.0 < self.screen_lines() as i32
+            && cursor.column.0 < columns
+        {
+            self.damage.last_cursor = cursor;
+        }
+    }
+
+    fn damage_copy_cursor(&mut self) {
+        let viewport_point = self.copy_cursor.and_then(|point| {
+            point_to_viewport(self.grid.display_offset(), point)
+        });
+        if let Some(point) = viewport_point
+            && point.line < self.screen_lines()
+        {
+            self.damage.damage_point(point);
+        }
+    }
+
+    /// Move the focused search match one step in `direction` and reveal it.
+    fn focus_match(&mut self, direction: Direction) {
+        let Some(mut regex) = self.search.take() else {
+            return;
+        };
+
+        let (origin, side) = match (&self.search_match, direction) {
+            (Some(focused), Direction::Right) => {
+                (focused.end().add(self, Boundary::None, 1), Side::Left)
+            },
+            (Some(focused), Direction::Left) => {
+                (focused.start().sub(self, Boundary::None, 1), Side::Right)
+            },
+            (None, Direction::Right) => {
+                (Point::new(self.grid.topmost_line(), Column(0)), Side::Left)
+            },
+            (None, Direction::Left) => (
+                Point::new(self.grid.bottommost_line(), self.last_column()),
+                Side::Right,
+            ),
+        };
+
+        self.search_match =
+            self.search_next(&mut regex, origin, direction, side, None);
+        self.search = Some(regex);
+
+        if let Some(point) = self.search_match.as_ref().map(|m| *m.start()) {
+            self.scroll_to_point(point);
+        }
+        self.mark_fully_damaged();
+    }
+
+    /// Scroll the display just enough to keep the copy cursor visible.
+    fn scroll_to_copy_cursor(&mut self) {
+        let Some(point) = self.copy_cursor else {
+            return;
+        };
+
+        let top = -(self.grid.display_offset() as i32);
+        let bottom = top + self.screen_lines() as i32 - 1;
+        if point.line.0 < top {
+            self.scroll_display(Scroll::Delta(top - point.line.0));
+        } else if point.line.0 > bottom {
+            self.scroll_display(Scroll::Delta(bottom - point.line.0));
+        }
+    }
+
     #[inline]
     fn set_keyboard_mode(
         &mut self,
@@ -812,6 +1254,8 @@ impl Surface {
 
             // Drop information about the primary screens saved cursor.
             self.grid.saved_cursor = self.grid.cursor.clone();
+            self.grid.saved_charset = self.active_charset;
+            self.grid.saved_origin = self.mode.contains(SurfaceMode::ORIGIN);
 
             // Reset alternate screen contents.
             self.inactive_grid.reset_region(..);
@@ -834,7 +1278,7 @@ impl Surface {
 
         mem::swap(&mut self.grid, &mut self.inactive_grid);
         self.mode ^= SurfaceMode::ALT_SCREEN;
-        self.selection = None;
+        self.clear_selections();
         self.mark_fully_damaged();
     }
 }
@@ -858,101 +1302,59 @@ impl Dimensions for Surface {
 
 impl SurfaceActor for Surface {
     fn print(&mut self, ch: char) {
-        // Number of cells the char will occupy.
-        let width = match ch.width() {
-            Some(width) => width,
-            None => return,
-        };
+        let ch = self.grid.cursor.charsets[self.active_charset].map(ch);
+        self.print_mapped(ch);
+    }
 
-        // Handle zero-width characters.
-        if width == 0 {
-            // Get previous column.
-            let mut column = self.grid.cursor.point.column;
-            if !self.grid.cursor.input_needs_wrap {
-                column.0 = column.saturating_sub(1);
-            }
+    fn print_right_half(&mut self, ch: char) {
+        let ch =
+            self.grid.cursor.charsets[self.right_charset].map_right_half(ch);
+        self.print_mapped(ch);
+    }
 
-            // Put zerowidth characters over first fullwidth character cell.
-            let line = self.grid.cursor.point.line;
-            if self.grid[line][column]
-                .flags
-                .contains(Flags::WIDE_CHAR_SPACER)
-            {
-                column.0 = column.saturating_sub(1);
-            }
-
-            self.grid[line][column].push_zerowidth(ch);
+    fn print_str(&mut self, text: &str) {
+        if self.mode.contains(SurfaceMode::INSERT) {
+            text.chars().for_each(|ch| self.print(ch));
             return;
         }
 
-        // Move cursor to next line.
-        if self.grid.cursor.input_needs_wrap {
-            self.wrapline();
-        }
-
-        // If in insert mode, first shift cells to the right.
+        // Plain ASCII is always one cell wide, so it skips the width lookup
+        // and is written from a single copy of the cursor template. Anything
+        // else takes the regular `print` path.
+        let template = self.grid.cursor.template.clone();
+        let charset = self.grid.cursor.charsets[self.active_charset];
         let columns = self.columns();
-        if self.mode.contains(SurfaceMode::INSERT)
-            && self.grid.cursor.point.column + width < columns
-        {
-            let line = self.grid.cursor.point.line;
-            let col = self.grid.cursor.point.column;
-            let row = &mut self.grid[line][..];
+        let kind = self.line_kind;
 
-            for col in (col.0..(columns - width)).rev() {
-                row.swap(col + width, col);
+        for ch in text.chars() {
+            if ch != ' ' && !ch.is_ascii_graphic() {
+                self.print(ch);
+                continue;
             }
-        }
 
-        if width == 1 {
-            self.write_at_cursor(ch);
-        } else {
-            if self.grid.cursor.point.column + 1 >= columns {
-                if self.mode.contains(SurfaceMode::LINE_WRAP) {
-                    // Insert placeholder before wide char if glyph does not fit in this row.
-                    self.grid
-                        .cursor
-                        .template
-                        .flags
-                        .insert(Flags::LEADING_WIDE_CHAR_SPACER);
-                    self.write_at_cursor(' ');
-                    self.grid
-                        .cursor
-                        .template
-                        .flags
-                        .remove(Flags::LEADING_WIDE_CHAR_SPACER);
-                    self.wrapline();
-                } else {
-                    // Prevent out of bounds crash when linewrapping is disabled.
-                    self.grid.cursor.input_needs_wrap = true;
-                    return;
-                }
+            if self.grid.cursor.input_needs_wrap {
+                self.wrapline();
             }
 
-            // Write full width glyph to current cursor cell.
-            self.grid.cursor.template.flags.insert(Flags::WIDE_CHAR);
-            self.write_at_cursor(ch);
-            self.grid.cursor.template.flags.remove(Flags::WIDE_CHAR);
+            let line = self.grid.cursor.point.line;
+            self.grid[line].kind = kind;
 
-            // Write spacer to cell following the wide glyph.
-            self.grid.cursor.point.column += 1;
-            self.grid
-                .cursor
-                .template
-                .flags
-                .insert(Flags::WIDE_CHAR_SPACER);
-            self.write_at_cursor(' ');
-            self.grid
-                .cursor
-                .template
+            let cursor_cell = self.grid.cursor_cell();
+            if cursor_cell
                 .flags
-                .remove(Flags::WIDE_CHAR_SPACER);
-        }
+                .intersects(Flags::WIDE_CHAR | Flags::WIDE_CHAR_SPACER)
+            {
+                self.write_at_cursor(charset.map(ch));
+            } else {
+                cursor_cell.clone_from(&template);
+                cursor_cell.c = charset.map(ch);
+            }
 
-        if self.grid.cursor.point.column + 1 < columns {
-            self.grid.cursor.point.column += 1;
-        } else {
-            self.grid.cursor.input_needs_wrap = true;
+            if self.grid.cursor.point.column + 1 < columns {
+                self.grid.cursor.point.column += 1;
+            } else {
+                self.grid.cursor.input_needs_wrap = true;
+            }
         }
     }
 
@@ -970,6 +1372,19 @@ impl SurfaceActor for Surface {
 
         debug!("New num_cols is {num_cols} and num_lines is {num_lines}");
 
+        // Pending damage is kept when it is partial, so compare the new
+        // viewport against the old one afterwards.
+        let old_viewport = (!self.damage.full
+            && self.grid.display_offset() == 0)
+            .then(|| ResizedViewport {
+                rows: (0..old_lines)
+                    .map(|line| self.grid[Line(line as i32)][..].to_vec())
+                    .collect(),
+                damage: mem::take(&mut self.damage.lines),
+                last_cursor: self.damage.last_cursor,
+                columns: old_cols,
+            });
+
         // Move vi mode cursor with the content.
         let history_size = self.history_size();
         let mut delta = num_lines as i32 - old_lines as i32;
@@ -981,28 +1396,54 @@ impl SurfaceActor for Surface {
         self.grid.resize(!is_alt, num_lines, num_cols);
         self.inactive_grid.resize(is_alt, num_lines, num_cols);
 
+        if let Some(point) = self.copy_cursor {
+            let point = Point::new(point.line + delta, point.column);
+            self.copy_cursor = Some(copy_mode::clamp_point(self, point));
+        }
+
+        // Reflow moves text around, so the focused match and the
+        // scrollback index are stale.
+        self.search_match = None;
+        self.rebuild_search_index();
+        self.semantic_zones.clear();
+
         // Invalidate selection and tabs only when necessary.
         if old_cols != num_cols {
-            self.selection = None;
+            self.clear_selections();
 
             // Recreate tabs list.
             self.tabs.resize(num_cols);
-        } else if let Some(selection) = self.selection.take() {
+        } else {
             let max_lines = cmp::max(num_lines, old_lines) as i32;
             let range = Line(0)..Line(max_lines);
-            self.selection = selection.rotate(self, &range, -delta);
+            self.rotate_selections(&range, -delta);
         }
 
-        // Reset scrolling region.
-        self.scroll_region = Line(0)..Line(self.screen_lines() as i32);
+        // Keep a partial scrolling region that still fits the new screen.
+        // Otherwise the region covers the whole screen again, and a region
+        // cut off by the resize homes the cursor like DECSTBM does.
+        let full_region =
+            self.scroll_region == (Line(0)..Line(old_lines as i32));
+        let region_fits =
+            full_region || self.scroll_region.end.0 as usize <= num_lines;
+        if full_region || !region_fits {
+            self.scroll_region = Line(0)..Line(num_lines as i32);
+        }
 
         // Resize damage information.
         self.damage.resize(num_cols, num_lines);
+        if let Some(old_viewport) = old_viewport {
+            self.damage_resized_viewport(old_viewport);
+        }
+
+        if !region_fits {
+            self.goto(0, 0);
+        }
     }
 
     fn insert_blank(&mut self, count: usize) {
+        let blank = self.blank_cell();
         let cursor = &self.grid.cursor;
-        let bg = cursor.template.bg;
 
         // Ensure inserting within terminal bounds
         let count = cmp::min(count, self.columns() - cursor.point.column.0);
@@ -1023,9 +1464,7 @@ impl SurfaceActor for Surface {
 
         // Cells were just moved out toward the end of the line;
         // fill in between source and dest with blanks.
-        for cell in &mut row[source.0..destination] {
-            *cell = bg.into();
-        }
+        row[source.0..destination].fill(blank);
     }
 
     fn insert_blank_lines(&mut self, count: usize) {
@@ -1050,8 +1489,8 @@ impl SurfaceActor for Surface {
 
     fn delete_chars(&mut self, count: usize) {
         let columns = self.columns();
+        let blank = self.blank_cell();
         let cursor = &self.grid.cursor;
-        let bg = cursor.template.bg;
 
         // Ensure deleting within terminal bounds.
         let count = cmp::min(count, columns);
@@ -1072,9 +1511,7 @@ impl SurfaceActor for Surface {
         // Clear last `count` cells in the row. If deleting 1 char, need to delete
         // 1 cell.
         let end = columns - count;
-        for cell in &mut row[end..] {
-            *cell = bg.into();
-        }
+        row[end..].fill(blank);
     }
 
     fn erase_chars(&mut self, count: usize) {
@@ -1089,13 +1526,10 @@ impl SurfaceActor for Surface {
         let end = cmp::min(start + count, Column(self.columns()));
 
         // Cleared cells have current background color set.
-        let bg = self.grid.cursor.template.bg;
+        let blank = self.blank_cell();
         let line = cursor.point.line;
         self.damage.damage_line(line.0 as usize, start.0, end.0);
-        let row = &mut self.grid[line];
-        for cell in &mut row[start..end] {
-            *cell = bg.into();
-        }
+        self.grid[line][start..end].fill(blank);
     }
 
     fn backspace(&mut self) {
@@ -1107,6 +1541,8 @@ impl SurfaceActor for Surface {
             self.grid.cursor.point.column -= 1;
             self.grid.cursor.input_needs_wrap = false;
             self.damage.damage_line(line, column - 1, column);
+        } else if let Some(line) = self.reverse_wrap_line() {
+            self.wrap_to_previous_line(line);
         }
     }
 
@@ -1164,23 +1600,34 @@ impl SurfaceActor for Surface {
             mem::swap(&mut self.grid, &mut self.inactive_grid);
         }
         self.active_charset = Default::default();
+        self.right_charset = CharsetIndex::G1;
         self.cursor_style = None;
+        self.attribute_change_extent = AttributeChangeExtent::default();
+        self.line_kind = LineKind::default();
+        self.semantic_zones.clear();
         self.grid.reset();
         self.inactive_grid.reset();
+        if let Some(index) = self.search_index.as_mut() {
+            index.rebuild(&self.grid);
+        }
         self.scroll_region = Line(0)..Line(self.screen_lines() as i32);
         self.tabs = TabStops::new(self.columns());
         self.title_stack = Vec::new();
         self.title = None;
-        self.selection = None;
+        self.clear_selections();
         self.keyboard_mode_stack = Default::default();
         self.inactive_keyboard_mode_stack = Default::default();
         self.mode.insert(SurfaceMode::default());
+        self.mode.set(
+            SurfaceMode::LINE_FEED_NEW_LINE,
+            self.config.line_feed_new_line,
+        );
         self.mark_fully_damaged();
     }
 
     fn clear_screen(&mut self, mode: ClearMode) {
         trace!("Clearing screen: {mode:?}");
-        let bg = self.grid.cursor.template.bg;
+        let blank = self.blank_cell();
 
         let screen_lines = self.screen_lines();
 
@@ -1188,68 +1635,71 @@ impl SurfaceActor for Surface {
             ClearMode::Above => {
                 let cursor = self.grid.cursor.point;
 
-                // If clearing more than one line.
-                if cursor.line > 1 {
-                    // Fully clear all lines before the current line.
-                    self.grid.reset_region(..cursor.line);
+                // Fully clear all lines before the current line.
+                if cursor.line > 0 {
+                    self.grid.reset_region(Line(0)..cursor.line);
                 }
 
                 // Clear up to the current column in the current line.
                 let end = cmp::min(cursor.column + 1, Column(self.columns()));
-                for cell in &mut self.grid[cursor.line][..end] {
-                    *cell = bg.into();
-                }
+                self.grid[cursor.line][..end].fill(blank);
 
                 let range = Line(0)..=cursor.line;
-                self.selection = self
-                    .selection
-                    .take()
-                    .filter(|s| !s.intersects_range(range));
+                self.clear_selections_in(range);
             },
             ClearMode::Below => {
                 let cursor = self.grid.cursor.point;
-                for cell in &mut self.grid[cursor.line][cursor.column..] {
-                    *cell = bg.into();
-                }
+                self.grid[cursor.line][cursor.column..].fill(blank);
 
                 if (cursor.line.0 as usize) < screen_lines - 1 {
-                    self.grid.reset_region((cursor.line + 1)..);
+                    let end = Line(screen_lines as i32);
+                    self.grid.reset_region((cursor.line + 1)..end);
                 }
 
                 let range = cursor.line..Line(screen_lines as i32);
-                self.selection = self
-                    .selection
-                    .take()
-                    .filter(|s| !s.intersects_range(range));
+                self.clear_selections_in(range);
             },
             ClearMode::All => {
+                let screen = Line(0)..Line(screen_lines as i32);
                 if self.mode.contains(SurfaceMode::ALT_SCREEN) {
-                    self.grid.reset_region(..);
+                    self.grid.reset_region(screen);
                 } else {
                     self.grid.clear_viewport();
+                    self.rebuild_search_index();
                 }
 
-                self.selection = None;
+                self.clear_selections();
             },
-            ClearMode::Saved if self.history_size() > 0 => {
+            ClearMode::Saved => {
+                // Wipe the screen in place, without pushing it into the
+                // scrollback that is dropped right after.
+                self.grid.reset_region(Line(0)..Line(screen_lines as i32));
                 self.grid.clear_history();
-                self.selection = self
-                    .selection
-                    .take()
-                    .filter(|s| !s.intersects_range(..Line(0)));
+                self.rebuild_search_index();
+                self.clear_selections();
+                self.semantic_zones.clear();
             },
-            // We have no history to clear.
-            ClearMode::Saved => (),
         }
 
         self.mark_fully_damaged();
     }
 
+    fn clear_all(&mut self) {
+        trace!("Clearing screen and scrollback of both screens");
+
+        self.clear_screen(ClearMode::Saved);
+        self.inactive_grid.clear_history();
+
+        // Home to the absolute origin whatever the origin mode says.
+        self.grid.cursor.point = Point::new(Line(0), Column(0));
+        self.grid.cursor.input_needs_wrap = false;
+    }
+
     fn clear_line(&mut self, mode: LineClearMode) {
         trace!("Clearing line: {mode:?}");
 
+        let blank = self.blank_cell();
         let cursor = &self.grid.cursor;
-        let bg = cursor.template.bg;
         let point = cursor.point;
 
         let (left, right) = match mode {
@@ -1262,25 +1712,24 @@ impl SurfaceActor for Surface {
         self.damage
             .damage_line(point.line.0 as usize, left.0, right.0 - 1);
 
-        let row = &mut self.grid[point.line];
-        for cell in &mut row[left..right] {
-            *cell = bg.into();
-        }
+        self.grid[point.line][left..right].fill(blank);
 
         let range = self.grid.cursor.point.line..=self.grid.cursor.point.line;
-        self.selection =
-            self.selection.take().filter(|s| !s.intersects_range(range));
+        self.clear_selections_in(range);
     }
 
-    fn insert_tabs(&mut self, mut count: usize) {
-        // A tab after the last column is the same as a linebreak.
-        if self.grid.cursor.input_needs_wrap {
-            self.wrapline();
-            return;
-        }
+    /// Move to the next tab stop, or to the last column if none is left.
+    ///
+    /// A tab never wraps: like the other cursor movements it cancels a
+    /// pending wrap, so the next character overwrites the last column.
+    fn insert_tabs(&mut self, count: usize) {
+        self.grid.cursor.input_needs_wrap = false;
 
-        while self.grid.cursor.point.column < self.columns() && count != 0 {
-            count -= 1;
+        let last_column = Column(self.columns() - 1);
+        for _ in 0..count {
+            if self.grid.cursor.point.column >= last_column {
+                break;
+            }
 
             // TODO:
             let c = self.grid.cursor.charsets[self.active_charset].map('\t');
@@ -1290,13 +1739,11 @@ impl SurfaceActor for Surface {
             }
 
             loop {
-                if (self.grid.cursor.point.column + 1) == self.columns() {
-                    break;
-                }
-
                 self.grid.cursor.point.column += 1;
 
-                if self.tabs[self.grid.cursor.point.column] {
+                if self.grid.cursor.point.column == last_column
+                    || self.tabs[self.grid.cursor.point.column]
+                {
                     break;
                 }
             }
@@ -1385,6 +1832,10 @@ impl SurfaceActor for Surface {
         self.active_charset = index;
     }
 
+    fn set_right_charset_index(&mut self, index: CharsetIndex) {
+        self.right_charset = index;
+    }
+
     fn configure_charset(&mut self, charset: Charset, index: CharsetIndex) {
         trace!("Configuring charset {index:?} as {charset:?}");
         self.grid.cursor.charsets[index] = charset;
@@ -1464,6 +1915,194 @@ impl SurfaceActor for Surface {
             .set_hyperlink(link.map(|e| e.into()));
     }
 
+    fn place_image(
+        &mut self,
+        placement: ImagePlacement,
+        columns: usize,
+        lines: usize,
+    ) {
+        trace!("Placing image: {placement:?} ({columns}x{lines})");
+        let start_column = self.grid.cursor.point.column;
+        let columns = columns.min(self.columns() - start_column.0);
+        let lines = lines.min(self.screen_lines());
+        if columns == 0 || lines == 0 {
+            return;
+        }
+
+        let id = match placement.id {
+            Some(id) => u64::from(id),
+            None => {
+                self.generated_image_ids += 1;
+                GENERATED_IMAGE_ID_BASE + self.generated_image_ids
+            },
+        };
+        let bg = self.grid.cursor.template.bg;
+
+        for line_offset in 0..lines {
+            if line_offset > 0 {
+                self.line_feed();
+            }
+
+            let line = self.grid.cursor.point.line;
+            for column_offset in 0..columns {
+                let cell = &mut self.grid[line][start_column + column_offset];
+                *cell = Cell {
+                    bg,
+                    ..Cell::default()
+                };
+                cell.set_image(Some(ImageCell {
+                    id,
+                    columns,
+                    lines,
+                    line_offset,
+                    column_offset,
+                }));
+            }
+        }
+
+        self.grid.cursor.input_needs_wrap = false;
+        if !placement.move_cursor {
+            // Return to the top-left cell, which moved up if the image
+            // scrolled the screen.
+            let top = self.grid.cursor.point.line - (lines - 1);
+            self.grid.cursor.point.line = top.max(Line(0));
+            self.grid.cursor.point.column = start_column;
+        } else if matches!(
+            placement.protocol,
+            ImageProtocol::Sixel | ImageProtocol::Iterm2
+        ) {
+            // Sixel and iTerm2 images leave the cursor below the image.
+            self.line_feed();
+            self.grid.cursor.point.column = start_column;
+        } else {
+            let column = start_column + columns;
+            self.grid.cursor.point.column = column.min(self.last_column());
+        }
+        self.mark_fully_damaged();
+    }
+
+    fn fill_rect(&mut self, area: RectArea, c: char) {
+        trace!("Filling rectangle {area:?} with {c:?}");
+        if c.width() != Some(1) {
+            debug!("Ignoring rectangle fill with {c:?}");
+            return;
+        }
+        let Some((top, left, bottom, right)) = self.rect_bounds(area) else {
+            return;
+        };
+        if left > right {
+            return;
+        }
+
+        let mut template = self.grid.cursor.template.clone();
+        template.c = c;
+        let last_column = self.last_column();
+        for line in top.0..=bottom.0 {
+            let row = &mut self.grid[Line(line)];
+
+            // Clear wide characters cut in half by the rectangle edges.
+            if left.0 > 0 && row[left].flags.contains(Flags::WIDE_CHAR_SPACER) {
+                let bg = row[left - 1].bg;
+                row[left - 1] = Cell {
+                    bg,
+                    ..self.config.default_cell.clone()
+                };
+            }
+            if right < last_column
+                && row[right].flags.contains(Flags::WIDE_CHAR)
+            {
+                let bg = row[right + 1].bg;
+                row[right + 1] = Cell {
+                    bg,
+                    ..self.config.default_cell.clone()
+                };
+            }
+
+            for column in left.0..=right.0 {
+                row[Column(column)] = template.clone();
+            }
+            self.damage.damage_line(line as usize, left.0, right.0);
+        }
+    }
+
+    fn change_rect_attributes(
+        &mut self,
+        area: RectArea,
+        change: RectAttrChange,
+    ) {
+        trace!("Changing rectangle {area:?} attributes: {change:?}");
+        let Some((top, left, bottom, right)) = self.rect_bounds(area) else {
+            return;
+        };
+
+        let stream =
+            self.attribute_change_extent == AttributeChangeExtent::Stream;
+        let last_column = self.last_column();
+        for line in top.0..=bottom.0 {
+            let line = Line(line);
+            let (start, end) = if stream {
+                (
+                    if line == top { left } else { Column(0) },
+                    if line == bottom { right } else { last_column },
+                )
+            } else {
+                (left, right)
+            };
+            if start > end {
+                continue;
+            }
+
+            let row = &mut self.grid[line];
+            for column in start.0..=end.0 {
+                let flags = &mut row[Column(column)].flags;
+                match &change {
+                    RectAttrChange::Set(attributes) => attributes
+                        .iter()
+                        .for_each(|attr| set_rect_attribute(flags, attr)),
+                    RectAttrChange::Reverse(attributes) => attributes
+                        .iter()
+                        .for_each(|attr| reverse_rect_attribute(flags, attr)),
+                }
+            }
+            self.damage.damage_line(line.0 as usize, start.0, end.0);
+        }
+    }
+
+    fn set_attribute_change_extent(&mut self, extent: AttributeChangeExtent) {
+        trace!("Setting attribute change extent: {extent:?}");
+        self.attribute_change_extent = extent;
+    }
+
+    fn semantic_prompt(&mut self, mark: SemanticPromptMark) {
+        trace!("Semantic prompt mark: {mark:?}");
+        let cursor = self.grid.cursor.point;
+        self.line_kind = match mark {
+            SemanticPromptMark::PromptStart { kind } => {
+                // Continuation and secondary prompts belong to the command
+                // started by the initial prompt.
+                if kind == PromptKind::Initial {
+                    self.semantic_zones.start_prompt(cursor.line);
+                }
+                LineKind::Prompt
+            },
+            SemanticPromptMark::CommandStart => LineKind::Command,
+            SemanticPromptMark::OutputStart => {
+                self.semantic_zones.start_output(cursor.line);
+                LineKind::Output
+            },
+            SemanticPromptMark::CommandFinished { exit_code } => {
+                // Output ends before a cursor left at the start of a line.
+                let end = if cursor.column.0 == 0 {
+                    cursor.line
+                } else {
+                    cursor.line + 1
+                };
+                self.semantic_zones.finish(end, exit_code);
+                LineKind::Output
+            },
+        };
+    }
+
     fn sgr(&mut self, attribute: CharacterAttribute) {
         trace!("Setting attribute: {attribute:?}");
         let cursor = &mut self.grid.cursor;
@@ -1536,6 +2175,12 @@ impl SurfaceActor for Surface {
             CharacterAttribute::CancelStrike => {
                 cursor.template.flags.remove(Flags::STRIKEOUT)
             },
+            CharacterAttribute::Overline => {
+                cursor.template.flags.insert(Flags::OVERLINE)
+            },
+            CharacterAttribute::CancelOverline => {
+                cursor.template.flags.remove(Flags::OVERLINE)
+            },
             _ => {
                 debug!("surface got unhandled attr: {attribute:?}");
             },
@@ -1558,6 +2203,8 @@ impl SurfaceActor for Surface {
     fn save_cursor(&mut self) {
         trace!("Saving cursor position");
         self.grid.saved_cursor = self.grid.cursor.clone();
+        self.grid.saved_charset = self.active_charset;
+        self.grid.saved_origin = self.mode.contains(SurfaceMode::ORIGIN);
     }
 
     fn restore_cursor(&mut self) {
@@ -1565,37 +2212,45 @@ impl SurfaceActor for Surface {
 
         self.damage_cursor();
         self.grid.cursor = self.grid.saved_cursor.clone();
+        self.active_charset = self.grid.saved_charset;
+        self.mode.set(SurfaceMode::ORIGIN, self.grid.saved_origin);
         self.damage_cursor();
     }
 
     fn move_up(&mut self, rows: usize, carriage_return: bool) {
         if carriage_return {
             trace!("Moving up and cr: {rows}");
-
-            let line = self.grid.cursor.point.line - rows;
-            self.goto(line.0, 0);
         } else {
             trace!("Moving up: {rows}");
-
-            let line = self.grid.cursor.point.line - rows;
-            let column = self.grid.cursor.point.column;
-            self.goto(line.0, column.0);
         }
+
+        // Stop at the top margin unless the cursor already is above it.
+        let cursor = self.grid.cursor.point.line;
+        let top = if cursor >= self.scroll_region.start {
+            self.scroll_region.start
+        } else {
+            Line(0)
+        };
+        let line = cmp::max(cursor - rows.min(self.screen_lines()), top);
+        self.move_vertically(line, carriage_return);
     }
 
     fn move_down(&mut self, rows: usize, carriage_return: bool) {
         if carriage_return {
             trace!("Moving down and cr: {rows}");
-
-            let line = self.grid.cursor.point.line + rows;
-            self.goto(line.0, 0);
         } else {
             trace!("Moving down: {rows}");
-
-            let line = self.grid.cursor.point.line + rows;
-            let column = self.grid.cursor.point.column;
-            self.goto(line.0, column.0);
         }
+
+        // Stop at the bottom margin unless the cursor already is below it.
+        let cursor = self.grid.cursor.point.line;
+        let bottom = if cursor < self.scroll_region.end {
+            self.scroll_region.end - 1
+        } else {
+            self.bottommost_line()
+        };
+        let line = cmp::min(cursor + rows.min(self.screen_lines()), bottom);
+        self.move_vertically(line, carriage_return);
     }
 
     fn move_forward(&mut self, cols: usize) {
@@ -1616,6 +2271,14 @@ impl SurfaceActor for Surface {
 
     fn move_backward(&mut self, cols: usize) {
         trace!("Moving backward: {cols}");
+        let mut cols = cols;
+        while cols > self.grid.cursor.point.column.0
+            && let Some(line) = self.reverse_wrap_line()
+        {
+            cols -= self.grid.cursor.point.column.0 + 1;
+            self.wrap_to_previous_line(line);
+        }
+
         let column = self.grid.cursor.point.column.saturating_sub(cols);
 
         let cursor_line = self.grid.cursor.point.line.0 as usize;
@@ -1845,6 +2508,9 @@ impl SurfaceActor for Surface {
             NamedPrivateMode::LineWrap => {
                 self.mode.insert(SurfaceMode::LINE_WRAP)
             },
+            NamedPrivateMode::ReverseWraparound => {
+                self.mode.insert(SurfaceMode::REVERSE_WRAP)
+            },
             NamedPrivateMode::Origin => {
                 self.mode.insert(SurfaceMode::ORIGIN);
                 self.goto(0, 0);
@@ -1913,6 +2579,9 @@ impl SurfaceActor for Surface {
             NamedPrivateMode::LineWrap => {
                 self.mode.remove(SurfaceMode::LINE_WRAP)
             },
+            NamedPrivateMode::ReverseWraparound => {
+                self.mode.remove(SurfaceMode::REVERSE_WRAP)
+            },
             NamedPrivateMode::Origin => self.mode.remove(SurfaceMode::ORIGIN),
             NamedPrivateMode::ColumnMode => self.deccolm(),
             NamedPrivateMode::BlinkingCursor => {
@@ -1943,6 +2612,9 @@ impl SurfaceActor for Surface {
                 NamedPrivateMode::LineWrap => {
                     self.mode.contains(SurfaceMode::LINE_WRAP).into()
                 },
+                NamedPrivateMode::ReverseWraparound => {
+                    self.mode.contains(SurfaceMode::REVERSE_WRAP).into()
+                },
                 NamedPrivateMode::BlinkingCursor => {
                     let style = self
                         .cursor_style
@@ -2089,7 +2761,14 @@ impl SurfaceActor for Surface {
             },
             6 => {
                 let pos = self.grid.cursor.point;
-                let text = format!("\x1b[{};{}R", pos.line + 1, pos.column + 1);
+                // In origin mode rows are reported relative to the scroll
+                // region, matching how CUP interprets them.
+                let line = if self.mode.contains(SurfaceMode::ORIGIN) {
+                    pos.line - self.scroll_region.start
+                } else {
+                    pos.line
+                };
+                let text = format!("\x1b[{};{}R", line + 1, pos.column + 1);
                 report_channel.extend(text.as_bytes());
             },
             _ => debug!("unknown device status query: {status}"),
@@ -2097,6 +2776,17 @@ impl SurfaceActor for Surface {
     }
 
     fn start_selection(&mut self, ty: SelectionType, point: Point, side: Side) {
+        self.extra_selections.clear();
+        self.selection = Some(Selection::new(ty, point, side));
+    }
+
+    fn add_selection(&mut self, ty: SelectionType, point: Point, side: Side) {
+        match self.selection.take() {
+            Some(active) if active.ty == ty => {
+                self.extra_selections.push(active)
+            },
+            _ => self.extra_selections.clear(),
+        }
         self.selection = Some(Selection::new(ty, point, side));
     }
 
@@ -2106,62 +2796,165 @@ impl SurfaceActor for Surface {
             s
         })
     }
-}
 
-/// The state of the [`Mode`] and [`PrivateMode`].
-#[repr(u8)]
-#[derive(Debug, Clone, Copy)]
-enum ModeState {
-    /// The mode is not supported.
-    NotSupported = 0,
-    /// The mode is currently set.
-    Set = 1,
-    /// The mode is currently not set.
-    Reset = 2,
-}
+    fn extend_selection(
+        &mut self,
+        point: Point,
+        granularity: SelectionGranularity,
+    ) {
+        let Some(mut selection) = self.selection.take() else {
+            return;
+        };
 
-impl From<bool> for ModeState {
-    fn from(value: bool) -> Self {
-        if value { Self::Set } else { Self::Reset }
+        selection.extend(self, point, granularity);
+        self.selection = Some(selection);
     }
-}
 
-/// Surface version for escape sequence reports.
-///
-/// This returns the current surface version as a unique number based on otty-surface's
-/// semver version. The different versions are padded to ensure that a higher semver version will
-/// always report a higher version number.
-fn version_number(mut version: &str) -> usize {
-    if let Some(separator) = version.rfind('-') {
-        version = &version[..separator];
+    fn clear_selection(&mut self) {
+        self.clear_selections();
     }
 
-    let mut version_number = 0;
+    fn enter_copy_mode(&mut self) {
+        if self.copy_cursor.is_some() {
+            return;
+        }
 
-    let semver_versions = version.split('.');
-    for (i, semver_version) in semver_versions.rev().enumerate() {
-        let semver_number = semver_version.parse::<usize>().unwrap_or(0);
-        version_number += usize::pow(100, i as u32) * semver_number;
+        let point = copy_mode::clamp_point(self, self.grid.cursor.point);
+        self.copy_cursor = Some(point);
+        self.damage_copy_cursor();
     }
 
-    version_number
-}
+    fn copy_mode_motion(&mut self, motion: Motion) {
+        let Some(point) = self.copy_cursor else {
+            return;
+        };
 
-struct TabStops {
-    tabs: Vec<bool>,
-}
+        self.damage_copy_cursor();
+        let point = copy_mode::apply_motion(self, point, motion);
+        self.copy_cursor = Some(point);
 
-impl TabStops {
-    #[inline]
-    fn new(columns: usize) -> TabStops {
-        TabStops {
-            tabs: (0..columns).map(|i| i % INITIAL_TABSTOPS == 0).collect(),
+        if let Some(selection) = self.selection.as_mut() {
+            selection.update(point, Side::Left);
+            selection.include_all();
+            self.mark_fully_damaged();
         }
+
+        self.scroll_to_copy_cursor();
+        self.damage_copy_cursor();
     }
 
-    /// Remove all tabstops.
-    #[inline]
-    fn clear_all(&mut self) {
+    fn toggle_copy_selection(&mut self, ty: SelectionType) {
+        let Some(point) = self.copy_cursor else {
+            return;
+        };
+
+        self.selection = match self.selection {
+            Some(_) => None,
+            None => {
+                let mut selection = Selection::new(ty, point, Side::Left);
+                selection.include_all();
+                Some(selection)
+            },
+        };
+        self.mark_fully_damaged();
+    }
+
+    fn exit_copy_mode(&mut self) -> Option<String> {
+        self.copy_cursor.take()?;
+
+        let text = self.selection_to_string();
+        self.clear_selections();
+        self.mark_fully_damaged();
+        text
+    }
+
+    fn hyperlink_span_at(&self, point: Point) -> Option<HyperlinkSpan> {
+        hyperlink::osc_span_at(&self.grid, point)
+    }
+
+    fn set_search(&mut self, search: Option<RegexSearch>) {
+        self.search = search;
+        self.search_match = None;
+        self.mark_fully_damaged();
+    }
+
+    fn focus_next_match(&mut self) {
+        self.focus_match(Direction::Right);
+    }
+
+    fn focus_previous_match(&mut self) {
+        self.focus_match(Direction::Left);
+    }
+
+    fn set_focus(&mut self, focused: bool, report_channel: &mut VecDeque<u8>) {
+        if self.is_focused == focused {
+            return;
+        }
+
+        self.is_focused = focused;
+        self.damage_cursor();
+
+        if self.mode.contains(SurfaceMode::FOCUS_IN_OUT) {
+            let report: &[u8] = if focused { b"\x1b[I" } else { b"\x1b[O" };
+            report_channel.extend(report);
+        }
+    }
+}
+
+/// The state of the [`Mode`] and [`PrivateMode`].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy)]
+enum ModeState {
+    /// The mode is not supported.
+    NotSupported = 0,
+    /// The mode is currently set.
+    Set = 1,
+    /// The mode is currently not set.
+    Reset = 2,
+}
+
+impl From<bool> for ModeState {
+    fn from(value: bool) -> Self {
+        if value { Self::Set } else { Self::Reset }
+    }
+}
+
+/// Surface version for escape sequence reports.
+///
+/// This returns the current surface version as a unique number based on otty-surface's
+/// semver version. The different versions are padded to ensure that a higher semver version will
+/// always report a higher version number.
+fn version_number(mut version: &str) -> usize {
+    if let Some(separator) = version.rfind('-') {
+        version = &version[..separator];
+    }
+
+    let mut version_number = 0;
+
+    let semver_versions = version.split('.');
+    for (i, semver_version) in semver_versions.rev().enumerate() {
+        let semver_number = semver_version.parse::<usize>().unwrap_or(0);
+        version_number += usize::pow(100, i as u32) * semver_number;
+    }
+
+    version_number
+}
+
+struct TabStops {
+    tabs: Vec<bool>,
+}
+
+impl TabStops {
+    #[inline]
+    fn new(columns: usize) -> TabStops {
+        TabStops {
+            tabs: (0..columns).map(|i| i % INITIAL_TABSTOPS == 0).collect(),
+        }
+    }
+
+    /// Remove all tabstops.
+    #[inline]
+    fn clear_all(&mut self) {
         unsafe {
             ptr::write_bytes(self.tabs.as_mut_ptr(), 0, self.tabs.len());
         }
@@ -2193,6 +2986,66 @@ impl IndexMut<Column> for TabStops {
     }
 }
 
+/// Viewport contents captured before a resize.
+struct ResizedViewport {
+    rows: Vec<Vec<Cell>>,
+    damage: Vec<LineDamageBounds>,
+    last_cursor: Point,
+    columns: usize,
+}
+
+/// Apply a DECCARA attribute to the flags of a cell.
+///
+/// Blinking is not tracked on cells, so blink attributes are ignored.
+fn set_rect_attribute(flags: &mut Flags, attribute: &CharacterAttribute) {
+    match attribute {
+        CharacterAttribute::Reset => flags.remove(
+            Flags::BOLD
+                | Flags::ALL_UNDERLINES
+                | Flags::INVERSE
+                | Flags::HIDDEN,
+        ),
+        CharacterAttribute::Bold => flags.insert(Flags::BOLD),
+        CharacterAttribute::Underline => {
+            flags.remove(Flags::ALL_UNDERLINES);
+            flags.insert(Flags::UNDERLINE);
+        },
+        CharacterAttribute::Reverse => flags.insert(Flags::INVERSE),
+        CharacterAttribute::Hidden => flags.insert(Flags::HIDDEN),
+        CharacterAttribute::CancelBold => flags.remove(Flags::BOLD),
+        CharacterAttribute::CancelUnderline => {
+            flags.remove(Flags::ALL_UNDERLINES)
+        },
+        CharacterAttribute::CancelReverse => flags.remove(Flags::INVERSE),
+        CharacterAttribute::CancelHidden => flags.remove(Flags::HIDDEN),
+        _ => {},
+    }
+}
+
+/// Toggle a DECRARA attribute in the flags of a cell.
+fn reverse_rect_attribute(flags: &mut Flags, attribute: &CharacterAttribute) {
+    let toggled = match attribute {
+        CharacterAttribute::Reset => {
+            Flags::BOLD | Flags::UNDERLINE | Flags::INVERSE | Flags::HIDDEN
+        },
+        CharacterAttribute::Bold => Flags::BOLD,
+        CharacterAttribute::Underline => Flags::UNDERLINE,
+        CharacterAttribute::Reverse => Flags::INVERSE,
+        CharacterAttribute::Hidden => Flags::HIDDEN,
+        _ => return,
+    };
+
+    // Any underline style counts as underlined.
+    if toggled.contains(Flags::UNDERLINE)
+        && flags.intersects(Flags::ALL_UNDERLINES)
+    {
+        flags.remove(Flags::ALL_UNDERLINES);
+        flags.toggle(toggled - Flags::UNDERLINE);
+    } else {
+        flags.toggle(toggled);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::mem;
@@ -2232,6 +3085,157 @@ mod tests {
         }
     }
 
+    #[test]
+    fn restore_cursor_brings_back_the_full_saved_state() {
+        let size = SurfaceSize::new(10, 5);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        let origin = PrivateMode::Named(NamedPrivateMode::Origin);
+
+        surface.set_scrolling_region(2, 4);
+        surface.set_private_mode(origin);
+        surface.goto(1, 3);
+        surface.sgr(CharacterAttribute::Foreground(Color::Std(StdColor::Red)));
+        surface.sgr(CharacterAttribute::Bold);
+        surface.configure_charset(Charset::DecLineDrawing, CharsetIndex::G1);
+        surface.set_active_charset_index(CharsetIndex::G1);
+        surface.save_cursor();
+
+        surface.sgr(CharacterAttribute::Reset);
+        surface.configure_charset(Charset::Ascii, CharsetIndex::G1);
+        surface.set_active_charset_index(CharsetIndex::G0);
+        surface.unset_private_mode(origin);
+        surface.goto(0, 0);
+        surface.restore_cursor();
+
+        let cursor = &surface.grid.cursor;
+        assert_eq!(cursor.point, Point::new(Line(2), Column(3)));
+        assert_eq!(cursor.template.fg, Color::Std(StdColor::Red));
+        assert!(cursor.template.flags.contains(Flags::BOLD));
+        assert_eq!(cursor.charsets[CharsetIndex::G1], Charset::DecLineDrawing);
+        assert_eq!(surface.active_charset, CharsetIndex::G1);
+        assert!(surface.mode.contains(SurfaceMode::ORIGIN));
+        surface.print('q');
+        assert_eq!(surface.grid[Line(2)][Column(3)].c, '─');
+
+        // A pending wrap is part of the saved state as well.
+        surface.goto(0, 9);
+        surface.print('x');
+        surface.save_cursor();
+        surface.goto(0, 0);
+        surface.restore_cursor();
+        assert!(surface.grid.cursor.input_needs_wrap);
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(9)));
+    }
+
+    #[test]
+    fn restore_cursor_without_save_homes_with_default_attributes() {
+        let size = SurfaceSize::new(10, 5);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+
+        surface.set_private_mode(PrivateMode::Named(NamedPrivateMode::Origin));
+        surface.goto(3, 4);
+        surface.sgr(CharacterAttribute::Foreground(Color::Std(StdColor::Red)));
+        surface.configure_charset(Charset::DecLineDrawing, CharsetIndex::G0);
+        surface.set_active_charset_index(CharsetIndex::G1);
+        surface.restore_cursor();
+
+        let cursor = &surface.grid.cursor;
+        assert_eq!(cursor.point, Point::new(Line(0), Column(0)));
+        assert_eq!(cursor.template, Cell::default());
+        assert_eq!(cursor.charsets[CharsetIndex::G0], Charset::Ascii);
+        assert_eq!(surface.active_charset, CharsetIndex::G0);
+        assert!(!surface.mode.contains(SurfaceMode::ORIGIN));
+    }
+
+    #[test]
+    fn saved_charset_and_origin_belong_to_their_screen() {
+        let size = SurfaceSize::new(10, 5);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        let origin = PrivateMode::Named(NamedPrivateMode::Origin);
+        let alt_screen =
+            PrivateMode::Named(NamedPrivateMode::SwapScreenAndSetRestoreCursor);
+
+        surface.set_active_charset_index(CharsetIndex::G1);
+        surface.set_private_mode(origin);
+        surface.save_cursor();
+
+        surface.set_private_mode(alt_screen);
+        surface.set_active_charset_index(CharsetIndex::G0);
+        surface.unset_private_mode(origin);
+        surface.save_cursor();
+        surface.unset_private_mode(alt_screen);
+
+        surface.restore_cursor();
+        assert_eq!(surface.active_charset, CharsetIndex::G1);
+        assert!(surface.mode.contains(SurfaceMode::ORIGIN));
+    }
+
+    #[test]
+    fn clear_all_homes_to_the_absolute_origin_in_origin_mode() {
+        let size = SurfaceSize::new(10, 5);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+
+        surface.set_scrolling_region(2, 4);
+        surface.set_private_mode(PrivateMode::Named(NamedPrivateMode::Origin));
+        surface.goto(1, 3);
+        surface.clear_all();
+
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(0), Column(0)));
+        assert!(surface.mode.contains(SurfaceMode::ORIGIN));
+    }
+
+    #[test]
+    fn clear_all_on_the_alt_screen_drops_the_primary_scrollback() {
+        let size = SurfaceSize::new(10, 3);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        for _ in 0..6 {
+            surface.print('x');
+            surface.carriage_return();
+            surface.new_line();
+        }
+        assert!(surface.history_size() > 0);
+
+        let alt_screen =
+            PrivateMode::Named(NamedPrivateMode::SwapScreenAndSetRestoreCursor);
+        surface.set_private_mode(alt_screen);
+        surface.print('y');
+        surface.clear_all();
+        surface.unset_private_mode(alt_screen);
+
+        assert_eq!(surface.history_size(), 0);
+        assert_eq!(surface.grid.display_offset(), 0);
+    }
+
+    #[test]
+    fn print_str_matches_per_char_printing() {
+        let text = "ab界c d漢字e\u{301}f gh🙂ij klm";
+
+        for columns in [5, 6, 7] {
+            let size = SurfaceSize::new(columns, 4);
+            let mut batched = Surface::new(SurfaceConfig::default(), &size);
+            let mut per_char = Surface::new(SurfaceConfig::default(), &size);
+
+            // Leave wide chars behind so the run overwrites some of them.
+            for surface in [&mut batched, &mut per_char] {
+                "漢字漢字".chars().for_each(|c| surface.print(c));
+                surface.goto(0, 1);
+            }
+
+            batched.print_str(text);
+            text.chars().for_each(|c| per_char.print(c));
+
+            let grid = batched.grid();
+            for line in grid.topmost_line().0..=grid.bottommost_line().0 {
+                assert_eq!(
+                    grid[Line(line)],
+                    per_char.grid()[Line(line)],
+                    "line {line} with {columns} columns"
+                );
+            }
+            assert_eq!(grid.cursor, per_char.grid().cursor);
+        }
+    }
+
     #[test]
     fn scroll_display_page_up() {
         let size = SurfaceSize::new(5, 10);
@@ -2567,6 +3571,179 @@ mod tests {
         assert_eq!(surface.grid.display_offset(), 0);
     }
 
+    fn fill_screen(surface: &mut Surface, text: &str) {
+        for line in 0..surface.screen_lines() {
+            for (column, c) in text.chars().enumerate() {
+                surface.grid[Line(line as i32)][Column(column)].c = c;
+            }
+        }
+    }
+
+    fn line_text(surface: &Surface, line: i32) -> String {
+        surface.grid[Line(line)][..]
+            .iter()
+            .map(|cell| cell.c)
+            .collect()
+    }
+
+    fn with_scrollback(lines: usize) -> Surface {
+        let size = SurfaceSize::new(4, 3);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        for _ in 0..lines {
+            surface.print('h');
+            surface.carriage_return();
+            surface.new_line();
+        }
+        fill_screen(&mut surface, "abcd");
+        surface.goto(1, 1);
+        surface.grid.cursor.template.bg = Color::Std(StdColor::Blue);
+        surface
+    }
+
+    #[test]
+    fn erase_below_clears_from_cursor_to_end() {
+        let mut surface = with_scrollback(0);
+
+        surface.clear_screen(ClearMode::Below);
+
+        assert_eq!(line_text(&surface, 0), "abcd");
+        assert_eq!(line_text(&surface, 1), "a   ");
+        assert_eq!(line_text(&surface, 2), "    ");
+        assert_eq!(
+            surface.grid[Line(2)][Column(3)].bg,
+            Color::Std(StdColor::Blue)
+        );
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(1)));
+    }
+
+    #[test]
+    fn erase_above_clears_from_start_to_cursor() {
+        let mut surface = with_scrollback(0);
+
+        surface.clear_screen(ClearMode::Above);
+
+        assert_eq!(line_text(&surface, 0), "    ");
+        assert_eq!(line_text(&surface, 1), "  cd");
+        assert_eq!(line_text(&surface, 2), "abcd");
+        assert_eq!(
+            surface.grid[Line(0)][Column(0)].bg,
+            Color::Std(StdColor::Blue)
+        );
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(1)));
+    }
+
+    #[test]
+    fn erase_all_keeps_scrollback() {
+        let mut surface = with_scrollback(5);
+        let history = surface.history_size();
+        assert!(history > 0);
+
+        surface.clear_screen(ClearMode::All);
+
+        assert!(surface.history_size() >= history);
+        assert!((0..3).all(|line| line_text(&surface, line) == "    "));
+        assert_eq!(
+            surface.grid[Line(2)][Column(3)].bg,
+            Color::Std(StdColor::Blue)
+        );
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(1)));
+    }
+
+    #[test]
+    fn erase_saved_clears_screen_and_scrollback() {
+        let mut surface = with_scrollback(5);
+        assert!(surface.history_size() > 0);
+
+        surface.clear_screen(ClearMode::Saved);
+
+        assert_eq!(surface.history_size(), 0);
+        assert!((0..3).all(|line| line_text(&surface, line) == "    "));
+        assert_eq!(
+            surface.grid[Line(0)][Column(0)].bg,
+            Color::Std(StdColor::Blue)
+        );
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(1)));
+    }
+
+    fn with_default_cell() -> Surface {
+        let config = SurfaceConfig {
+            default_cell: Cell {
+                c: '.',
+                flags: Flags::DIM,
+                ..Cell::default()
+            },
+            ..SurfaceConfig::default()
+        };
+        let mut surface = Surface::new(config, &SurfaceSize::new(4, 3));
+        fill_screen(&mut surface, "abcd");
+        surface.goto(1, 1);
+        surface.grid.cursor.template.bg = Color::Std(StdColor::Blue);
+        surface
+    }
+
+    #[test]
+    fn erased_cells_use_the_configured_default_cell() {
+        let mut surface = with_default_cell();
+
+        surface.clear_screen(ClearMode::Below);
+        assert_eq!(line_text(&surface, 1), "a...");
+        assert_eq!(line_text(&surface, 2), "....");
+        let cell = &surface.grid[Line(2)][Column(0)];
+        assert_eq!(cell.bg, Color::Std(StdColor::Blue));
+        assert_eq!(cell.flags, Flags::DIM);
+
+        surface.goto(0, 2);
+        surface.clear_line(LineClearMode::Left);
+        assert_eq!(line_text(&surface, 0), "...d");
+
+        surface.goto(0, 3);
+        surface.erase_chars(1);
+        assert_eq!(line_text(&surface, 0), "....");
+    }
+
+    #[test]
+    fn clearing_the_screen_fills_it_with_the_default_cell() {
+        let mut surface = with_default_cell();
+
+        surface.clear_screen(ClearMode::All);
+
+        assert!((0..3).all(|line| line_text(&surface, line) == "...."));
+        assert_eq!(
+            surface.grid[Line(0)][Column(0)].bg,
+            Color::Std(StdColor::Blue)
+        );
+    }
+
+    #[test]
+    fn scrolled_in_lines_use_the_default_cell() {
+        let mut surface = with_default_cell();
+
+        surface.goto(2, 0);
+        surface.line_feed();
+        assert_eq!(line_text(&surface, 2), "....");
+        assert_eq!(
+            surface.grid[Line(2)][Column(0)].bg,
+            Color::Std(StdColor::Blue)
+        );
+
+        surface.goto(0, 0);
+        surface.insert_blank_lines(1);
+        assert_eq!(line_text(&surface, 0), "....");
+
+        surface.delete_lines(1);
+        assert_eq!(line_text(&surface, 2), "....");
+    }
+
+    #[test]
+    fn resizing_adds_default_cells() {
+        let mut surface = with_default_cell();
+
+        surface.resize(SurfaceSize::new(6, 4));
+
+        assert!((0..4).all(|line| line_text(&surface, line).ends_with("..")));
+        assert_eq!(line_text(&surface, 3), "......");
+    }
+
     #[test]
     fn clear_saved_lines() {
         let size = SurfaceSize::new(7, 17);
@@ -3059,6 +4236,96 @@ mod tests {
         );
     }
 
+    fn damaged_lines(surface: &Surface) -> Vec<(usize, usize, usize)> {
+        surface
+            .damage
+            .lines
+            .iter()
+            .filter(|bounds| bounds.is_damaged())
+            .map(|bounds| (bounds.line, bounds.left, bounds.right))
+            .collect()
+    }
+
+    #[test]
+    fn growing_by_one_column_damages_only_that_column() {
+        let mut surface =
+            Surface::new(SurfaceConfig::default(), &SurfaceSize::new(10, 3));
+        for c in "hello".chars() {
+            surface.print(c);
+        }
+        surface.reset_damage();
+
+        surface.resize(SurfaceSize::new(11, 3));
+
+        assert!(!surface.damage.full);
+        assert_eq!(
+            damaged_lines(&surface),
+            [(0, 10, 10), (1, 10, 10), (2, 10, 10)]
+        );
+    }
+
+    #[test]
+    fn shrinking_keeps_pending_damage_and_adds_none() {
+        let mut surface =
+            Surface::new(SurfaceConfig::default(), &SurfaceSize::new(10, 3));
+        surface.reset_damage();
+        surface.goto(1, 2);
+        surface.print('x');
+
+        surface.resize(SurfaceSize::new(8, 2));
+
+        assert!(!surface.damage.full);
+        // The cursor moves and the printed cell survive the resize.
+        assert_eq!(damaged_lines(&surface), [(0, 0, 0), (1, 2, 2)]);
+    }
+
+    #[test]
+    fn resize_damages_lines_whose_content_moved() {
+        let mut surface =
+            Surface::new(SurfaceConfig::default(), &SurfaceSize::new(4, 3));
+        for c in "abcdef".chars() {
+            surface.print(c);
+        }
+        surface.reset_damage();
+
+        // Unwrapping "abcd" + "ef" joins both lines into the first one.
+        surface.resize(SurfaceSize::new(6, 3));
+
+        assert!(!surface.damage.full);
+        assert_eq!(damaged_lines(&surface), [(0, 0, 5), (1, 0, 5), (2, 4, 5)]);
+    }
+
+    #[test]
+    fn repaint_after_a_full_clear_is_one_full_damage() {
+        let mut surface =
+            Surface::new(SurfaceConfig::default(), &SurfaceSize::new(10, 4));
+        surface.reset_damage();
+
+        surface.clear_screen(ClearMode::All);
+        for line in 0..4 {
+            surface.goto(line, 0);
+            for c in "repaint".chars() {
+                surface.print(c);
+            }
+        }
+
+        assert!(damaged_lines(&surface).is_empty());
+        assert!(matches!(surface.damage(), SurfaceDamage::Full));
+        assert_eq!(
+            crate::SnapshotOwned::from_surface(&mut surface)
+                .view()
+                .damage,
+            &crate::SnapshotDamage::Full
+        );
+
+        // The next frame tracks damage per line again: the printed cell and
+        // the cell the cursor left.
+        surface.reset_damage();
+        surface.goto(2, 0);
+        surface.print('x');
+        assert_eq!(damaged_lines(&surface), [(2, 0, 0), (3, 7, 7)]);
+    }
+
     #[test]
     fn full_damage() {
         let size = SurfaceSize::new(100, 10);
@@ -3140,9 +4407,10 @@ mod tests {
         assert!(surface.damage.full);
         surface.reset_damage();
 
+        // Shrinking only gives up cells, which needs no repaint.
         let size = SurfaceSize::new(10, 10);
         surface.resize(size);
-        assert!(surface.damage.full);
+        assert!(!surface.damage.full);
     }
 
     #[test]
@@ -3188,6 +4456,468 @@ mod tests {
         assert_eq!(surface.title, None);
     }
 
+    #[test]
+    fn focus_changes_are_reported_only_when_enabled() {
+        let size = SurfaceSize::new(5, 5);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        let mut report = VecDeque::new();
+
+        surface.set_focus(false, &mut report);
+        assert!(!surface.is_focused);
+        assert!(report.is_empty());
+
+        surface.mode.insert(SurfaceMode::FOCUS_IN_OUT);
+        surface.set_focus(true, &mut report);
+        surface.set_focus(true, &mut report);
+        surface.set_focus(false, &mut report);
+
+        let report: Vec<u8> = report.into_iter().collect();
+        assert_eq!(report, b"\x1b[I\x1b[O");
+    }
+
+    #[test]
+    fn cursor_position_report_is_one_based() {
+        let size = SurfaceSize::new(10, 10);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        let mut report = VecDeque::new();
+
+        surface.goto(2, 4);
+        surface.report_device_status(6, &mut report);
+        surface.report_device_status(5, &mut report);
+
+        let report: Vec<u8> = report.into_iter().collect();
+        assert_eq!(report, b"\x1b[3;5R\x1b[0n");
+    }
+
+    #[test]
+    fn cursor_position_report_honors_origin_mode() {
+        let size = SurfaceSize::new(10, 10);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        let mut report = VecDeque::new();
+
+        surface.set_scrolling_region(5, 8);
+        surface.set_private_mode(NamedPrivateMode::Origin.into());
+        surface.goto(1, 2);
+        surface.report_device_status(6, &mut report);
+
+        assert_eq!(surface.grid.cursor.point.line, Line(5));
+        let report: Vec<u8> = report.into_iter().collect();
+        assert_eq!(report, b"\x1b[2;3R");
+    }
+
+    /// Six-line surface whose lines are labelled `0`..`5` with a scroll
+    /// region covering lines 1..=3.
+    fn with_scroll_region() -> Surface {
+        let size = SurfaceSize::new(3, 6);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        for line in 0..6 {
+            let label = char::from_digit(line as u32, 10).unwrap_or('?');
+            surface.grid[Line(line)][Column(0)].c = label;
+        }
+        surface.set_scrolling_region(2, 4);
+        surface
+    }
+
+    fn labels(surface: &Surface) -> String {
+        (0..surface.screen_lines() as i32)
+            .map(|line| surface.grid[Line(line)][Column(0)].c)
+            .collect()
+    }
+
+    #[test]
+    fn index_at_bottom_margin_scrolls_only_the_region() {
+        let mut surface = with_scroll_region();
+        surface.goto(3, 1);
+
+        surface.line_feed();
+
+        assert_eq!(labels(&surface), "023 45");
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(3), Column(1)));
+        assert_eq!(surface.history_size(), 0);
+    }
+
+    #[test]
+    fn index_below_region_moves_without_scrolling() {
+        let mut surface = with_scroll_region();
+        surface.goto(4, 0);
+
+        surface.line_feed();
+        surface.line_feed();
+
+        assert_eq!(labels(&surface), "012345");
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(5), Column(0)));
+    }
+
+    #[test]
+    fn reverse_index_at_top_margin_scrolls_only_the_region() {
+        let mut surface = with_scroll_region();
+        surface.goto(1, 2);
+
+        surface.reverse_index();
+
+        assert_eq!(labels(&surface), "0 1245");
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(2)));
+    }
+
+    #[test]
+    fn reverse_index_above_region_moves_without_scrolling() {
+        let mut surface = with_scroll_region();
+        surface.goto(0, 0);
+
+        surface.reverse_index();
+
+        assert_eq!(labels(&surface), "012345");
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(0), Column(0)));
+    }
+
+    #[test]
+    fn resize_below_the_region_bottom_resets_the_region() {
+        let mut surface = with_scroll_region();
+        surface.goto(1, 2);
+
+        surface.resize(SurfaceSize::new(3, 3));
+
+        assert_eq!(surface.scroll_region, Line(0)..Line(3));
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(0), Column(0)));
+    }
+
+    #[test]
+    fn resize_keeps_a_region_that_still_fits() {
+        let mut surface = with_scroll_region();
+        surface.goto(2, 1);
+
+        surface.resize(SurfaceSize::new(3, 5));
+        assert_eq!(surface.scroll_region, Line(1)..Line(4));
+
+        surface.resize(SurfaceSize::new(3, 8));
+        assert_eq!(surface.scroll_region, Line(1)..Line(4));
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(2), Column(1)));
+    }
+
+    #[test]
+    fn full_screen_region_follows_the_new_size() {
+        let mut surface =
+            Surface::new(SurfaceConfig::default(), &SurfaceSize::new(3, 6));
+
+        surface.resize(SurfaceSize::new(3, 4));
+        assert_eq!(surface.scroll_region, Line(0)..Line(4));
+
+        surface.resize(SurfaceSize::new(3, 9));
+        assert_eq!(surface.scroll_region, Line(0)..Line(9));
+    }
+
+    /// Five-column surface with `abcdefg` wrapped over two lines and the
+    /// cursor at the start of the second one.
+    fn with_wrapped_line(reverse_wrap: bool) -> Surface {
+        let size = SurfaceSize::new(5, 3);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        if reverse_wrap {
+            surface
+                .set_private_mode(NamedPrivateMode::ReverseWraparound.into());
+        }
+        for c in "abcdefg".chars() {
+            surface.print(c);
+        }
+        surface.goto(1, 0);
+        surface
+    }
+
+    #[test]
+    fn backspace_wraps_to_the_previous_line_with_reverse_wraparound() {
+        let mut surface = with_wrapped_line(true);
+
+        surface.backspace();
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(0), Column(4)));
+
+        // The first line did not wrap from anywhere.
+        surface.goto(0, 0);
+        surface.backspace();
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(0), Column(0)));
+
+        surface.goto(1, 1);
+        surface.move_backward(3);
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(0), Column(3)));
+    }
+
+    #[test]
+    fn backspace_stays_at_the_left_margin_without_reverse_wraparound() {
+        let mut surface = with_wrapped_line(false);
+
+        surface.backspace();
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(0)));
+
+        surface.move_backward(3);
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(0)));
+
+        // An unwrapped previous line stops the cursor even with the mode.
+        let mut surface = with_wrapped_line(true);
+        surface.goto(2, 0);
+        surface.backspace();
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(2), Column(0)));
+    }
+
+    #[test]
+    fn line_feed_keeps_column_without_lnm() {
+        let size = SurfaceSize::new(10, 5);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+
+        for c in "abc".chars() {
+            surface.print(c);
+        }
+        surface.new_line();
+
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(3)));
+    }
+
+    #[test]
+    fn line_feed_returns_to_first_column_with_lnm() {
+        let size = SurfaceSize::new(10, 5);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+
+        surface.set_mode(NamedMode::LineFeedNewLine.into());
+        for c in "abc".chars() {
+            surface.print(c);
+        }
+        surface.new_line();
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(1), Column(0)));
+
+        // IND never returns the carriage.
+        surface.print('d');
+        surface.line_feed();
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(2), Column(1)));
+    }
+
+    #[test]
+    fn line_feed_new_line_config_survives_reset() {
+        let size = SurfaceSize::new(10, 5);
+        let config = SurfaceConfig {
+            line_feed_new_line: true,
+            ..SurfaceConfig::default()
+        };
+        let mut surface = Surface::new(config, &size);
+        assert!(surface.mode().contains(SurfaceMode::LINE_FEED_NEW_LINE));
+
+        surface.unset_mode(NamedMode::LineFeedNewLine.into());
+        surface.reset();
+
+        assert!(surface.mode().contains(SurfaceMode::LINE_FEED_NEW_LINE));
+    }
+
+    fn rect_area(
+        top: usize,
+        left: usize,
+        bottom: usize,
+        right: usize,
+    ) -> RectArea {
+        RectArea {
+            top,
+            left,
+            bottom: Some(bottom),
+            right: Some(right),
+        }
+    }
+
+    #[test]
+    fn strike_and_overline_resets_clear_only_their_flag() {
+        let size = SurfaceSize::new(4, 1);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        let both = Flags::STRIKEOUT | Flags::OVERLINE;
+
+        surface.sgr(CharacterAttribute::Strike);
+        surface.sgr(CharacterAttribute::Overline);
+        surface.print('a');
+        surface.sgr(CharacterAttribute::CancelStrike);
+        surface.print('b');
+        surface.sgr(CharacterAttribute::Strike);
+        surface.sgr(CharacterAttribute::CancelOverline);
+        surface.print('c');
+
+        let flags =
+            |column: usize| surface.grid[Line(0)][Column(column)].flags & both;
+        assert_eq!(flags(0), both);
+        assert_eq!(flags(1), Flags::OVERLINE);
+        assert_eq!(flags(2), Flags::STRIKEOUT);
+    }
+
+    #[test]
+    fn dim_is_kept_apart_from_bold() {
+        let size = SurfaceSize::new(4, 1);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+
+        surface.sgr(CharacterAttribute::Bold);
+        surface.sgr(CharacterAttribute::Dim);
+        surface.print('a');
+        surface.sgr(CharacterAttribute::CancelBoldDim);
+        surface.print('b');
+        surface.sgr(CharacterAttribute::Dim);
+        surface.print('c');
+
+        let flags = |column: usize| {
+            surface.grid[Line(0)][Column(column)].flags
+                & (Flags::BOLD | Flags::DIM)
+        };
+        assert_eq!(flags(0), Flags::BOLD | Flags::DIM);
+        assert_eq!(flags(1), Flags::empty());
+        assert_eq!(flags(2), Flags::DIM);
+    }
+
+    #[test]
+    fn fill_rect_only_touches_the_rectangle() {
+        let size = SurfaceSize::new(6, 5);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        surface.sgr(CharacterAttribute::Bold);
+
+        surface.fill_rect(rect_area(1, 2, 3, 4), '#');
+
+        for line in 0..5 {
+            for column in 0..6 {
+                let cell = &surface.grid[Line(line)][Column(column)];
+                let inside =
+                    (1..=3).contains(&line) && (2..=4).contains(&column);
+                if inside {
+                    assert_eq!(cell.c, '#', "({line}, {column})");
+                    assert!(cell.flags.contains(Flags::BOLD));
+                } else {
+                    assert_eq!(*cell, Cell::default(), "({line}, {column})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rect_attributes_follow_the_change_extent() {
+        let size = SurfaceSize::new(5, 4);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        let reverse = || RectAttrChange::Set(vec![CharacterAttribute::Reverse]);
+        let inverse_cells = |surface: &Surface| {
+            let mut cells = Vec::new();
+            for line in 0..4 {
+                for column in 0..5 {
+                    let cell = &surface.grid[Line(line)][Column(column)];
+                    if cell.flags.contains(Flags::INVERSE) {
+                        cells.push((line, column));
+                    }
+                }
+            }
+            cells
+        };
+
+        surface.set_attribute_change_extent(AttributeChangeExtent::Rectangle);
+        surface.change_rect_attributes(rect_area(1, 1, 2, 2), reverse());
+        assert_eq!(inverse_cells(&surface), [(1, 1), (1, 2), (2, 1), (2, 2)]);
+
+        // DECRARA toggles the attribute back off.
+        surface.change_rect_attributes(
+            rect_area(1, 1, 2, 2),
+            RectAttrChange::Reverse(vec![CharacterAttribute::Reverse]),
+        );
+        assert!(inverse_cells(&surface).is_empty());
+
+        // A stream runs to the end of the first line and from the start of
+        // the last one.
+        surface.set_attribute_change_extent(AttributeChangeExtent::Stream);
+        surface.change_rect_attributes(rect_area(1, 3, 2, 0), reverse());
+        assert_eq!(inverse_cells(&surface), [(1, 3), (1, 4), (2, 0)]);
+    }
+
+    #[test]
+    fn semantic_marks_record_command_zones_with_exit_codes() {
+        let size = SurfaceSize::new(10, 4);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        let prompt = |kind| SemanticPromptMark::PromptStart { kind };
+        let write_line = |surface: &mut Surface, text: &str| {
+            surface.print_str(text);
+            surface.carriage_return();
+            surface.line_feed();
+        };
+
+        surface.semantic_prompt(prompt(PromptKind::Initial));
+        surface.print_str("$ ");
+        surface.semantic_prompt(SemanticPromptMark::CommandStart);
+        write_line(&mut surface, "for a in");
+        // The continuation prompt stays in the same command.
+        surface.semantic_prompt(prompt(PromptKind::Continuation));
+        surface.print_str("> ");
+        surface.semantic_prompt(SemanticPromptMark::CommandStart);
+        write_line(&mut surface, "done");
+        surface.semantic_prompt(SemanticPromptMark::OutputStart);
+        write_line(&mut surface, "x");
+        write_line(&mut surface, "y");
+        surface.semantic_prompt(SemanticPromptMark::CommandFinished {
+            exit_code: Some(1),
+        });
+
+        // The output scrolled one line into the history.
+        let zone = SemanticZone {
+            prompt_start: Line(-1),
+            output_start: Some(Line(1)),
+            output_end: Some(Line(3)),
+            exit_code: Some(1),
+        };
+        assert_eq!(zone.output_lines(), Some(Line(1)..Line(3)));
+        assert_eq!(surface.semantic_zones(), [zone]);
+
+        surface.semantic_prompt(prompt(PromptKind::Initial));
+        assert_eq!(surface.semantic_zones().len(), 2);
+        assert!(!surface.semantic_zones()[1].is_finished());
+
+        surface.reset();
+        assert!(surface.semantic_zones().is_empty());
+    }
+
+    #[test]
+    fn tabs_stop_at_the_last_column_without_wrapping() {
+        let size = SurfaceSize::new(12, 2);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        surface.clear_tabs(TabClearMode::All);
+        for column in [4, 6] {
+            surface.goto(0, column);
+            surface.set_horizontal_tab();
+        }
+        surface.goto(0, 2);
+
+        let mut landings = Vec::new();
+        for _ in 0..4 {
+            surface.insert_tabs(1);
+            landings.push(surface.grid.cursor.point.column.0);
+        }
+        assert_eq!(landings, [4, 6, 11, 11]);
+
+        surface.goto(0, 0);
+        surface.insert_tabs(5);
+        assert_eq!(surface.grid.cursor.point.column, Column(11));
+    }
+
+    #[test]
+    fn tab_cancels_a_pending_wrap() {
+        let size = SurfaceSize::new(4, 2);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        "abcd".chars().for_each(|c| surface.print(c));
+        assert!(surface.grid.cursor.input_needs_wrap);
+
+        surface.insert_tabs(1);
+        assert!(!surface.grid.cursor.input_needs_wrap);
+        assert_eq!(surface.grid.cursor.point, Point::new(Line(0), Column(3)));
+
+        surface.print('x');
+        assert_eq!(line_text(&surface, 0), "abcx");
+        assert_eq!(line_text(&surface, 1), "    ");
+    }
+
+    #[test]
+    fn memory_usage_grows_with_scrollback() {
+        let size = SurfaceSize::new(5, 10);
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        let initial = surface.memory_usage();
+        assert!(initial >= 2 * 10 * 5 * mem::size_of::<Cell>());
+
+        for _ in 0..20 {
+            surface.new_line();
+        }
+
+        assert!(surface.memory_usage() > initial);
+    }
+
     #[test]
     fn parse_cargo_version() {
         assert_eq!(version_number("0.0.1-dev"), 1);
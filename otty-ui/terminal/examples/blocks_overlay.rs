@@ -1,4 +1,4 @@
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 
 use iced::alignment::Vertical;
 use iced::widget::canvas::{
@@ -221,6 +221,7 @@ impl BlockOverlay {
             Point::new(0.0, 0.0),
             Size::new(bounds.width, bounds.height),
             cell_height,
+            &HashSet::new(),
         );
         (rects, cell_height)
     }
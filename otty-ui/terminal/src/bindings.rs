@@ -6,10 +6,16 @@ use otty_libterm::surface::SurfaceMode;
 #[derive(Clone, Hash, Debug, PartialEq, Eq)]
 pub enum BindingAction {
     Copy,
+    /// Copy and clear the selection if there is one, otherwise handle the
+    /// key as the binding it replaced would, so a copy chord shared with a
+    /// control key keeps its meaning.
+    CopyOrKey,
     Paste,
     Char(char),
     Esc(String),
     LinkOpen,
+    /// Switch between the plain grid and the block layout.
+    ToggleSurfaceLayout,
     Ignore,
 }
 
@@ -17,6 +23,8 @@ pub enum BindingAction {
 pub enum InputKind {
     Char(String),
     KeyCode(Named),
+    /// Numeric keypad key, by its character or `"Enter"`.
+    Numpad(String),
     Mouse(Button),
 }
 
@@ -83,6 +91,9 @@ macro_rules! generate_bindings {
 #[derive(Clone, Debug)]
 pub(crate) struct BindingsLayout {
     layout: Vec<(Binding<InputKind>, BindingAction)>,
+    /// Bindings replaced by a [`BindingAction::CopyOrKey`] one, which falls
+    /// back to them while nothing is selected.
+    shadowed: Vec<(Binding<InputKind>, BindingAction)>,
 }
 
 impl Default for BindingsLayout {
@@ -95,7 +106,9 @@ impl BindingsLayout {
     pub(crate) fn new() -> Self {
         let mut layout = Self {
             layout: default_keyboard_bindings(),
+            shadowed: Vec::new(),
         };
+        layout.add_bindings(keypad_application_bindings());
         layout.add_bindings(platform_keyboard_bindings());
         layout.add_bindings(mouse_default_bindings());
         layout
@@ -111,7 +124,16 @@ impl BindingsLayout {
                 .iter()
                 .position(|(layout_binding, _)| layout_binding == &binding)
             {
-                Some(position) => self.layout[position] = (binding, action),
+                Some(position) => {
+                    let copy_or_key = action == BindingAction::CopyOrKey;
+                    let replaced = std::mem::replace(
+                        &mut self.layout[position],
+                        (binding, action),
+                    );
+                    if copy_or_key {
+                        self.shadowed.push(replaced);
+                    }
+                },
                 None => self.layout.push((binding, action)),
             }
         }
@@ -123,27 +145,47 @@ impl BindingsLayout {
         modifiers: &Modifiers,
         mode: SurfaceMode,
     ) -> BindingAction {
-        for (binding, action) in &self.layout {
-            let is_trigered = binding.target == input
-                && &binding.modifiers == modifiers
-                && mode.contains(binding.mode_include)
-                && !mode.intersects(binding.mode_exclude);
-
-            if is_trigered {
-                return action.clone();
-            };
-        }
+        find_action(&self.layout, &input, modifiers, mode)
+    }
 
-        BindingAction::Ignore
+    /// Action of the binding a [`BindingAction::CopyOrKey`] one replaced.
+    pub(crate) fn get_shadowed_action(
+        &self,
+        input: InputKind,
+        modifiers: &Modifiers,
+        mode: SurfaceMode,
+    ) -> BindingAction {
+        find_action(&self.shadowed, &input, modifiers, mode)
     }
 }
 
+fn find_action(
+    bindings: &[(Binding<InputKind>, BindingAction)],
+    input: &InputKind,
+    modifiers: &Modifiers,
+    mode: SurfaceMode,
+) -> BindingAction {
+    for (binding, action) in bindings {
+        let is_trigered = &binding.target == input
+            && &binding.modifiers == modifiers
+            && mode.contains(binding.mode_include)
+            && !mode.intersects(binding.mode_exclude);
+
+        if is_trigered {
+            return action.clone();
+        };
+    }
+
+    BindingAction::Ignore
+}
+
 fn default_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
     generate_bindings!(
         KeyboardBinding;
         // ANY
         Space;     BindingAction::Char(' ');
-        Enter;     BindingAction::Char('\x0d');
+        Enter,     ~SurfaceMode::LINE_FEED_NEW_LINE; BindingAction::Char('\x0d');
+        Enter,     +SurfaceMode::LINE_FEED_NEW_LINE; BindingAction::Esc("\r\n".into());
         Backspace; BindingAction::Char('\x7f');
         Escape;    BindingAction::Char('\x1b');
         Tab;       BindingAction::Char('\x09');
@@ -238,7 +280,8 @@ fn default_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
         "\'",       Modifiers::CTRL; BindingAction::Char('\x1c');
         "-",        Modifiers::CTRL; BindingAction::Char('\x1f');
         // SHIFT
-        Enter,      Modifiers::SHIFT; BindingAction::Char('\x0d');
+        Enter,      Modifiers::SHIFT, ~SurfaceMode::LINE_FEED_NEW_LINE; BindingAction::Char('\x0d');
+        Enter,      Modifiers::SHIFT, +SurfaceMode::LINE_FEED_NEW_LINE; BindingAction::Esc("\r\n".into());
         Backspace,  Modifiers::SHIFT; BindingAction::Char('\x7f');
         Tab,        Modifiers::SHIFT; BindingAction::Esc("\x1b[Z".into());
         End,        Modifiers::SHIFT, +SurfaceMode::ALT_SCREEN; BindingAction::Esc("\x1b[1;2F".into());
@@ -323,6 +366,41 @@ fn default_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
     )
 }
 
+/// DECKPAM sequences; in numeric mode the keypad types plain text.
+fn keypad_application_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
+    [
+        ("0", "\x1bOp"),
+        ("1", "\x1bOq"),
+        ("2", "\x1bOr"),
+        ("3", "\x1bOs"),
+        ("4", "\x1bOt"),
+        ("5", "\x1bOu"),
+        ("6", "\x1bOv"),
+        ("7", "\x1bOw"),
+        ("8", "\x1bOx"),
+        ("9", "\x1bOy"),
+        ("*", "\x1bOj"),
+        ("+", "\x1bOk"),
+        (",", "\x1bOl"),
+        ("-", "\x1bOm"),
+        (".", "\x1bOn"),
+        ("/", "\x1bOo"),
+        ("=", "\x1bOX"),
+        ("Enter", "\x1bOM"),
+    ]
+    .into_iter()
+    .map(|(key, sequence)| {
+        let binding = KeyboardBinding {
+            target: InputKind::Numpad(key.into()),
+            modifiers: Modifiers::empty(),
+            mode_include: SurfaceMode::APP_KEYPAD,
+            mode_exclude: SurfaceMode::empty(),
+        };
+        (binding, BindingAction::Esc(sequence.into()))
+    })
+    .collect()
+}
+
 #[cfg(target_os = "macos")]
 fn platform_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
     generate_bindings!(
@@ -332,10 +410,13 @@ fn platform_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
     )
 }
 
+/// Outside macOS Ctrl+Shift+C/V copy and paste, and a plain Ctrl+C copies
+/// only a selection so it still interrupts the foreground program.
 #[cfg(not(target_os = "macos"))]
 fn platform_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
     generate_bindings!(
         KeyboardBinding;
+        "c", Modifiers::COMMAND; BindingAction::CopyOrKey;
         "c", Modifiers::SHIFT | Modifiers::COMMAND; BindingAction::Copy;
         "v", Modifiers::SHIFT | Modifiers::COMMAND; BindingAction::Paste;
     )
@@ -474,6 +555,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn enter_sends_crlf_in_line_feed_new_line_mode() {
+        let current_layout = BindingsLayout::default();
+        let enter = InputKind::KeyCode(Named::Enter);
+
+        assert_eq!(
+            current_layout.get_action(
+                enter.clone(),
+                &Modifiers::empty(),
+                SurfaceMode::default(),
+            ),
+            BindingAction::Char('\x0d')
+        );
+        assert_eq!(
+            current_layout.get_action(
+                enter,
+                &Modifiers::empty(),
+                SurfaceMode::default() | SurfaceMode::LINE_FEED_NEW_LINE,
+            ),
+            BindingAction::Esc("\r\n".into())
+        );
+    }
+
+    #[test]
+    fn arrow_keys_follow_application_cursor_mode() {
+        let current_layout = BindingsLayout::default();
+        let up = InputKind::KeyCode(Named::ArrowUp);
+
+        assert_eq!(
+            current_layout.get_action(
+                up.clone(),
+                &Modifiers::empty(),
+                SurfaceMode::default(),
+            ),
+            BindingAction::Esc("\x1b[A".into())
+        );
+        assert_eq!(
+            current_layout.get_action(
+                up,
+                &Modifiers::empty(),
+                SurfaceMode::default() | SurfaceMode::APP_CURSOR,
+            ),
+            BindingAction::Esc("\x1bOA".into())
+        );
+    }
+
+    #[test]
+    fn keypad_digits_follow_application_keypad_mode() {
+        let current_layout = BindingsLayout::default();
+        let seven = InputKind::Numpad("7".into());
+
+        assert_eq!(
+            current_layout.get_action(
+                seven.clone(),
+                &Modifiers::empty(),
+                SurfaceMode::default(),
+            ),
+            BindingAction::Ignore
+        );
+        assert_eq!(
+            current_layout.get_action(
+                seven,
+                &Modifiers::empty(),
+                SurfaceMode::default() | SurfaceMode::APP_KEYPAD,
+            ),
+            BindingAction::Esc("\x1bOw".into())
+        );
+        assert_eq!(
+            current_layout.get_action(
+                InputKind::Numpad("Enter".into()),
+                &Modifiers::empty(),
+                SurfaceMode::default() | SurfaceMode::APP_KEYPAD,
+            ),
+            BindingAction::Esc("\x1bOM".into())
+        );
+    }
+
     #[test]
     fn get_action_with_custom_bindings() {
         let mut current_layout = BindingsLayout::default();
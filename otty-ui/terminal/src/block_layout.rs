@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use iced::{Point, Rectangle, Size};
 use otty_libterm::surface::{BlockKind, SnapshotView};
 
@@ -12,18 +14,87 @@ pub struct BlockRect {
     pub rect: Rectangle<f32>,
 }
 
+/// Snapshot lines hidden by collapsed blocks.
+///
+/// A collapsed block keeps only its first (prompt/command) line, and every
+/// line below it is drawn that many lines higher.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CollapsedLines {
+    /// Hidden `start..end` line ranges ordered from top to bottom.
+    hidden: Vec<(i32, i32)>,
+}
+
+impl CollapsedLines {
+    /// Collect the hidden ranges of the collapsed blocks in `view`.
+    pub(crate) fn new(
+        view: &SnapshotView<'_>,
+        collapsed: &HashSet<String>,
+    ) -> Self {
+        let mut hidden: Vec<(i32, i32)> = view
+            .blocks()
+            .iter()
+            .filter(|block| {
+                block.line_count > 1 && collapsed.contains(&block.meta.id)
+            })
+            .map(|block| {
+                (
+                    block.start_line + 1,
+                    block.start_line + block.line_count as i32,
+                )
+            })
+            .collect();
+        hidden.sort_unstable();
+
+        Self { hidden }
+    }
+
+    /// Map a snapshot line to the line it is drawn on, or `None` if hidden.
+    pub(crate) fn visual_line(&self, line: i32) -> Option<i32> {
+        let mut shift = 0;
+        for &(start, end) in &self.hidden {
+            if line < start {
+                break;
+            }
+            if line < end {
+                return None;
+            }
+            shift += end - start;
+        }
+
+        Some(line - shift)
+    }
+
+    /// Map a drawn line back to the snapshot line shown there.
+    pub(crate) fn actual_line(&self, visual_line: i32) -> i32 {
+        let mut line = visual_line;
+        for &(start, end) in &self.hidden {
+            if line < start {
+                break;
+            }
+            line += end - start;
+        }
+
+        line
+    }
+}
+
 /// Compute layout rectangles for every visible block in the snapshot.
+///
+/// Blocks listed in `collapsed` are reduced to their header line and the
+/// blocks below them move up accordingly.
 pub fn block_rects(
     view: &SnapshotView<'_>,
     layout_pos: Point,
     layout_size: Size<f32>,
     cell_height: f32,
+    collapsed: &HashSet<String>,
 ) -> Vec<BlockRect> {
     if layout_size.width <= 0.0 || cell_height <= 0.0 {
         return Vec::new();
     }
 
     let display_offset = view.display_offset as f32;
+    let collapsed_lines = CollapsedLines::new(view, collapsed);
     view.blocks()
         .iter()
         .filter_map(|block| {
@@ -31,12 +102,19 @@ pub fn block_rects(
                 return None;
             }
 
-            let block_height = block.line_count as f32 * cell_height;
+            let line_count = if collapsed.contains(&block.meta.id) {
+                1
+            } else {
+                block.line_count
+            };
+            let block_height = line_count as f32 * cell_height;
             if block_height <= 0.0 {
                 return None;
             }
 
-            let block_top = block.start_line as f32;
+            let block_top = collapsed_lines
+                .visual_line(block.start_line)
+                .unwrap_or(block.start_line) as f32;
             let y = layout_pos.y + ((block_top + display_offset) * cell_height);
 
             Some(BlockRect {
@@ -97,6 +175,7 @@ mod tests {
             Point::new(10.0, 20.0),
             Size::new(120.0, 300.0),
             4.0,
+            &HashSet::new(),
         );
 
         assert_eq!(rects.len(), 1);
@@ -118,6 +197,7 @@ mod tests {
             Point::new(5.0, 7.0),
             Size::new(80.0, 200.0),
             5.0,
+            &HashSet::new(),
         );
 
         assert_eq!(rects.len(), 2);
@@ -126,4 +206,51 @@ mod tests {
         assert_eq!(rects[0].rect.width, 80.0);
         assert_eq!(rects[1].rect.y, 7.0 + ((2.0 + 3.0) * 5.0));
     }
+
+    #[test]
+    fn collapsed_block_shrinks_to_header_and_expand_restores_it() {
+        let snapshot = snapshot_with_blocks(vec![
+            block("cmd", BlockKind::Command, 0, 4),
+            block("prompt", BlockKind::Prompt, 4, 1),
+        ]);
+        let view = snapshot.view();
+        let origin = Point::new(0.0, 0.0);
+        let size = Size::new(80.0, 200.0);
+        let mut collapsed = HashSet::new();
+
+        let expanded = block_rects(&view, origin, size, 10.0, &collapsed);
+        assert_eq!(expanded[0].rect.height, 40.0);
+        assert_eq!(expanded[1].rect.y, 40.0);
+
+        collapsed.insert(String::from("cmd"));
+        let rects = block_rects(&view, origin, size, 10.0, &collapsed);
+        assert_eq!(rects[0].rect.height, 10.0);
+        assert_eq!(rects[1].rect.y, 10.0);
+
+        collapsed.remove("cmd");
+        let rects = block_rects(&view, origin, size, 10.0, &collapsed);
+        assert_eq!(rects, expanded);
+    }
+
+    #[test]
+    fn collapsed_lines_map_between_snapshot_and_drawn_lines() {
+        let snapshot = snapshot_with_blocks(vec![
+            block("a", BlockKind::Command, 0, 3),
+            block("b", BlockKind::Command, 3, 2),
+            block("c", BlockKind::Command, 5, 3),
+        ]);
+        let view = snapshot.view();
+        let collapsed = HashSet::from([String::from("a"), String::from("c")]);
+        let lines = CollapsedLines::new(&view, &collapsed);
+
+        assert_eq!(lines.visual_line(0), Some(0));
+        assert_eq!(lines.visual_line(1), None);
+        assert_eq!(lines.visual_line(3), Some(1));
+        assert_eq!(lines.visual_line(5), Some(3));
+        assert_eq!(lines.visual_line(7), None);
+        assert_eq!(lines.visual_line(8), Some(4));
+        assert_eq!(lines.actual_line(1), 3);
+        assert_eq!(lines.actual_line(3), 5);
+        assert_eq!(lines.actual_line(4), 8);
+    }
 }
@@ -0,0 +1,128 @@
+//! What lies under the pointer when the user asks for a context menu.
+
+use otty_libterm::surface::{Point, SnapshotView};
+
+/// Details of a right click, published with [`crate::Event::ContextMenu`]
+/// so hosts can build a menu without hit-testing the grid again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextMenuContext {
+    point: Point,
+    has_selection: bool,
+    point_selected: bool,
+    link: Option<String>,
+}
+
+impl ContextMenuContext {
+    /// Grid cell that was clicked, in scrollback-aware coordinates.
+    pub fn point(&self) -> Point {
+        self.point
+    }
+
+    /// Whether any text is selected, wherever it is.
+    pub fn has_selection(&self) -> bool {
+        self.has_selection
+    }
+
+    /// Whether the clicked cell is part of the selection.
+    pub fn is_point_selected(&self) -> bool {
+        self.point_selected
+    }
+
+    /// URI of the hyperlink under the pointer.
+    pub fn link(&self) -> Option<&str> {
+        self.link.as_deref()
+    }
+
+    pub(crate) fn at(view: &SnapshotView<'_>, point: Point) -> Self {
+        Self {
+            point,
+            has_selection: view.selection.is_some()
+                || !view.extra_selections.is_empty(),
+            point_selected: view.is_selected(point),
+            link: view
+                .hyperlink_span_at(point)
+                .map(|span| span.link.uri().to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use otty_libterm::TerminalSize;
+    use otty_libterm::escape::Hyperlink;
+    use otty_libterm::surface::{
+        Column, Line, SelectionType, Side, SnapshotOwned, Surface,
+        SurfaceActor, SurfaceConfig, SurfaceModel,
+    };
+
+    use super::*;
+
+    fn snapshot(link: bool, selection: bool) -> SnapshotOwned {
+        let size = TerminalSize::default();
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        for (column, ch) in "hello".chars().enumerate() {
+            surface.grid_mut()[Line(0)][Column(column)].c = ch;
+        }
+        if link {
+            let link = Hyperlink {
+                id: None,
+                uri: "https://example.com".to_string(),
+            };
+            surface.grid_mut()[Line(0)][Column(4)]
+                .set_hyperlink(Some(link.into()));
+        }
+        if selection {
+            surface.start_selection(
+                SelectionType::Simple,
+                Point::new(Line(0), Column(0)),
+                Side::Left,
+            );
+            surface
+                .update_selection(Point::new(Line(0), Column(1)), Side::Right);
+        }
+        surface.snapshot_owned()
+    }
+
+    #[test]
+    fn reports_the_selection_around_the_point() {
+        let selected = snapshot(false, true);
+        let inside = Point::new(Line(0), Column(1));
+        let outside = Point::new(Line(0), Column(3));
+
+        let context = ContextMenuContext::at(&selected.view(), inside);
+        assert!(context.has_selection);
+        assert!(context.point_selected);
+
+        let context = ContextMenuContext::at(&selected.view(), outside);
+        assert!(context.has_selection);
+        assert!(!context.point_selected);
+
+        let plain = snapshot(false, false);
+        assert_eq!(
+            ContextMenuContext::at(&plain.view(), inside),
+            ContextMenuContext {
+                point: inside,
+                has_selection: false,
+                point_selected: false,
+                link: None,
+            }
+        );
+    }
+
+    #[test]
+    fn reports_the_link_under_the_point() {
+        let linked = snapshot(true, false);
+
+        let context = ContextMenuContext::at(
+            &linked.view(),
+            Point::new(Line(0), Column(4)),
+        );
+        assert_eq!(context.link.as_deref(), Some("https://example.com"));
+
+        let context = ContextMenuContext::at(
+            &linked.view(),
+            Point::new(Line(0), Column(2)),
+        );
+        assert_eq!(context.link, None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Appearance of the terminal cursor for the frame being drawn.
+
+use std::time::Duration;
+
+use otty_libterm::escape::{CursorShape, CursorStyle};
+use otty_libterm::surface::CursorSnapshot;
+
+/// Time between two blink phase flips of a blinking cursor.
+pub const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(530);
+
+/// How the cursor cell is drawn in the current frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorDescriptor {
+    pub shape: CursorShape,
+    /// Draw only the outline of the cell, as for an unfocused terminal.
+    pub hollow: bool,
+    /// Whether the cursor is shown in the current blink phase.
+    pub visible: bool,
+}
+
+/// Cursor style announced by the backend, together with the blink phase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct CursorState {
+    /// Latest shape requested by the application, `None` for the default.
+    shape: Option<CursorShape>,
+    blinking: bool,
+    /// Whether the cursor is in the hidden half of its blink cycle.
+    blink_off: bool,
+}
+
+impl CursorState {
+    /// Track a `DECSCUSR`-style shape change.
+    pub(crate) fn set_shape(&mut self, shape: CursorShape) {
+        self.shape = Some(shape);
+    }
+
+    /// Track a cursor style change; `None` restores the default style.
+    pub(crate) fn set_style(&mut self, style: Option<CursorStyle>) {
+        self.shape = style.map(|style| style.shape);
+        self.blinking = style.is_some_and(|style| style.blinking);
+        self.blink_off = false;
+    }
+
+    /// Whether the cursor currently needs blink ticks.
+    pub(crate) fn is_blinking(&self) -> bool {
+        self.blinking
+    }
+
+    /// Flip the blink phase, returning whether the cursor needs a redraw.
+    pub(crate) fn toggle_blink(&mut self) -> bool {
+        if !self.blinking {
+            return false;
+        }
+
+        self.blink_off = !self.blink_off;
+        true
+    }
+
+    /// Describe how to draw `cursor` given the terminal focus.
+    ///
+    /// Unfocused terminals show a steady hollow block whatever the shape,
+    /// while the copy mode cursor is always a steady block.
+    pub(crate) fn descriptor(
+        &self,
+        cursor: &CursorSnapshot,
+        focused: bool,
+    ) -> CursorDescriptor {
+        let shape = match cursor.shape {
+            CursorShape::Hidden => CursorShape::Hidden,
+            _ if cursor.copy_mode || !focused => CursorShape::Block,
+            shape => self.shape.unwrap_or(shape),
+        };
+        let blinks = self.blinking && focused && !cursor.copy_mode;
+
+        CursorDescriptor {
+            shape,
+            hollow: !focused && shape != CursorShape::Hidden,
+            visible: shape != CursorShape::Hidden
+                && !(blinks && self.blink_off),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use otty_libterm::surface::{Cell, Point};
+
+    use super::*;
+
+    fn snapshot_cursor(shape: CursorShape) -> CursorSnapshot {
+        CursorSnapshot {
+            shape,
+            cell: Cell::default(),
+            point: Point::default(),
+            copy_mode: false,
+        }
+    }
+
+    #[test]
+    fn descriptor_follows_the_latest_shape_and_style() {
+        let cursor = snapshot_cursor(CursorShape::Block);
+        let mut state = CursorState::default();
+
+        state.set_shape(CursorShape::Beam);
+        assert_eq!(state.descriptor(&cursor, true).shape, CursorShape::Beam);
+
+        state.set_style(Some(CursorStyle {
+            shape: CursorShape::Underline,
+            blinking: false,
+        }));
+        assert_eq!(
+            state.descriptor(&cursor, true),
+            CursorDescriptor {
+                shape: CursorShape::Underline,
+                hollow: false,
+                visible: true,
+            }
+        );
+
+        state.set_style(None);
+        assert_eq!(state.descriptor(&cursor, true).shape, CursorShape::Block);
+    }
+
+    #[test]
+    fn blinking_cursor_alternates_visibility_while_focused() {
+        let cursor = snapshot_cursor(CursorShape::Beam);
+        let mut state = CursorState::default();
+        assert!(!state.toggle_blink());
+
+        state.set_style(Some(CursorStyle {
+            shape: CursorShape::Beam,
+            blinking: true,
+        }));
+        assert!(state.is_blinking());
+        assert!(state.descriptor(&cursor, true).visible);
+
+        assert!(state.toggle_blink());
+        assert!(!state.descriptor(&cursor, true).visible);
+        assert!(state.descriptor(&cursor, false).visible);
+
+        assert!(state.toggle_blink());
+        assert!(state.descriptor(&cursor, true).visible);
+    }
+
+    #[test]
+    fn unfocused_cursor_is_a_steady_hollow_block() {
+        let mut state = CursorState::default();
+        state.set_style(Some(CursorStyle {
+            shape: CursorShape::Underline,
+            blinking: true,
+        }));
+        state.toggle_blink();
+
+        assert_eq!(
+            state.descriptor(&snapshot_cursor(CursorShape::Underline), false),
+            CursorDescriptor {
+                shape: CursorShape::Block,
+                hollow: true,
+                visible: true,
+            }
+        );
+        assert_eq!(
+            state.descriptor(&snapshot_cursor(CursorShape::Hidden), false),
+            CursorDescriptor {
+                shape: CursorShape::Hidden,
+                hollow: false,
+                visible: false,
+            }
+        );
+    }
+}
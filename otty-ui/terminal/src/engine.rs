@@ -6,8 +6,8 @@ use iced_core::Size;
 #[cfg(test)]
 use otty_libterm::Runtime;
 use otty_libterm::surface::{
-    Column, Point, Scroll, SelectionType, Side, SnapshotOwned, SurfaceMode,
-    viewport_to_point,
+    Column, Point, Scroll, SelectionType, Side, SnapshotOwned, SurfaceLayout,
+    SurfaceMode, viewport_to_point,
 };
 use otty_libterm::{
     DefaultParser, DefaultSurface, RuntimeRequestProxy, RuntimeTerminal,
@@ -298,6 +298,7 @@ impl Engine {
         selection_type: SelectionType,
         x: f32,
         y: f32,
+        additive: bool,
     ) {
         let location = Self::selection_point(
             x,
@@ -310,6 +311,7 @@ impl Engine {
             ty: selection_type,
             point: location,
             direction: self.selection_side(x),
+            additive,
         });
     }
 
@@ -323,6 +325,10 @@ impl Engine {
         });
     }
 
+    pub(crate) fn clear_selection(&self) {
+        let _ = self.request_proxy.send(TerminalRequest::ClearSelection);
+    }
+
     pub(crate) fn selection_point(
         x: f32,
         y: f32,
@@ -385,6 +391,12 @@ impl Engine {
         self.sroll_bottom();
     }
 
+    pub(crate) fn set_surface_layout(&self, layout: SurfaceLayout) {
+        let _ = self
+            .request_proxy
+            .send(TerminalRequest::SetSurfaceLayout(layout));
+    }
+
     fn sroll_bottom(&self) {
         let _ = self
             .request_proxy
@@ -12,6 +12,7 @@ use crate::settings::FontSettings;
 pub struct TermFont {
     pub(crate) size: f32,
     pub(crate) font_type: Font,
+    pub(crate) fallback_fonts: Vec<Font>,
     pub(crate) scale_factor: f32,
     pub(crate) measure: Size<f32>,
 }
@@ -21,6 +22,7 @@ impl TermFont {
         Self {
             size: settings.size,
             font_type: settings.font_type,
+            fallback_fonts: settings.fallback_fonts,
             scale_factor: settings.scale_factor,
             measure: font_measure(
                 settings.size,
@@ -31,6 +33,10 @@ impl TermFont {
     }
 }
 
+/// Size of one terminal cell for the primary font.
+///
+/// Fallback fonts never change the grid: their glyphs are fitted to the
+/// cells measured here.
 pub fn font_measure(
     font_size: f32,
     scale_factor: f32,
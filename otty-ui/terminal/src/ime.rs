@@ -0,0 +1,152 @@
+use std::ops::Range;
+
+use iced_core::input_method;
+use unicode_width::UnicodeWidthStr;
+
+/// In-progress IME composition (preedit) for a terminal view.
+///
+/// Preedit text is only rendered locally; nothing reaches the PTY until the
+/// input method commits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Composition {
+    preedit: String,
+    cursor: Option<Range<usize>>,
+}
+
+impl Composition {
+    /// Apply an input method event, returning committed bytes to write.
+    pub(crate) fn handle(
+        &mut self,
+        event: &input_method::Event,
+    ) -> Option<Vec<u8>> {
+        match event {
+            // Session boundaries drop any stale preedit text.
+            input_method::Event::Opened | input_method::Event::Closed => {
+                self.cancel();
+                None
+            },
+            input_method::Event::Preedit(text, cursor) => {
+                self.update(text, cursor.clone());
+                None
+            },
+            input_method::Event::Commit(text) => self.commit(text),
+        }
+    }
+
+    /// Check whether there is uncommitted preedit text.
+    pub(crate) fn is_composing(&self) -> bool {
+        !self.preedit.is_empty()
+    }
+
+    /// Uncommitted preedit text, if any.
+    pub(crate) fn preedit(&self) -> Option<&str> {
+        if self.preedit.is_empty() {
+            None
+        } else {
+            Some(&self.preedit)
+        }
+    }
+
+    /// Preedit cursor offset in terminal cells, if the IME reported one.
+    pub(crate) fn cursor_cells(&self) -> Option<usize> {
+        let start = self.cursor.as_ref()?.start;
+        self.preedit.get(..start).map(UnicodeWidthStr::width)
+    }
+
+    /// Width of the preedit text in terminal cells.
+    pub(crate) fn width_cells(&self) -> usize {
+        self.preedit.width()
+    }
+
+    fn update(&mut self, text: &str, cursor: Option<Range<usize>>) {
+        // An empty preedit is how input methods signal a cancelled
+        // composition.
+        if text.is_empty() {
+            self.cancel();
+            return;
+        }
+
+        self.preedit.clear();
+        self.preedit.push_str(text);
+        self.cursor = cursor;
+    }
+
+    fn commit(&mut self, text: &str) -> Option<Vec<u8>> {
+        self.cancel();
+
+        if text.is_empty() {
+            None
+        } else {
+            Some(text.as_bytes().to_vec())
+        }
+    }
+
+    fn cancel(&mut self) {
+        self.preedit.clear();
+        self.cursor = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preedit(
+        text: &str,
+        cursor: Option<Range<usize>>,
+    ) -> input_method::Event {
+        input_method::Event::Preedit(text.into(), cursor)
+    }
+
+    #[test]
+    fn start_discards_stale_preedit() {
+        let mut composition = Composition::default();
+        composition.handle(&preedit("か", None));
+
+        assert_eq!(composition.handle(&input_method::Event::Opened), None);
+
+        assert!(!composition.is_composing());
+        assert_eq!(composition.preedit(), None);
+    }
+
+    #[test]
+    fn update_replaces_preedit_and_cursor() {
+        let mut composition = Composition::default();
+        composition.handle(&input_method::Event::Opened);
+
+        assert_eq!(composition.handle(&preedit("に", Some(3..3))), None);
+        assert_eq!(composition.handle(&preedit("にほ", Some(6..6))), None);
+
+        assert_eq!(composition.preedit(), Some("にほ"));
+        assert_eq!(composition.width_cells(), 4);
+        assert_eq!(composition.cursor_cells(), Some(4));
+    }
+
+    #[test]
+    fn commit_returns_bytes_and_clears_preedit() {
+        let mut composition = Composition::default();
+        composition.handle(&input_method::Event::Opened);
+        composition.handle(&preedit("にほん", None));
+
+        let committed =
+            composition.handle(&input_method::Event::Commit("日本".into()));
+
+        assert_eq!(committed, Some("日本".as_bytes().to_vec()));
+        assert_eq!(composition.preedit(), None);
+    }
+
+    #[test]
+    fn cancel_discards_preedit_without_writing() {
+        let mut composition = Composition::default();
+        composition.handle(&input_method::Event::Opened);
+        composition.handle(&preedit("にほ", Some(6..6)));
+
+        assert_eq!(composition.handle(&preedit("", None)), None);
+        assert!(!composition.is_composing());
+        assert_eq!(composition.cursor_cells(), None);
+
+        composition.handle(&preedit("か", None));
+        assert_eq!(composition.handle(&input_method::Event::Closed), None);
+        assert_eq!(composition.preedit(), None);
+    }
+}
@@ -1,4 +1,5 @@
-use iced::keyboard::Key;
+use iced::keyboard::key::Named;
+use iced::keyboard::{Key, Location, Modifiers};
 use iced::mouse::ScrollDelta;
 use iced::{Point, Size};
 use iced_core::clipboard::Kind as ClipboardKind;
@@ -7,8 +8,11 @@ use otty_libterm::surface::{BlockKind, SelectionType, SurfaceMode};
 use otty_libterm::{SnapshotArc, TerminalSize};
 
 use crate::bindings::{BindingAction, BindingsLayout, InputKind};
+use crate::block_layout::CollapsedLines;
+use crate::context_menu::ContextMenuContext;
 use crate::engine::{Engine, MouseButton};
 use crate::font::TermFont;
+use crate::paste::{PasteFilter, PasteMode};
 use crate::settings::BlockSelectionMode;
 use crate::view::TerminalViewState;
 
@@ -16,6 +20,9 @@ pub(crate) struct InputManager<'a> {
     terminal_id: u64,
     bindings: &'a BindingsLayout,
     block_selection_mode: BlockSelectionMode,
+    copy_on_select: bool,
+    paste_mode: PasteMode,
+    paste_filter: PasteFilter,
 }
 
 impl<'a> InputManager<'a> {
@@ -28,9 +35,30 @@ impl<'a> InputManager<'a> {
             terminal_id,
             bindings,
             block_selection_mode,
+            copy_on_select: false,
+            paste_mode: PasteMode::default(),
+            paste_filter: PasteFilter::default(),
         }
     }
 
+    /// Copy released selections into the clipboard when enabled.
+    pub(crate) fn with_copy_on_select(mut self, enabled: bool) -> Self {
+        self.copy_on_select = enabled;
+        self
+    }
+
+    /// Encode pastes with `mode` unless the application brackets them.
+    pub(crate) fn with_paste_mode(mut self, mode: PasteMode) -> Self {
+        self.paste_mode = mode;
+        self
+    }
+
+    /// Filter pasted text with `filter` before it is encoded.
+    pub(crate) fn with_paste_filter(mut self, filter: PasteFilter) -> Self {
+        self.paste_filter = filter;
+        self
+    }
+
     pub(crate) fn handle_mouse_event(
         &self,
         view_state: &mut TerminalViewState,
@@ -40,7 +68,7 @@ impl<'a> InputManager<'a> {
         layout_position: Point,
         cursor_position: Point,
         event: &iced::mouse::Event,
-        _clipboard: &mut dyn iced_graphics::core::Clipboard,
+        clipboard: &mut dyn iced_graphics::core::Clipboard,
         publisher: &mut impl FnMut(crate::Event),
     ) -> iced::event::Status {
         match event {
@@ -68,6 +96,27 @@ impl<'a> InputManager<'a> {
                 view_state,
                 terminal_content,
                 self.bindings,
+                clipboard,
+                publisher,
+            ),
+            iced_core::mouse::Event::ButtonPressed(
+                iced_core::mouse::Button::Right,
+            ) => self.handle_right_button(
+                view_state,
+                terminal_content,
+                cursor_position,
+                layout_position,
+                true,
+                publisher,
+            ),
+            iced_core::mouse::Event::ButtonReleased(
+                iced_core::mouse::Button::Right,
+            ) => self.handle_right_button(
+                view_state,
+                terminal_content,
+                cursor_position,
+                layout_position,
+                false,
                 publisher,
             ),
             iced::mouse::Event::WheelScrolled { delta } => self
@@ -121,6 +170,7 @@ impl<'a> InputManager<'a> {
                     cursor_position.x - layout_position.x,
                     cursor_position.y - layout_position.y,
                 ),
+                additive: state.keyboard_modifiers.alt(),
             }
         };
         publisher(cmd);
@@ -133,6 +183,47 @@ impl<'a> InputManager<'a> {
         iced::event::Status::Captured
     }
 
+    /// Report right clicks to mouse aware applications, and otherwise
+    /// describe the clicked cell so the host can open a context menu.
+    fn handle_right_button(
+        &self,
+        state: &mut TerminalViewState,
+        terminal_state: SnapshotArc,
+        cursor_position: Point,
+        layout_position: Point,
+        pressed: bool,
+        publisher: &mut impl FnMut(crate::Event),
+    ) -> iced::event::Status {
+        let view = terminal_state.view();
+        if view.mode.intersects(SurfaceMode::MOUSE_MODE) {
+            publisher(crate::Event::MouseReport {
+                id: self.terminal_id,
+                button: MouseButton::RightButton,
+                modifiers: state.keyboard_modifiers,
+                point: state.mouse_position_on_grid,
+                pressed,
+            });
+            return iced::event::Status::Captured;
+        }
+
+        if !pressed {
+            return iced::event::Status::Ignored;
+        }
+
+        publisher(crate::Event::ContextMenu {
+            id: self.terminal_id,
+            position: (
+                cursor_position.x - layout_position.x,
+                cursor_position.y - layout_position.y,
+            ),
+            context: ContextMenuContext::at(
+                &view,
+                state.mouse_position_on_grid,
+            ),
+        });
+        iced::event::Status::Captured
+    }
+
     fn handle_cursor_moved(
         &self,
         state: &mut TerminalViewState,
@@ -143,8 +234,7 @@ impl<'a> InputManager<'a> {
         publisher: &mut impl FnMut(crate::Event),
     ) -> iced::event::Status {
         let terminal_state = terminal_state.view();
-        let in_alt_screen =
-            terminal_state.mode.contains(SurfaceMode::ALT_SCREEN);
+        let in_alt_screen = terminal_state.alt_screen;
         let cursor_x = position.x - layout_position.x;
         let cursor_y = position.y - layout_position.y;
         state.mouse_position_on_grid = Engine::selection_point(
@@ -153,6 +243,14 @@ impl<'a> InputManager<'a> {
             &terminal_size,
             terminal_state.display_offset,
         );
+        if !in_alt_screen {
+            let collapsed_lines = CollapsedLines::new(
+                &terminal_state,
+                &state.collapsed_block_ids,
+            );
+            let line = &mut state.mouse_position_on_grid.line;
+            line.0 = collapsed_lines.actual_line(line.0);
+        }
 
         // Handle command or selection update based on terminal mode and modifiers
         if state.is_dragged {
@@ -246,6 +344,7 @@ impl<'a> InputManager<'a> {
         state: &mut TerminalViewState,
         terminal_state: SnapshotArc,
         bindings: &BindingsLayout, // Use the actual type of your bindings here
+        clipboard: &mut dyn iced_graphics::core::Clipboard,
         publisher: &mut impl FnMut(crate::Event),
     ) -> iced::event::Status {
         state.is_dragged = false;
@@ -264,6 +363,11 @@ impl<'a> InputManager<'a> {
                 pressed: false,
             });
             published = true;
+        } else if was_selecting && self.copy_on_select {
+            let content = terminal_state.selectable_content();
+            if !content.is_empty() {
+                clipboard.write(ClipboardKind::Standard, content);
+            }
         }
 
         if bindings.get_action(
@@ -286,7 +390,7 @@ impl<'a> InputManager<'a> {
         if self.block_selection_mode == BlockSelectionMode::PrimaryClick
             && !was_selecting
             && !terminal_state.mode.intersects(SurfaceMode::MOUSE_MODE)
-            && !terminal_state.mode.contains(SurfaceMode::ALT_SCREEN)
+            && !terminal_state.alt_screen
         {
             use mouse::click::Kind;
             let click_kind = state
@@ -397,6 +501,22 @@ impl<'a> InputManager<'a> {
         }
     }
 
+    /// Resolve a numeric keypad key press against the keypad bindings.
+    fn keypad_binding(
+        &self,
+        key: &Key,
+        modifiers: &Modifiers,
+        mode: SurfaceMode,
+    ) -> BindingAction {
+        let name = match key {
+            Key::Character(c) => c.to_string(),
+            Key::Named(Named::Enter) => "Enter".to_string(),
+            _ => return BindingAction::Ignore,
+        };
+        self.bindings
+            .get_action(InputKind::Numpad(name), modifiers, mode)
+    }
+
     pub(crate) fn handle_keyboard_event(
         &self,
         view_state: &mut TerminalViewState,
@@ -405,28 +525,59 @@ impl<'a> InputManager<'a> {
         event: &iced::keyboard::Event,
         publisher: &mut impl FnMut(crate::Event),
     ) -> iced::event::Status {
+        // Modifiers are tracked even while unfocused, so a click that
+        // focuses the terminal sees the keys already held down.
+        if let iced::keyboard::Event::ModifiersChanged(modifiers) = event {
+            view_state.keyboard_modifiers = *modifiers;
+            return iced::event::Status::Ignored;
+        }
+        if !view_state.is_focused {
+            return iced::event::Status::Ignored;
+        }
+
         let mut binding_action = BindingAction::Ignore;
         let terminal_state_ref = terminal_state.view();
 
+        // Application keypad mode re-encodes numpad keys; otherwise they
+        // fall through to the regular key handling below.
+        if let iced::keyboard::Event::KeyPressed {
+            key,
+            location: Location::Numpad,
+            modifiers,
+            ..
+        } = event
+        {
+            binding_action =
+                self.keypad_binding(key, modifiers, terminal_state_ref.mode);
+        }
+
         match event {
-            iced::keyboard::Event::ModifiersChanged(m) => {
-                view_state.keyboard_modifiers = *m;
-            },
             iced::keyboard::Event::KeyPressed {
                 key,
                 modifiers,
                 text,
                 ..
-            } => match &key {
+            } if binding_action == BindingAction::Ignore => match &key {
                 // Use the physical character key for bindings even when text is None (e.g., Ctrl/Cmd combos)
                 Key::Character(k) => {
                     let lower = k.to_ascii_lowercase();
                     binding_action = self.bindings.get_action(
-                        InputKind::Char(lower),
+                        InputKind::Char(lower.clone()),
                         &view_state.keyboard_modifiers,
                         terminal_state_ref.mode,
                     );
 
+                    // Without a selection a copy chord is a plain key press.
+                    if binding_action == BindingAction::CopyOrKey
+                        && terminal_state_ref.selectable_content().is_empty()
+                    {
+                        binding_action = self.bindings.get_shadowed_action(
+                            InputKind::Char(lower),
+                            &view_state.keyboard_modifiers,
+                            terminal_state_ref.mode,
+                        );
+                    }
+
                     // If no binding matched, only write printable text (when provided)
                     if binding_action == BindingAction::Ignore {
                         if let Some(c) = text {
@@ -438,6 +589,8 @@ impl<'a> InputManager<'a> {
                         }
                     }
                 },
+                // A modifier alone never produces input.
+                Key::Named(code) if is_modifier_key(*code) => {},
                 Key::Named(code) => {
                     binding_action = self.bindings.get_action(
                         InputKind::KeyCode(*code),
@@ -469,7 +622,10 @@ impl<'a> InputManager<'a> {
             },
             BindingAction::Paste => {
                 if let Some(data) = clipboard.read(ClipboardKind::Standard) {
-                    let input: Vec<u8> = data.bytes().collect();
+                    let input = self
+                        .paste_mode
+                        .resolve(terminal_state_ref.mode)
+                        .encode(&self.paste_filter.apply(&data));
                     publisher(crate::Event::Write {
                         id: self.terminal_id,
                         data: input,
@@ -486,11 +642,47 @@ impl<'a> InputManager<'a> {
                 );
                 iced::event::Status::Ignored
             },
+            BindingAction::CopyOrKey => {
+                clipboard.write(
+                    ClipboardKind::Standard,
+                    terminal_state_ref.selectable_content(),
+                );
+                publisher(crate::Event::ClearSelection {
+                    id: self.terminal_id,
+                });
+                iced::event::Status::Captured
+            },
+            BindingAction::ToggleSurfaceLayout => {
+                publisher(crate::Event::ToggleSurfaceLayout {
+                    id: self.terminal_id,
+                });
+                iced::event::Status::Captured
+            },
             _ => iced::event::Status::Ignored,
         }
     }
 }
 
+fn is_modifier_key(key: Named) -> bool {
+    matches!(
+        key,
+        Named::Shift
+            | Named::Control
+            | Named::Alt
+            | Named::AltGraph
+            | Named::Super
+            | Named::Meta
+            | Named::Hyper
+            | Named::Fn
+            | Named::Symbol
+            | Named::CapsLock
+            | Named::NumLock
+            | Named::ScrollLock
+            | Named::FnLock
+            | Named::SymbolLock
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -499,7 +691,7 @@ mod tests {
     use otty_libterm::escape::{Hyperlink, NamedPrivateMode};
     use otty_libterm::surface::{
         BlockKind, BlockMeta, BlockSnapshot, Column, Line,
-        Point as TerminalGridPoint, SnapshotOwned, Surface, SurfaceActor,
+        Point as TerminalGridPoint, Side, SnapshotOwned, Surface, SurfaceActor,
         SurfaceConfig, SurfaceModel,
     };
 
@@ -536,6 +728,48 @@ mod tests {
         Arc::new(surface.snapshot_owned())
     }
 
+    fn snapshot_with_text(text: &str) -> Arc<SnapshotOwned> {
+        let size = TerminalSize::default();
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        text.chars().for_each(|ch| surface.print(ch));
+        Arc::new(surface.snapshot_owned())
+    }
+
+    fn snapshot_with_selection(text: &str) -> Arc<SnapshotOwned> {
+        let size = TerminalSize::default();
+        let mut surface = Surface::new(SurfaceConfig::default(), &size);
+        for (column, ch) in text.chars().enumerate() {
+            surface.grid_mut()[Line(0)][Column(column)].c = ch;
+        }
+        let end = Column(text.chars().count() - 1);
+        surface.start_selection(
+            SelectionType::Simple,
+            TerminalGridPoint::new(Line(0), Column(0)),
+            Side::Left,
+        );
+        surface.update_selection(
+            TerminalGridPoint::new(Line(0), end),
+            Side::Right,
+        );
+        Arc::new(surface.snapshot_owned())
+    }
+
+    #[derive(Default)]
+    struct RecordingClipboard {
+        contents: Option<String>,
+        writes: Vec<(ClipboardKind, String)>,
+    }
+
+    impl iced_core::Clipboard for RecordingClipboard {
+        fn read(&self, _kind: ClipboardKind) -> Option<String> {
+            self.contents.clone()
+        }
+
+        fn write(&mut self, kind: ClipboardKind, contents: String) {
+            self.writes.push((kind, contents));
+        }
+    }
+
     fn snapshot_with_block(
         id: &str,
         kind: BlockKind,
@@ -633,7 +867,8 @@ mod tests {
                 crate::Event::SelectStart {
                     id: TEST_ID,
                     selection_type: SelectionType::Simple,
-                    position: (150.0, 100.0)
+                    position: (150.0, 100.0),
+                    additive: false,
                 }
             ));
             assert!(matches!(commands[1], crate::Event::Redraw { .. }));
@@ -641,6 +876,85 @@ mod tests {
         }
     }
 
+    mod handle_right_button_tests {
+        use super::*;
+        use crate::bindings;
+
+        fn right_click(snapshot: Arc<SnapshotOwned>) -> Vec<crate::Event> {
+            let mut state = TerminalViewState::new();
+            let mut commands = Vec::new();
+            let mut publish = |event| commands.push(event);
+            let bindings = bindings::BindingsLayout::new();
+            let input_manager = InputManager::new(
+                TEST_ID,
+                &bindings,
+                BlockSelectionMode::PrimaryClick,
+            );
+
+            for pressed in [true, false] {
+                input_manager.handle_right_button(
+                    &mut state,
+                    snapshot.clone(),
+                    Point { x: 12.0, y: 8.0 },
+                    Point { x: 2.0, y: 2.0 },
+                    pressed,
+                    &mut publish,
+                );
+            }
+            commands
+        }
+
+        #[test]
+        fn publishes_the_context_under_the_pointer() {
+            let commands = right_click(snapshot_with_hyperlink("https://a.b"));
+
+            assert_eq!(commands.len(), 1);
+            let crate::Event::ContextMenu {
+                id: TEST_ID,
+                position,
+                context,
+            } = &commands[0]
+            else {
+                panic!("expected a context menu event, got {commands:?}");
+            };
+            assert_eq!(*position, (10.0, 6.0));
+            assert_eq!(context.link(), Some("https://a.b"));
+            assert!(!context.has_selection());
+
+            let commands = right_click(snapshot_with_selection("ab"));
+            assert!(matches!(
+                &commands[..],
+                [crate::Event::ContextMenu { context, .. }]
+                    if context.has_selection()
+                        && context.is_point_selected()
+                        && context.link().is_none()
+            ));
+        }
+
+        #[test]
+        fn reports_right_clicks_in_mouse_mode() {
+            let commands = right_click(snapshot_with_modes(&[
+                NamedPrivateMode::ReportMouseClicks,
+            ]));
+
+            assert!(matches!(
+                &commands[..],
+                [
+                    crate::Event::MouseReport {
+                        button: MouseButton::RightButton,
+                        pressed: true,
+                        ..
+                    },
+                    crate::Event::MouseReport {
+                        button: MouseButton::RightButton,
+                        pressed: false,
+                        ..
+                    },
+                ]
+            ));
+        }
+    }
+
     mod handle_cursor_moved_tests {
         use super::*;
         use crate::bindings;
@@ -852,6 +1166,7 @@ mod tests {
                 &mut state,
                 snapshot_with_modes(&[NamedPrivateMode::ReportMouseClicks]),
                 &bindings,
+                &mut iced_core::clipboard::Null,
                 &mut publish,
             );
 
@@ -893,6 +1208,7 @@ mod tests {
                 &mut state,
                 snapshot_with_hyperlink("https://example.com"),
                 &bindings,
+                &mut iced_core::clipboard::Null,
                 &mut publish,
             );
 
@@ -902,6 +1218,47 @@ mod tests {
             )));
         }
 
+        #[test]
+        fn publishes_open_link_event_for_detected_url() {
+            let mut state = TerminalViewState::new();
+            state.keyboard_modifiers = Modifiers::COMMAND;
+            let bindings = BindingsLayout::new();
+            let input_manager = InputManager::new(
+                TEST_ID,
+                &bindings,
+                BlockSelectionMode::PrimaryClick,
+            );
+            let snapshot = snapshot_with_text("see https://otty.sh/docs now");
+
+            let mut open_link_at = |column| {
+                state.mouse_position_on_grid =
+                    TerminalGridPoint::new(Line(0), Column(column));
+                let mut commands = Vec::new();
+                input_manager.handle_button_released(
+                    &mut state,
+                    snapshot.clone(),
+                    &bindings,
+                    &mut iced_core::clipboard::Null,
+                    &mut |event| commands.push(event),
+                );
+                commands.into_iter().find_map(|event| match event {
+                    crate::Event::OpenLink { uri, .. } => Some(uri),
+                    _ => None,
+                })
+            };
+
+            assert_eq!(
+                open_link_at(4).as_deref(),
+                Some("https://otty.sh/docs")
+            );
+            assert_eq!(
+                open_link_at(23).as_deref(),
+                Some("https://otty.sh/docs")
+            );
+            assert_eq!(open_link_at(2), None);
+            assert_eq!(open_link_at(25), None);
+        }
+
         #[test]
         fn selects_block_on_simple_click() {
             let mut state = TerminalViewState::new();
@@ -920,6 +1277,7 @@ mod tests {
                 &mut state,
                 default_snapshot(),
                 &bindings,
+                &mut iced_core::clipboard::Null,
                 &mut publish,
             );
 
@@ -958,6 +1316,7 @@ mod tests {
                 &mut state,
                 default_snapshot(),
                 &bindings,
+                &mut iced_core::clipboard::Null,
                 &mut publish,
             );
 
@@ -985,6 +1344,7 @@ mod tests {
                 &mut state,
                 default_snapshot(),
                 &bindings,
+                &mut iced_core::clipboard::Null,
                 &mut publish,
             );
 
@@ -1023,6 +1383,7 @@ mod tests {
                 &mut state,
                 default_snapshot(),
                 &bindings,
+                &mut iced_core::clipboard::Null,
                 &mut publish,
             );
 
@@ -1054,6 +1415,7 @@ mod tests {
                 &mut state,
                 default_snapshot(),
                 &bindings,
+                &mut iced_core::clipboard::Null,
                 &mut publish,
             );
 
@@ -1061,6 +1423,421 @@ mod tests {
             assert!(commands.is_empty());
             assert_eq!(status, iced::event::Status::Ignored);
         }
+
+        #[test]
+        fn copy_on_select_stores_released_selection() {
+            let mut state = TerminalViewState::new();
+            state.selection_in_progress = true;
+            let bindings = BindingsLayout::new();
+            let mut clipboard = RecordingClipboard::default();
+            let mut publish = |_| {};
+            let input_manager = InputManager::new(
+                TEST_ID,
+                &bindings,
+                BlockSelectionMode::PrimaryClick,
+            )
+            .with_copy_on_select(true);
+
+            input_manager.handle_button_released(
+                &mut state,
+                snapshot_with_selection("hello"),
+                &bindings,
+                &mut clipboard,
+                &mut publish,
+            );
+
+            assert_eq!(
+                clipboard.writes,
+                vec![(ClipboardKind::Standard, String::from("hello"))]
+            );
+        }
+
+        #[test]
+        fn copy_on_select_disabled_leaves_clipboard_untouched() {
+            let mut state = TerminalViewState::new();
+            state.selection_in_progress = true;
+            let bindings = BindingsLayout::new();
+            let mut clipboard = RecordingClipboard::default();
+            let mut publish = |_| {};
+            let input_manager = InputManager::new(
+                TEST_ID,
+                &bindings,
+                BlockSelectionMode::PrimaryClick,
+            );
+
+            input_manager.handle_button_released(
+                &mut state,
+                snapshot_with_selection("hello"),
+                &bindings,
+                &mut clipboard,
+                &mut publish,
+            );
+
+            assert!(clipboard.writes.is_empty());
+        }
+    }
+
+    mod handle_keyboard_event_tests {
+        use iced::keyboard::key::{Code, Physical};
+
+        use super::*;
+
+        fn key_press(
+            key: Key,
+            code: Code,
+            location: Location,
+        ) -> iced::keyboard::Event {
+            let text = match &key {
+                Key::Character(c) => Some(c.clone()),
+                _ => None,
+            };
+            iced::keyboard::Event::KeyPressed {
+                modified_key: key.clone(),
+                key,
+                physical_key: Physical::Code(code),
+                location,
+                modifiers: Modifiers::empty(),
+                text,
+                repeat: false,
+            }
+        }
+
+        fn written(
+            snapshot: Arc<SnapshotOwned>,
+            event: &iced::keyboard::Event,
+        ) -> Vec<u8> {
+            let mut state = TerminalViewState::new();
+            let mut clipboard = RecordingClipboard::default();
+            written_to(&mut state, &mut clipboard, snapshot, event)
+        }
+
+        fn written_to(
+            state: &mut TerminalViewState,
+            clipboard: &mut RecordingClipboard,
+            snapshot: Arc<SnapshotOwned>,
+            event: &iced::keyboard::Event,
+        ) -> Vec<u8> {
+            let bindings = BindingsLayout::new();
+            let input_manager = InputManager::new(
+                TEST_ID,
+                &bindings,
+                BlockSelectionMode::PrimaryClick,
+            );
+            let mut writes = Vec::new();
+            let mut publish = |event| {
+                if let crate::Event::Write { data, .. } = event {
+                    writes.extend(data);
+                }
+            };
+
+            input_manager.handle_keyboard_event(
+                state,
+                snapshot,
+                clipboard,
+                event,
+                &mut publish,
+            );
+            writes
+        }
+
+        fn pasted(
+            paste_mode: PasteMode,
+            paste_filter: PasteFilter,
+            snapshot: Arc<SnapshotOwned>,
+            text: &str,
+        ) -> Vec<u8> {
+            let bindings = BindingsLayout::new();
+            let input_manager = InputManager::new(
+                TEST_ID,
+                &bindings,
+                BlockSelectionMode::PrimaryClick,
+            )
+            .with_paste_mode(paste_mode)
+            .with_paste_filter(paste_filter);
+            let mut clipboard = RecordingClipboard {
+                contents: Some(text.to_string()),
+                ..RecordingClipboard::default()
+            };
+            let modifiers = if cfg!(target_os = "macos") {
+                Modifiers::COMMAND
+            } else {
+                Modifiers::SHIFT | Modifiers::COMMAND
+            };
+            let paste = iced::keyboard::Event::KeyPressed {
+                modified_key: Key::Character("v".into()),
+                key: Key::Character("v".into()),
+                physical_key: Physical::Code(Code::KeyV),
+                location: Location::Standard,
+                modifiers,
+                text: None,
+                repeat: false,
+            };
+            let mut state = TerminalViewState::new();
+            state.keyboard_modifiers = modifiers;
+            let mut writes = Vec::new();
+
+            input_manager.handle_keyboard_event(
+                &mut state,
+                snapshot,
+                &mut clipboard,
+                &paste,
+                &mut |event| {
+                    if let crate::Event::Write { data, .. } = event {
+                        writes.extend(data);
+                    }
+                },
+            );
+            writes
+        }
+
+        #[test]
+        fn paste_is_encoded_with_the_configured_mode() {
+            let text = "ls\x1b[2J\nexit\n";
+            let keystrokes = PasteMode::AsKeystrokes {
+                newline_to_cr: true,
+            };
+
+            assert_eq!(
+                pasted(
+                    PasteMode::Raw,
+                    PasteFilter::default(),
+                    default_snapshot(),
+                    text
+                ),
+                text.as_bytes()
+            );
+            assert_eq!(
+                pasted(
+                    keystrokes,
+                    PasteFilter::default(),
+                    default_snapshot(),
+                    text
+                ),
+                b"ls[2J\rexit\r"
+            );
+            assert_eq!(
+                pasted(
+                    keystrokes,
+                    PasteFilter::default(),
+                    snapshot_with_modes(&[NamedPrivateMode::BracketedPaste]),
+                    text
+                ),
+                b"\x1b[200~ls[2J\nexit\n\x1b[201~"
+            );
+        }
+
+        #[test]
+        fn pasted_end_marker_cannot_close_the_bracket() {
+            let text = "safe\x1b[201~\nrm -rf ~\n";
+            let bracketed =
+                snapshot_with_modes(&[NamedPrivateMode::BracketedPaste]);
+            let join = PasteFilter::default().with_join_lines(true);
+
+            assert_eq!(
+                pasted(
+                    PasteMode::Raw,
+                    PasteFilter::default(),
+                    Arc::clone(&bracketed),
+                    text
+                ),
+                b"\x1b[200~safe[201~\nrm -rf ~\n\x1b[201~"
+            );
+            assert_eq!(
+                pasted(PasteMode::Raw, join, bracketed, text),
+                b"\x1b[200~safe[201~ rm -rf ~\x1b[201~"
+            );
+        }
+
+        #[test]
+        fn only_a_focused_terminal_writes_keystrokes() {
+            let key = key_press(
+                Key::Character("a".into()),
+                Code::KeyA,
+                Location::Standard,
+            );
+            let mut state = TerminalViewState::new();
+            let mut clipboard = RecordingClipboard::default();
+            assert_eq!(
+                written_to(
+                    &mut state,
+                    &mut clipboard,
+                    default_snapshot(),
+                    &key
+                ),
+                b"a"
+            );
+
+            state.is_focused = false;
+            assert!(
+                written_to(
+                    &mut state,
+                    &mut clipboard,
+                    default_snapshot(),
+                    &key
+                )
+                .is_empty()
+            );
+
+            let modifiers =
+                iced::keyboard::Event::ModifiersChanged(Modifiers::CTRL);
+            written_to(
+                &mut state,
+                &mut clipboard,
+                default_snapshot(),
+                &modifiers,
+            );
+            assert_eq!(state.keyboard_modifiers, Modifiers::CTRL);
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        #[test]
+        fn ctrl_c_copies_a_selection_and_interrupts_otherwise() {
+            let key = key_press(
+                Key::Character("c".into()),
+                Code::KeyC,
+                Location::Standard,
+            );
+            let mut state = TerminalViewState::new();
+            state.keyboard_modifiers = Modifiers::CTRL;
+
+            let mut clipboard = RecordingClipboard::default();
+            let selected = snapshot_with_selection("hello");
+            assert!(
+                written_to(&mut state, &mut clipboard, selected, &key)
+                    .is_empty()
+            );
+            assert_eq!(
+                clipboard.writes,
+                [(ClipboardKind::Standard, "hello".to_string())]
+            );
+
+            let mut clipboard = RecordingClipboard::default();
+            assert_eq!(
+                written_to(
+                    &mut state,
+                    &mut clipboard,
+                    default_snapshot(),
+                    &key
+                ),
+                b"\x03"
+            );
+            assert!(clipboard.writes.is_empty());
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        #[test]
+        fn second_ctrl_c_after_a_copy_interrupts() {
+            let key = key_press(
+                Key::Character("c".into()),
+                Code::KeyC,
+                Location::Standard,
+            );
+            let mut state = TerminalViewState::new();
+            state.keyboard_modifiers = Modifiers::CTRL;
+            let bindings = BindingsLayout::new();
+            let input_manager = InputManager::new(
+                TEST_ID,
+                &bindings,
+                BlockSelectionMode::PrimaryClick,
+            );
+            let mut clipboard = RecordingClipboard::default();
+            let mut surface = Surface::new(
+                SurfaceConfig::default(),
+                &TerminalSize::default(),
+            );
+            surface.grid_mut()[Line(0)][Column(0)].c = 'x';
+            surface.start_selection(
+                SelectionType::Simple,
+                TerminalGridPoint::new(Line(0), Column(0)),
+                Side::Left,
+            );
+            surface.update_selection(
+                TerminalGridPoint::new(Line(0), Column(0)),
+                Side::Right,
+            );
+
+            let mut events = Vec::new();
+            input_manager.handle_keyboard_event(
+                &mut state,
+                Arc::new(surface.snapshot_owned()),
+                &mut clipboard,
+                &key,
+                &mut |event| events.push(event),
+            );
+            assert!(matches!(
+                events.as_slice(),
+                [crate::Event::ClearSelection { id: TEST_ID }]
+            ));
+            assert_eq!(clipboard.writes.len(), 1);
+
+            // The engine drops the selection on the published request.
+            surface.clear_selection();
+            let snapshot = Arc::new(surface.snapshot_owned());
+            assert_eq!(
+                written_to(&mut state, &mut clipboard, snapshot, &key),
+                b"\x03"
+            );
+            assert_eq!(clipboard.writes.len(), 1);
+        }
+
+        #[test]
+        fn modifier_keys_alone_write_nothing() {
+            for (key, code) in [
+                (Named::Shift, Code::ShiftLeft),
+                (Named::Control, Code::ControlLeft),
+                (Named::Alt, Code::AltRight),
+                (Named::Super, Code::SuperLeft),
+            ] {
+                let event = key_press(Key::Named(key), code, Location::Left);
+                assert!(written(default_snapshot(), &event).is_empty());
+            }
+        }
+
+        #[test]
+        fn arrow_keys_switch_encoding_with_decckm() {
+            let up = key_press(
+                Key::Named(Named::ArrowUp),
+                Code::ArrowUp,
+                Location::Standard,
+            );
+
+            assert_eq!(written(default_snapshot(), &up), b"\x1b[A");
+            assert_eq!(
+                written(
+                    snapshot_with_modes(&[NamedPrivateMode::CursorKeys]),
+                    &up
+                ),
+                b"\x1bOA"
+            );
+        }
+
+        #[test]
+        fn keypad_digits_switch_encoding_with_deckpam() {
+            let size = TerminalSize::default();
+            let mut surface = Surface::new(SurfaceConfig::default(), &size);
+            let five = key_press(
+                Key::Character("5".into()),
+                Code::Numpad5,
+                Location::Numpad,
+            );
+
+            assert_eq!(
+                written(Arc::new(surface.snapshot_owned()), &five),
+                b"5"
+            );
+
+            surface.set_keypad_application_mode(true);
+            assert_eq!(
+                written(Arc::new(surface.snapshot_owned()), &five),
+                b"\x1bOu"
+            );
+
+            surface.set_keypad_application_mode(false);
+            assert_eq!(
+                written(Arc::new(surface.snapshot_owned()), &five),
+                b"5"
+            );
+        }
     }
 
     mod handle_wheel_scrolled_tests {
@@ -3,11 +3,16 @@ pub mod settings;
 
 mod block_controls;
 mod block_layout;
+mod context_menu;
+mod cursor;
 mod engine;
 mod error;
 mod font;
+mod ime;
 mod input;
+mod paste;
 mod render_runs;
+mod scrollbar;
 mod shaped_text;
 mod term;
 mod theme;
@@ -17,9 +22,14 @@ pub use block_controls::{
     BlockActionButtonGeometry, compute_action_button_geometry,
 };
 pub use block_layout::{BlockRect, block_rects};
+pub use context_menu::ContextMenuContext;
+pub use cursor::{CURSOR_BLINK_INTERVAL, CursorDescriptor};
 pub use font::font_measure;
-pub use otty_libterm::surface::{BlockKind, BlockSnapshot, SurfaceMode};
+pub use otty_libterm::surface::{
+    BlockKind, BlockSnapshot, SurfaceLayout, SurfaceMode,
+};
 pub use otty_libterm::{SnapshotArc, TerminalEvent};
+pub use paste::{PasteFilter, PasteMode};
 pub use term::{BlockCommand, BlockUiMode, Event, Terminal};
 pub use theme::{ColorPalette, Theme, parse_hex_color};
 pub use view::TerminalView;
@@ -0,0 +1,238 @@
+//! Encoding of clipboard text before it is written to the PTY.
+
+use std::borrow::Cow;
+
+use otty_libterm::surface::SurfaceMode;
+
+const BRACKETED_PASTE_START: &[u8] = b"\x1b[200~";
+const BRACKETED_PASTE_END: &[u8] = b"\x1b[201~";
+/// 8-bit form of `ESC [`, which can also spell the bracketed paste markers.
+const C1_CSI: char = '\u{9b}';
+
+/// How pasted text is delivered to the running application.
+///
+/// Applications that enable bracketed paste always receive
+/// [`PasteMode::Bracketed`] pastes; the configured mode applies otherwise.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum PasteMode {
+    /// Wrap the text in bracketed paste markers so the application can tell
+    /// it apart from typed input. Embedded `ESC` and 8-bit CSI characters
+    /// are dropped so the text cannot close the bracket early.
+    Bracketed,
+    /// Write the text unchanged.
+    #[default]
+    Raw,
+    /// Deliver the text as if it was typed key by key.
+    ///
+    /// Control characters other than tabs and newlines are dropped, which
+    /// keeps pasted escape sequences from reaching the application.
+    AsKeystrokes {
+        /// Send newlines as carriage returns, like the Enter key does.
+        newline_to_cr: bool,
+    },
+}
+
+impl PasteMode {
+    /// Pick the mode for a paste into a terminal with the given modes.
+    pub fn resolve(self, mode: SurfaceMode) -> Self {
+        if mode.contains(SurfaceMode::BRACKETED_PASTE) {
+            Self::Bracketed
+        } else {
+            self
+        }
+    }
+
+    /// Encode `text` into the bytes written to the PTY.
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        match self {
+            Self::Bracketed => {
+                let mut bytes = Vec::with_capacity(
+                    text.len()
+                        + BRACKETED_PASTE_START.len()
+                        + BRACKETED_PASTE_END.len(),
+                );
+                bytes.extend_from_slice(BRACKETED_PASTE_START);
+                let inner = text
+                    .chars()
+                    .filter(|&ch| !matches!(ch, '\x1b' | C1_CSI))
+                    .collect::<String>();
+                bytes.extend_from_slice(inner.as_bytes());
+                bytes.extend_from_slice(BRACKETED_PASTE_END);
+                bytes
+            },
+            Self::Raw => text.as_bytes().to_vec(),
+            Self::AsKeystrokes { newline_to_cr } => {
+                encode_keystrokes(text, newline_to_cr)
+            },
+        }
+    }
+}
+
+/// Filtering applied to pasted text before it is encoded, whatever the
+/// [`PasteMode`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct PasteFilter {
+    strip_controls: bool,
+    join_lines: bool,
+}
+
+impl PasteFilter {
+    /// Whether control characters other than tabs and line breaks are
+    /// dropped.
+    pub fn strips_controls(&self) -> bool {
+        self.strip_controls
+    }
+
+    /// Whether text spanning several lines is pasted as a single line.
+    pub fn joins_lines(&self) -> bool {
+        self.join_lines
+    }
+
+    /// Drop control characters other than tabs and line breaks.
+    pub fn with_strip_controls(mut self, strip_controls: bool) -> Self {
+        self.strip_controls = strip_controls;
+        self
+    }
+
+    /// Paste text spanning several lines as a single line, so its line
+    /// breaks cannot run commands. Trailing line breaks are dropped and the
+    /// others become spaces.
+    pub fn with_join_lines(mut self, join_lines: bool) -> Self {
+        self.join_lines = join_lines;
+        self
+    }
+
+    /// Apply the filter to `text`, borrowing it when nothing changes.
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let strips = |ch: char| {
+            self.strip_controls
+                && ch.is_control()
+                && !matches!(ch, '\t' | '\n' | '\r')
+        };
+        let is_line_break = |ch: char| matches!(ch, '\n' | '\r');
+        let untouched = !text
+            .chars()
+            .any(|ch| strips(ch) || (self.join_lines && is_line_break(ch)));
+        if untouched {
+            return Cow::Borrowed(text);
+        }
+
+        let text = if self.join_lines {
+            text.trim_end_matches(is_line_break)
+        } else {
+            text
+        };
+        let mut filtered = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                ch if strips(ch) => {},
+                '\r' | '\n' if self.join_lines => {
+                    // A CRLF pair is a single line break.
+                    if ch == '\r' {
+                        chars.next_if_eq(&'\n');
+                    }
+                    filtered.push(' ');
+                },
+                ch => filtered.push(ch),
+            }
+        }
+        Cow::Owned(filtered)
+    }
+}
+
+fn encode_keystrokes(text: &str, newline_to_cr: bool) -> Vec<u8> {
+    let mut encoded = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\r' if newline_to_cr => {
+                // Collapse CRLF into the single CR an Enter key press sends.
+                chars.next_if_eq(&'\n');
+                encoded.push('\r');
+            },
+            '\n' if newline_to_cr => encoded.push('\r'),
+            '\t' | '\n' | '\r' => encoded.push(ch),
+            ch if ch.is_control() => {},
+            ch => encoded.push(ch),
+        }
+    }
+
+    encoded.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_paste_is_written_unchanged() {
+        let text = "echo hi\n\x1b[31mred\r\n";
+
+        assert_eq!(PasteMode::Raw.encode(text), text.as_bytes());
+    }
+
+    #[test]
+    fn bracketed_paste_wraps_text_and_drops_escape_bytes() {
+        let encoded = PasteMode::Bracketed.encode("rm -rf /\x1b[201~\nls\n");
+
+        assert_eq!(encoded, b"\x1b[200~rm -rf /[201~\nls\n\x1b[201~");
+    }
+
+    #[test]
+    fn bracketed_paste_neutralizes_8_bit_markers() {
+        let encoded = PasteMode::Bracketed.encode("a\u{9b}201~b\u{9b}200~é");
+
+        assert_eq!(encoded, "\x1b[200~a201~b200~é\x1b[201~".as_bytes());
+    }
+
+    #[test]
+    fn paste_filter_strips_controls_and_joins_lines() {
+        let text = "echo a\x07\x1b[2J\r\necho b\n\n";
+        let strip = PasteFilter::default().with_strip_controls(true);
+        let join = PasteFilter::default().with_join_lines(true);
+
+        assert_eq!(PasteFilter::default().apply(text), text);
+        assert_eq!(strip.apply(text), "echo a[2J\r\necho b\n\n");
+        assert_eq!(join.apply(text), "echo a\x07\x1b[2J echo b");
+        assert!(matches!(join.apply("ls -l"), Cow::Borrowed("ls -l")));
+    }
+
+    #[test]
+    fn keystroke_paste_strips_embedded_escapes() {
+        let text = "a\x1b[2J\x07b\u{9b}0m\tc\x03\nd";
+
+        assert_eq!(
+            PasteMode::AsKeystrokes {
+                newline_to_cr: false
+            }
+            .encode(text),
+            "a[2Jb0m\tc\nd".as_bytes()
+        );
+    }
+
+    #[test]
+    fn keystroke_paste_can_send_newlines_as_enter() {
+        let mode = PasteMode::AsKeystrokes {
+            newline_to_cr: true,
+        };
+
+        assert_eq!(
+            mode.encode("one\ntwo\r\nthree\rfour"),
+            b"one\rtwo\rthree\rfour"
+        );
+    }
+
+    #[test]
+    fn bracketed_paste_mode_overrides_configured_mode() {
+        let mode = PasteMode::AsKeystrokes {
+            newline_to_cr: true,
+        };
+
+        assert_eq!(
+            mode.resolve(SurfaceMode::BRACKETED_PASTE),
+            PasteMode::Bracketed
+        );
+        assert_eq!(mode.resolve(SurfaceMode::empty()), mode);
+    }
+}
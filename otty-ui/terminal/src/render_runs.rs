@@ -10,7 +10,8 @@ use std::ops::Range;
 use iced::font::{Style as FontStyle, Weight as FontWeight};
 use iced::{Color, Font};
 use otty_libterm::surface::{
-    Flags, Point as TerminalPoint, SelectionRange, SnapshotCell, SnapshotView,
+    Flags, Match, Point as TerminalPoint, SelectionRange, SnapshotCell,
+    SnapshotView,
 };
 
 use crate::theme::Theme;
@@ -35,6 +36,10 @@ impl RenderRun {
         self.line
     }
 
+    pub(crate) fn set_line(&mut self, line: i32) {
+        self.line = line;
+    }
+
     pub(crate) fn start_column(&self) -> usize {
         self.start_column
     }
@@ -219,6 +224,8 @@ impl RenderTextStyle {
 
 struct RenderRunBuildContext<'a> {
     selection: Option<&'a SelectionRange>,
+    extra_selections: &'a [SelectionRange],
+    search_match: Option<&'a Match>,
     cursor_point: TerminalPoint,
     theme: &'a Theme,
     base_font: Font,
@@ -236,6 +243,8 @@ pub(crate) fn build_render_runs(
 ) -> Vec<RenderRun> {
     let context = RenderRunBuildContext {
         selection: view.selection,
+        extra_selections: view.extra_selections,
+        search_match: view.search_match,
         cursor_point: view.cursor.point,
         theme,
         base_font,
@@ -260,8 +269,7 @@ where
     let mut current: Option<RenderRun> = None;
 
     for indexed in cells {
-        let flags = indexed.cell.flags;
-        if flags.contains(Flags::WIDE_CHAR_SPACER) {
+        if indexed.is_wide_spacer() {
             continue;
         }
         if indexed.cell.c == '\t' {
@@ -272,11 +280,7 @@ where
         let point = indexed.point;
         let line = point.line.0;
         let column = point.column.0;
-        let cell_columns = if flags.contains(Flags::WIDE_CHAR) {
-            2
-        } else {
-            1
-        };
+        let cell_columns = if indexed.is_wide() { 2 } else { 1 };
         let style = resolve_text_style(indexed, context, &span_id_at);
         let requires_isolated_run = requires_grid_isolated_run(indexed);
         let can_extend = current.as_ref().is_some_and(|run| {
@@ -354,10 +358,17 @@ where
 {
     let flags = indexed.cell.flags;
     let is_inverse = flags.contains(Flags::INVERSE);
-    let is_dim = flags.intersects(Flags::DIM | Flags::DIM_BOLD);
+    let is_dim = flags.contains(Flags::DIM);
     let selected = context
         .selection
-        .is_some_and(|range| range.contains(indexed.point));
+        .is_some_and(|range| range.contains(indexed.point))
+        || context
+            .extra_selections
+            .iter()
+            .any(|range| range.contains(indexed.point))
+        || context
+            .search_match
+            .is_some_and(|range| range.contains(&indexed.point));
     let hovered_hyperlink = context
         .hovered_span_id
         .is_some_and(|target| span_id_at(indexed.point) == Some(target));
@@ -377,7 +388,7 @@ where
     }
 
     let mut font = context.base_font;
-    if flags.intersects(Flags::BOLD | Flags::DIM_BOLD) {
+    if flags.contains(Flags::BOLD) {
         font.weight = FontWeight::Bold;
     }
     if flags.contains(Flags::ITALIC) {
@@ -440,6 +451,8 @@ mod tests {
         let theme = Theme::default();
         let context = RenderRunBuildContext {
             selection: None,
+            extra_selections: &[],
+            search_match: None,
             cursor_point: TerminalPoint::default(),
             theme: &theme,
             base_font: Font::MONOSPACE,
@@ -606,6 +619,28 @@ mod tests {
         assert_eq!(runs[2].font().style, FontStyle::Italic);
     }
 
+    #[test]
+    fn dim_cells_are_faint_without_turning_bold() {
+        let mut cells = cells_from_text(0, "abc");
+        cells[1].cell.flags.insert(Flags::DIM);
+        cells[2].cell.flags.insert(Flags::BOLD);
+
+        let runs = build(&cells);
+
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].text(), "ab");
+        assert_eq!(runs[0].font().weight, FontWeight::Normal);
+        assert_eq!(runs[1].font().weight, FontWeight::Bold);
+
+        let spans = runs[0].color_spans();
+        assert_eq!(spans.len(), 2);
+        assert!(spans[1].foreground().a < spans[0].foreground().a);
+        assert_eq!(
+            runs[1].color_spans()[0].foreground().a,
+            spans[0].foreground().a
+        );
+    }
+
     #[test]
     fn foreground_changes_create_color_spans_without_splitting_shape_run() {
         let mut cells = cells_from_text(0, "abc");
@@ -637,6 +672,8 @@ mod tests {
         let theme = Theme::default();
         let context = RenderRunBuildContext {
             selection: Some(&selection),
+            extra_selections: &[],
+            search_match: None,
             cursor_point: TerminalPoint::default(),
             theme: &theme,
             base_font: Font::MONOSPACE,
@@ -668,6 +705,8 @@ mod tests {
         let theme = Theme::default();
         let context = RenderRunBuildContext {
             selection: Some(&selection),
+            extra_selections: &[],
+            search_match: None,
             cursor_point: TerminalPoint::default(),
             theme: &theme,
             base_font: Font::MONOSPACE,
@@ -688,6 +727,8 @@ mod tests {
         let theme = Theme::default();
         let context = RenderRunBuildContext {
             selection: None,
+            extra_selections: &[],
+            search_match: None,
             cursor_point: TerminalPoint::default(),
             theme: &theme,
             base_font: Font::MONOSPACE,
@@ -709,6 +750,8 @@ mod tests {
         let theme = Theme::default();
         let context = RenderRunBuildContext {
             selection: None,
+            extra_selections: &[],
+            search_match: None,
             cursor_point: TerminalPoint::new(Line(0), Column(1)),
             theme: &theme,
             base_font: Font::MONOSPACE,
@@ -0,0 +1,193 @@
+//! Scrollbar reflecting the scrollback position of the viewport.
+
+use iced::{Point, Rectangle, Size};
+
+/// Width of the scrollbar track, in logical pixels.
+pub(crate) const SCROLLBAR_WIDTH: f32 = 8.0;
+/// Smallest thumb height, so long histories keep a grabbable thumb.
+const MIN_THUMB_HEIGHT: f32 = 16.0;
+
+/// Geometry of the scrollbar for one snapshot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Scrollbar {
+    track: Rectangle,
+    thumb_height: f32,
+    /// Scrollback lines above the screen.
+    history: usize,
+}
+
+/// Thumb drag in progress.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ScrollbarDrag {
+    /// Distance between the pointer and the top of the thumb.
+    grab: f32,
+    /// Scroll offset requested by the last drag step.
+    offset: usize,
+}
+
+impl Scrollbar {
+    /// Scrollbar along the right edge of `bounds`.
+    ///
+    /// Returns `None` when there is no scrollback to move through, which
+    /// hides the scrollbar.
+    pub(crate) fn new(
+        bounds: Rectangle,
+        screen_lines: usize,
+        total_lines: usize,
+    ) -> Option<Self> {
+        let history = total_lines.saturating_sub(screen_lines);
+        if history == 0 || bounds.height <= 0.0 {
+            return None;
+        }
+
+        let track = Rectangle::new(
+            Point::new(bounds.x + bounds.width - SCROLLBAR_WIDTH, bounds.y),
+            Size::new(SCROLLBAR_WIDTH, bounds.height),
+        );
+        let visible = screen_lines as f32 / total_lines as f32;
+        let thumb_height = (track.height * visible)
+            .max(MIN_THUMB_HEIGHT)
+            .min(track.height);
+
+        Some(Self {
+            track,
+            thumb_height,
+            history,
+        })
+    }
+
+    /// Thumb rectangle for the viewport scrolled `scroll_offset` lines up.
+    pub(crate) fn thumb(&self, scroll_offset: usize) -> Rectangle {
+        let scrolled = scroll_offset.min(self.history) as f32;
+        let progress = 1.0 - scrolled / self.history as f32;
+        Rectangle::new(
+            Point::new(self.track.x, self.track.y + self.travel() * progress),
+            Size::new(self.track.width, self.thumb_height),
+        )
+    }
+
+    /// Scroll offset placing the top of the thumb at `thumb_top`.
+    ///
+    /// The top of the track maps to the oldest line, the bottom to the
+    /// live output.
+    pub(crate) fn offset_at(&self, thumb_top: f32) -> usize {
+        let travel = self.travel();
+        let progress = if travel > 0.0 {
+            ((thumb_top - self.track.y) / travel).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        ((1.0 - progress) * self.history as f32).round() as usize
+    }
+
+    /// Start a drag for a press at `point`.
+    ///
+    /// Pressing the thumb grabs it where it was hit, pressing elsewhere on
+    /// the track centers the thumb under the pointer. Returns the drag
+    /// together with the scroll delta to request right away, or `None` if
+    /// the press missed the track.
+    pub(crate) fn press(
+        &self,
+        point: Point,
+        scroll_offset: usize,
+    ) -> Option<(ScrollbarDrag, i32)> {
+        if !self.track.contains(point) {
+            return None;
+        }
+
+        let thumb = self.thumb(scroll_offset);
+        let grab = if thumb.contains(point) {
+            point.y - thumb.y
+        } else {
+            self.thumb_height / 2.0
+        };
+
+        let mut drag = ScrollbarDrag {
+            grab,
+            offset: scroll_offset,
+        };
+        let delta = drag.move_to(self, point.y);
+        Some((drag, delta))
+    }
+
+    fn travel(&self) -> f32 {
+        self.track.height - self.thumb_height
+    }
+}
+
+impl ScrollbarDrag {
+    /// Follow the pointer to `y`, returning the scroll delta to request.
+    ///
+    /// Positive deltas scroll up into the history.
+    pub(crate) fn move_to(&mut self, scrollbar: &Scrollbar, y: f32) -> i32 {
+        let offset = scrollbar.offset_at(y - self.grab);
+        let delta = offset as i64 - self.offset as i64;
+        self.offset = offset;
+        delta as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrollbar() -> Scrollbar {
+        // 10 of 50 lines visible on a 200px track: a 40px thumb.
+        Scrollbar::new(
+            Rectangle::new(Point::ORIGIN, Size::new(100.0, 200.0)),
+            10,
+            50,
+        )
+        .expect("scrollback")
+    }
+
+    #[test]
+    fn scrollbar_is_hidden_without_scrollback() {
+        let bounds = Rectangle::new(Point::ORIGIN, Size::new(100.0, 200.0));
+        assert_eq!(Scrollbar::new(bounds, 10, 10), None);
+    }
+
+    #[test]
+    fn thumb_position_maps_to_scroll_offset() {
+        let scrollbar = scrollbar();
+
+        let bottom = scrollbar.thumb(0);
+        assert_eq!(bottom.x, 92.0);
+        assert_eq!(bottom.y, 160.0);
+        assert_eq!(bottom.height, 40.0);
+        assert_eq!(scrollbar.thumb(40).y, 0.0);
+        assert_eq!(scrollbar.thumb(20).y, 80.0);
+
+        assert_eq!(scrollbar.offset_at(160.0), 0);
+        assert_eq!(scrollbar.offset_at(80.0), 20);
+        assert_eq!(scrollbar.offset_at(0.0), 40);
+        assert_eq!(scrollbar.offset_at(-30.0), 40);
+        assert_eq!(scrollbar.offset_at(500.0), 0);
+    }
+
+    #[test]
+    fn dragging_the_thumb_to_the_top_reaches_the_oldest_line() {
+        let scrollbar = scrollbar();
+
+        let (mut drag, delta) = scrollbar
+            .press(Point::new(95.0, 170.0), 0)
+            .expect("press on thumb");
+        assert_eq!(delta, 0);
+
+        assert_eq!(drag.move_to(&scrollbar, 90.0), 20);
+        assert_eq!(drag.move_to(&scrollbar, -50.0), 20);
+        assert_eq!(drag.move_to(&scrollbar, 130.0), -30);
+    }
+
+    #[test]
+    fn pressing_the_track_centers_the_thumb_under_the_pointer() {
+        let scrollbar = scrollbar();
+
+        assert_eq!(scrollbar.press(Point::new(50.0, 20.0), 0), None);
+
+        let (_, delta) = scrollbar
+            .press(Point::new(95.0, 20.0), 0)
+            .expect("press on track");
+        assert_eq!(delta, 40);
+    }
+}
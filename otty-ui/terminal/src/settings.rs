@@ -8,6 +8,7 @@ use iced::Font;
 use otty_libterm::TerminalSize;
 use otty_libterm::pty::SSHAuth;
 
+use crate::paste::{PasteFilter, PasteMode};
 use crate::theme::ColorPalette;
 
 #[cfg(target_os = "windows")]
@@ -51,6 +52,9 @@ pub enum BlockSelectionMode {
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
 pub struct InteractionSettings {
     block_selection_mode: BlockSelectionMode,
+    copy_on_select: bool,
+    paste_mode: PasteMode,
+    paste_filter: PasteFilter,
 }
 
 impl InteractionSettings {
@@ -67,6 +71,39 @@ impl InteractionSettings {
         self.block_selection_mode = mode;
         self
     }
+
+    /// Return whether finished selections are copied automatically.
+    pub fn copy_on_select(&self) -> bool {
+        self.copy_on_select
+    }
+
+    /// Copy the selection to the clipboard as soon as it is released.
+    pub fn with_copy_on_select(mut self, enabled: bool) -> Self {
+        self.copy_on_select = enabled;
+        self
+    }
+
+    /// Return how pastes are delivered when bracketed paste is off.
+    pub fn paste_mode(&self) -> PasteMode {
+        self.paste_mode
+    }
+
+    /// Set how pastes are delivered when bracketed paste is off.
+    pub fn with_paste_mode(mut self, mode: PasteMode) -> Self {
+        self.paste_mode = mode;
+        self
+    }
+
+    /// Return the filter applied to pasted text.
+    pub fn paste_filter(&self) -> PasteFilter {
+        self.paste_filter
+    }
+
+    /// Set the filter applied to pasted text in every paste mode.
+    pub fn with_paste_filter(mut self, filter: PasteFilter) -> Self {
+        self.paste_filter = filter;
+        self
+    }
 }
 
 #[derive(Default, Debug, Clone)]
@@ -235,6 +272,9 @@ pub struct FontSettings {
     pub size: f32,
     pub scale_factor: f32,
     pub font_type: Font,
+    /// Fonts tried in order for glyphs missing from `font_type`, before
+    /// the system fallback.
+    pub fallback_fonts: Vec<Font>,
 }
 
 impl Default for FontSettings {
@@ -243,6 +283,7 @@ impl Default for FontSettings {
             size: 14.0,
             scale_factor: 1.3,
             font_type: Font::MONOSPACE,
+            fallback_fonts: Vec::new(),
         }
     }
 }
@@ -262,6 +303,11 @@ impl FontSettings {
         self.font_type = font_type;
         self
     }
+
+    pub fn with_fallback_fonts(mut self, fonts: Vec<Font>) -> Self {
+        self.fallback_fonts = fonts;
+        self
+    }
 }
 
 #[derive(Default, Debug, Clone)]
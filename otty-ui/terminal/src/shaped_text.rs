@@ -6,12 +6,15 @@
 //! updates.
 
 use std::cell::RefCell;
+use std::ops::Range;
 use std::sync::Arc;
 
-use iced::{Color, Point, Rectangle};
+use iced::font::Weight;
+use iced::{Color, Font, Point, Rectangle};
 use iced_core::Pixels;
 use iced_core::text::LineHeight;
 use iced_graphics::text::{self, Renderer as TextRenderer, cosmic_text};
+use unicode_width::UnicodeWidthChar;
 
 use crate::render_runs::RenderRun;
 
@@ -23,6 +26,7 @@ pub(crate) struct TextRunDrawConfig {
     cell_height: f32,
     font_size: f32,
     font_scale_factor: f32,
+    fallback_fonts: Vec<Font>,
 }
 
 impl TextRunDrawConfig {
@@ -42,8 +46,16 @@ impl TextRunDrawConfig {
             cell_height,
             font_size,
             font_scale_factor,
+            fallback_fonts: Vec::new(),
         }
     }
+
+    /// Draw glyphs missing from a run's font with the first of `fonts`
+    /// that has them.
+    pub(crate) fn with_fallback_fonts(mut self, fonts: &[Font]) -> Self {
+        self.fallback_fonts = fonts.to_vec();
+        self
+    }
 }
 
 /// Keeps shaped text buffers alive and reuses them across unchanged draws.
@@ -125,6 +137,7 @@ struct TextRunShapeKey {
     cell_height: u32,
     font_size: u32,
     font_scale_factor: u32,
+    fallback_fonts: Vec<Font>,
 }
 
 impl TextRunShapeKey {
@@ -135,6 +148,7 @@ impl TextRunShapeKey {
             cell_height: config.cell_height.to_bits(),
             font_size: config.font_size.to_bits(),
             font_scale_factor: config.font_scale_factor.to_bits(),
+            fallback_fonts: config.fallback_fonts.clone(),
         }
     }
 
@@ -143,6 +157,7 @@ impl TextRunShapeKey {
             && self.cell_height == config.cell_height.to_bits()
             && self.font_size == config.font_size.to_bits()
             && self.font_scale_factor == config.font_scale_factor.to_bits()
+            && self.fallback_fonts == config.fallback_fonts
             && self.runs == runs
     }
 }
@@ -207,13 +222,17 @@ fn shape_render_run(
     buffer.set_size(font_system, Some(run_width), Some(config.cell_height));
     buffer.set_wrap(font_system, cosmic_text::Wrap::None);
     buffer.set_monospace_width(font_system, Some(config.cell_width));
-    let mut raw_color = set_buffer_text(font_system, &mut buffer, run, None);
-    if let Some(letter_spacing) = grid_fit_letter_spacing(&buffer, run, config)
+    let segments = font_segments(font_system, run, config);
+    let mut raw_color =
+        set_buffer_text(font_system, &mut buffer, run, &segments, None);
+    if let Some(letter_spacing) =
+        grid_fit_letter_spacing(&buffer, run, &segments, config)
     {
         raw_color = set_buffer_text(
             font_system,
             &mut buffer,
             run,
+            &segments,
             Some(letter_spacing),
         );
     }
@@ -262,10 +281,163 @@ fn fill_raw_run<Renderer>(
     });
 }
 
+/// Part of a run drawn with a single font.
+#[derive(Debug, Clone, PartialEq)]
+struct FontSegment {
+    range: Range<usize>,
+    font: Font,
+    /// Spacing, in ems, that widens a fallback glyph to the cells of its
+    /// character, or `None` where the shaper fits the face to the grid.
+    letter_spacing: Option<f32>,
+}
+
+/// Glyph for a character in the face that a font resolves to.
+struct FaceGlyph {
+    /// Advance of the glyph, in ems.
+    advance: f32,
+    /// Weight of the face, which the shaper only picks on an exact match.
+    weight: Weight,
+    /// Whether the shaper fits the face to the monospace grid by itself.
+    monospaced: bool,
+}
+
+/// Split a run into byte ranges drawn with the run font or a fallback.
+///
+/// Every character keeps the run font unless it lacks the glyph and one of
+/// `fallbacks` has it; fallbacks inherit the style of the run and take
+/// the weight of their face closest to the run's.
+fn font_segments(
+    font_system: &mut cosmic_text::FontSystem,
+    run: &RenderRun,
+    config: &TextRunDrawConfig,
+) -> Vec<FontSegment> {
+    let primary = run.font();
+    let text = run.text();
+    let whole = FontSegment {
+        range: 0..text.len(),
+        font: primary,
+        letter_spacing: None,
+    };
+    if config.fallback_fonts.is_empty() || text.is_ascii() {
+        return vec![whole];
+    }
+
+    let mut segments: Vec<FontSegment> = Vec::new();
+    for (start, ch) in text.char_indices() {
+        let range = start..start + ch.len_utf8();
+        let fallback = (!ch.is_ascii()
+            && face_glyph(font_system, primary, ch).is_none())
+        .then(|| {
+            config.fallback_fonts.iter().find_map(|fallback| {
+                let font = Font {
+                    weight: primary.weight,
+                    style: primary.style,
+                    ..*fallback
+                };
+                face_glyph(font_system, font, ch).map(|glyph| {
+                    let font = Font {
+                        weight: glyph.weight,
+                        ..font
+                    };
+                    (font, glyph)
+                })
+            })
+        })
+        .flatten();
+
+        if let Some((font, glyph)) = fallback {
+            let cells = ch.width().unwrap_or(1) as f32;
+            let letter_spacing = (!glyph.monospaced && config.font_size > 0.0)
+                .then(|| {
+                    cells * config.cell_width / config.font_size - glyph.advance
+                });
+            segments.push(FontSegment {
+                range,
+                font,
+                letter_spacing,
+            });
+            continue;
+        }
+
+        match segments.last_mut() {
+            Some(last) if last.font == primary => last.range.end = range.end,
+            _ => segments.push(FontSegment {
+                range,
+                ..whole.clone()
+            }),
+        }
+    }
+    segments
+}
+
+/// Glyph for `ch` in the face that `font` resolves to, or `None` if the
+/// face has no such glyph.
+fn face_glyph(
+    font_system: &mut cosmic_text::FontSystem,
+    font: Font,
+    ch: char,
+) -> Option<FaceGlyph> {
+    let attrs = text::to_attributes(font);
+    let id = resolve_face(font_system.db(), &attrs)?;
+    let weight = face_weight(font_system.db().face(id)?.weight);
+    let face = font_system.get_font(id, attrs.weight)?;
+    let monospaced = face.monospace_em_width().is_some();
+    let face = face.as_swash();
+
+    let glyph = face.charmap().map(ch);
+    let units_per_em = face.metrics(&[]).units_per_em;
+    (glyph != 0 && units_per_em != 0).then(|| FaceGlyph {
+        advance: face.glyph_metrics(&[]).advance_width(glyph)
+            / f32::from(units_per_em),
+        weight,
+        monospaced,
+    })
+}
+
+/// Font weight closest to the weight of a face.
+fn face_weight(weight: cosmic_text::fontdb::Weight) -> Weight {
+    match weight.0 {
+        ..=150 => Weight::Thin,
+        151..=250 => Weight::ExtraLight,
+        251..=350 => Weight::Light,
+        351..=450 => Weight::Normal,
+        451..=550 => Weight::Medium,
+        551..=650 => Weight::Semibold,
+        651..=750 => Weight::Bold,
+        751..=850 => Weight::ExtraBold,
+        _ => Weight::Black,
+    }
+}
+
+/// Face the shaper picks for `attrs`.
+///
+/// Generic families without a configured name, such as the monospace
+/// family on systems without the default one, resolve to the first
+/// matching face of that kind.
+fn resolve_face(
+    db: &cosmic_text::fontdb::Database,
+    attrs: &cosmic_text::Attrs<'_>,
+) -> Option<cosmic_text::fontdb::ID> {
+    let query = cosmic_text::fontdb::Query {
+        families: &[attrs.family],
+        weight: attrs.weight,
+        stretch: attrs.stretch,
+        style: attrs.style,
+    };
+    db.query(&query).or_else(|| {
+        let monospace = attrs.family == cosmic_text::Family::Monospace;
+        db.faces()
+            .filter(|face| attrs.matches(face) && face.monospaced == monospace)
+            .min_by_key(|face| face.weight.0.abs_diff(attrs.weight.0))
+            .map(|face| face.id)
+    })
+}
+
 fn set_buffer_text(
     font_system: &mut cosmic_text::FontSystem,
     buffer: &mut cosmic_text::Buffer,
     run: &RenderRun,
+    segments: &[FontSegment],
     letter_spacing: Option<f32>,
 ) -> Color {
     let default_attrs = text_attributes(run.font(), letter_spacing);
@@ -275,6 +447,35 @@ fn set_buffer_text(
         .iter()
         .any(|span| span.foreground() != fallback_foreground);
 
+    if segments.iter().any(|segment| segment.font != run.font()) {
+        let colors = if has_color_overrides {
+            run.color_spans()
+                .iter()
+                .map(|span| (span.byte_range().clone(), span.foreground()))
+                .collect()
+        } else {
+            vec![(0..run.text().len(), fallback_foreground)]
+        };
+        let pieces = colors.iter().flat_map(|(colors, foreground)| {
+            segments.iter().filter_map(move |segment| {
+                let start = segment.range.start.max(colors.start);
+                let end = segment.range.end.min(colors.end);
+                let spacing = segment.letter_spacing.or(letter_spacing);
+                let attrs = text_attributes(segment.font, spacing)
+                    .color(text::to_color(*foreground));
+                (start < end).then(|| (&run.text()[start..end], attrs))
+            })
+        });
+        buffer.set_rich_text(
+            font_system,
+            pieces,
+            &default_attrs,
+            cosmic_text::Shaping::Advanced,
+            Some(cosmic_text::Align::Left),
+        );
+        return Color::WHITE;
+    }
+
     if !has_color_overrides {
         buffer.set_text(
             font_system,
@@ -318,6 +519,7 @@ fn text_attributes(
 fn grid_fit_letter_spacing(
     buffer: &cosmic_text::Buffer,
     run: &RenderRun,
+    segments: &[FontSegment],
     config: &TextRunDrawConfig,
 ) -> Option<f32> {
     if run.text().chars().count() != run.cell_columns() {
@@ -328,10 +530,17 @@ fn grid_fit_letter_spacing(
         return None;
     }
 
+    // Fallback glyphs are already as wide as their cells.
+    let fitted = |start: usize| {
+        segments.iter().any(|segment| {
+            segment.font != run.font() && segment.range.contains(&start)
+        })
+    };
     let glyph_count = buffer
         .layout_runs()
-        .map(|layout_run| layout_run.glyphs.len())
-        .sum::<usize>();
+        .flat_map(|layout_run| layout_run.glyphs.iter())
+        .filter(|glyph| !fitted(glyph.start))
+        .count();
     if glyph_count == 0 {
         return None;
     }
@@ -385,11 +594,43 @@ fn baseline_for_line(
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
     use iced::{Font, Size};
 
     use super::*;
+    use crate::font::font_measure;
     use crate::render_runs::{RenderRun, RenderTextStyle};
 
+    const TERM_3270: Font = Font::with_name("3270 Nerd Font");
+    const JETBRAINS_MONO: Font = Font {
+        weight: Weight::Bold,
+        ..Font::with_name("JetBrainsMono Nerd Font Mono")
+    };
+
+    /// Load the fonts bundled with the repository, so fallback tests don't
+    /// depend on the fonts installed on the host.
+    fn load_test_fonts() {
+        let mut font_system =
+            text::font_system().write().expect("Write font system");
+        font_system.load_font(Cow::Borrowed(include_bytes!(
+            "../../../assets/fonts/3270/3270NerdFont-Regular.ttf"
+        )));
+        font_system.load_font(Cow::Borrowed(include_bytes!(
+            "../../../assets/fonts/JetBrains/JetBrainsMonoNerdFontMono-Bold.ttf"
+        )));
+    }
+
+    fn run_in(text: &str, font: Font) -> RenderRun {
+        RenderRun::new_empty_color_spans(
+            text,
+            2,
+            3,
+            text.chars().count(),
+            RenderTextStyle::from_fg_and_font(Color::WHITE, font),
+        )
+    }
+
     fn run(text: &str) -> RenderRun {
         RenderRun::new_empty_color_spans(
             text,
@@ -426,6 +667,53 @@ mod tests {
         )
     }
 
+    #[test]
+    fn missing_glyphs_are_taken_from_the_first_fallback_that_has_them() {
+        // 3270 has the IJ ligature but no schwa, JetBrains Mono has both.
+        load_test_fonts();
+        let run = run_in("a\u{132}\u{18f}b", TERM_3270);
+        let mut font_system =
+            text::font_system().write().expect("Write font system");
+        let mut fonts = |config: &TextRunDrawConfig| {
+            font_segments(font_system.raw(), &run, config)
+                .into_iter()
+                .map(|segment| (segment.range, segment.font))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(fonts(&config()), vec![(0..6, TERM_3270)]);
+        // The bold-only JetBrains Mono draws the schwa in bold.
+        assert_eq!(
+            fonts(&config().with_fallback_fonts(&[TERM_3270, JETBRAINS_MONO])),
+            vec![(0..3, TERM_3270), (3..5, JETBRAINS_MONO), (5..6, TERM_3270)]
+        );
+    }
+
+    #[test]
+    fn fallback_glyphs_advance_by_the_primary_cell_width() {
+        load_test_fonts();
+        let cell = font_measure(14.0, 1.2, TERM_3270);
+        let run = run_in("a\u{18f}b", TERM_3270);
+        let config = config_with_cell_width(cell.width)
+            .with_fallback_fonts(&[JETBRAINS_MONO]);
+        let mut font_system =
+            text::font_system().write().expect("Write font system");
+
+        let shaped = shape_render_run(&run, &config, font_system.raw());
+        let glyphs = shaped
+            .buffer
+            .layout_runs()
+            .flat_map(|layout_run| layout_run.glyphs.iter())
+            .map(|glyph| (glyph.x, glyph.w))
+            .collect::<Vec<_>>();
+
+        assert_eq!(glyphs.len(), 3);
+        let (fallback_x, fallback_advance) = glyphs[1];
+        assert!((fallback_x - cell.width).abs() < 0.01, "{glyphs:?}");
+        assert!((fallback_advance - cell.width).abs() < 0.01, "{glyphs:?}");
+        assert!((glyphs[2].0 - 2.0 * cell.width).abs() < 0.01, "{glyphs:?}");
+    }
+
     #[test]
     fn shape_render_run_uses_terminal_grid_constraints() {
         let run = run("ab cd");
@@ -8,16 +8,20 @@ use iced::futures::{SinkExt, StreamExt};
 use iced::widget::canvas::Cache;
 use iced::{Size, Subscription};
 use log::debug;
+use otty_libterm::escape::{CursorShape, CursorStyle, ProgressState};
 use otty_libterm::surface::{
-    BlockSnapshot, Point, SelectionType, SnapshotOwned,
+    BlockSnapshot, Point, SelectionType, SnapshotOwned, SurfaceLayout,
 };
 use otty_libterm::{SnapshotArc, TerminalEvent};
 use tokio::sync::Mutex;
 use tokio::sync::mpsc::{self, Receiver};
 
 use crate::bindings::{Binding, BindingAction, BindingsLayout, InputKind};
+use crate::context_menu::ContextMenuContext;
+use crate::cursor::{CursorDescriptor, CursorState};
 use crate::engine::MouseButton;
 use crate::font::TermFont;
+use crate::paste::{PasteFilter, PasteMode};
 use crate::settings::{
     BlockSelectionMode, FontSettings, Settings, ThemeSettings,
 };
@@ -47,6 +51,10 @@ pub enum BlockCommand {
     CopyCommand(String),
     /// Paste clipboard contents into the focused terminal.
     PasteClipboard,
+    /// Collapse the block down to its prompt/command header line.
+    Collapse(String),
+    /// Expand a previously collapsed block back to its full height.
+    Expand(String),
 }
 
 /// Mode describing how block-level UI chrome is rendered.
@@ -84,11 +92,17 @@ pub enum Event {
         id: u64,
         selection_type: SelectionType,
         position: (f32, f32),
+        /// Keep the current selection next to the new one.
+        additive: bool,
     },
     SelectUpdate {
         id: u64,
         position: (f32, f32),
     },
+    /// Drop the grid selection, e.g. once it was copied.
+    ClearSelection {
+        id: u64,
+    },
     MouseReport {
         id: u64,
         button: MouseButton,
@@ -100,6 +114,13 @@ pub enum Event {
         id: u64,
         uri: String,
     },
+    /// The user right-clicked outside of mouse reporting mode.
+    ContextMenu {
+        id: u64,
+        /// Pointer position relative to the widget.
+        position: (f32, f32),
+        context: ContextMenuContext,
+    },
     Resize {
         id: u64,
         layout_size: Option<Size>,
@@ -112,6 +133,41 @@ pub enum Event {
     ResetTitle {
         id: u64,
     },
+    CursorShapeChanged {
+        id: u64,
+        shape: CursorShape,
+    },
+    CursorStyleChanged {
+        id: u64,
+        style: Option<CursorStyle>,
+    },
+    /// A running command block gained output lines, for live tailing.
+    BlockOutput {
+        id: u64,
+        block_id: String,
+        appended_text: String,
+    },
+    /// A command block finished; no more output follows for it.
+    BlockFinished {
+        id: u64,
+        block_id: String,
+        exit_code: Option<i32>,
+    },
+    /// The application updated its progress indicator.
+    ProgressChanged {
+        id: u64,
+        state: ProgressState,
+        percent: u8,
+    },
+    /// Switch between the plain grid and the block layout.
+    ToggleSurfaceLayout {
+        id: u64,
+    },
+    /// Flip the blink phase of a blinking cursor, see
+    /// [`Terminal::cursor_blinking`].
+    BlinkCursor {
+        id: u64,
+    },
     BlockSelected {
         id: u64,
         block_id: String,
@@ -154,11 +210,13 @@ impl Debug for Event {
                 id,
                 selection_type,
                 position,
-            } => f.write_fmt(format_args!("Event::SelectStart id: {id}, ty: {selection_type:?}, pos: {position:?}")),
+                additive,
+            } => f.write_fmt(format_args!("Event::SelectStart id: {id}, ty: {selection_type:?}, pos: {position:?}, additive: {additive}")),
             SelectUpdate {
                 id,
                 position,
             } => f.write_fmt(format_args!("Event::SelectUpdate id: {id}, pos: {position:?}")),
+            ClearSelection { id } => f.write_fmt(format_args!("Event::ClearSelection id: {id}")),
             MouseReport {
                 id,
                 button,
@@ -176,7 +234,15 @@ impl Debug for Event {
                 title,
             } => f.write_fmt(format_args!("Event::TitleChanged id: {id}, title: {title}")),
             OpenLink { id, uri } => f.write_fmt(format_args!("Event::OpenLink id: {id}, uri: {uri}")),
+            ContextMenu { id, position, context } => f.write_fmt(format_args!("Event::ContextMenu id: {id}, pos: {position:?}, context: {context:?}")),
             ResetTitle { id } => f.write_fmt(format_args!("Event::ResetTitle id: {id}")),
+            CursorShapeChanged { id, shape } => f.write_fmt(format_args!("Event::CursorShapeChanged id: {id}, shape: {shape:?}")),
+            CursorStyleChanged { id, style } => f.write_fmt(format_args!("Event::CursorStyleChanged id: {id}, style: {style:?}")),
+            BlockOutput { id, block_id, appended_text } => f.write_fmt(format_args!("Event::BlockOutput id: {id}, block_id: {block_id}, appended_text: {appended_text:?}")),
+            BlockFinished { id, block_id, exit_code } => f.write_fmt(format_args!("Event::BlockFinished id: {id}, block_id: {block_id}, exit_code: {exit_code:?}")),
+            ProgressChanged { id, state, percent } => f.write_fmt(format_args!("Event::ProgressChanged id: {id}, state: {state:?}, percent: {percent}")),
+            BlinkCursor { id } => f.write_fmt(format_args!("Event::BlinkCursor id: {id}")),
+            ToggleSurfaceLayout { id } => f.write_fmt(format_args!("Event::ToggleSurfaceLayout id: {id}")),
             BlockSelected { id, block_id } => {
                 f.write_fmt(format_args!("Event::BlockSelected id: {id}, block_id: {block_id}"))
             }
@@ -203,11 +269,20 @@ impl Event {
             Scroll { id, .. } => id,
             SelectStart { id, .. } => id,
             SelectUpdate { id, .. } => id,
+            ClearSelection { id } => id,
             MouseReport { id, .. } => id,
             Resize { id, .. } => id,
             TitleChanged { id, .. } => id,
             OpenLink { id, .. } => id,
+            ContextMenu { id, .. } => id,
             ResetTitle { id } => id,
+            CursorShapeChanged { id, .. } => id,
+            CursorStyleChanged { id, .. } => id,
+            BlockOutput { id, .. } => id,
+            BlockFinished { id, .. } => id,
+            ProgressChanged { id, .. } => id,
+            BlinkCursor { id } => id,
+            ToggleSurfaceLayout { id } => id,
             BlockSelected { id, .. } => id,
             BlockCopied { id, .. } => id,
             BlockSelectionCleared { id } => id,
@@ -226,6 +301,31 @@ impl Event {
                 Event::TitleChanged { id, title }
             },
             TerminalEvent::ResetTitle => Event::ResetTitle { id },
+            TerminalEvent::CursorShapeChanged { shape } => {
+                Event::CursorShapeChanged { id, shape }
+            },
+            TerminalEvent::CursorStyleChanged { style } => {
+                Event::CursorStyleChanged { id, style }
+            },
+            TerminalEvent::BlockOutput {
+                block_id,
+                appended_text,
+            } => Event::BlockOutput {
+                id,
+                block_id,
+                appended_text,
+            },
+            TerminalEvent::BlockFinished {
+                block_id,
+                exit_code,
+            } => Event::BlockFinished {
+                id,
+                block_id,
+                exit_code,
+            },
+            TerminalEvent::Progress { state, percent } => {
+                Event::ProgressChanged { id, state, percent }
+            },
             _ => Event::Ignore { id },
         }
     }
@@ -240,7 +340,11 @@ pub struct Terminal {
     pub(crate) bindings: BindingsLayout,
     pub(crate) engine: engine::Engine,
     block_selection_mode: BlockSelectionMode,
+    copy_on_select: bool,
+    paste_mode: PasteMode,
+    paste_filter: PasteFilter,
     block_ui_mode: BlockUiMode,
+    pub(crate) cursor: CursorState,
     backend_event_rx: Arc<Mutex<Receiver<TerminalEvent>>>,
 }
 
@@ -278,7 +382,11 @@ impl Terminal {
             cache: Cache::default(),
             engine,
             block_selection_mode: interaction.block_selection_mode(),
+            copy_on_select: interaction.copy_on_select(),
+            paste_mode: interaction.paste_mode(),
+            paste_filter: interaction.paste_filter(),
             block_ui_mode: BlockUiMode::Internal,
+            cursor: CursorState::default(),
             backend_event_rx: Arc::new(Mutex::new(backend_event_rx)),
         })
     }
@@ -352,15 +460,18 @@ impl Terminal {
             SelectStart {
                 selection_type,
                 position,
+                additive,
                 ..
             } => self.engine.start_selection(
                 selection_type,
                 position.0,
                 position.1,
+                additive,
             ),
             SelectUpdate { position, .. } => {
                 self.engine.update_selection(position.0, position.1)
             },
+            ClearSelection { .. } => self.engine.clear_selection(),
             MouseReport {
                 button,
                 modifiers,
@@ -378,6 +489,24 @@ impl Terminal {
             OpenLink { uri, .. } => {
                 let _ = open::that_detached(uri);
             },
+            CursorShapeChanged { shape, .. } => {
+                self.cursor.set_shape(shape);
+                self.cache.clear();
+            },
+            CursorStyleChanged { style, .. } => {
+                self.cursor.set_style(style);
+                self.cache.clear();
+            },
+            ToggleSurfaceLayout { .. } => {
+                let layout = match self.surface_layout() {
+                    SurfaceLayout::Grid => SurfaceLayout::Blocks,
+                    SurfaceLayout::Blocks => SurfaceLayout::Grid,
+                };
+                self.set_surface_layout(layout);
+            },
+            BlinkCursor { .. } if self.cursor.toggle_blink() => {
+                self.cache.clear();
+            },
             _ => {},
         }
     }
@@ -405,6 +534,46 @@ impl Terminal {
         self.block_selection_mode
     }
 
+    /// Return whether finished selections are copied automatically.
+    pub fn copy_on_select(&self) -> bool {
+        self.copy_on_select
+    }
+
+    /// Return how pastes are delivered when bracketed paste is off.
+    pub fn paste_mode(&self) -> PasteMode {
+        self.paste_mode
+    }
+
+    /// Return the filter applied to pasted text.
+    pub fn paste_filter(&self) -> PasteFilter {
+        self.paste_filter
+    }
+
+    /// Return whether the cursor blinks and needs an [`Event::BlinkCursor`]
+    /// every [`CURSOR_BLINK_INTERVAL`].
+    ///
+    /// [`CURSOR_BLINK_INTERVAL`]: crate::CURSOR_BLINK_INTERVAL
+    pub fn cursor_blinking(&self) -> bool {
+        self.cursor.is_blinking()
+    }
+
+    /// Return how the cursor is drawn in the latest frame.
+    pub fn cursor_descriptor(&self, focused: bool) -> CursorDescriptor {
+        let snapshot = self.engine.snapshot();
+        self.cursor.descriptor(snapshot.view().cursor, focused)
+    }
+
+    /// Return the layout of the latest frame.
+    pub fn surface_layout(&self) -> SurfaceLayout {
+        self.engine.snapshot().view().layout
+    }
+
+    /// Ask the backend to present its content as a plain grid or as
+    /// blocks. The change shows up with the next frame.
+    pub fn set_surface_layout(&self, layout: SurfaceLayout) {
+        self.engine.set_surface_layout(layout);
+    }
+
     /// Return the current block UI rendering mode.
     pub fn block_ui_mode(&self) -> BlockUiMode {
         self.block_ui_mode
@@ -593,7 +762,11 @@ mod tests {
                 settings.backend.size,
             ),
             block_selection_mode: settings.interaction.block_selection_mode(),
+            copy_on_select: settings.interaction.copy_on_select(),
+            paste_mode: settings.interaction.paste_mode(),
+            paste_filter: settings.interaction.paste_filter(),
             block_ui_mode: BlockUiMode::Internal,
+            cursor: CursorState::default(),
             backend_event_rx: Arc::new(Mutex::new(backend_event_rx)),
         }
     }
@@ -607,6 +780,41 @@ mod tests {
         assert!(Arc::ptr_eq(&latest, &snapshot));
     }
 
+    #[test]
+    fn cursor_descriptor_tracks_backend_cursor_events() {
+        let mut terminal = terminal_with_snapshot(snapshot_with_blocks());
+        let style = CursorStyle {
+            shape: CursorShape::Underline,
+            blinking: true,
+        };
+
+        terminal.handle(Event::from_terminal_event(
+            7,
+            TerminalEvent::CursorStyleChanged { style: Some(style) },
+        ));
+        assert!(terminal.cursor_blinking());
+        assert_eq!(
+            terminal.cursor_descriptor(true),
+            CursorDescriptor {
+                shape: CursorShape::Underline,
+                hollow: false,
+                visible: true,
+            }
+        );
+
+        terminal.handle(Event::BlinkCursor { id: 7 });
+        assert!(!terminal.cursor_descriptor(true).visible);
+        assert!(terminal.cursor_descriptor(false).hollow);
+
+        terminal.handle(Event::from_terminal_event(
+            7,
+            TerminalEvent::CursorShapeChanged {
+                shape: CursorShape::Beam,
+            },
+        ));
+        assert_eq!(terminal.cursor_descriptor(true).shape, CursorShape::Beam);
+    }
+
     #[test]
     fn blocks_returns_cloned_block_metadata() {
         let snapshot = snapshot_with_blocks();
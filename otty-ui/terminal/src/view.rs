@@ -1,27 +1,32 @@
 use std::any::Any;
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::time::{Duration, Instant};
 
 use iced::mouse::Cursor;
-use iced::widget::canvas::Path;
+use iced::widget::canvas::{self, Path};
 use iced::widget::container;
 use iced::{Color, Element, Length, Point, Rectangle, Size, Theme};
 use iced_core::clipboard::Kind as ClipboardKind;
+use iced_core::input_method::{InputMethod, Preedit, Purpose};
 use iced_core::keyboard::Modifiers;
 use iced_core::mouse;
+use iced_core::text::LineHeight;
 use iced_core::widget::operation;
 use iced_graphics::core::Widget;
 use iced_graphics::core::widget::{Tree, tree};
 use iced_graphics::geometry::Stroke;
 use otty_libterm::escape::{self as ansi, CursorShape, StdColor};
 use otty_libterm::surface::{
-    BlockKind, Flags, Point as TerminalGridPoint, SurfaceMode,
+    BlockKind, Flags, Point as TerminalGridPoint, SnapshotView, SurfaceMode,
 };
 
 use crate::block_controls::BlockActionButtonGeometry;
-use crate::block_layout::{self, BlockRect};
+use crate::block_layout::{self, BlockRect, CollapsedLines};
+use crate::cursor::CursorDescriptor;
+use crate::ime::Composition;
 use crate::input::InputManager;
 use crate::render_runs::build_render_runs;
+use crate::scrollbar::{Scrollbar, ScrollbarDrag};
 use crate::shaped_text::{
     TextRunBufferStore, TextRunDrawConfig, draw_render_runs,
 };
@@ -92,7 +97,10 @@ impl<'a> TerminalView<'a> {
                 term.id,
                 &term.bindings,
                 term.block_selection_mode(),
-            ),
+            )
+            .with_copy_on_select(term.copy_on_select())
+            .with_paste_mode(term.paste_mode())
+            .with_paste_filter(term.paste_filter()),
         })
         .padding(10)
         .width(Length::Fill)
@@ -162,6 +170,56 @@ impl<'a> TerminalView<'a> {
         false
     }
 
+    /// Drag the scrollback through the scrollbar.
+    ///
+    /// Returns whether the event was consumed by the scrollbar.
+    fn handle_scrollbar_event(
+        &self,
+        view_state: &mut TerminalViewState,
+        view: &SnapshotView<'_>,
+        bounds: Rectangle,
+        cursor: Cursor,
+        event: &mouse::Event,
+        publish: &mut impl FnMut(Event),
+    ) -> bool {
+        let scrollbar =
+            Scrollbar::new(bounds, view.size.screen_lines, view.total_lines);
+        let Some(scrollbar) = scrollbar else {
+            view_state.scrollbar_drag = None;
+            return false;
+        };
+
+        let delta = match event {
+            mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                let Some((drag, delta)) = cursor.position().and_then(|point| {
+                    scrollbar.press(point, view.display_offset)
+                }) else {
+                    return false;
+                };
+                view_state.scrollbar_drag = Some(drag);
+                delta
+            },
+            mouse::Event::CursorMoved { position } => {
+                let Some(drag) = view_state.scrollbar_drag.as_mut() else {
+                    return false;
+                };
+                drag.move_to(&scrollbar, position.y)
+            },
+            mouse::Event::ButtonReleased(mouse::Button::Left) => {
+                return view_state.scrollbar_drag.take().is_some();
+            },
+            _ => return false,
+        };
+
+        if delta != 0 {
+            publish(Event::Scroll {
+                id: self.term.id,
+                delta,
+            });
+        }
+        true
+    }
+
     fn process_pending_commands(
         &self,
         view_state: &mut TerminalViewState,
@@ -286,12 +344,18 @@ impl<'a> TerminalView<'a> {
                 }
             },
             BlockCommand::ScrollTo(block_id) => {
-                self.scroll_block_into_view(&block_id, layout, shell);
+                self.scroll_block_into_view(
+                    &block_id, view_state, layout, shell,
+                );
             },
             BlockCommand::PasteClipboard => {
                 if let Some(data) = clipboard.read(ClipboardKind::Standard) {
-                    let payload: Vec<u8> = data.bytes().collect();
-                    if !payload.is_empty() {
+                    if !data.is_empty() {
+                        let payload = self
+                            .term
+                            .paste_mode()
+                            .resolve(view.mode)
+                            .encode(&self.term.paste_filter().apply(&data));
                         shell.publish(Event::Write {
                             id: self.term.id,
                             data: payload,
@@ -299,6 +363,18 @@ impl<'a> TerminalView<'a> {
                     }
                 }
             },
+            BlockCommand::Collapse(block_id) => {
+                let exists =
+                    view.blocks().iter().any(|block| block.meta.id == block_id);
+                if exists && view_state.collapsed_block_ids.insert(block_id) {
+                    shell.publish(Event::Redraw { id: self.term.id });
+                }
+            },
+            BlockCommand::Expand(block_id) => {
+                if view_state.collapsed_block_ids.remove(&block_id) {
+                    shell.publish(Event::Redraw { id: self.term.id });
+                }
+            },
         }
     }
 
@@ -414,6 +490,7 @@ impl<'a> TerminalView<'a> {
     fn scroll_block_into_view(
         &self,
         block_id: &str,
+        view_state: &TerminalViewState,
         layout: iced_graphics::core::Layout<'_>,
         shell: &mut iced_graphics::core::Shell<'_, Event>,
     ) {
@@ -427,6 +504,7 @@ impl<'a> TerminalView<'a> {
             layout_position,
             layout_bounds.size(),
             cell_height,
+            &view_state.collapsed_block_ids,
         );
 
         let Some(block_rect) = rects
@@ -461,6 +539,62 @@ impl<'a> TerminalView<'a> {
             }
         }
     }
+
+    /// Overlay uncommitted IME text at the terminal cursor.
+    fn draw_preedit(
+        &self,
+        renderer: &mut iced::Renderer,
+        size: Size,
+        composition: &Composition,
+        preedit: &str,
+        origin: Point,
+        cell_size: Size,
+    ) {
+        let mut frame = canvas::Frame::new(renderer, size);
+        let fg = self
+            .term
+            .theme
+            .get_color(ansi::Color::Std(StdColor::Foreground));
+        let bg = self
+            .term
+            .theme
+            .get_color(ansi::Color::Std(StdColor::Background));
+        let width = composition.width_cells().max(1) as f32 * cell_size.width;
+
+        frame.fill_rectangle(origin, Size::new(width, cell_size.height), bg);
+        frame.fill_text(canvas::Text {
+            content: preedit.to_string(),
+            position: origin,
+            color: fg,
+            size: self.term.font.size.into(),
+            line_height: LineHeight::Relative(self.term.font.scale_factor),
+            font: self.term.font.font_type,
+            ..canvas::Text::default()
+        });
+
+        let underline_y = origin.y + cell_size.height - 1.0;
+        frame.stroke(
+            &Path::line(
+                Point::new(origin.x, underline_y),
+                Point::new(origin.x + width, underline_y),
+            ),
+            Stroke::default().with_width(1.0).with_color(fg),
+        );
+
+        if let Some(offset) = composition.cursor_cells() {
+            let caret_x = origin.x + offset as f32 * cell_size.width;
+            frame.stroke(
+                &Path::line(
+                    Point::new(caret_x, origin.y),
+                    Point::new(caret_x, origin.y + cell_size.height),
+                ),
+                Stroke::default().with_width(1.0).with_color(fg),
+            );
+        }
+
+        use iced::advanced::graphics::geometry::Renderer as _;
+        renderer.draw_geometry(frame.into_geometry());
+    }
 }
 
 impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
@@ -515,6 +649,7 @@ impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
         let state = tree.state.downcast_ref::<TerminalViewState>();
         let content = self.term.engine.snapshot();
         let view = content.view();
+        let cursor = self.term.cursor.descriptor(view.cursor, state.is_focused);
         let term_size = self.term.engine.terminal_size();
         let cell_width = term_size.cell_width as f32;
         let cell_height = term_size.cell_height as f32;
@@ -525,25 +660,39 @@ impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
         let layout_offset_y = layout_position.y;
         let layout_bounds = layout.bounds();
         let display_offset = view.display_offset as f32;
-        let block_rects = if view.mode.contains(SurfaceMode::ALT_SCREEN) {
-            Vec::new()
-        } else {
-            block_layout::block_rects(
-                &view,
-                layout_position,
-                layout_bounds.size(),
-                cell_height,
-            )
-        };
+        let (block_rects, collapsed_lines) =
+            if view.mode.contains(SurfaceMode::ALT_SCREEN) {
+                (Vec::new(), CollapsedLines::default())
+            } else {
+                (
+                    block_layout::block_rects(
+                        &view,
+                        layout_position,
+                        layout_bounds.size(),
+                        cell_height,
+                        &state.collapsed_block_ids,
+                    ),
+                    CollapsedLines::new(&view, &state.collapsed_block_ids),
+                )
+            };
         let hovered_span_id =
             view.hyperlink_span_id_at(state.mouse_position_on_grid);
-        let render_runs = build_render_runs(
+        let mut render_runs = build_render_runs(
             &view,
             &self.term.theme,
             self.term.font.font_type,
             hovered_span_id,
             !view.mode.contains(SurfaceMode::ALT_SCREEN),
         );
+        render_runs.retain_mut(|run| {
+            match collapsed_lines.visual_line(run.line()) {
+                Some(line) => {
+                    run.set_line(line);
+                    true
+                },
+                None => false,
+            }
+        });
         let text_config = TextRunDrawConfig::new(
             layout_position,
             display_offset,
@@ -551,7 +700,8 @@ impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
             cell_height,
             font_size,
             font_scale_factor,
-        );
+        )
+        .with_fallback_fonts(&self.term.font.fallback_fonts);
 
         let geom = self.term.cache.draw(renderer, viewport.size(), |frame| {
             // We use the background pallete color as a default
@@ -577,21 +727,25 @@ impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
             let mut bg_batch_rect = BackgroundRect::default();
 
             for indexed in view.cells {
-                let flags = indexed.cell.flags;
-                let is_wide_char_spacer =
-                    flags.contains(Flags::WIDE_CHAR_SPACER);
-                if is_wide_char_spacer {
+                if indexed.is_wide_spacer() {
                     continue;
                 }
 
-                let is_wide_char = flags.contains(Flags::WIDE_CHAR);
+                let flags = indexed.cell.flags;
+                let is_wide_char = indexed.is_wide();
                 let is_inverse = flags.contains(Flags::INVERSE);
-                let is_dim = flags.intersects(Flags::DIM | Flags::DIM_BOLD);
-                let is_selected =
-                    view.selection.is_some_and(|r| r.contains(indexed.point));
-
-                // Compute per-cell geometry cheaply
-                let line = indexed.point.line.0;
+                let is_dim = flags.contains(Flags::DIM);
+                let is_selected = view.is_selected(indexed.point)
+                    || view
+                        .search_match
+                        .is_some_and(|m| m.contains(&indexed.point));
+
+                // Compute per-cell geometry cheaply, skipping collapsed lines
+                let Some(line) =
+                    collapsed_lines.visual_line(indexed.point.line.0)
+                else {
+                    continue;
+                };
                 let col = indexed.point.column.0 as f32;
 
                 // Resolve position point for this cell
@@ -688,14 +842,16 @@ impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
                 }
 
                 // Handle cursor rendering
-                if view.cursor.point == indexed.point
-                    && !matches!(view.cursor.shape, CursorShape::Hidden)
-                {
+                if view.cursor.point == indexed.point && cursor.visible {
                     let cursor_color =
                         self.term.theme.get_color(view.cursor.cell.fg);
-                    let cursor_rect =
-                        Path::rectangle(Point::new(x, y), cell_size);
-                    frame.fill(&cursor_rect, cursor_color);
+                    draw_cursor(
+                        frame,
+                        cursor,
+                        Point::new(x, y),
+                        cell_size,
+                        cursor_color,
+                    );
                 }
             }
 
@@ -720,6 +876,24 @@ impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
                 );
             }
 
+            if let Some(scrollbar) = Scrollbar::new(
+                layout_bounds,
+                view.size.screen_lines,
+                view.total_lines,
+            ) {
+                let thumb = scrollbar.thumb(view.display_offset);
+                let mut thumb_color = self
+                    .term
+                    .theme
+                    .get_color(ansi::Color::Std(StdColor::Foreground));
+                thumb_color.a = 0.3;
+                frame.fill_rectangle(
+                    thumb.position(),
+                    thumb.size(),
+                    thumb_color,
+                );
+            }
+
             for button in &block_visuals.action_buttons {
                 let is_hovered = state.hovered_action_block_id.as_deref()
                     == Some(button.block_id.as_str());
@@ -762,6 +936,29 @@ impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
             layout_bounds,
             &state.text_buffers,
         );
+
+        if let Some(preedit) = state.composition.preedit() {
+            let mut cursor_point = view.cursor.point;
+            if let Some(line) = collapsed_lines.visual_line(cursor_point.line.0)
+            {
+                cursor_point.line.0 = line;
+            }
+            let origin = cursor_origin(
+                layout_position,
+                cursor_point,
+                display_offset,
+                cell_width,
+                cell_height,
+            );
+            self.draw_preedit(
+                renderer,
+                viewport.size(),
+                &state.composition,
+                preedit,
+                origin,
+                Size::new(cell_width, cell_height),
+            );
+        }
     }
 
     fn update(
@@ -798,10 +995,49 @@ impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
             );
         }
 
+        if view_state.is_focused {
+            let view = terminal_state.view();
+            let origin = cursor_origin(
+                layout.position(),
+                view.cursor.point,
+                view.display_offset as f32,
+                terminal_size.cell_width as f32,
+                terminal_size.cell_height as f32,
+            );
+            let cursor_bounds = Rectangle::new(
+                origin,
+                Size::new(
+                    terminal_size.cell_width as f32,
+                    terminal_size.cell_height as f32,
+                ),
+            );
+            // The widget draws the preedit itself, so the platform overlay
+            // only needs the caret position.
+            shell.request_input_method(&InputMethod::<&str>::Enabled {
+                cursor: cursor_bounds,
+                purpose: Purpose::Terminal,
+                preedit: None::<Preedit<&str>>,
+            });
+        }
+
         let mut publish = |event: Event| {
             shell.publish(event);
         };
 
+        // A thumb drag keeps following the pointer outside the widget.
+        if let iced::Event::Mouse(mouse_event) = event
+            && self.handle_scrollbar_event(
+                view_state,
+                &terminal_state.view(),
+                layout.bounds(),
+                cursor,
+                mouse_event,
+                &mut publish,
+            )
+        {
+            return;
+        }
+
         match event {
             iced::Event::Mouse(mouse_event)
                 if self.is_cursor_in_layout(cursor, layout) =>
@@ -827,7 +1063,25 @@ impl Widget<Event, Theme, iced::Renderer> for TerminalView<'_> {
                     &mut publish,
                 )
             },
-            iced::Event::Keyboard(keyboard_event) if view_state.is_focused => {
+            iced::Event::InputMethod(ime_event) if view_state.is_focused => {
+                if let Some(data) = view_state.composition.handle(ime_event) {
+                    publish(Event::Write {
+                        id: terminal_id,
+                        data,
+                    });
+                }
+                publish(Event::Redraw { id: terminal_id });
+                iced::event::Status::Captured
+            },
+            // Keystrokes belong to the input method while composing.
+            iced::Event::Keyboard(_)
+                if view_state.composition.is_composing() =>
+            {
+                iced::event::Status::Captured
+            },
+            // Unfocused terminals still follow the modifier state; the input
+            // manager drops their keystrokes.
+            iced::Event::Keyboard(keyboard_event) => {
                 self.input_manager.handle_keyboard_event(
                     view_state,
                     terminal_state,
@@ -898,9 +1152,12 @@ pub(crate) struct TerminalViewState {
     pub hovered_block_kind: Option<BlockKind>,
     pub selected_block_id: Option<String>,
     pub selected_block_kind: Option<BlockKind>,
+    pub collapsed_block_ids: HashSet<String>,
     pub hovered_action_block_id: Option<String>,
     pub selection_in_progress: bool,
     pub terminal_id: Option<u64>,
+    scrollbar_drag: Option<ScrollbarDrag>,
+    composition: Composition,
     pending_block_commands: VecDeque<BlockCommand>,
     pending_resize: Option<Size<f32>>,
     pending_cell_size: Option<Size<f32>>,
@@ -924,9 +1181,12 @@ impl TerminalViewState {
             hovered_block_kind: None,
             selected_block_id: None,
             selected_block_kind: None,
+            collapsed_block_ids: HashSet::new(),
             hovered_action_block_id: None,
             selection_in_progress: false,
             terminal_id: None,
+            scrollbar_drag: None,
+            composition: Composition::default(),
             pending_block_commands: VecDeque::new(),
             pending_resize: None,
             pending_cell_size: None,
@@ -1112,6 +1372,66 @@ impl BackgroundRect {
     }
 }
 
+/// Top-left corner of the cell under the terminal cursor.
+fn cursor_origin(
+    layout_position: Point,
+    cursor: TerminalGridPoint,
+    display_offset: f32,
+    cell_width: f32,
+    cell_height: f32,
+) -> Point {
+    Point::new(
+        layout_position.x + cursor.column.0 as f32 * cell_width,
+        layout_position.y
+            + (cursor.line.0 as f32 + display_offset) * cell_height,
+    )
+}
+
+/// Draw the cursor over the cell at `origin` in the shape it describes.
+fn draw_cursor(
+    frame: &mut canvas::Frame,
+    cursor: CursorDescriptor,
+    origin: Point,
+    cell_size: Size,
+    color: Color,
+) {
+    // Thickness of bar, underline and hollow block strokes.
+    let thickness = (cell_size.width * 0.15).max(1.0);
+
+    match cursor.shape {
+        CursorShape::Block if cursor.hollow => {
+            let inset = thickness / 2.0;
+            let outline = Path::rectangle(
+                Point::new(origin.x + inset, origin.y + inset),
+                Size::new(
+                    cell_size.width - thickness,
+                    cell_size.height - thickness,
+                ),
+            );
+            frame.stroke(
+                &outline,
+                Stroke::default().with_width(thickness).with_color(color),
+            );
+        },
+        CursorShape::Block => {
+            frame.fill(&Path::rectangle(origin, cell_size), color);
+        },
+        CursorShape::Underline => {
+            let underline = Path::rectangle(
+                Point::new(origin.x, origin.y + cell_size.height - thickness),
+                Size::new(cell_size.width, thickness),
+            );
+            frame.fill(&underline, color);
+        },
+        CursorShape::Beam => {
+            let bar =
+                Path::rectangle(origin, Size::new(thickness, cell_size.height));
+            frame.fill(&bar, color);
+        },
+        CursorShape::Hidden => {},
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
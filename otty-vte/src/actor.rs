@@ -57,6 +57,15 @@ pub trait VTActor {
     /// `hook`. C0 controls will also be passed to the handler.
     fn put(&mut self, byte: u8);
 
+    /// Signals the start of an Application Program Command (APC) string.
+    fn apc_start(&mut self) {}
+
+    /// Pass a byte of the active APC string, including C0 controls.
+    fn apc_put(&mut self, _byte: u8) {}
+
+    /// Marks the end of the active APC string.
+    fn apc_end(&mut self) {}
+
     /// Dispatches an Operating System Command (OSC).
     fn osc_dispatch(&mut self, params: &[&[u8]], byte: u8);
 
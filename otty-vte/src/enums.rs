@@ -22,6 +22,7 @@ pub(crate) enum State {
     /// |-----------------------------|-----------------------|----------------------------------------------|
     /// | Printable (graphic)         | `0x20..=0x7E`         | [`Action::Print`] / [`State::Ground`]        |
     /// | C0 controls (excluding ESC) | `0x00..=0x1F`         | [`Action::Execute`] / [`State::Ground`]      |
+    /// | DEL                         | `0x7F`                | [`Action::Ignore`] / [`State::Ground`]       |
     /// | ESC                         | `0x1B`                | [`Action::None`] / [`State::Escape`]         |
     /// | CSI (C1 mode)               | `0x9B`                | [`Action::None`] / [`State::CsiEntry`]       |
     /// | DCS (C1 mode)               | `0x90`                | [`Action::None`] / [`State::DcsEntry`]       |
@@ -289,18 +290,18 @@ pub(crate) enum State {
     /// - `ESC ] 52 ; c ; <base64> ST` — clipboard (OSC 52) with base64 payload, terminated by `ST`.
     OscString,
     /// Collects SOS, PM, or APC strings (introduced via `ESC X`, `ESC ^`,
-    /// `ESC _` or C1 `0x98`/`0x9E`/`0x9F`). Bytes are ignored until a string
-    /// terminator (ST: `ESC \\` or C1 `0x9C`) is observed, then the parser
-    /// returns to ground.
+    /// `ESC _` or C1 `0x98`/`0x9E`/`0x9F`). APC bytes are streamed to the
+    /// actor, SOS and PM bytes are ignored until a string terminator (ST:
+    /// `ESC \\` or C1 `0x9C`) is observed, then the parser returns to ground.
     ///
     /// ## What the happens in [`State::SosPmApcString`]
     /// | Class                       | Byte range            | Action / Transition                            |
     /// |-----------------------------|-----------------------|------------------------------------------------|
-    /// | C0 / Printable              | `0x00..=0x7F`         | [`Action::Ignore`] / [`State::SosPmApcString`] |
+    /// | C0 / Printable              | `0x00..=0x7F`         | [`Action::ApcPut`] / [`State::SosPmApcString`] |
     /// | String Terminator (ST)      | `0x9C` or `ESC \\`    | [`Action::None`] / [`State::Ground`]           |
     ///
     /// ## Examples
-    /// - `ESC _ note goes here ST` — APC string ignored until `ST`.
+    /// - `ESC _ G a=T,f=100 ; <base64> ST` — kitty graphics APC string.
     /// - `ESC ^ program message ST` — PM string ignored until `ST`.
     SosPmApcString,
     /// Idle state used when no data has been processed yet or as a sentinel
@@ -354,4 +355,10 @@ pub(crate) enum Action {
     OscPut,
     /// Finalize the OSC payload and dispatch it.
     OscEnd,
+    /// Start streaming an APC string, ignoring SOS and PM strings.
+    ApcStart,
+    /// Pass a byte through to the active APC string.
+    ApcPut,
+    /// Terminate the active APC string.
+    ApcEnd,
 }
@@ -1,3 +1,7 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 mod actor;
 mod csi;
 mod enums;
@@ -11,4 +15,11 @@ pub use parser::Parser;
 
 pub trait VTParser {
     fn advance<A: VTActor>(&mut self, _bytes: &[u8], _actor: &mut A) {}
+
+    /// Whether the parser sits in the ground state with no partial escape
+    /// sequence or UTF-8 character buffered, so that the next printable
+    /// ASCII byte would be printed as is.
+    fn is_ground(&self) -> bool {
+        false
+    }
 }
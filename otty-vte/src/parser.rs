@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use crate::actor::VTActor;
 use crate::enums::{Action, State};
 use crate::{CsiParam, VTParser, transitions, utf8};
@@ -191,6 +193,8 @@ pub struct Parser {
     intermediates: Intermediates,
     params: Params,
     osc: OscState,
+    /// Whether the active SOS/PM/APC string is an APC.
+    apc: bool,
     utf8_parser: utf8::Utf8Parser,
 }
 
@@ -201,6 +205,11 @@ impl VTParser for Parser {
             self.process_byte(byte, actor);
         }
     }
+
+    #[inline]
+    fn is_ground(&self) -> bool {
+        self.state == State::Ground
+    }
 }
 
 impl Parser {
@@ -294,6 +303,17 @@ impl Parser {
             OscPut => self.osc.put(byte),
             OscEnd => self.osc_dispatch(actor, byte as u8),
             Utf8 => self.handle_utf8_step(actor, byte as u8),
+            ApcStart => {
+                self.apc = matches!(byte as u8, b'_' | 0x9f);
+                if self.apc {
+                    actor.apc_start();
+                }
+            },
+            ApcPut if self.apc => actor.apc_put(byte as u8),
+            ApcEnd if self.apc => {
+                self.apc = false;
+                actor.apc_end();
+            },
             _ => {},
         }
     }
@@ -400,6 +420,8 @@ mod tests {
             params: Vec<Vec<u8>>,
             byte: u8,
         },
+        Apc(Vec<u8>),
+        ApcEnd,
     }
 
     #[derive(Default)]
@@ -439,6 +461,20 @@ mod tests {
             self.actions.push(ActorEvents::Unhook);
         }
 
+        fn apc_start(&mut self) {
+            self.actions.push(ActorEvents::Apc(Vec::new()));
+        }
+
+        fn apc_put(&mut self, byte: u8) {
+            if let Some(ActorEvents::Apc(data)) = self.actions.last_mut() {
+                data.push(byte);
+            }
+        }
+
+        fn apc_end(&mut self) {
+            self.actions.push(ActorEvents::ApcEnd);
+        }
+
         fn esc_dispatch(
             &mut self,
             params: &[i64],
@@ -523,6 +559,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ground_state_excludes_partial_sequences() {
+        let mut parser = Parser::new();
+        let mut actor = CollectingActor::default();
+        assert!(parser.is_ground());
+
+        for (bytes, ground) in [
+            (&b"\x1b["[..], false),
+            (b"1m", true),
+            (&[0xd0], false),
+            (&[0x9f], true),
+            (b"\x1b]0;title", false),
+            (b"\x07ok", true),
+        ] {
+            parser.advance(bytes, &mut actor);
+            assert_eq!(parser.is_ground(), ground, "after {bytes:?}");
+        }
+    }
+
+    #[test]
+    fn apc_strings_are_streamed_and_sos_pm_ignored() {
+        assert_eq!(
+            parse(b"\x1bXsos\x1b\\\x1b^pm\x1b\\\x1b_Ga=T;AAAA\x1b\\x"),
+            vec![
+                ActorEvents::EscDispatch {
+                    params: vec![],
+                    intermediates: vec![],
+                    ignored_excess_intermediates: false,
+                    byte: b'\\',
+                },
+                ActorEvents::EscDispatch {
+                    params: vec![],
+                    intermediates: vec![],
+                    ignored_excess_intermediates: false,
+                    byte: b'\\',
+                },
+                ActorEvents::Apc(b"Ga=T;AAAA".to_vec()),
+                ActorEvents::ApcEnd,
+                ActorEvents::EscDispatch {
+                    params: vec![],
+                    intermediates: vec![],
+                    ignored_excess_intermediates: false,
+                    byte: b'\\',
+                },
+                ActorEvents::Print('x'),
+            ]
+        );
+    }
+
+    #[test]
+    fn del_is_ignored() {
+        assert_eq!(
+            parse(b"a\x7fb"),
+            vec![ActorEvents::Print('a'), ActorEvents::Print('b')]
+        );
+    }
+
     #[test]
     fn print_utf8() {
         assert_eq!(
@@ -762,6 +855,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn c0_controls_inside_csi_are_executed_immediately() {
+        assert_eq!(
+            parse(b"\x1b[1\n2\rA"),
+            vec![
+                ActorEvents::Execute(b'\n'),
+                ActorEvents::Execute(b'\r'),
+                ActorEvents::CsiDispatch {
+                    params: vec![CsiParam::Integer(12)],
+                    parameters_truncated: false,
+                    byte: b'A'
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn can_and_sub_abort_csi() {
+        assert_eq!(
+            parse(b"\x1b[12;3\x18A\x1b[?4\x1aB\x1b[5C"),
+            vec![
+                ActorEvents::Execute(0x18),
+                ActorEvents::Print('A'),
+                ActorEvents::Execute(0x1a),
+                ActorEvents::Print('B'),
+                // Nothing from the aborted sequences leaks into this one.
+                ActorEvents::CsiDispatch {
+                    params: vec![CsiParam::Integer(5)],
+                    parameters_truncated: false,
+                    byte: b'C'
+                },
+            ]
+        );
+
+        let mut parser = Parser::new();
+        parser.advance(b"\x1b[12;3\x18", &mut CollectingActor::default());
+        assert!(parser.is_ground());
+    }
+
+    #[test]
+    fn bel_inside_osc_terminates_it() {
+        assert_eq!(
+            parse(b"\x1b]2;ti\x07tle"),
+            vec![
+                ActorEvents::OscDispatch {
+                    params: vec![b"2".to_vec(), b"ti".to_vec()],
+                    byte: 0x07,
+                },
+                ActorEvents::Print('t'),
+                ActorEvents::Print('l'),
+                ActorEvents::Print('e'),
+            ]
+        );
+    }
+
     #[test]
     fn osc_utf8() {
         assert_eq!(
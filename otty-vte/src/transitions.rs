@@ -39,7 +39,8 @@ const fn ground(byte: u8) -> (State, Action) {
 
     match byte {
         0x00..=0x17 | 0x19 | 0x1c..=0x1f => (Ground, Execute),
-        0x20..=0x7f => (Ground, Print),
+        0x20..=0x7e => (Ground, Print),
+        0x7f => (Ground, Ignore),
         0xc2..=0xf4 => (Utf8Sequence, Utf8),
         _ => anywhere(Ground, byte),
     }
@@ -261,7 +262,7 @@ const fn sos_pm_apc_string(byte: u8) -> (State, Action) {
 
     match byte {
         0x00..=0x17 | 0x19 | 0x1c..=0x1f | 0x20..=0x7f => {
-            (SosPmApcString, Ignore)
+            (SosPmApcString, ApcPut)
         },
         _ => anywhere(SosPmApcString, byte),
     }
@@ -287,7 +288,7 @@ pub(crate) const fn entry_action(state: State) -> Action {
         DcsPassthrough => Hook,
         DcsIgnore => None,
         OscString => OscStart,
-        SosPmApcString => None,
+        SosPmApcString => ApcStart,
         Anywhere => None,
         Utf8Sequence => None,
         Nothing => None,
@@ -314,7 +315,7 @@ pub(crate) const fn exit_action(state: State) -> Action {
         DcsPassthrough => Unhook,
         DcsIgnore => None,
         OscString => OscEnd,
-        SosPmApcString => None,
+        SosPmApcString => ApcEnd,
         Anywhere => None,
         Utf8Sequence => None,
         Nothing => None,
@@ -23,6 +23,23 @@ pub(super) fn subscription(app: &App) -> Subscription<AppEvent> {
                 ))
             });
             subs.push(sub);
+
+            let terminal = entry.terminal();
+            if terminal.cursor_blinking() {
+                let blink =
+                    iced::time::every(otty_ui_term::CURSOR_BLINK_INTERVAL)
+                        .with(terminal.id)
+                        .map(|(id, _)| {
+                            AppEvent::TerminalWorkspace(
+                                TerminalWorkspaceEvent::Intent(
+                                    TerminalWorkspaceIntent::Widget(
+                                        otty_ui_term::Event::BlinkCursor { id },
+                                    ),
+                                ),
+                            )
+                        });
+                subs.push(blink);
+            }
         }
     }
 